@@ -139,6 +139,19 @@ impl Config {
         if self.debugger.default_speed_khz == 0 {
             return Err(DebugError::InvalidConfig("default_speed_khz must be > 0".to_string()));
         }
+        if self.debugger.max_step_count == 0 {
+            return Err(DebugError::InvalidConfig("debugger.max_step_count must be > 0".to_string()));
+        }
+        // A configured default must be usable, since connect relies on it when the argument is omitted
+        if self.debugger.default_probe_selector.as_deref().is_some_and(|probe| probe.trim().is_empty()) {
+            return Err(DebugError::InvalidConfig("debugger.default_probe_selector must not be empty".to_string()));
+        }
+        if self.debugger.default_target_chip.as_deref().is_some_and(|chip| chip.trim().is_empty()) {
+            return Err(DebugError::InvalidConfig("debugger.default_target_chip must not be empty".to_string()));
+        }
+        if self.memory.read_chunk_size == 0 {
+            return Err(DebugError::InvalidConfig("memory.read_chunk_size must be > 0".to_string()));
+        }
         if self.rtt.buffer_size == 0 {
             return Err(DebugError::InvalidConfig("rtt.buffer_size must be > 0".to_string()));
         }
@@ -226,12 +239,29 @@ impl Default for ServerConfig {
 pub struct DebuggerConfig {
     pub default_speed_khz: u32,
     pub connection_timeout_ms: u64,
+    /// Extra open+attach attempts of `connect` after a failure
     pub retry_count: u32,
+    /// Delay between `connect` attempts
+    #[serde(default = "default_connect_retry_delay_ms")]
+    pub connect_retry_delay_ms: u64,
     pub probe_discovery_timeout_ms: u64,
     pub halt_on_connect: bool,
     pub reset_on_connect: bool,
     pub connect_under_reset: bool,
     pub default_reset_type: String,
+    /// Most instructions a single `step` call may execute
+    #[serde(default = "default_max_step_count")]
+    pub max_step_count: u32,
+    /// Host directories searched for source files whose DWARF path does not exist locally
+    #[serde(default)]
+    pub source_search_paths: Vec<String>,
+    /// Lines of source shown either side of the halt location
+    #[serde(default = "default_source_context_lines")]
+    pub source_context_lines: u64,
+    /// Probe used by `connect` when no probe_selector is given
+    pub default_probe_selector: Option<String>,
+    /// Chip used by `connect` when no target_chip is given
+    pub default_target_chip: Option<String>,
 }
 
 impl Default for DebuggerConfig {
@@ -240,15 +270,33 @@ impl Default for DebuggerConfig {
             default_speed_khz: 4000,
             connection_timeout_ms: 5000,
             retry_count: 3,
+            connect_retry_delay_ms: 500,
             probe_discovery_timeout_ms: 2000,
             halt_on_connect: true,
             reset_on_connect: false,
             connect_under_reset: false,
             default_reset_type: "hardware".to_string(),
+            max_step_count: 10_000,
+            source_search_paths: Vec::new(),
+            source_context_lines: 3,
+            default_probe_selector: None,
+            default_target_chip: None,
         }
     }
 }
 
+fn default_connect_retry_delay_ms() -> u64 {
+    DebuggerConfig::default().connect_retry_delay_ms
+}
+
+fn default_max_step_count() -> u32 {
+    DebuggerConfig::default().max_step_count
+}
+
+fn default_source_context_lines() -> u64 {
+    DebuggerConfig::default().source_context_lines
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RttConfig {
     pub buffer_size: usize,
@@ -278,6 +326,12 @@ impl Default for RttConfig {
 pub struct MemoryConfig {
     pub max_read_size: usize,
     pub max_write_size: usize,
+    #[serde(default = "default_read_chunk_size")]
+    pub read_chunk_size: usize,
+    #[serde(default = "default_max_search_size")]
+    pub max_search_size: usize,
+    #[serde(default = "default_max_batch_read_size")]
+    pub max_batch_read_size: usize,
     pub cache_enable: bool,
     pub cache_size: usize,
 }
@@ -287,12 +341,27 @@ impl Default for MemoryConfig {
         Self {
             max_read_size: 65536,  // 64KB
             max_write_size: 4096,  // 4KB
+            read_chunk_size: 4096, // 4KB
+            max_search_size: 1048576, // 1MB
+            max_batch_read_size: 16384, // 16KB
             cache_enable: true,
             cache_size: 1048576,   // 1MB
         }
     }
 }
 
+fn default_read_chunk_size() -> usize {
+    MemoryConfig::default().read_chunk_size
+}
+
+fn default_max_search_size() -> usize {
+    MemoryConfig::default().max_search_size
+}
+
+fn default_max_batch_read_size() -> usize {
+    MemoryConfig::default().max_batch_read_size
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FlashConfig {
     pub default_erase_timeout_ms: u64,
@@ -0,0 +1,282 @@
+//! Stack unwinding based on the DWARF debug info of the firmware ELF
+
+use std::path::Path;
+use probe_rs::debug::{DebugInfo, DebugRegister, DebugRegisters, ColumnType, VariableLocation};
+use probe_rs::debug::stack_frame::StackFrameInfo;
+use probe_rs::{exception_handler_for_core, Core, CoreStatus, RegisterId, RegisterValue};
+use tracing::{debug, warn};
+
+use crate::error::{DebugError, Result};
+use super::stepping;
+
+/// A single frame of an unwound call stack
+#[derive(Debug, Clone)]
+pub struct BacktraceFrame {
+    pub index: usize,
+    pub address: u64,
+    /// Stack pointer of the frame, when the unwinder recovered it
+    pub sp: Option<u64>,
+    pub function_name: String,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub is_inlined: bool,
+    /// Context the hardware stacked on Cortex-M exception entry, i.e. the interrupted code
+    pub is_exception_frame: bool,
+}
+
+/// Result of unwinding the stack of a halted core
+#[derive(Debug, Clone)]
+pub struct Backtrace {
+    pub frames: Vec<BacktraceFrame>,
+    /// The frame limit was hit or unwind info ran out
+    pub truncated: bool,
+    /// No usable unwind info: the frames only hold the PC and link register
+    pub lr_fallback: bool,
+}
+
+/// A parameter or local variable in scope at a frame
+#[derive(Debug, Clone)]
+pub struct LocalVariable {
+    pub name: String,
+    pub type_name: String,
+    /// Where the value lives: "memory 0x...", "register or constant", "optimized out" or "unsupported"
+    pub location: String,
+    /// Decoded value, with struct and array members expanded
+    pub value: String,
+}
+
+/// Variables in scope at one frame of a halted core, from `locals`
+#[derive(Debug, Clone)]
+pub struct FrameLocals {
+    pub index: usize,
+    pub address: u64,
+    pub function_name: String,
+    pub is_inlined: bool,
+    pub variables: Vec<LocalVariable>,
+}
+
+/// How many levels of struct and array members are expanded in local values
+const LOCALS_MAX_DEPTH: usize = 4;
+
+/// Unwind the stack of a halted core.
+///
+/// Returns at most `max_frames` frames. Interrupted code below a Cortex-M
+/// exception handler shows up as an exception frame. Without an ELF, or when
+/// the ELF has no unwind info for the PC, the trace falls back to the PC and
+/// the link register.
+pub fn unwind(core: &mut Core<'_>, elf_path: Option<&Path>, max_frames: usize) -> Result<Backtrace> {
+    if !matches!(core.status()?, CoreStatus::Halted(_)) {
+        return Err(DebugError::InternalError("Core must be halted to unwind the stack".to_string()));
+    }
+
+    let debug_info = elf_path.map(load_debug_info).transpose()?;
+    let initial_registers = DebugRegisters::from_core(core);
+    Ok(unwind_registers(core, debug_info.as_ref(), initial_registers, max_frames))
+}
+
+/// Load the DWARF info of an ELF for unwinding
+pub fn load_debug_info(path: &Path) -> Result<DebugInfo> {
+    DebugInfo::from_file(path)
+        .map_err(|e| DebugError::InternalError(format!("Failed to load debug info from {}: {}", path.display(), e)))
+}
+
+/// The live registers of a halted core with some replaced, e.g. by the
+/// context an RTOS saved for a switched-out task
+pub fn registers_with(core: &mut Core<'_>, overrides: &[(u16, u32)]) -> DebugRegisters {
+    let mut registers = DebugRegisters::from_core(core);
+    for &(id, value) in overrides {
+        if let Some(register) = registers.get_register_mut(RegisterId(id)) {
+            register.value = Some(RegisterValue::U32(value));
+        }
+    }
+    registers
+}
+
+/// Unwind the stack starting from `initial_registers`, which need not be
+/// the live ones. Memory is read through the halted core.
+pub fn unwind_registers(core: &mut Core<'_>, debug_info: Option<&DebugInfo>, initial_registers: DebugRegisters, max_frames: usize) -> Backtrace {
+    let Some(debug_info) = debug_info else {
+        return lr_fallback(core, &initial_registers, None, max_frames);
+    };
+
+    let exception_handler = exception_handler_for_core(core.core_type());
+    let instruction_set = core.instruction_set().ok();
+
+    let frames = match debug_info.unwind(core, initial_registers.clone(), exception_handler.as_ref(), instruction_set) {
+        Ok(frames) if !frames.is_empty() => frames,
+        Ok(_) => return lr_fallback(core, &initial_registers, Some(debug_info), max_frames),
+        Err(e) => {
+            warn!("Stack unwinding failed: {}", e);
+            return lr_fallback(core, &initial_registers, Some(debug_info), max_frames);
+        }
+    };
+
+    let is_cortex_m = core.core_type().is_cortex_m();
+    let truncated = frames.len() > max_frames;
+    let mut caller_of_exception = false;
+    let frames = frames
+        .into_iter()
+        .take(max_frames)
+        .enumerate()
+        .map(|(index, frame)| {
+            let address: u64 = frame.pc.try_into().unwrap_or(0u64);
+            let sp = frame.registers.get_stack_pointer()
+                .and_then(|register| register.value)
+                .and_then(|value| value.try_into().ok());
+            let (file, line, column) = match &frame.source_location {
+                Some(location) => (
+                    Some(location.path.to_path().display().to_string()),
+                    location.line,
+                    match location.column {
+                        Some(ColumnType::Column(c)) => Some(c),
+                        _ => None,
+                    },
+                ),
+                None => (None, None, None),
+            };
+
+            // A frame whose return address is EXC_RETURN was entered by an exception,
+            // so the next frame is the stacked context of the interrupted code
+            let is_exception_frame = caller_of_exception;
+            caller_of_exception = is_cortex_m && frame.registers.get_return_address()
+                .and_then(|register| register.value)
+                .and_then(|value| value.try_into().ok())
+                .is_some_and(stepping::is_exc_return);
+
+            BacktraceFrame {
+                index,
+                address,
+                sp,
+                function_name: demangle(&frame.function_name),
+                file,
+                line,
+                column,
+                is_inlined: frame.is_inlined,
+                is_exception_frame,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    debug!("Unwound {} stack frames (truncated: {})", frames.len(), truncated);
+    Backtrace { frames, truncated, lr_fallback: false }
+}
+
+/// Evaluate the parameters and local variables in scope at frame `frame_index`.
+///
+/// Locations are evaluated with the registers the unwinder recovered for that
+/// frame, so callee-saved registers of caller frames hold their values at the
+/// call rather than the live ones.
+pub fn locals(core: &mut Core<'_>, elf_path: &Path, frame_index: usize) -> Result<FrameLocals> {
+    if !matches!(core.status()?, CoreStatus::Halted(_)) {
+        return Err(DebugError::InvalidConfig("Core must be halted to read local variables; use 'halt' first".to_string()));
+    }
+
+    let debug_info = load_debug_info(elf_path)?;
+    let initial_registers = DebugRegisters::from_core(core);
+    let exception_handler = exception_handler_for_core(core.core_type());
+    let instruction_set = core.instruction_set().ok();
+
+    let mut frames = debug_info.unwind(core, initial_registers, exception_handler.as_ref(), instruction_set)
+        .map_err(|e| DebugError::InternalError(format!("Stack unwinding failed: {}", e)))?;
+    let frame_count = frames.len();
+    if frame_index >= frame_count {
+        return Err(DebugError::InvalidConfig(format!(
+            "Frame {} does not exist; the stack has {} frame(s)", frame_index, frame_count
+        )));
+    }
+    let mut frame = frames.swap_remove(frame_index);
+
+    let mut variables = Vec::new();
+    if let Some(mut cache) = frame.local_variables.take() {
+        cache.recurse_deferred_variables(
+            &debug_info,
+            core,
+            LOCALS_MAX_DEPTH,
+            StackFrameInfo {
+                registers: &frame.registers,
+                frame_base: frame.frame_base,
+                canonical_frame_address: frame.canonical_frame_address,
+            },
+        );
+        let root = cache.root_variable().variable_key();
+        for variable in cache.get_children(root) {
+            let location = match &variable.memory_location {
+                VariableLocation::Address(address) => format!("memory 0x{:08X}", address),
+                VariableLocation::Value => "register or constant".to_string(),
+                VariableLocation::Unavailable => "optimized out".to_string(),
+                VariableLocation::Unknown => "unknown".to_string(),
+                VariableLocation::Error(_) | VariableLocation::Unsupported(_) => "unsupported".to_string(),
+            };
+            variables.push(LocalVariable {
+                name: variable.name.to_string(),
+                type_name: variable.type_name(),
+                location,
+                value: variable.to_string(&cache),
+            });
+        }
+    }
+
+    debug!("Read {} local variable(s) of frame {}", variables.len(), frame_index);
+    Ok(FrameLocals {
+        index: frame_index,
+        address: frame.pc.try_into().unwrap_or(0u64),
+        function_name: demangle(&frame.function_name),
+        is_inlined: frame.is_inlined,
+        variables,
+    })
+}
+
+/// Conservative two-frame trace from the PC and the link register
+fn lr_fallback(core: &mut Core<'_>, registers: &DebugRegisters, debug_info: Option<&DebugInfo>, max_frames: usize) -> Backtrace {
+    let value = |register: Option<&DebugRegister>| -> u64 {
+        register.and_then(|register| register.value).and_then(|value| value.try_into().ok()).unwrap_or(0)
+    };
+    let pc = value(registers.get_program_counter());
+    let sp = value(registers.get_stack_pointer());
+    let lr = value(registers.get_return_address());
+
+    let frame = |index: usize, address: u64| {
+        let location = debug_info.and_then(|info| info.get_source_location(address));
+        BacktraceFrame {
+            index,
+            address,
+            sp: (index == 0).then_some(sp),
+            function_name: "<unknown>".to_string(),
+            file: location.as_ref().map(|l| l.path.to_path().display().to_string()),
+            line: location.as_ref().and_then(|l| l.line),
+            column: None,
+            is_inlined: false,
+            is_exception_frame: false,
+        }
+    };
+
+    let mut frames = vec![frame(0, pc)];
+    // EXC_RETURN is not an address; without unwind info the interrupted PC is unknown
+    let has_caller = lr != 0 && !(core.core_type().is_cortex_m() && stepping::is_exc_return(lr));
+    if has_caller && max_frames > 1 {
+        // Thumb return addresses carry the interworking bit
+        frames.push(frame(1, lr & !1));
+    }
+
+    debug!("Fell back to an LR-based backtrace with {} frame(s)", frames.len());
+    Backtrace { frames, truncated: true, lr_fallback: true }
+}
+
+/// Demangle a Rust symbol name, leaving other names untouched
+fn demangle(name: &str) -> String {
+    rustc_demangle::try_demangle(name)
+        .map(|demangled| format!("{:#}", demangled))
+        .unwrap_or_else(|_| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(demangle("_ZN4core9panicking5panic17h0123456789abcdefE"), "core::panicking::panic");
+        assert_eq!(demangle("HardFault"), "HardFault");
+    }
+}
@@ -0,0 +1,89 @@
+//! Sequences of primitive core operations run under a single session lock
+
+use probe_rs::{Core, MemoryInterface};
+
+/// How long a batched halt waits for the core to stop
+const BATCH_HALT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// One primitive operation of a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    Halt,
+    Run,
+    Read { address: u64, size: usize },
+    Write { address: u64, data: Vec<u8> },
+}
+
+impl BatchOp {
+    /// Memory range the operation touches, if any
+    pub fn memory_range(&self) -> Option<(u64, usize)> {
+        match self {
+            BatchOp::Read { address, size } => Some((*address, *size)),
+            BatchOp::Write { address, data } => Some((*address, data.len())),
+            BatchOp::Halt | BatchOp::Run => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BatchOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchOp::Halt => write!(f, "halt"),
+            BatchOp::Run => write!(f, "run"),
+            BatchOp::Read { address, size } => write!(f, "read {} bytes at 0x{:08X}", size, address),
+            BatchOp::Write { address, data } => write!(f, "write {} bytes at 0x{:08X}", data.len(), address),
+        }
+    }
+}
+
+/// Outcome of one batched operation
+#[derive(Debug)]
+pub enum BatchOpResult {
+    Halted { pc: u64 },
+    Resumed,
+    Read(Vec<u8>),
+    Written(usize),
+    Failed(String),
+    /// Not attempted because an earlier operation failed
+    Skipped,
+}
+
+/// Run `ops` in order on a core, skipping the rest after the first failure when `stop_on_error` is set
+pub fn execute(core: &mut Core<'_>, ops: &[BatchOp], stop_on_error: bool) -> Vec<BatchOpResult> {
+    let mut results = Vec::with_capacity(ops.len());
+    let mut failed = false;
+    for op in ops {
+        if failed && stop_on_error {
+            results.push(BatchOpResult::Skipped);
+            continue;
+        }
+
+        let result = match op {
+            BatchOp::Halt => core.halt(BATCH_HALT_TIMEOUT).map(|info| BatchOpResult::Halted { pc: info.pc }),
+            BatchOp::Run => core.run().map(|_| BatchOpResult::Resumed),
+            BatchOp::Read { address, size } => {
+                let mut data = vec![0u8; *size];
+                core.read_8(*address, &mut data).map(|_| BatchOpResult::Read(data))
+            }
+            BatchOp::Write { address, data } => core.write_8(*address, data).map(|_| BatchOpResult::Written(data.len())),
+        };
+        results.push(result.unwrap_or_else(|e| {
+            failed = true;
+            BatchOpResult::Failed(e.to_string())
+        }));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_op_memory_range() {
+        assert_eq!(BatchOp::Read { address: 0x2000_0000, size: 16 }.memory_range(), Some((0x2000_0000, 16)));
+        assert_eq!(BatchOp::Write { address: 0x2000_0010, data: vec![1, 2, 3] }.memory_range(), Some((0x2000_0010, 3)));
+        assert_eq!(BatchOp::Halt.memory_range(), None);
+        assert_eq!(BatchOp::Write { address: 0x10, data: vec![0; 4] }.to_string(), "write 4 bytes at 0x00000010");
+    }
+}
@@ -0,0 +1,442 @@
+//! Bookkeeping for breakpoints installed through the tools
+
+use std::fmt;
+
+use probe_rs::{Core, CoreInterface, CoreType, MemoryInterface};
+use tracing::warn;
+
+use crate::error::{DebugError, Result};
+
+/// How a breakpoint is implemented on the target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakpointKind {
+    /// A comparator in the core's breakpoint unit
+    Hardware,
+    /// A breakpoint instruction patched into RAM, with the bytes it replaced
+    Software { original: Vec<u8> },
+}
+
+impl BreakpointKind {
+    pub fn is_software(&self) -> bool {
+        matches!(self, BreakpointKind::Software { .. })
+    }
+}
+
+impl fmt::Display for BreakpointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakpointKind::Hardware => write!(f, "hardware"),
+            BreakpointKind::Software { .. } => write!(f, "software"),
+        }
+    }
+}
+
+/// A breakpoint installed on the target
+#[derive(Debug, Clone)]
+pub struct BreakpointInfo {
+    /// Session-unique id, kept while the breakpoint exists
+    pub id: u32,
+    pub address: u64,
+    pub core_index: usize,
+    /// Symbol the address was resolved from, if any
+    pub symbol: Option<String>,
+    pub kind: BreakpointKind,
+    /// Removed automatically the first time the core halts on it
+    pub temporary: bool,
+    /// Disabled breakpoints stay known but are not installed on the target
+    pub enabled: bool,
+    /// Times the core was seen halted on the breakpoint
+    pub hit_count: u64,
+    /// Hits resumed automatically before the breakpoint halts for good
+    pub skip_count: u32,
+}
+
+impl BreakpointInfo {
+    /// Whether the latest counted hit is still within `skip_count`, so the core should resume
+    pub fn skips_hit(&self) -> bool {
+        self.hit_count <= self.skip_count as u64
+    }
+}
+
+/// Access to the hardware breakpoint comparators of a core
+pub trait BreakpointUnits {
+    /// Address held by each comparator, `None` for a free one
+    fn comparators(&mut self) -> std::result::Result<Vec<Option<u64>>, probe_rs::Error>;
+
+    /// Program a free comparator (or the one already holding `address`)
+    fn set_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error>;
+
+    /// Free the comparator holding `address`
+    fn clear_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error>;
+
+    /// Write code bytes, e.g. the instruction under a software breakpoint
+    fn write_code(&mut self, address: u64, code: &[u8]) -> std::result::Result<(), probe_rs::Error>;
+}
+
+impl BreakpointUnits for Core<'_> {
+    fn comparators(&mut self) -> std::result::Result<Vec<Option<u64>>, probe_rs::Error> {
+        CoreInterface::hw_breakpoints(self)
+    }
+
+    fn set_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error> {
+        self.set_hw_breakpoint(address)
+    }
+
+    fn clear_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error> {
+        self.clear_hw_breakpoint(address)
+    }
+
+    fn write_code(&mut self, address: u64, code: &[u8]) -> std::result::Result<(), probe_rs::Error> {
+        self.write_8(address, code)
+    }
+}
+
+/// Hardware breakpoint comparators in use on a core
+#[derive(Debug, Clone, Copy)]
+pub struct BreakpointCapacity {
+    pub used: usize,
+    pub total: usize,
+}
+
+/// Count the used and available hardware breakpoint comparators
+pub fn capacity(core: &mut impl BreakpointUnits) -> Result<BreakpointCapacity> {
+    let comparators = core.comparators()?;
+    Ok(BreakpointCapacity {
+        used: comparators.iter().flatten().count(),
+        total: comparators.len(),
+    })
+}
+
+/// Set a hardware breakpoint, reporting which addresses hold the comparators when all are in use
+pub fn set_hardware_breakpoint(core: &mut impl BreakpointUnits, address: u64) -> Result<()> {
+    let comparators = core.comparators()?;
+    let occupied: Vec<u64> = comparators.iter().flatten().copied().collect();
+    if !occupied.contains(&address) && occupied.len() >= comparators.len() {
+        return Err(DebugError::BreakpointLimitReached {
+            used: occupied.len(),
+            max: comparators.len(),
+            occupied,
+        });
+    }
+    core.set_comparator(address)?;
+    Ok(())
+}
+
+/// Remove a breakpoint from the target, restoring the instruction under a
+/// software breakpoint or freeing the hardware comparator
+pub fn uninstall(core: &mut impl BreakpointUnits, breakpoint: &BreakpointInfo) -> Result<()> {
+    match &breakpoint.kind {
+        BreakpointKind::Software { original } => core.write_code(breakpoint.address, original)?,
+        BreakpointKind::Hardware => core.clear_comparator(breakpoint.address)?,
+    }
+    Ok(())
+}
+
+/// Re-apply a known breakpoint after a reset or re-attach.
+///
+/// Hardware comparators are programmed again. A software breakpoint is
+/// patched back only if the memory still holds the instruction it replaced;
+/// anything else means the code there changed and the breakpoint is stale.
+pub fn rearm(core: &mut Core<'_>, breakpoint: &BreakpointInfo) -> Result<()> {
+    let original = match &breakpoint.kind {
+        BreakpointKind::Hardware => return set_hardware_breakpoint(core, breakpoint.address),
+        BreakpointKind::Software { original } => original,
+    };
+
+    let opcode = software_opcode(core, original)?;
+    let mut current = vec![0u8; original.len()];
+    core.read_8(breakpoint.address, &mut current)?;
+    if current == opcode {
+        return Ok(());
+    }
+    if &current != original {
+        return Err(DebugError::MemoryAccessFailed(format!(
+            "code at 0x{:08X} changed since the breakpoint was set", breakpoint.address
+        )));
+    }
+    core.write_8(breakpoint.address, &opcode)?;
+    Ok(())
+}
+
+/// Re-arm every enabled breakpoint with `rearm`, disabling the ones that fail.
+///
+/// Returns the restored addresses and the failed ones with the reason.
+pub fn rearm_enabled<'a>(
+    breakpoints: impl IntoIterator<Item = &'a mut BreakpointInfo>,
+    mut rearm: impl FnMut(&BreakpointInfo) -> Result<()>,
+) -> (Vec<u64>, Vec<(u64, String)>) {
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    for breakpoint in breakpoints.into_iter().filter(|breakpoint| breakpoint.enabled) {
+        match rearm(breakpoint) {
+            Ok(()) => restored.push(breakpoint.address),
+            Err(e) => {
+                warn!("Failed to restore breakpoint at 0x{:08X}: {}", breakpoint.address, e);
+                breakpoint.enabled = false;
+                failed.push((breakpoint.address, e.to_string()));
+            }
+        }
+    }
+    (restored, failed)
+}
+
+/// Remove every breakpoint with `uninstall`, skipping disabled ones that are not on the target.
+///
+/// Returns the removed addresses and the ones that failed with the reason.
+pub fn uninstall_each<'a>(
+    breakpoints: impl IntoIterator<Item = &'a BreakpointInfo>,
+    mut uninstall: impl FnMut(&BreakpointInfo) -> Result<()>,
+) -> (Vec<u64>, Vec<(u64, String)>) {
+    let mut removed = Vec::new();
+    let mut failed = Vec::new();
+    for breakpoint in breakpoints {
+        match if breakpoint.enabled { uninstall(breakpoint) } else { Ok(()) } {
+            Ok(()) => removed.push(breakpoint.address),
+            Err(e) => {
+                warn!("Failed to remove breakpoint at 0x{:08X}: {}", breakpoint.address, e);
+                failed.push((breakpoint.address, e.to_string()));
+            }
+        }
+    }
+    (removed, failed)
+}
+
+/// Step the core off the instruction under a breakpoint it is halted on, leaving the breakpoint installed.
+///
+/// probe-rs steps over a hardware breakpoint at the PC by itself; a software
+/// breakpoint has its original instruction put back for the step.
+pub fn step_past(core: &mut Core<'_>, breakpoint: &BreakpointInfo) -> Result<()> {
+    let BreakpointKind::Software { original } = &breakpoint.kind else {
+        core.step()?;
+        return Ok(());
+    };
+
+    let opcode = software_opcode(core, original)?;
+    core.write_8(breakpoint.address, original)?;
+    let stepped = core.step();
+    core.write_8(breakpoint.address, &opcode)?;
+    stepped?;
+    Ok(())
+}
+
+/// Breakpoint instruction patched over `original`
+fn software_opcode(core: &Core<'_>, original: &[u8]) -> Result<Vec<u8>> {
+    let head = u16::from_le_bytes([original[0], original.get(1).copied().unwrap_or(0)]);
+    breakpoint_instruction(core.core_type(), head).ok_or_else(|| DebugError::InvalidConfig(format!(
+        "Software breakpoints are not supported on {:?} cores", core.core_type()
+    )))
+}
+
+/// Parse a breakpoint type name, returning whether it is a software breakpoint
+pub fn parse_breakpoint_type(name: &str) -> std::result::Result<bool, String> {
+    match name.to_lowercase().as_str() {
+        "hardware" | "hw" => Ok(false),
+        "software" | "sw" => Ok(true),
+        other => Err(format!("Unknown breakpoint type '{}'. Use 'hardware' or 'software'", other)),
+    }
+}
+
+/// Breakpoint instruction to patch over an instruction starting with `first_halfword`.
+///
+/// The opcode is sized to replace exactly one instruction: Thumb `BKPT #0` on
+/// Cortex-M, `BKPT`/`BRK` on Cortex-A and `c.ebreak`/`ebreak` on RISC-V
+/// depending on whether the original instruction is compressed.
+pub fn breakpoint_instruction(core_type: CoreType, first_halfword: u16) -> Option<Vec<u8>> {
+    match core_type {
+        CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+            Some(0xBE00u16.to_le_bytes().to_vec())
+        }
+        CoreType::Armv7a => Some(0xE120_0070u32.to_le_bytes().to_vec()),
+        CoreType::Armv8a => Some(0xD420_0000u32.to_le_bytes().to_vec()),
+        CoreType::Riscv if first_halfword & 0b11 == 0b11 => Some(0x0010_0073u32.to_le_bytes().to_vec()),
+        CoreType::Riscv => Some(0x9002u16.to_le_bytes().to_vec()),
+        CoreType::Xtensa => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Breakpoint unit with a fixed number of comparators
+    struct MockCore {
+        comparators: Vec<Option<u64>>,
+        /// Code writes, in order
+        code: Vec<(u64, Vec<u8>)>,
+    }
+
+    impl BreakpointUnits for MockCore {
+        fn comparators(&mut self) -> std::result::Result<Vec<Option<u64>>, probe_rs::Error> {
+            Ok(self.comparators.clone())
+        }
+
+        fn set_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error> {
+            if self.comparators.contains(&Some(address)) {
+                return Ok(());
+            }
+            match self.comparators.iter_mut().find(|comparator| comparator.is_none()) {
+                Some(free) => {
+                    *free = Some(address);
+                    Ok(())
+                }
+                None => Err(probe_rs::Error::Other("No available hardware breakpoints".to_string())),
+            }
+        }
+
+        fn clear_comparator(&mut self, address: u64) -> std::result::Result<(), probe_rs::Error> {
+            for comparator in self.comparators.iter_mut().filter(|comparator| **comparator == Some(address)) {
+                *comparator = None;
+            }
+            Ok(())
+        }
+
+        fn write_code(&mut self, address: u64, code: &[u8]) -> std::result::Result<(), probe_rs::Error> {
+            self.code.push((address, code.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_breakpoint_limit_reached() {
+        let mut core = MockCore { comparators: vec![None; 4], code: Vec::new() };
+        for address in [0x0800_0100, 0x0800_0200, 0x0800_0300, 0x0800_0400] {
+            set_hardware_breakpoint(&mut core, address).unwrap();
+        }
+        let used = capacity(&mut core).unwrap();
+        assert_eq!((used.used, used.total), (4, 4));
+
+        // Re-setting an address that already holds a comparator is not an error
+        set_hardware_breakpoint(&mut core, 0x0800_0200).unwrap();
+
+        match set_hardware_breakpoint(&mut core, 0x0800_0500) {
+            Err(DebugError::BreakpointLimitReached { used, max, occupied }) => {
+                assert_eq!((used, max), (4, 4));
+                assert_eq!(occupied, vec![0x0800_0100, 0x0800_0200, 0x0800_0300, 0x0800_0400]);
+            }
+            other => panic!("expected BreakpointLimitReached, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_uninstall_temporary_breakpoints() {
+        let breakpoint = |address, kind| BreakpointInfo {
+            id: 1,
+            address,
+            core_index: 0,
+            symbol: None,
+            kind,
+            temporary: true,
+            enabled: true,
+            hit_count: 1,
+            skip_count: 0,
+        };
+        let mut core = MockCore { comparators: vec![None; 2], code: Vec::new() };
+        set_hardware_breakpoint(&mut core, 0x0800_0100).unwrap();
+
+        uninstall(&mut core, &breakpoint(0x0800_0100, BreakpointKind::Hardware)).unwrap();
+        assert_eq!(capacity(&mut core).unwrap().used, 0);
+
+        uninstall(&mut core, &breakpoint(0x2000_0200, BreakpointKind::Software { original: vec![0x70, 0x47] })).unwrap();
+        assert_eq!(core.code, vec![(0x2000_0200, vec![0x70, 0x47])]);
+    }
+
+    #[test]
+    fn test_rearm_enabled_disables_failures() {
+        let breakpoint = |id, address, enabled| BreakpointInfo {
+            id,
+            address,
+            core_index: 0,
+            symbol: None,
+            kind: BreakpointKind::Hardware,
+            temporary: false,
+            enabled,
+            hit_count: 0,
+            skip_count: 0,
+        };
+        let mut breakpoints = vec![
+            breakpoint(1, 0x0800_0100, true),
+            breakpoint(2, 0x0800_0200, false),
+            breakpoint(3, 0x2000_0300, true),
+        ];
+
+        let mut attempted = Vec::new();
+        let (restored, failed) = rearm_enabled(&mut breakpoints, |breakpoint| {
+            attempted.push(breakpoint.id);
+            match breakpoint.address {
+                0x2000_0300 => Err(DebugError::MemoryAccessFailed("code changed".to_string())),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(attempted, vec![1, 3]);
+        assert_eq!(restored, vec![0x0800_0100]);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, 0x2000_0300);
+        assert!(failed[0].1.contains("code changed"));
+        assert_eq!(breakpoints.iter().map(|breakpoint| breakpoint.enabled).collect::<Vec<_>>(), vec![true, false, false]);
+    }
+
+    #[test]
+    fn test_uninstall_each_skips_disabled() {
+        let breakpoint = |address, enabled, kind| BreakpointInfo {
+            id: 1,
+            address,
+            core_index: 0,
+            symbol: None,
+            kind,
+            temporary: false,
+            enabled,
+            hit_count: 0,
+            skip_count: 0,
+        };
+        let breakpoints = vec![
+            breakpoint(0x0800_0100, true, BreakpointKind::Hardware),
+            breakpoint(0x0800_0200, false, BreakpointKind::Hardware),
+            breakpoint(0x2000_0300, true, BreakpointKind::Software { original: vec![0x00, 0xBF] }),
+        ];
+        let mut core = MockCore { comparators: vec![Some(0x0800_0100), None], code: Vec::new() };
+
+        let (removed, failed) = uninstall_each(&breakpoints, |breakpoint| match breakpoint.kind {
+            BreakpointKind::Hardware => uninstall(&mut core, breakpoint),
+            BreakpointKind::Software { .. } => Err(DebugError::MemoryAccessFailed("target not responding".to_string())),
+        });
+
+        assert_eq!(removed, vec![0x0800_0100, 0x0800_0200]);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, 0x2000_0300);
+        assert_eq!(core.comparators, vec![None, None]);
+    }
+
+    #[test]
+    fn test_skips_hit_until_skip_count() {
+        let mut breakpoint = BreakpointInfo {
+            id: 1,
+            address: 0x0800_0100,
+            core_index: 0,
+            symbol: None,
+            kind: BreakpointKind::Hardware,
+            temporary: false,
+            enabled: true,
+            hit_count: 0,
+            skip_count: 2,
+        };
+        let mut skipped = Vec::new();
+        for _ in 0..4 {
+            breakpoint.hit_count += 1;
+            skipped.push(breakpoint.skips_hit());
+        }
+        assert_eq!(skipped, vec![true, true, false, false]);
+
+        breakpoint.skip_count = 0;
+        breakpoint.hit_count = 1;
+        assert!(!breakpoint.skips_hit());
+    }
+
+    #[test]
+    fn test_breakpoint_instruction() {
+        assert_eq!(breakpoint_instruction(CoreType::Armv7em, 0xF000).unwrap(), vec![0x00, 0xBE]);
+        assert_eq!(breakpoint_instruction(CoreType::Riscv, 0x0513).unwrap(), vec![0x73, 0x00, 0x10, 0x00]);
+        assert_eq!(breakpoint_instruction(CoreType::Riscv, 0x4501).unwrap(), vec![0x02, 0x90]);
+        assert!(breakpoint_instruction(CoreType::Xtensa, 0).is_none());
+    }
+}
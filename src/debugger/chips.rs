@@ -0,0 +1,176 @@
+//! Lookup of chips in the probe-rs built-in target registry
+
+use std::path::Path;
+
+use probe_rs::config::{Chip, CoreType, MemoryRegion};
+use tracing::{debug, info};
+
+use crate::error::{DebugError, Result};
+
+/// Summary of a chip known to probe-rs
+#[derive(Debug, Clone)]
+pub struct ChipInfo {
+    /// Name to pass as `target_chip` when connecting
+    pub name: String,
+    /// Package-specific names that are also accepted as `target_chip`
+    pub package_variants: Vec<String>,
+    pub family: String,
+    pub cores: Vec<CoreType>,
+    /// Total size of the non-volatile memory regions, aliases excluded
+    pub flash_bytes: u64,
+    pub ram_bytes: u64,
+}
+
+/// A chip family with the number of chips it contains
+#[derive(Debug, Clone)]
+pub struct FamilyInfo {
+    pub name: String,
+    pub chip_count: usize,
+}
+
+impl ChipInfo {
+    fn from_chip(family: &str, chip: &Chip) -> Self {
+        let size = |region: &MemoryRegion| match region {
+            MemoryRegion::Nvm(nvm) if !nvm.is_alias => (nvm.range.end - nvm.range.start, 0),
+            MemoryRegion::Ram(ram) => (0, ram.range.end - ram.range.start),
+            _ => (0, 0),
+        };
+        let (flash_bytes, ram_bytes) = chip.memory_map
+            .iter()
+            .map(size)
+            .fold((0, 0), |(flash, ram), (f, r)| (flash + f, ram + r));
+
+        Self {
+            name: chip.name.clone(),
+            package_variants: chip.package_variants.clone(),
+            family: family.to_string(),
+            cores: chip.cores.iter().map(|core| core.core_type).collect(),
+            flash_bytes,
+            ram_bytes,
+        }
+    }
+}
+
+/// Chips whose name or a package variant contains `query` (ignoring case), sorted by name.
+///
+/// At most `limit` chips are returned together with the total number of matches.
+pub fn search_chips(query: &str, limit: usize) -> (Vec<ChipInfo>, usize) {
+    let query = query.to_lowercase();
+    let families = probe_rs::config::families();
+    let mut matches: Vec<ChipInfo> = families
+        .iter()
+        .flat_map(|family| {
+            family.variants
+                .iter()
+                .filter(|chip| chip.package_variants().any(|name| name.to_lowercase().contains(&query)))
+                .map(|chip| ChipInfo::from_chip(&family.name, chip))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| a.name.cmp(&b.name));
+    let total = matches.len();
+    matches.truncate(limit);
+    debug!("Chip search '{}' matched {} chips", query, total);
+    (matches, total)
+}
+
+/// Chip families of the registry whose name contains `filter` (ignoring case), sorted by name
+pub fn list_families(filter: Option<&str>) -> Vec<FamilyInfo> {
+    let filter = filter.map(str::to_lowercase);
+    let families = probe_rs::config::families();
+    let mut result: Vec<FamilyInfo> = families
+        .iter()
+        .filter(|family| filter.as_ref().is_none_or(|f| family.name.to_lowercase().contains(f)))
+        .map(|family| FamilyInfo { name: family.name.clone(), chip_count: family.variants.len() })
+        .collect();
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+/// Register the chip family described by a probe-rs target YAML file.
+///
+/// Returns the family name and the chips it added, which `connect` then
+/// accepts as `target_chip`. A family that is already registered is replaced.
+pub fn load_target_family(path: &Path) -> Result<(String, Vec<String>)> {
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    if extension.as_deref() == Some("pack") {
+        return Err(DebugError::InvalidConfig(format!(
+            "{} is a CMSIS-Pack; convert it to a probe-rs target YAML with `target-gen pack` first",
+            path.display()
+        )));
+    }
+
+    let file = std::fs::File::open(path).map_err(|e| {
+        DebugError::InvalidConfig(format!("Failed to open target file {}: {}", path.display(), e))
+    })?;
+    let family = probe_rs::config::add_target_from_yaml(file).map_err(|e| {
+        let detail = std::error::Error::source(&e).map(|source| format!(": {}", source)).unwrap_or_default();
+        DebugError::InvalidConfig(format!("Failed to load target file {}: {}{}", path.display(), e, detail))
+    })?;
+    let chips = probe_rs::config::get_targets_by_family_name(&family).map_err(|e| {
+        DebugError::InternalError(format!("Target family '{}' was not registered: {}", family, e))
+    })?;
+
+    info!("Registered target family '{}' with {} chips from {}", family, chips.len(), path.display());
+    Ok((family, chips))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_chips() {
+        let (chips, total) = search_chips("stm32f407vgt", 10);
+        assert!(total >= 1);
+        let chip = chips.iter().find(|chip| chip.package_variants.iter().any(|name| name == "STM32F407VGTx")).unwrap();
+        assert_eq!(chip.cores, vec![CoreType::Armv7em]);
+        assert_eq!(chip.flash_bytes, 1024 * 1024);
+        assert!(chip.ram_bytes > 0);
+
+        let (chips, total) = search_chips("stm32", 3);
+        assert_eq!(chips.len(), 3);
+        assert!(total > 3);
+        assert!(list_families(Some("stm32f4")).iter().all(|family| family.chip_count > 0));
+    }
+
+    #[test]
+    fn test_load_target_family() {
+        let yaml = r#"
+name: Example Custom Family
+variants:
+- name: EXAMPLE_MCU1
+  cores:
+  - name: main
+    type: armv7em
+    core_access_options: !Arm
+      ap: 0
+      psel: 0x0
+  memory_map:
+  - !Nvm
+    range:
+      start: 0x08000000
+      end: 0x08010000
+    cores:
+    - main
+  - !Ram
+    range:
+      start: 0x20000000
+      end: 0x20004000
+    cores:
+    - main
+flash_algorithms: []
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("example.yaml");
+        std::fs::write(&path, yaml).unwrap();
+
+        let (family, chips) = load_target_family(&path).unwrap();
+        assert_eq!(family, "Example Custom Family");
+        assert_eq!(chips, vec!["EXAMPLE_MCU1"]);
+
+        let (found, _) = search_chips("example_mcu", 5);
+        assert_eq!(found[0].flash_bytes, 0x10000);
+        assert!(load_target_family(&dir.path().join("vendor.pack")).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! Post-mortem core files: ELF cores for GDB and probe-rs's own coredump format
+
+use probe_rs::{CoreType, RegisterId};
+
+use crate::error::{DebugError, Result};
+
+/// Note type of the register set, `NT_PRSTATUS`
+const NT_PRSTATUS: u32 = 1;
+/// Owner name of the metadata note
+pub const METADATA_NOTE_NAME: &str = "embedded-debugger-mcp";
+/// Note type of the metadata note, a `key=value` line per entry
+const NT_METADATA: u32 = 1;
+
+const ELF_HEADER_SIZE: usize = 52;
+const PROGRAM_HEADER_SIZE: usize = 32;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+/// Segment flags of dumped memory: readable and writable
+const PF_RW: u32 = 0x6;
+
+/// Bytes of `elf_prstatus` on a 32-bit target before `pr_reg`
+const PRSTATUS_HEADER_SIZE: usize = 72;
+
+/// Result of `core_dump`
+#[derive(Debug, Clone)]
+pub struct CoreDumpResult {
+    /// Dumped memory regions as (address, size)
+    pub regions: Vec<(u64, usize)>,
+    pub registers: usize,
+    pub file_size: usize,
+    /// Whether the core was running and got resumed after the dump
+    pub resumed: bool,
+    pub elapsed_ms: u64,
+}
+
+/// File format of a core dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreDumpFormat {
+    /// ELF core file with an `NT_PRSTATUS` note, for `gdb firmware.elf core`
+    Elf,
+    /// probe-rs's own coredump format
+    ProbeRs,
+}
+
+impl CoreDumpFormat {
+    /// Parse a format name: "elf" or "probe-rs"
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "elf" | "core" => Ok(CoreDumpFormat::Elf),
+            "probe-rs" | "probers" | "probe_rs" => Ok(CoreDumpFormat::ProbeRs),
+            _ => Err(DebugError::InvalidConfig(format!(
+                "Unsupported core dump format '{}'. Use 'elf' or 'probe-rs'",
+                format
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for CoreDumpFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoreDumpFormat::Elf => write!(f, "elf"),
+            CoreDumpFormat::ProbeRs => write!(f, "probe-rs"),
+        }
+    }
+}
+
+/// Architecture an ELF core file is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreMachine {
+    /// Cortex-M, with the Linux ARM `pr_reg` layout GDB reads
+    Arm,
+    /// 32-bit RISC-V, with the Linux RISC-V `pr_reg` layout
+    RiscV32,
+}
+
+impl CoreMachine {
+    /// Machine for a core type, `None` when ELF cores are not supported for it
+    pub fn for_core_type(core_type: CoreType) -> Option<Self> {
+        match core_type {
+            CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => Some(CoreMachine::Arm),
+            CoreType::Riscv => Some(CoreMachine::RiscV32),
+            _ => None,
+        }
+    }
+
+    fn e_machine(&self) -> u16 {
+        match self {
+            CoreMachine::Arm => 40,
+            CoreMachine::RiscV32 => 243,
+        }
+    }
+
+    /// probe-rs registers making up `pr_reg`, in order
+    pub fn prstatus_registers(&self) -> Vec<RegisterId> {
+        match self {
+            // R0-R15 then xPSR, which stands in for CPSR
+            CoreMachine::Arm => (0..=16).map(RegisterId).collect(),
+            // PC then x1-x31
+            CoreMachine::RiscV32 => std::iter::once(RegisterId(0x7b1)).chain((0x1001..=0x101f).map(RegisterId)).collect(),
+        }
+    }
+
+    /// Descriptor of the `NT_PRSTATUS` note for register values in `prstatus_registers` order
+    fn prstatus(&self, values: &[u32]) -> Vec<u8> {
+        let mut registers = values.to_vec();
+        if *self == CoreMachine::Arm {
+            // GDB takes the Thumb state from the CPSR T bit (bit 5), which xPSR keeps in bit 24
+            if registers[16] & (1 << 24) != 0 {
+                registers[16] |= 1 << 5;
+            }
+            // orig_r0
+            registers.push(registers[0]);
+        }
+
+        let mut descriptor = vec![0u8; PRSTATUS_HEADER_SIZE];
+        descriptor.extend(registers.iter().flat_map(|value| value.to_le_bytes()));
+        // pr_fpvalid
+        descriptor.extend(0u32.to_le_bytes());
+        descriptor
+    }
+}
+
+/// Build an ELF32 core file: a note segment with the registers and the
+/// metadata, then one loadable segment per memory region.
+///
+/// `registers` are in `machine.prstatus_registers()` order.
+pub fn elf_core(machine: CoreMachine, registers: &[u32], regions: &[(u64, Vec<u8>)], metadata: &[(&str, String)]) -> Result<Vec<u8>> {
+    if registers.len() != machine.prstatus_registers().len() {
+        return Err(DebugError::InternalError(format!(
+            "Expected {} registers for the core file, got {}",
+            machine.prstatus_registers().len(), registers.len()
+        )));
+    }
+    for (address, data) in regions {
+        if address.saturating_add(data.len() as u64) > 1 << 32 {
+            return Err(DebugError::InvalidAddress {
+                address: *address,
+                reason: "range does not fit in a 32-bit core file".to_string(),
+            });
+        }
+    }
+
+    let mut notes = Vec::new();
+    push_note(&mut notes, "CORE", NT_PRSTATUS, &machine.prstatus(registers));
+    let text: String = metadata.iter().map(|(key, value)| format!("{}={}\n", key, value)).collect();
+    push_note(&mut notes, METADATA_NOTE_NAME, NT_METADATA, text.as_bytes());
+
+    let segment_count = 1 + regions.len();
+    let notes_offset = ELF_HEADER_SIZE + PROGRAM_HEADER_SIZE * segment_count;
+    let mut data_offset = notes_offset + notes.len();
+
+    let mut file = Vec::with_capacity(data_offset + regions.iter().map(|(_, data)| data.len()).sum::<usize>());
+    file.extend([0x7F, b'E', b'L', b'F', 1, 1, 1, 0]);
+    file.extend([0u8; 8]);
+    push_u16(&mut file, 4); // ET_CORE
+    push_u16(&mut file, machine.e_machine());
+    push_u32(&mut file, 1); // EV_CURRENT
+    push_u32(&mut file, 0); // e_entry
+    push_u32(&mut file, ELF_HEADER_SIZE as u32); // e_phoff
+    push_u32(&mut file, 0); // e_shoff
+    push_u32(&mut file, 0); // e_flags
+    push_u16(&mut file, ELF_HEADER_SIZE as u16);
+    push_u16(&mut file, PROGRAM_HEADER_SIZE as u16);
+    push_u16(&mut file, segment_count as u16);
+    push_u16(&mut file, 40); // e_shentsize
+    push_u16(&mut file, 0); // e_shnum
+    push_u16(&mut file, 0); // e_shstrndx
+
+    push_program_header(&mut file, PT_NOTE, notes_offset, 0, notes.len(), 0, 4);
+    for (address, data) in regions {
+        push_program_header(&mut file, PT_LOAD, data_offset, *address as u32, data.len(), PF_RW, 1);
+        data_offset += data.len();
+    }
+    file.extend(notes);
+    for (_, data) in regions {
+        file.extend(data);
+    }
+    Ok(file)
+}
+
+fn push_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend(value.to_le_bytes());
+}
+
+fn push_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend(value.to_le_bytes());
+}
+
+fn push_program_header(out: &mut Vec<u8>, kind: u32, offset: usize, address: u32, size: usize, flags: u32, align: u32) {
+    push_u32(out, kind);
+    push_u32(out, offset as u32);
+    push_u32(out, address); // p_vaddr
+    push_u32(out, address); // p_paddr
+    push_u32(out, size as u32); // p_filesz
+    push_u32(out, size as u32); // p_memsz
+    push_u32(out, flags);
+    push_u32(out, align);
+}
+
+/// Append an ELF note, padding name and descriptor to 4 bytes
+fn push_note(out: &mut Vec<u8>, name: &str, kind: u32, descriptor: &[u8]) {
+    push_u32(out, name.len() as u32 + 1);
+    push_u32(out, descriptor.len() as u32);
+    push_u32(out, kind);
+    out.extend(name.as_bytes());
+    out.push(0);
+    out.resize(out.len().next_multiple_of(4), 0);
+    out.extend(descriptor);
+    out.resize(out.len().next_multiple_of(4), 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elf_core_layout() {
+        let mut registers: Vec<u32> = (0..17).collect();
+        registers[15] = 0x0800_0124;
+        registers[16] = 0x0100_0003;
+        let regions = vec![(0x2000_0000, vec![0xAA; 8]), (0x2000_1000, vec![0x55; 4])];
+        let metadata = [("chip", "STM32F407VGTx".to_string())];
+        let core = elf_core(CoreMachine::Arm, &registers, &regions, &metadata).unwrap();
+
+        let elf = goblin::elf::Elf::parse(&core).unwrap();
+        assert_eq!(elf.header.e_type, goblin::elf::header::ET_CORE);
+        assert_eq!(elf.header.e_machine, goblin::elf::header::EM_ARM);
+        assert!(!elf.is_64);
+
+        let loads: Vec<_> = elf.program_headers.iter().filter(|ph| ph.p_type == PT_LOAD).collect();
+        assert_eq!(loads.len(), 2);
+        assert_eq!(loads[1].p_vaddr, 0x2000_1000);
+        assert_eq!(&core[loads[1].file_range()], &[0x55; 4]);
+
+        let notes: Vec<_> = elf.iter_note_headers(&core).unwrap().map(|note| note.unwrap()).collect();
+        assert_eq!(notes[0].name, "CORE");
+        assert_eq!(notes[0].n_type, NT_PRSTATUS);
+        // 18 registers after the header, PC at index 15, CPSR with the Thumb bit set
+        let pr_reg = &notes[0].desc[PRSTATUS_HEADER_SIZE..];
+        assert_eq!(notes[0].desc.len(), PRSTATUS_HEADER_SIZE + 18 * 4 + 4);
+        assert_eq!(u32::from_le_bytes(pr_reg[60..64].try_into().unwrap()), 0x0800_0124);
+        assert_eq!(u32::from_le_bytes(pr_reg[64..68].try_into().unwrap()), 0x0100_0023);
+        assert_eq!(notes[1].name, METADATA_NOTE_NAME);
+        assert_eq!(notes[1].desc, b"chip=STM32F407VGTx\n");
+
+        assert!(elf_core(CoreMachine::Arm, &registers[..4], &regions, &metadata).is_err());
+        assert!(elf_core(CoreMachine::Arm, &registers, &[(0xFFFF_FFFC, vec![0; 8])], &metadata).is_err());
+    }
+}
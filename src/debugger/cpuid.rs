@@ -0,0 +1,84 @@
+//! Cortex-M CPUID register decoding
+
+/// CPUID Base Register in the System Control Block
+pub const CPUID: u64 = 0xE000_ED00;
+
+/// ARM Ltd implementer code
+const IMPLEMENTER_ARM: u8 = 0x41;
+
+/// Decoded fields of the CPUID register
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuId {
+    pub raw: u32,
+    pub implementer: u8,
+    /// Major revision, the `r` of rNpM
+    pub variant: u8,
+    /// Architecture field: 0xC for ARMv6-M and ARMv8-M Baseline, 0xF for ARMv7-M and ARMv8-M Mainline
+    pub architecture: u8,
+    pub part_number: u16,
+    /// Minor revision, the `p` of rNpM
+    pub revision: u8,
+    /// Core name for a known ARM part number, e.g. "Cortex-M4"
+    pub core: Option<&'static str>,
+}
+
+impl CpuId {
+    /// Split a raw CPUID value into its fields
+    pub fn decode(raw: u32) -> Self {
+        let implementer = (raw >> 24) as u8;
+        let part_number = ((raw >> 4) & 0xFFF) as u16;
+        let core = if implementer == IMPLEMENTER_ARM { core_name(part_number) } else { None };
+        CpuId {
+            raw,
+            implementer,
+            variant: ((raw >> 20) & 0xF) as u8,
+            architecture: ((raw >> 16) & 0xF) as u8,
+            part_number,
+            revision: (raw & 0xF) as u8,
+            core,
+        }
+    }
+
+    /// Revision in ARM's rNpM notation
+    pub fn revision_name(&self) -> String {
+        format!("r{}p{}", self.variant, self.revision)
+    }
+}
+
+fn core_name(part_number: u16) -> Option<&'static str> {
+    Some(match part_number {
+        0xC20 => "Cortex-M0",
+        0xC60 => "Cortex-M0+",
+        0xC21 => "Cortex-M1",
+        0xC23 => "Cortex-M3",
+        0xC24 => "Cortex-M4",
+        0xC27 => "Cortex-M7",
+        0xD20 => "Cortex-M23",
+        0xD21 => "Cortex-M33",
+        0xD31 => "Cortex-M35P",
+        0xD22 => "Cortex-M55",
+        0xD23 => "Cortex-M85",
+        0xD24 => "Cortex-M52",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_cpuid() {
+        let m4 = CpuId::decode(0x410F_C241);
+        assert_eq!(m4.core, Some("Cortex-M4"));
+        assert_eq!(m4.implementer, 0x41);
+        assert_eq!(m4.architecture, 0xF);
+        assert_eq!(m4.revision_name(), "r0p1");
+
+        assert_eq!(CpuId::decode(0x410C_C601).core, Some("Cortex-M0+"));
+        assert_eq!(CpuId::decode(0x411F_C271).revision_name(), "r1p1");
+        // Unknown parts and other implementers are left undecoded
+        assert_eq!(CpuId::decode(0x410F_C990).core, None);
+        assert_eq!(CpuId::decode(0x510F_C240).core, None);
+    }
+}
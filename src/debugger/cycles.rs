@@ -0,0 +1,124 @@
+//! Timing with the Cortex-M DWT cycle counter
+
+use probe_rs::{Core, MemoryInterface};
+
+use crate::error::{DebugError, Result};
+use super::watchpoints::{DEMCR, DEMCR_TRCENA, DWT_CTRL};
+
+/// Cycle Count Register, counting core clock cycles while the core runs
+const DWT_CYCCNT: u64 = 0xE000_1004;
+const DWT_CTRL_CYCCNTENA: u32 = 1 << 0;
+/// Set on cores built without the cycle counter
+const DWT_CTRL_NOCYCCNT: u32 = 1 << 25;
+
+/// Cycles after which the 32-bit counter wraps
+pub const CYCCNT_PERIOD: u64 = 1 << 32;
+
+/// Cycles between two counter samples
+#[derive(Debug, Clone)]
+pub struct CycleMeasurement {
+    pub start_cycles: u32,
+    pub end_cycles: u32,
+    /// Cycles elapsed, assuming the counter wrapped at most once
+    pub cycles: u64,
+    /// Whether the counter wrapped between the samples
+    pub wrapped: bool,
+    /// Whether the host saw enough time pass for the counter to wrap more
+    /// than once, so `cycles` may be short by multiples of 2^32
+    pub ambiguous: bool,
+    /// Duration at the given CPU clock
+    pub microseconds: Option<f64>,
+}
+
+impl CycleMeasurement {
+    /// Measurement between two samples, `host_elapsed` being an upper bound on
+    /// the time the core ran in between
+    pub fn new(start_cycles: u32, end_cycles: u32, host_elapsed: std::time::Duration, cpu_hz: Option<u64>) -> Self {
+        let wrapped = end_cycles < start_cycles;
+        let cycles = u64::from(end_cycles.wrapping_sub(start_cycles));
+        let ambiguous = cpu_hz.is_some_and(|hz| host_elapsed.as_secs_f64() * hz as f64 >= CYCCNT_PERIOD as f64);
+        CycleMeasurement {
+            start_cycles,
+            end_cycles,
+            cycles,
+            wrapped,
+            ambiguous,
+            microseconds: cpu_hz.map(|hz| cycles_to_microseconds(cycles, hz)),
+        }
+    }
+}
+
+/// Duration of `cycles` at `cpu_hz`
+pub fn cycles_to_microseconds(cycles: u64, cpu_hz: u64) -> f64 {
+    cycles as f64 * 1_000_000.0 / cpu_hz.max(1) as f64
+}
+
+fn dwt_error(e: probe_rs::Error) -> DebugError {
+    DebugError::InternalError(format!("DWT access failed: {}", e))
+}
+
+/// Fail unless the core has a DWT cycle counter
+fn ensure_cycle_counter(core: &mut Core<'_>) -> Result<()> {
+    if !core.core_type().is_cortex_m() {
+        return Err(DebugError::TargetNotSupported(format!(
+            "The cycle counter is part of the Cortex-M DWT unit; not available on {:?} cores", core.core_type()
+        )));
+    }
+    let demcr = core.read_word_32(DEMCR).map_err(dwt_error)?;
+    if demcr & DEMCR_TRCENA == 0 {
+        core.write_word_32(DEMCR, demcr | DEMCR_TRCENA).map_err(dwt_error)?;
+    }
+    let ctrl = core.read_word_32(DWT_CTRL).map_err(dwt_error)?;
+    if ctrl & DWT_CTRL_NOCYCCNT != 0 {
+        return Err(DebugError::TargetNotSupported(
+            "This core has no DWT cycle counter (ARMv6-M cores and some ARMv8-M baseline cores lack it)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Enable the cycle counter, powering the DWT first. Returns the current count.
+pub fn enable(core: &mut Core<'_>) -> Result<u32> {
+    ensure_cycle_counter(core)?;
+    let ctrl = core.read_word_32(DWT_CTRL).map_err(dwt_error)?;
+    if ctrl & DWT_CTRL_CYCCNTENA == 0 {
+        core.write_word_32(DWT_CTRL, ctrl | DWT_CTRL_CYCCNTENA).map_err(dwt_error)?;
+    }
+    core.read_word_32(DWT_CYCCNT).map_err(dwt_error)
+}
+
+/// Read the counter and whether it is counting
+pub fn read(core: &mut Core<'_>) -> Result<(u32, bool)> {
+    ensure_cycle_counter(core)?;
+    let enabled = core.read_word_32(DWT_CTRL).map_err(dwt_error)? & DWT_CTRL_CYCCNTENA != 0;
+    Ok((core.read_word_32(DWT_CYCCNT).map_err(dwt_error)?, enabled))
+}
+
+/// Set the counter back to zero
+pub fn reset(core: &mut Core<'_>) -> Result<()> {
+    ensure_cycle_counter(core)?;
+    core.write_word_32(DWT_CYCCNT, 0).map_err(dwt_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cycle_measurement_wraparound() {
+        let plain = CycleMeasurement::new(1_000, 169_000, Duration::from_millis(5), Some(168_000_000));
+        assert_eq!(plain.cycles, 168_000);
+        assert!(!plain.wrapped && !plain.ambiguous);
+        assert_eq!(plain.microseconds, Some(1000.0));
+
+        let wrapped = CycleMeasurement::new(0xFFFF_FF00, 0x100, Duration::from_millis(5), None);
+        assert_eq!(wrapped.cycles, 0x200);
+        assert!(wrapped.wrapped);
+        assert_eq!(wrapped.microseconds, None);
+
+        // 2^32 cycles take about 25.6 s at 168 MHz
+        let long = CycleMeasurement::new(10, 20, Duration::from_secs(30), Some(168_000_000));
+        assert!(long.ambiguous);
+    }
+}
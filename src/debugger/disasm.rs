@@ -0,0 +1,132 @@
+//! Instruction disassembly of target code through Capstone
+
+use capstone::prelude::*;
+use probe_rs::{Core, InstructionSet};
+
+use crate::error::{DebugError, Result};
+
+/// Longest instruction of any supported instruction set, in bytes
+pub const MAX_INSTRUCTION_SIZE: u64 = 4;
+
+/// Instruction set to decode with, chosen from the core
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisasmMode {
+    /// Thumb-2, with the M-profile system instructions on Cortex-M
+    Thumb { m_profile: bool },
+    Arm,
+    Arm64,
+}
+
+impl std::fmt::Display for DisasmMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisasmMode::Thumb { m_profile: true } => write!(f, "Thumb-2 (Cortex-M)"),
+            DisasmMode::Thumb { m_profile: false } => write!(f, "Thumb-2"),
+            DisasmMode::Arm => write!(f, "A32"),
+            DisasmMode::Arm64 => write!(f, "A64"),
+        }
+    }
+}
+
+/// One decoded instruction
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub operands: String,
+    /// Enclosing symbol and the offset into it, when symbols are loaded
+    pub symbol: Option<(String, u64)>,
+    /// Destination of a direct branch, with its symbol when known
+    pub branch_target: Option<(u64, Option<String>)>,
+}
+
+/// Pick the decoder for the instruction set the core is currently executing
+pub fn mode_for(core: &mut Core<'_>) -> Result<DisasmMode> {
+    let instruction_set = core.instruction_set()
+        .map_err(|e| DebugError::InternalError(format!("Failed to read the instruction set: {}", e)))?;
+    match instruction_set {
+        InstructionSet::Thumb2 => Ok(DisasmMode::Thumb { m_profile: core.core_type().is_cortex_m() }),
+        InstructionSet::A32 => Ok(DisasmMode::Arm),
+        InstructionSet::A64 => Ok(DisasmMode::Arm64),
+        other => Err(DebugError::TargetNotSupported(format!(
+            "Disassembly of {:?} code is not supported; the bundled Capstone only decodes ARM instruction sets",
+            other
+        ))),
+    }
+}
+
+/// Decode `code` loaded at `address`, stopping at the first invalid instruction
+pub fn decode(mode: DisasmMode, code: &[u8], address: u64) -> Result<Vec<Instruction>> {
+    let capstone = match mode {
+        DisasmMode::Thumb { m_profile } => {
+            let extra = m_profile.then_some(arch::arm::ArchExtraMode::MClass);
+            Capstone::new().arm().mode(arch::arm::ArchMode::Thumb).extra_mode(extra.into_iter()).build()
+        }
+        DisasmMode::Arm => Capstone::new().arm().mode(arch::arm::ArchMode::Arm).build(),
+        DisasmMode::Arm64 => Capstone::new().arm64().mode(arch::arm64::ArchMode::Arm).build(),
+    }
+    .map_err(|e| DebugError::InternalError(format!("Failed to set up the disassembler: {}", e)))?;
+
+    let decoded = capstone.disasm_all(code, address)
+        .map_err(|e| DebugError::InternalError(format!("Disassembly at 0x{:08X} failed: {}", address, e)))?;
+
+    Ok(decoded
+        .iter()
+        .map(|insn| {
+            let mnemonic = insn.mnemonic().unwrap_or("").to_string();
+            let operands = insn.op_str().unwrap_or("").to_string();
+            Instruction {
+                address: insn.address(),
+                bytes: insn.bytes().to_vec(),
+                branch_target: branch_target(&mnemonic, &operands).map(|target| (target, None)),
+                mnemonic,
+                operands,
+                symbol: None,
+            }
+        })
+        .collect())
+}
+
+/// Immediate destination of a direct branch, e.g. `bl #0x8000124` or `cbz r0, #0x8000010`
+fn branch_target(mnemonic: &str, operands: &str) -> Option<u64> {
+    let is_branch = mnemonic.starts_with('b') && !mnemonic.starts_with("bic") && !mnemonic.starts_with("bfc")
+        && !mnemonic.starts_with("bfi") && !mnemonic.starts_with("bkpt")
+        || mnemonic.starts_with("cbz")
+        || mnemonic.starts_with("cbnz");
+    if !is_branch {
+        return None;
+    }
+    let immediate = operands.rsplit(',').next()?.trim().strip_prefix('#')?;
+    u64::from_str_radix(immediate.strip_prefix("0x")?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_thumb_mix() {
+        // nop; bl (4 bytes); bx lr
+        let code = [0x00, 0xBF, 0x00, 0xF0, 0x02, 0xF8, 0x70, 0x47];
+        let instructions = decode(DisasmMode::Thumb { m_profile: true }, &code, 0x0800_0100).unwrap();
+
+        let addresses: Vec<u64> = instructions.iter().map(|insn| insn.address).collect();
+        assert_eq!(addresses, vec![0x0800_0100, 0x0800_0102, 0x0800_0106]);
+        assert_eq!(instructions[0].mnemonic, "nop");
+        assert_eq!(instructions[1].mnemonic, "bl");
+        assert_eq!(instructions[1].bytes.len(), 4);
+        assert_eq!(instructions[1].branch_target, Some((0x0800_010A, None)));
+        assert_eq!(instructions[2].mnemonic, "bx");
+        assert_eq!(instructions[2].branch_target, None);
+    }
+
+    #[test]
+    fn test_branch_target() {
+        assert_eq!(branch_target("b.w", "#0x8000124"), Some(0x0800_0124));
+        assert_eq!(branch_target("cbz", "r0, #0x8000010"), Some(0x0800_0010));
+        assert_eq!(branch_target("bx", "lr"), None);
+        assert_eq!(branch_target("bic", "r0, r0, #0x10"), None);
+        assert_eq!(branch_target("movs", "r0, #0x10"), None);
+    }
+}
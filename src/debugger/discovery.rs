@@ -1,10 +1,17 @@
 //! Debug probe discovery and enumeration
 
 use probe_rs::probe::list::Lister;
+use probe_rs::probe::DebugProbeInfo;
 use crate::error::{DebugError, Result};
 use crate::utils::ProbeType;
 use tracing::{debug, info, warn};
 
+/// Time a probe needs to re-enumerate after a USB reset
+pub const PROBE_RESET_SETTLE: std::time::Duration = std::time::Duration::from_millis(1000);
+
+/// Reported when a probe exposes no version
+pub const UNKNOWN_VERSION: &str = "unknown";
+
 /// Information about discovered debug probe
 #[derive(Debug, Clone)]
 pub struct ProbeInfo {
@@ -14,9 +21,25 @@ pub struct ProbeInfo {
     pub serial_number: Option<String>,
     pub probe_type: String,
     pub speed_khz: u32,
+    /// Firmware release from the USB device descriptor, if the probe was found on the bus
     pub version: Option<String>,
 }
 
+impl ProbeInfo {
+    /// Identity of `probe` as reported to clients, with the type derived from its USB IDs
+    pub fn new(probe: &DebugProbeInfo, speed_khz: u32, version: Option<String>) -> Self {
+        ProbeInfo {
+            identifier: probe.identifier.clone(),
+            vendor_id: probe.vendor_id,
+            product_id: probe.product_id,
+            serial_number: probe.serial_number.clone(),
+            probe_type: ProbeType::from_vid_pid(probe.vendor_id, probe.product_id).to_string(),
+            speed_khz,
+            version,
+        }
+    }
+}
+
 /// Debug probe discovery utility
 pub struct ProbeDiscovery;
 
@@ -30,19 +53,8 @@ impl ProbeDiscovery {
         let probes = lister
             .list_all()
             .into_iter()
-            .map(|probe_info| {
-                let probe_type = ProbeType::from_vid_pid(probe_info.vendor_id, probe_info.product_id);
-                
-                ProbeInfo {
-                    identifier: probe_info.identifier.clone(),
-                    vendor_id: probe_info.vendor_id,
-                    product_id: probe_info.product_id,
-                    serial_number: probe_info.serial_number.clone(),
-                    probe_type: probe_type.to_string(),
-                    speed_khz: 4000, // Default speed
-                    version: Some("USB".to_string()),
-                }
-            })
+            // Default speed
+            .map(|probe_info| ProbeInfo::new(&probe_info, 4000, Self::firmware_version(&probe_info)))
             .collect::<Vec<_>>();
 
         info!("Found {} debug probes", probes.len());
@@ -170,6 +182,63 @@ impl ProbeDiscovery {
             .ok_or_else(|| DebugError::ProbeNotFound(format!("Probe not found: {}", identifier)))
     }
 
+    /// Whether opening a probe failed because another process or driver still holds it
+    pub fn is_probe_busy(error: &(dyn std::error::Error + 'static)) -> bool {
+        let mut current = Some(error);
+        while let Some(error) = current {
+            if error.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::ResourceBusy) {
+                return true;
+            }
+            let text = error.to_string().to_lowercase();
+            if ["busy", "in use", "already attached", "already claimed"].iter().any(|marker| text.contains(marker)) {
+                return true;
+            }
+            current = error.source();
+        }
+        false
+    }
+
+    /// USB device of a probe, matched by VID, PID and serial number
+    fn usb_device(probe: &DebugProbeInfo) -> Result<Option<nusb::DeviceInfo>> {
+        Ok(nusb::list_devices()
+            .map_err(|e| DebugError::ConnectionFailed(format!("Failed to list USB devices: {}", e)))?
+            .find(|device| {
+                device.vendor_id() == probe.vendor_id
+                    && device.product_id() == probe.product_id
+                    && (probe.serial_number.is_none() || device.serial_number() == probe.serial_number.as_deref())
+            }))
+    }
+
+    /// Firmware release of a probe, as its USB device descriptor reports it.
+    ///
+    /// probe-rs reads the J-Link and ST-Link protocol versions only for its
+    /// own use, so the descriptor's `bcdDevice` is the version available
+    /// without opening the probe. DAPLink and most CMSIS-DAP firmware bump it
+    /// with each release.
+    pub fn firmware_version(probe: &DebugProbeInfo) -> Option<String> {
+        match Self::usb_device(probe) {
+            Ok(device) => device.map(|device| format_bcd_version(device.device_version())),
+            Err(e) => {
+                debug!("No firmware version for probe '{}': {}", probe.identifier, e);
+                None
+            }
+        }
+    }
+
+    /// Reset the USB device of a probe, releasing a claim left behind by a
+    /// crashed process. The probe re-enumerates afterwards and has to be
+    /// opened again.
+    pub fn reset_probe_usb(probe: &DebugProbeInfo) -> Result<()> {
+        let device_info = Self::usb_device(probe)?
+            .ok_or_else(|| DebugError::ProbeNotFound(format!("No USB device found for probe '{}'", probe.identifier)))?;
+
+        info!("Resetting USB device of probe '{}'", probe.identifier);
+        let device = device_info.open()
+            .map_err(|e| DebugError::ConnectionFailed(format!("Failed to open the USB device of '{}': {}", probe.identifier, e)))?;
+        device.reset()
+            .map_err(|e| DebugError::ConnectionFailed(format!("USB reset of '{}' failed: {}", probe.identifier, e)))
+    }
+
     /// Check if a probe supports a specific target
     pub fn check_target_support(probe_type: &ProbeType, target_chip: &str) -> bool {
         match probe_type {
@@ -207,6 +276,11 @@ impl ProbeDiscovery {
     }
 }
 
+/// Format a USB BCD release number such as 0x0221 as "2.21"
+pub fn format_bcd_version(bcd: u16) -> String {
+    format!("{:x}.{:02x}", bcd >> 8, bcd & 0xFF)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +293,34 @@ mod tests {
         assert!(!ProbeDiscovery::check_target_support(&ProbeType::StLink, "ESP32"));
     }
 
+    #[test]
+    fn test_probe_info_from_usb_ids() {
+        let probe = DebugProbeInfo::new("STLink V2-1", 0x0483, 0x374B, Some("003A00".to_string()), &probe_rs::probe::stlink::StLinkFactory, None);
+        let info = ProbeInfo::new(&probe, 8000, Some("2.21".to_string()));
+        assert_eq!(info.identifier, "STLink V2-1");
+        assert_eq!((info.vendor_id, info.product_id), (0x0483, 0x374B));
+        assert_eq!(info.serial_number.as_deref(), Some("003A00"));
+        assert_eq!(info.probe_type, ProbeType::StLink.to_string());
+        assert_eq!((info.speed_khz, info.version.as_deref()), (8000, Some("2.21")));
+    }
+
+    #[test]
+    fn test_format_bcd_version() {
+        assert_eq!(format_bcd_version(0x0221), "2.21");
+        assert_eq!(format_bcd_version(0x1000), "10.00");
+    }
+
+    #[test]
+    fn test_is_probe_busy() {
+        let busy = probe_rs::probe::DebugProbeError::ProbeCouldNotBeCreated(
+            probe_rs::probe::ProbeCreationError::Usb(std::io::Error::from(std::io::ErrorKind::ResourceBusy)),
+        );
+        assert!(ProbeDiscovery::is_probe_busy(&busy));
+
+        let missing = probe_rs::probe::DebugProbeError::ProbeCouldNotBeCreated(probe_rs::probe::ProbeCreationError::NotFound);
+        assert!(!ProbeDiscovery::is_probe_busy(&missing));
+    }
+
     #[tokio::test]
     async fn test_list_probes() {
         // This test will only pass if debug probes are connected
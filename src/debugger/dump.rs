@@ -0,0 +1,161 @@
+//! Encoding of target memory images into host file formats
+
+use std::fmt::Write;
+
+use crate::error::{DebugError, Result};
+
+/// Data bytes per Intel HEX / S-record line
+const BYTES_PER_RECORD: usize = 16;
+
+/// Host file format for memory dumps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Raw binary image
+    Binary,
+    /// Intel HEX (I32HEX)
+    IntelHex,
+    /// Motorola S-record (S3 data records)
+    SRecord,
+}
+
+impl DumpFormat {
+    /// Parse a format name: "bin", "ihex" or "srec"
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "bin" | "binary" | "raw" => Ok(DumpFormat::Binary),
+            "ihex" | "hex" | "intel-hex" => Ok(DumpFormat::IntelHex),
+            "srec" | "s19" | "s28" | "s37" | "mot" => Ok(DumpFormat::SRecord),
+            _ => Err(DebugError::InvalidConfig(format!(
+                "Unsupported dump format '{}'. Use 'bin', 'ihex' or 'srec'",
+                format
+            ))),
+        }
+    }
+
+    /// Encode `data` located at `address` in this format
+    pub fn encode(&self, address: u64, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            DumpFormat::Binary => Ok(data.to_vec()),
+            DumpFormat::IntelHex => encode_ihex(address, data).map(String::into_bytes),
+            DumpFormat::SRecord => encode_srec(address, data).map(String::into_bytes),
+        }
+    }
+}
+
+impl std::fmt::Display for DumpFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DumpFormat::Binary => write!(f, "bin"),
+            DumpFormat::IntelHex => write!(f, "ihex"),
+            DumpFormat::SRecord => write!(f, "srec"),
+        }
+    }
+}
+
+fn check_32bit_range(address: u64, len: usize) -> Result<()> {
+    if address.saturating_add(len as u64) > 1 << 32 {
+        return Err(DebugError::InvalidAddress {
+            address,
+            reason: "range does not fit in a 32-bit address space".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Encode as Intel HEX with extended linear address records
+fn encode_ihex(address: u64, data: &[u8]) -> Result<String> {
+    check_32bit_range(address, data.len())?;
+
+    let mut records = Vec::new();
+    let mut upper = None;
+    let mut offset = 0;
+    while offset < data.len() {
+        let current = address + offset as u64;
+        if upper != Some(current >> 16) {
+            upper = Some(current >> 16);
+            records.push(ihex::Record::ExtendedLinearAddress((current >> 16) as u16));
+        }
+        // Lines never cross a 64 KiB segment boundary
+        let segment_left = 0x1_0000 - (current & 0xFFFF) as usize;
+        let len = BYTES_PER_RECORD.min(segment_left).min(data.len() - offset);
+        records.push(ihex::Record::Data {
+            offset: (current & 0xFFFF) as u16,
+            value: data[offset..offset + len].to_vec(),
+        });
+        offset += len;
+    }
+    records.push(ihex::Record::EndOfFile);
+
+    ihex::create_object_file_representation(&records)
+        .map_err(|e| DebugError::InternalError(format!("Failed to encode Intel HEX: {}", e)))
+}
+
+/// Append one S-record line; `fields` is the address followed by the data
+fn push_srec(out: &mut String, record_type: u8, fields: &[u8]) {
+    let count = fields.len() as u8 + 1;
+    let sum = fields.iter().fold(count, |acc, b| acc.wrapping_add(*b));
+    let _ = write!(out, "S{}{:02X}", record_type, count);
+    for byte in fields {
+        let _ = write!(out, "{:02X}", byte);
+    }
+    let _ = writeln!(out, "{:02X}", !sum);
+}
+
+/// Encode as Motorola S-records with 32-bit addresses
+fn encode_srec(address: u64, data: &[u8]) -> Result<String> {
+    check_32bit_range(address, data.len())?;
+
+    let mut out = String::new();
+    push_srec(&mut out, 0, &[0, 0]);
+    for (index, line) in data.chunks(BYTES_PER_RECORD).enumerate() {
+        let line_address = (address as u32).wrapping_add((index * BYTES_PER_RECORD) as u32);
+        let mut fields = line_address.to_be_bytes().to_vec();
+        fields.extend_from_slice(line);
+        push_srec(&mut out, 3, &fields);
+    }
+    push_srec(&mut out, 7, &(address as u32).to_be_bytes());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_formats() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+
+        let hex = String::from_utf8(DumpFormat::IntelHex.encode(0x0800_0000, &data).unwrap()).unwrap();
+        assert_eq!(hex, ":020000040800F2\n:0400000001020304F2\n:00000001FF\n");
+
+        let srec = String::from_utf8(DumpFormat::SRecord.encode(0x0800_0000, &data).unwrap()).unwrap();
+        assert_eq!(srec, "S0030000FC\nS3090800000001020304E4\nS70508000000F2\n");
+
+        assert!(DumpFormat::SRecord.encode(0xFFFF_FFFF, &data).is_err());
+        assert_eq!(DumpFormat::parse("srec").unwrap(), DumpFormat::SRecord);
+        assert!(DumpFormat::parse("elf").is_err());
+    }
+
+    #[test]
+    fn test_ihex_round_trip_across_segments() {
+        // 40 bytes straddling the 0x0801_0000 segment boundary
+        let data: Vec<u8> = (0..40).collect();
+        let hex = String::from_utf8(DumpFormat::IntelHex.encode(0x0800_FFF0, &data).unwrap()).unwrap();
+
+        // The reader rejects records whose checksum does not match
+        let mut upper = 0u32;
+        let mut decoded = Vec::new();
+        for record in ihex::Reader::new(&hex) {
+            match record.unwrap() {
+                ihex::Record::ExtendedLinearAddress(value) => upper = (value as u32) << 16,
+                ihex::Record::Data { offset, value } => {
+                    assert_eq!(upper + offset as u32, 0x0800_FFF0 + decoded.len() as u32);
+                    decoded.extend(value);
+                }
+                ihex::Record::EndOfFile => break,
+                other => panic!("unexpected record {:?}", other),
+            }
+        }
+        assert_eq!(decoded, data);
+    }
+}
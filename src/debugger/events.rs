@@ -0,0 +1,83 @@
+//! Target events pushed to the client while the firmware runs
+
+/// Shortest interval between two checks of the event monitor
+pub const MIN_EVENT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// Something the target did that the client did not ask about
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetEvent {
+    /// The core stopped running
+    Halted {
+        core_index: usize,
+        reason: String,
+        pc: Option<u64>,
+        /// Whether the halt looks like a crash: an exception catch or a lockup
+        fault: bool,
+    },
+    /// Text arrived on an RTT up channel
+    RttData {
+        channel: u32,
+        text: String,
+    },
+}
+
+impl TargetEvent {
+    /// Notification payload for the client
+    pub fn to_json(&self, session_id: &str) -> serde_json::Value {
+        match self {
+            TargetEvent::Halted { core_index, reason, pc, fault } => serde_json::json!({
+                "session_id": session_id,
+                "event": "halted",
+                "core_index": core_index,
+                "reason": reason,
+                "pc": pc.map(|pc| format!("0x{:08X}", pc)),
+                "fault": fault,
+            }),
+            TargetEvent::RttData { channel, text } => serde_json::json!({
+                "session_id": session_id,
+                "event": "rtt_data",
+                "channel": channel,
+                "text": text,
+            }),
+        }
+    }
+}
+
+/// What an event monitor watches
+#[derive(Debug, Clone)]
+pub struct EventSubscription {
+    pub core_index: usize,
+    /// Whether RTT up channels are drained into `rtt_data` events
+    pub rtt: bool,
+    pub interval: std::time::Duration,
+}
+
+/// A running event monitor; the task is stopped when the monitor is dropped
+#[derive(Debug)]
+pub struct EventMonitor {
+    pub subscription: EventSubscription,
+    pub task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for EventMonitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_payload() {
+        let halted = TargetEvent::Halted { core_index: 0, reason: "Breakpoint".to_string(), pc: Some(0x0800_0100), fault: false };
+        let json = halted.to_json("session_1");
+        assert_eq!(json["event"], "halted");
+        assert_eq!(json["pc"], "0x08000100");
+        assert_eq!(json["session_id"], "session_1");
+
+        let data = TargetEvent::RttData { channel: 0, text: "boot\n".to_string() };
+        assert_eq!(data.to_json("session_1")["text"], "boot\n");
+    }
+}
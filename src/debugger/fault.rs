@@ -0,0 +1,180 @@
+//! Cortex-M fault analysis: SCB fault status registers and the stacked exception frame
+
+use serde::Serialize;
+
+/// Interrupt Control and State Register, whose VECTACTIVE field is the active exception
+pub const ICSR: u64 = 0xE000_ED04;
+/// Configurable Fault Status Register (MMFSR, BFSR and UFSR)
+pub const CFSR: u64 = 0xE000_ED28;
+/// HardFault Status Register
+pub const HFSR: u64 = 0xE000_ED2C;
+/// MemManage Fault Address Register
+pub const MMFAR: u64 = 0xE000_ED34;
+/// BusFault Address Register
+pub const BFAR: u64 = 0xE000_ED38;
+
+const MMARVALID: u32 = 1 << 7;
+const BFARVALID: u32 = 1 << 15;
+
+/// CFSR bits other than the address-valid flags, with their meaning
+const CFSR_BITS: &[(u32, &str)] = &[
+    (1 << 0, "MemManage: instruction fetch from a non-executable region (IACCVIOL)"),
+    (1 << 3, "MemManage: unstacking on exception return violated the MPU (MUNSTKERR)"),
+    (1 << 4, "MemManage: stacking on exception entry violated the MPU (MSTKERR)"),
+    (1 << 5, "MemManage: lazy floating-point state preservation violated the MPU (MLSPERR)"),
+    (1 << 8, "BusFault: instruction fetch bus error (IBUSERR)"),
+    (1 << 10, "BusFault: imprecise data bus error; the stacked PC is past the faulting store (IMPRECISERR)"),
+    (1 << 11, "BusFault: unstacking on exception return failed (UNSTKERR)"),
+    (1 << 12, "BusFault: stacking on exception entry failed, likely a stack overflow (STKERR)"),
+    (1 << 13, "BusFault: lazy floating-point state preservation failed (LSPERR)"),
+    (1 << 16, "UsageFault: undefined instruction (UNDEFINSTR)"),
+    (1 << 17, "UsageFault: invalid state, e.g. a branch to an even address without the Thumb bit (INVSTATE)"),
+    (1 << 18, "UsageFault: invalid EXC_RETURN on exception return (INVPC)"),
+    (1 << 19, "UsageFault: coprocessor access while it is disabled, e.g. FPU not enabled (NOCP)"),
+    (1 << 20, "UsageFault: stack limit register overflow (STKOF)"),
+    (1 << 24, "UsageFault: unaligned access with unaligned trapping enabled (UNALIGNED)"),
+    (1 << 25, "UsageFault: divide by zero with division trapping enabled (DIVBYZERO)"),
+];
+
+/// HFSR bits with their meaning
+const HFSR_BITS: &[(u32, &str)] = &[
+    (1 << 1, "HardFault: bus error reading the vector table (VECTTBL)"),
+    (1 << 30, "HardFault: escalated from a configurable fault that was disabled or could not run (FORCED)"),
+    (1 << 31, "HardFault: debug event while halting debug is disabled (DEBUGEVT)"),
+];
+
+/// Name of a fault exception number, as found in ICSR.VECTACTIVE or IPSR
+pub fn fault_exception_name(number: u32) -> Option<&'static str> {
+    Some(match number {
+        3 => "HardFault",
+        4 => "MemManage",
+        5 => "BusFault",
+        6 => "UsageFault",
+        7 => "SecureFault",
+        _ => return None,
+    })
+}
+
+/// Raw SCB fault registers, absent on ARMv6-M cores
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FaultStatus {
+    pub cfsr: u32,
+    pub hfsr: u32,
+    pub mmfar: u32,
+    pub bfar: u32,
+}
+
+impl FaultStatus {
+    /// Whether no fault bit is set
+    pub fn is_clear(&self) -> bool {
+        self.cfsr == 0 && self.hfsr == 0
+    }
+
+    /// One human-readable line per set fault bit
+    pub fn describe(&self) -> Vec<String> {
+        let mut faults = Vec::new();
+        if self.cfsr & (1 << 1) != 0 {
+            faults.push(match self.cfsr & MMARVALID != 0 {
+                true => format!("MemManage: data access violation at 0x{:08X}, MMFAR valid (DACCVIOL)", self.mmfar),
+                false => "MemManage: data access violation, MMFAR not valid (DACCVIOL)".to_string(),
+            });
+        }
+        if self.cfsr & (1 << 9) != 0 {
+            faults.push(match self.cfsr & BFARVALID != 0 {
+                true => format!("BusFault: precise bus fault at 0x{:08X}, BFAR valid (PRECISERR)", self.bfar),
+                false => "BusFault: precise bus fault, BFAR not valid (PRECISERR)".to_string(),
+            });
+        }
+        faults.extend(CFSR_BITS.iter().filter(|(bit, _)| self.cfsr & bit != 0).map(|(_, text)| text.to_string()));
+        faults.extend(HFSR_BITS.iter().filter(|(bit, _)| self.hfsr & bit != 0).map(|(_, text)| text.to_string()));
+        faults
+    }
+}
+
+/// Stack an exception frame was pushed to and whether it includes FP state,
+/// from the EXC_RETURN value in LR
+pub fn frame_stack(exc_return: u32) -> (&'static str, bool) {
+    let stack = if exc_return & (1 << 2) != 0 { "PSP" } else { "MSP" };
+    // FType clear means the frame was extended with S0-S15 and FPSCR
+    (stack, exc_return & (1 << 4) == 0)
+}
+
+/// Registers the core pushed on exception entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExceptionFrame {
+    /// "MSP" or "PSP"
+    pub stack: &'static str,
+    /// Address of the frame on that stack
+    pub address: u64,
+    /// Whether the frame also holds floating-point state after these registers
+    pub extended: bool,
+    pub r0: u32,
+    pub r1: u32,
+    pub r2: u32,
+    pub r3: u32,
+    pub r12: u32,
+    pub lr: u32,
+    pub pc: u32,
+    pub xpsr: u32,
+}
+
+impl ExceptionFrame {
+    /// Build a frame from the eight words at the stack pointer, in stacking order
+    pub fn from_words(words: &[u32; 8], stack: &'static str, address: u64, extended: bool) -> Self {
+        let [r0, r1, r2, r3, r12, lr, pc, xpsr] = *words;
+        ExceptionFrame { stack, address, extended, r0, r1, r2, r3, r12, lr, pc, xpsr }
+    }
+}
+
+/// Everything `analyze_fault` found out about the core's fault state
+#[derive(Debug, Clone, Serialize)]
+pub struct FaultReport {
+    /// Whether the core is executing a fault handler
+    pub in_fault: bool,
+    /// Active exception number from ICSR.VECTACTIVE, 0 in thread mode
+    pub active_exception: u32,
+    /// Name of the active fault exception
+    pub exception: Option<&'static str>,
+    /// Fault registers, `None` on ARMv6-M which only has HardFault
+    pub status: Option<FaultStatus>,
+    /// Decoded fault bits
+    pub faults: Vec<String>,
+    /// LR at the halt, when it still holds EXC_RETURN
+    pub exc_return: Option<u32>,
+    pub frame: Option<ExceptionFrame>,
+    /// Symbol and source line of the stacked PC, the faulting instruction
+    pub pc_symbol: Option<String>,
+    /// Symbol and source line of the stacked LR, the caller of the faulting function
+    pub lr_symbol: Option<String>,
+    /// Caveats about the analysis
+    pub notes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_fault_status() {
+        // Precise bus fault on a null pointer read, escalated to HardFault
+        let status = FaultStatus { cfsr: (1 << 9) | BFARVALID, hfsr: 1 << 30, mmfar: 0, bfar: 0 };
+        let faults = status.describe();
+        assert_eq!(faults.len(), 2);
+        assert!(faults[0].contains("precise bus fault at 0x00000000, BFAR valid"));
+        assert!(faults[1].contains("FORCED"));
+
+        let usage = FaultStatus { cfsr: (1 << 17) | (1 << 25), ..Default::default() };
+        assert_eq!(usage.describe().len(), 2);
+        assert!(FaultStatus::default().is_clear());
+        assert!(FaultStatus::default().describe().is_empty());
+    }
+
+    #[test]
+    fn test_frame_stack_from_exc_return() {
+        assert_eq!(frame_stack(0xFFFF_FFF9), ("MSP", false));
+        assert_eq!(frame_stack(0xFFFF_FFFD), ("PSP", false));
+        assert_eq!(frame_stack(0xFFFF_FFED), ("PSP", true));
+        assert_eq!(fault_exception_name(3), Some("HardFault"));
+        assert_eq!(fault_exception_name(15), None);
+    }
+}
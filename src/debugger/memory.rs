@@ -0,0 +1,725 @@
+//! Helpers for bulk target memory operations
+
+use std::ops::Range;
+use probe_rs::config::{MemoryRegion, RawFlashAlgorithm};
+
+use crate::error::{DebugError, Result};
+use crate::utils::{Endianness, TypedValue};
+
+/// Default chunk size for bulk memory transfers
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// Result of a memory fill
+#[derive(Debug)]
+pub struct FillResult {
+    pub bytes_written: usize,
+    pub pattern: Vec<u8>,
+    pub elapsed_ms: u64,
+}
+
+/// Result of a chunked memory read
+#[derive(Debug)]
+pub struct ReadResult {
+    pub data: Vec<u8>,
+    pub elapsed_ms: u64,
+    /// Whether the core was running when the read started
+    pub was_running: bool,
+    /// Set when a running read faulted and the core had to be halted
+    pub note: Option<String>,
+}
+
+impl ReadResult {
+    /// Effective transfer rate in kB/s
+    pub fn throughput_kbps(&self) -> f64 {
+        if self.elapsed_ms == 0 {
+            return 0.0;
+        }
+        self.data.len() as f64 / 1024.0 / (self.elapsed_ms as f64 / 1000.0)
+    }
+}
+
+/// One sample of a memory watch
+#[derive(Debug, Clone)]
+pub struct WatchSample {
+    /// Time since the first sample
+    pub elapsed_ms: u64,
+    pub data: Vec<u8>,
+    pub was_running: bool,
+    pub note: Option<String>,
+}
+
+/// Upper bound on samples taken by a single memory watch
+pub const MAX_WATCH_SAMPLES: usize = 1000;
+
+/// Result of a memory dump to a host file
+#[derive(Debug)]
+pub struct DumpResult {
+    pub bytes_read: usize,
+    pub file_size: usize,
+    pub elapsed_ms: u64,
+}
+
+/// Outcome of one entry of a batched write
+#[derive(Debug, PartialEq, Eq)]
+pub enum BatchWriteStatus {
+    Written,
+    Failed(String),
+    /// Not attempted because an earlier entry failed
+    Skipped,
+}
+
+/// Offsets at which the bytes read back differ from the bytes written
+pub fn mismatched_offsets(written: &[u8], read_back: &[u8]) -> Vec<usize> {
+    written.iter()
+        .zip(read_back)
+        .enumerate()
+        .filter(|(_, (expected, actual))| expected != actual)
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+/// Read `(address, size)` regions in order through `read`, each failing on its own.
+///
+/// With a `memory_map`, a region outside it fails without being read.
+pub fn read_batch(
+    requests: &[(u64, usize)],
+    memory_map: Option<&[MemoryRegion]>,
+    mut read: impl FnMut(u64, &mut [u8]) -> std::result::Result<(), String>,
+) -> Vec<Result<Vec<u8>>> {
+    requests
+        .iter()
+        .map(|&(address, size)| {
+            if let Some(memory_map) = memory_map {
+                validate_range(memory_map, address, size as u64)?;
+            }
+            let mut data = vec![0u8; size];
+            read(address, &mut data)
+                .map(|_| data)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Read at 0x{:08X} failed: {}", address, e)))
+        })
+        .collect()
+}
+
+/// Apply `writes` in order through `write`, skipping the rest after a failure when `stop_on_error` is set
+pub fn write_batch(
+    writes: &[Segment],
+    stop_on_error: bool,
+    mut write: impl FnMut(u64, &[u8]) -> std::result::Result<(), String>,
+) -> Vec<BatchWriteStatus> {
+    let mut failed = false;
+    writes
+        .iter()
+        .map(|(address, data)| {
+            if failed && stop_on_error {
+                return BatchWriteStatus::Skipped;
+            }
+            match write(*address, data) {
+                Ok(()) => BatchWriteStatus::Written,
+                Err(e) => {
+                    failed = true;
+                    BatchWriteStatus::Failed(e)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Digest algorithm for memory checksums
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// Parse an algorithm name: "crc32" or "sha256"
+    pub fn parse(algorithm: &str) -> Result<Self> {
+        match algorithm.to_lowercase().as_str() {
+            "crc32" => Ok(ChecksumAlgorithm::Crc32),
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            _ => Err(DebugError::InvalidConfig(format!(
+                "Unsupported checksum algorithm '{}'. Use 'crc32' or 'sha256'",
+                algorithm
+            ))),
+        }
+    }
+
+    /// Start an incremental digest
+    pub fn hasher(&self) -> Checksummer {
+        match self {
+            ChecksumAlgorithm::Crc32 => Checksummer::Crc32(crc32fast::Hasher::new()),
+            ChecksumAlgorithm::Sha256 => Checksummer::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+        }
+    }
+
+    /// Digest `data` in one go, as a lowercase hex string
+    pub fn digest(&self, data: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+impl std::fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumAlgorithm::Crc32 => write!(f, "crc32"),
+            ChecksumAlgorithm::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+/// Incremental digest state
+pub enum Checksummer {
+    Crc32(crc32fast::Hasher),
+    Sha256(sha2::Sha256),
+}
+
+impl Checksummer {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Checksummer::Crc32(hasher) => hasher.update(data),
+            Checksummer::Sha256(hasher) => sha2::Digest::update(hasher, data),
+        }
+    }
+
+    /// Finish the digest as a lowercase hex string
+    pub fn finalize(self) -> String {
+        match self {
+            Checksummer::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+            Checksummer::Sha256(hasher) => hex::encode(sha2::Digest::finalize(hasher)),
+        }
+    }
+}
+
+/// Result of a memory checksum
+#[derive(Debug)]
+pub struct ChecksumResult {
+    pub digest: String,
+    pub bytes_read: usize,
+    pub elapsed_ms: u64,
+}
+
+/// Value erased flash reads as when no flash algorithm of the target says otherwise
+pub const DEFAULT_ERASED_BYTE: u8 = 0xFF;
+
+/// Result of a flash blank check
+#[derive(Debug)]
+pub struct BlankCheckResult {
+    /// Lowest address not holding the erased value, if any
+    pub first_non_blank: Option<u64>,
+    pub erased_value: u8,
+    /// Bytes read before the check finished; stops early at the first non-blank byte
+    pub bytes_checked: usize,
+    pub elapsed_ms: u64,
+}
+
+impl BlankCheckResult {
+    pub fn is_blank(&self) -> bool {
+        self.first_non_blank.is_none()
+    }
+}
+
+/// Erased byte value of the flash algorithm covering `address`
+pub fn erased_byte_value(algorithms: &[RawFlashAlgorithm], address: u64) -> u8 {
+    algorithms.iter()
+        .find(|algorithm| algorithm.flash_properties.address_range.contains(&address))
+        .map(|algorithm| algorithm.flash_properties.erased_byte_value)
+        .unwrap_or(DEFAULT_ERASED_BYTE)
+}
+
+/// A block of reference data and the address it belongs at
+pub type Segment = (u64, Vec<u8>);
+
+/// A byte that differs between the target and the reference data
+#[derive(Debug)]
+pub struct CompareDiff {
+    pub address: u64,
+    /// Offset from the start of the reference segment
+    pub offset: usize,
+    pub expected: u8,
+    pub actual: u8,
+}
+
+/// Result of comparing target memory against reference data
+#[derive(Debug, Default)]
+pub struct CompareResult {
+    pub bytes_compared: usize,
+    pub mismatch_count: usize,
+    /// The first mismatches, up to the requested limit
+    pub diffs: Vec<CompareDiff>,
+    /// Set when a read failed partway; the counts cover the bytes compared before it
+    pub error: Option<String>,
+}
+
+impl CompareResult {
+    /// Whether every compared byte matched and the whole reference was compared
+    pub fn identical(&self) -> bool {
+        self.mismatch_count == 0 && self.error.is_none()
+    }
+
+    /// Count the bytes of one chunk read at `address`, `offset` bytes into its segment,
+    /// keeping at most `max_diffs` mismatches
+    pub fn add_chunk(&mut self, address: u64, offset: usize, expected: &[u8], actual: &[u8], max_diffs: usize) {
+        for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            if expected != actual {
+                self.mismatch_count += 1;
+                if self.diffs.len() < max_diffs {
+                    self.diffs.push(CompareDiff {
+                        address: address + i as u64,
+                        offset: offset + i,
+                        expected: *expected,
+                        actual: *actual,
+                    });
+                }
+            }
+        }
+        self.bytes_compared += expected.len();
+    }
+}
+
+/// Extract the loadable segments of an ELF image as `(load address, bytes)`.
+///
+/// Returns `None` when `data` is not an ELF file.
+pub fn elf_load_segments(data: &[u8]) -> Result<Option<Vec<Segment>>> {
+    if !data.starts_with(b"\x7FELF") {
+        return Ok(None);
+    }
+
+    let elf = goblin::elf::Elf::parse(data)
+        .map_err(|e| DebugError::InvalidConfig(format!("Failed to parse ELF file: {}", e)))?;
+
+    let segments = elf
+        .program_headers
+        .iter()
+        .filter(|ph| ph.p_type == goblin::elf::program_header::PT_LOAD && ph.p_filesz > 0)
+        .map(|ph| {
+            data.get(ph.file_range())
+                .map(|bytes| (ph.p_paddr, bytes.to_vec()))
+                .ok_or_else(|| DebugError::InvalidConfig("ELF segment extends past end of file".to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(segments))
+}
+
+/// Bytes of context reported on each side of a search match
+pub const SEARCH_CONTEXT_BYTES: usize = 8;
+
+/// A pattern match found by a memory search
+#[derive(Debug)]
+pub struct SearchMatch {
+    pub address: u64,
+    /// Address of the first context byte
+    pub context_address: u64,
+    /// Bytes surrounding the match, including the match itself
+    pub context: Vec<u8>,
+}
+
+/// Find the offsets of all (possibly overlapping) occurrences of `pattern` in `haystack`
+pub fn find_pattern(haystack: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.is_empty() || haystack.len() < pattern.len() {
+        return Vec::new();
+    }
+    haystack
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern)
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+/// Parse a 1, 2 or 4 byte fill pattern like "0xAA" or "0xDEADBEEF".
+///
+/// The width is taken from the number of hex digits and the value is laid out
+/// little-endian, matching how the target would store it as a word.
+pub fn parse_fill_pattern(pattern: &str) -> Result<Vec<u8>> {
+    let digits = pattern.trim();
+    let digits = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")).unwrap_or(digits);
+
+    let width = match digits.len() {
+        1 | 2 => 1,
+        3 | 4 => 2,
+        5..=8 => 4,
+        _ => {
+            return Err(DebugError::InvalidConfig(format!(
+                "Invalid fill pattern '{}': expected 1, 2 or 4 bytes of hex",
+                pattern
+            )))
+        }
+    };
+
+    let value = u32::from_str_radix(digits, 16)
+        .map_err(|e| DebugError::InvalidConfig(format!("Invalid fill pattern '{}': {}", pattern, e)))?;
+
+    Ok(value.to_le_bytes()[..width].to_vec())
+}
+
+/// Find the first part of `[address, address + size)` not covered by any memory region
+pub fn find_unmapped_gap(memory_map: &[MemoryRegion], address: u64, size: u64) -> Option<Range<u64>> {
+    let end = address.saturating_add(size);
+    let mut cursor = address;
+    while cursor < end {
+        match memory_map.iter().find(|region| region.contains(cursor)) {
+            Some(region) => cursor = region.address_range().end,
+            None => {
+                let gap_end = memory_map
+                    .iter()
+                    .map(|region| region.address_range().start)
+                    .filter(|start| *start > cursor)
+                    .min()
+                    .unwrap_or(end)
+                    .min(end);
+                return Some(cursor..gap_end);
+            }
+        }
+    }
+    None
+}
+
+/// Result of a typed memory read
+#[derive(Debug, Clone)]
+pub struct TypedReadResult {
+    /// Byte order the values were decoded with
+    pub endianness: Endianness,
+    pub values: Vec<TypedValue>,
+}
+
+/// Maximum number of regions listed when an access hits unmapped memory
+const NEAREST_REGION_COUNT: usize = 2;
+
+/// Display name of a memory region, falling back to its kind
+pub fn region_name(region: &MemoryRegion) -> String {
+    let (name, kind) = match region {
+        MemoryRegion::Ram(region) => (&region.name, "RAM"),
+        MemoryRegion::Nvm(region) => (&region.name, "Flash"),
+        MemoryRegion::Generic(region) => (&region.name, "Generic"),
+    };
+    name.clone().unwrap_or_else(|| kind.to_string())
+}
+
+/// Access summary of a memory region such as "ram rwx" or "nvm r-x boot"
+pub fn region_access(region: &MemoryRegion) -> String {
+    let (kind, access) = match region {
+        MemoryRegion::Ram(region) => ("ram", region.access),
+        MemoryRegion::Nvm(region) if region.is_alias => ("nvm alias", region.access),
+        MemoryRegion::Nvm(region) => ("nvm", region.access),
+        MemoryRegion::Generic(region) => ("generic", region.access),
+    };
+    let access = access.unwrap_or_default();
+    format!(
+        "{} {}{}{}{}",
+        kind,
+        if access.read { 'r' } else { '-' },
+        if access.write { 'w' } else { '-' },
+        if access.execute { 'x' } else { '-' },
+        if access.boot { " boot" } else { "" }
+    )
+}
+
+/// Check that `[address, address + size)` is covered by the memory map.
+///
+/// An empty memory map is treated as unknown and accepted. On failure the
+/// error names the regions closest to the unmapped part of the range.
+pub fn validate_range(memory_map: &[MemoryRegion], address: u64, size: u64) -> Result<()> {
+    if address.checked_add(size).is_none() {
+        return Err(DebugError::InvalidAddress {
+            address,
+            reason: format!("a {} byte access wraps past the end of the address space", size),
+        });
+    }
+    if memory_map.is_empty() {
+        return Ok(());
+    }
+    let Some(gap) = find_unmapped_gap(memory_map, address, size) else {
+        return Ok(());
+    };
+
+    let mut regions: Vec<&MemoryRegion> = memory_map.iter().collect();
+    regions.sort_by_key(|region| {
+        let range = region.address_range();
+        if gap.start < range.start {
+            range.start - gap.start
+        } else {
+            gap.start.saturating_sub(range.end - 1)
+        }
+    });
+    let nearest: Vec<String> = regions
+        .iter()
+        .take(NEAREST_REGION_COUNT)
+        .map(|region| {
+            let range = region.address_range();
+            format!("{} 0x{:08X}..0x{:08X}", region_name(region), range.start, range.end)
+        })
+        .collect();
+
+    Err(DebugError::InvalidAddress {
+        address: gap.start,
+        reason: format!(
+            "not mapped; nearest regions: {} (set allow_unmapped to bypass this check)",
+            nearest.join(", ")
+        ),
+    })
+}
+
+/// Check that a `width`-byte register access is naturally aligned
+pub fn check_alignment(address: u64, width: u64) -> Result<()> {
+    if !address.is_multiple_of(width) {
+        return Err(DebugError::InvalidAddress {
+            address,
+            reason: format!(
+                "a {}-bit access must be {}-byte aligned; the nearest aligned address is 0x{:08X}",
+                width * 8, width, address & !(width - 1)
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Find a non-volatile memory region overlapping `[address, address + size)`
+pub fn find_nvm_overlap(memory_map: &[MemoryRegion], address: u64, size: u64) -> Option<(String, Range<u64>)> {
+    let end = address.saturating_add(size);
+    memory_map
+        .iter()
+        .filter_map(|region| region.as_nvm_region())
+        .find(|region| region.range.start < end && address < region.range.end)
+        .map(|region| (region.name.clone().unwrap_or_else(|| "Flash".to_string()), region.range.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erased_byte_value() {
+        let mut algorithm = RawFlashAlgorithm::default();
+        algorithm.flash_properties.address_range = 0x0800_0000..0x0810_0000;
+        algorithm.flash_properties.erased_byte_value = 0x00;
+        let algorithms = [algorithm];
+        assert_eq!(erased_byte_value(&algorithms, 0x0800_1000), 0x00);
+        assert_eq!(erased_byte_value(&algorithms, 0x0810_0000), DEFAULT_ERASED_BYTE);
+        assert_eq!(erased_byte_value(&[], 0x0800_1000), DEFAULT_ERASED_BYTE);
+    }
+
+    #[test]
+    fn test_compare_chunks() {
+        let mut result = CompareResult::default();
+        result.add_chunk(0x2000_0000, 0, &[1, 2, 3, 4], &[1, 0, 3, 0], 1);
+        result.add_chunk(0x2000_0004, 4, &[5, 6], &[0, 6], 1);
+
+        assert_eq!(result.bytes_compared, 6);
+        assert_eq!(result.mismatch_count, 3);
+        assert_eq!(result.diffs.len(), 1);
+        assert_eq!((result.diffs[0].address, result.diffs[0].offset, result.diffs[0].expected, result.diffs[0].actual), (0x2000_0001, 1, 2, 0));
+        assert!(!result.identical());
+
+        let mut result = CompareResult::default();
+        result.add_chunk(0x2000_0000, 0, &[7, 8], &[7, 8], 16);
+        assert!(result.identical());
+        result.error = Some("Read failed".to_string());
+        assert!(!result.identical());
+    }
+
+    #[test]
+    fn test_elf_load_segments_needs_elf() {
+        assert!(elf_load_segments(&[0xDE, 0xAD, 0xBE, 0xEF]).unwrap().is_none());
+        assert!(elf_load_segments(b"\x7FELF truncated").is_err());
+    }
+
+    #[test]
+    fn test_write_batch_stop_on_error() {
+        let writes: Vec<Segment> = vec![(0x2000_0000, vec![1]), (0xE000_0000, vec![2]), (0x2000_0008, vec![3])];
+        let write = |address: u64, _: &[u8]| if address < 0x4000_0000 { Ok(()) } else { Err("unmapped".to_string()) };
+
+        assert_eq!(
+            write_batch(&writes, true, write),
+            vec![BatchWriteStatus::Written, BatchWriteStatus::Failed("unmapped".to_string()), BatchWriteStatus::Skipped]
+        );
+        assert_eq!(
+            write_batch(&writes, false, write),
+            vec![BatchWriteStatus::Written, BatchWriteStatus::Failed("unmapped".to_string()), BatchWriteStatus::Written]
+        );
+    }
+
+    #[test]
+    fn test_mismatched_offsets() {
+        assert_eq!(mismatched_offsets(&[1, 2, 3, 4], &[1, 0, 3, 0]), vec![1, 3]);
+        assert!(mismatched_offsets(&[0xAA; 8], &[0xAA; 8]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_fill_pattern() {
+        assert_eq!(parse_fill_pattern("0xAA").unwrap(), vec![0xAA]);
+        assert_eq!(parse_fill_pattern("0xBEEF").unwrap(), vec![0xEF, 0xBE]);
+        assert_eq!(parse_fill_pattern("0xDEADBEEF").unwrap(), vec![0xEF, 0xBE, 0xAD, 0xDE]);
+        assert!(parse_fill_pattern("0x123456789").is_err());
+        assert!(parse_fill_pattern("0xZZ").is_err());
+    }
+
+    #[test]
+    fn test_checksum_digest() {
+        assert_eq!(ChecksumAlgorithm::Crc32.digest(b"123456789"), "cbf43926");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.digest(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(ChecksumAlgorithm::parse("md5").is_err());
+    }
+
+    #[test]
+    fn test_validate_range() {
+        use probe_rs::config::{NvmRegion, RamRegion};
+
+        let memory_map = vec![
+            MemoryRegion::Nvm(NvmRegion {
+                name: Some("FLASH".to_string()),
+                range: 0x0800_0000..0x0810_0000,
+                cores: vec![],
+                is_alias: false,
+                access: None,
+            }),
+            MemoryRegion::Ram(RamRegion {
+                name: Some("SRAM".to_string()),
+                range: 0x2000_0000..0x2002_0000,
+                cores: vec![],
+                access: None,
+            }),
+        ];
+
+        assert!(validate_range(&memory_map, 0x2000_0000, 0x100).is_ok());
+        assert!(validate_range(&[], 0x8000_0000, 4).is_ok());
+
+        let message = validate_range(&memory_map, 0x8000_0000, 4).unwrap_err().to_string();
+        assert!(message.contains("0x80000000: not mapped"));
+        assert!(message.contains("SRAM 0x20000000..0x20020000"));
+
+        // A range running off the end of SRAM is rejected at the first unmapped byte
+        let error = validate_range(&memory_map, 0x2001_FFF0, 0x20).unwrap_err();
+        assert!(matches!(error, DebugError::InvalidAddress { address: 0x2002_0000, .. }));
+
+        assert_eq!(region_name(&memory_map[0]), "FLASH");
+        assert_eq!(region_access(&memory_map[0]), "nvm rwx");
+        assert_eq!(region_access(&memory_map[1]), "ram rwx");
+    }
+
+    #[test]
+    fn test_read_batch_checks_memory_map() {
+        use probe_rs::config::RamRegion;
+
+        let memory_map = vec![MemoryRegion::Ram(RamRegion {
+            name: Some("SRAM".to_string()),
+            range: 0x2000_0000..0x2000_1000,
+            cores: vec![],
+            access: None,
+        })];
+        let requests = [(0x2000_0000, 2), (0x4000_0000, 4), (0x2000_0FFC, 4)];
+        let read = |address: u64, data: &mut [u8]| {
+            if address == 0x2000_0FFC {
+                return Err("bus fault".to_string());
+            }
+            data.fill(0xAB);
+            Ok(())
+        };
+
+        let results = read_batch(&requests, Some(&memory_map), read);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![0xAB, 0xAB]);
+        assert!(matches!(results[1], Err(DebugError::InvalidAddress { address: 0x4000_0000, .. })));
+        assert_eq!(results[2].as_ref().unwrap_err().code(), "memory-access-failed");
+
+        let results = read_batch(&requests, None, read);
+        assert_eq!(results[1].as_ref().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_region_name_and_access() {
+        use probe_rs::config::{GenericRegion, MemoryAccess, NvmRegion, RamRegion};
+
+        let boot_flash = MemoryRegion::Nvm(NvmRegion {
+            name: None,
+            range: 0x0000_0000..0x0010_0000,
+            cores: vec![],
+            is_alias: true,
+            access: Some(MemoryAccess { read: true, write: false, execute: true, boot: true }),
+        });
+        assert_eq!(region_name(&boot_flash), "Flash");
+        assert_eq!(region_access(&boot_flash), "nvm alias r-x boot");
+
+        let ram = MemoryRegion::Ram(RamRegion {
+            name: None,
+            range: 0x2000_0000..0x2000_8000,
+            cores: vec![],
+            access: Some(MemoryAccess { read: true, write: true, execute: false, boot: false }),
+        });
+        assert_eq!(region_name(&ram), "RAM");
+        assert_eq!(region_access(&ram), "ram rw-");
+
+        let peripherals = MemoryRegion::Generic(GenericRegion {
+            name: Some("PERIPH".to_string()),
+            range: 0x4000_0000..0x6000_0000,
+            cores: vec![],
+            access: None,
+        });
+        assert_eq!(region_name(&peripherals), "PERIPH");
+        assert_eq!(region_access(&peripherals), "generic rwx");
+    }
+
+    #[test]
+    fn test_validate_range_boundaries() {
+        use probe_rs::config::{GenericRegion, RamRegion};
+
+        // Two adjacent RAM banks followed by a gap and a peripheral block
+        let ram = |name: &str, range: Range<u64>| MemoryRegion::Ram(RamRegion {
+            name: Some(name.to_string()),
+            range,
+            cores: vec![],
+            access: None,
+        });
+        let memory_map = vec![
+            ram("SRAM1", 0x2000_0000..0x2001_0000),
+            ram("SRAM2", 0x2001_0000..0x2002_0000),
+            MemoryRegion::Generic(GenericRegion {
+                name: Some("PERIPH".to_string()),
+                range: 0x4000_0000..0x4000_1000,
+                cores: vec![],
+                access: None,
+            }),
+        ];
+
+        // Ranges ending exactly at a region end, spanning adjacent regions, or empty
+        assert!(validate_range(&memory_map, 0x2001_FFFC, 4).is_ok());
+        assert!(validate_range(&memory_map, 0x2000_FFF0, 0x20).is_ok());
+        assert!(validate_range(&memory_map, 0x2000_0000, 0).is_ok());
+        assert!(validate_range(&memory_map, 0x4000_0FFF, 1).is_ok());
+
+        // One byte past the end, or just below the start
+        let error = validate_range(&memory_map, 0x2001_FFFD, 4).unwrap_err();
+        assert!(matches!(error, DebugError::InvalidAddress { address: 0x2002_0000, .. }));
+        let error = validate_range(&memory_map, 0x1FFF_FFFF, 2).unwrap_err();
+        assert!(matches!(error, DebugError::InvalidAddress { address: 0x1FFF_FFFF, .. }));
+
+        // The common missing-zero typo names the nearest regions
+        let message = validate_range(&memory_map, 0x0200_0000, 4).unwrap_err().to_string();
+        assert!(message.contains("SRAM1 0x20000000..0x20010000"));
+
+        // Wrapping ranges are rejected even without a memory map
+        assert!(validate_range(&[], u64::MAX - 1, 4).is_err());
+    }
+
+    #[test]
+    fn test_check_alignment() {
+        assert!(check_alignment(0x4002_1000, 4).is_ok());
+        assert!(check_alignment(0x4002_1002, 2).is_ok());
+        let message = check_alignment(0x4002_1002, 4).unwrap_err().to_string();
+        assert!(message.contains("32-bit access must be 4-byte aligned"));
+        assert!(message.contains("0x40021000"));
+        assert!(check_alignment(0x4002_1001, 2).is_err());
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        assert_eq!(find_pattern(b"abcabcab", b"abc"), vec![0, 3]);
+        assert_eq!(find_pattern(b"aaaa", b"aa"), vec![0, 1, 2]);
+        assert!(find_pattern(b"ab", b"abc").is_empty());
+        assert!(find_pattern(b"abc", b"").is_empty());
+    }
+}
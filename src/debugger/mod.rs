@@ -1,6 +1,31 @@
 //! Debugger session management
 
+pub mod backtrace;
+pub mod batch;
+pub mod breakpoints;
+pub mod chips;
+pub mod coredump;
+pub mod cpuid;
+pub mod cycles;
+pub mod disasm;
 pub mod discovery;
+pub mod dump;
+pub mod events;
+pub mod fault;
+pub mod memory;
+pub mod poll;
+pub mod registers;
+pub mod rtos;
+pub mod semihosting;
+pub mod session;
+pub mod stack;
+pub mod stepping;
+pub mod testrun;
+pub mod vector_catch;
+pub mod watchpoints;
+
+pub use batch::{BatchOp, BatchOpResult};
+pub use session::{attach, get_core, lock_session, target_selector, AUTO_TARGET, Capabilities, ClearAllResult, DebugSession, Disassembly, HaltEvent, ProbeSpeed, ReconnectResult, SharedSession, SemihostingRun, StepResult, SymbolStatus, SymbolizedAddress, TestRunResult, VariableWrite, WriteResult};
 
 /// Configuration for a debug session
 #[derive(Debug, Clone)]
@@ -11,4 +36,4 @@ pub struct SessionConfig {
     pub connect_under_reset: bool,
     /// Whether to halt after connecting
     pub halt_after_connect: bool,
-}
\ No newline at end of file
+}
@@ -0,0 +1,96 @@
+//! Background sampling of a variable while the firmware runs
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::utils::{ScalarType, TypedValue};
+
+/// Most polls a session may run at once
+pub const MAX_POLLS: usize = 8;
+
+/// Shortest interval between two samples of a poll
+pub const MIN_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// What a poll samples and how often
+#[derive(Debug, Clone)]
+pub struct PollInfo {
+    pub id: u32,
+    pub core_index: usize,
+    /// Symbol name or hex address the poll was started with
+    pub label: String,
+    pub address: u64,
+    pub scalar_type: ScalarType,
+    pub interval: std::time::Duration,
+    /// Samples kept before the oldest are dropped
+    pub capacity: usize,
+}
+
+/// One sample of a poll
+#[derive(Debug, Clone)]
+pub struct PollSample {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The value, or why the read failed
+    pub value: std::result::Result<TypedValue, String>,
+}
+
+/// Rolling sample history of a poll
+#[derive(Debug)]
+pub struct PollHistory {
+    samples: VecDeque<PollSample>,
+    capacity: usize,
+    /// Samples taken since the poll started, including dropped ones
+    pub total: u64,
+}
+
+impl PollHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity.min(1024)), capacity, total: 0 }
+    }
+
+    /// Append a sample, dropping the oldest once the history is full
+    pub fn push(&mut self, sample: PollSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.total += 1;
+    }
+
+    /// The `count` most recent samples, oldest first
+    pub fn recent(&self, count: usize) -> Vec<PollSample> {
+        self.samples.iter().skip(self.samples.len().saturating_sub(count)).cloned().collect()
+    }
+}
+
+/// A running poll: its settings, its history and the sampler task, which is
+/// stopped when the poll is dropped
+#[derive(Debug)]
+pub struct VariablePoll {
+    pub info: PollInfo,
+    pub history: Arc<std::sync::Mutex<PollHistory>>,
+    pub task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for VariablePoll {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_history_rolls_over() {
+        let mut history = PollHistory::new(3);
+        for value in 0..5u64 {
+            history.push(PollSample { timestamp: chrono::Utc::now(), value: Ok(TypedValue::Unsigned(value)) });
+        }
+        assert_eq!(history.total, 5);
+
+        let values: Vec<_> = history.recent(10).into_iter().map(|sample| sample.value.unwrap()).collect();
+        assert_eq!(values, vec![TypedValue::Unsigned(2), TypedValue::Unsigned(3), TypedValue::Unsigned(4)]);
+        assert_eq!(history.recent(1)[0].value, Ok(TypedValue::Unsigned(4)));
+    }
+}
@@ -0,0 +1,517 @@
+//! Core register access helpers
+
+use probe_rs::{Architecture, Core, CoreRegister, CoreRegisters, CoreType, RegisterValue};
+
+use crate::error::{DebugError, Result};
+
+/// RISC-V ABI names of x0..x31
+const RISCV_ABI_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2",
+    "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7",
+    "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
+/// Cortex-M special registers probe-rs packs into its "EXTRA" register, with
+/// their bit offsets. Each field is 8 bits wide.
+const PACKED_SPECIAL_REGISTERS: [(&str, u32); 4] = [
+    ("PRIMASK", 0),
+    ("BASEPRI", 8),
+    ("FAULTMASK", 16),
+    ("CONTROL", 24),
+];
+
+/// Name of the probe-rs register holding the packed special registers
+const PACKED_REGISTER_NAME: &str = "EXTRA";
+
+/// Value of one core register
+#[derive(Debug, Clone)]
+pub struct RegisterReading {
+    /// Canonical name, with role aliases like "R13/SP"
+    pub name: String,
+    pub bits: usize,
+    pub value: Option<u128>,
+    /// Why the register could not be read, if it failed
+    pub error: Option<String>,
+    /// Whether the value holds an IEEE 754 float (S and D registers)
+    pub floating_point: bool,
+}
+
+impl RegisterReading {
+    /// Value formatted as zero-padded hex, plus the decoded float for FPU registers, or the read error
+    pub fn display_value(&self) -> String {
+        match (self.value, &self.error) {
+            (Some(value), _) => {
+                let hex = format!("0x{:0width$X}", value, width = self.bits.div_ceil(8) * 2);
+                match (self.floating_point, self.bits) {
+                    (true, 32) => format!("{} ({:?})", hex, f32::from_bits(value as u32)),
+                    (true, 64) => format!("{} ({:?})", hex, f64::from_bits(value as u64)),
+                    _ => hex,
+                }
+            }
+            (None, Some(e)) => format!("<unavailable: {}>", e),
+            (None, None) => "<unavailable>".to_string(),
+        }
+    }
+}
+
+/// Registers read by name, plus the names that matched nothing
+#[derive(Debug, Clone, Default)]
+pub struct RegisterSelection {
+    pub readings: Vec<RegisterReading>,
+    pub unrecognized: Vec<String>,
+}
+
+/// ABI name of a RISC-V integer register like "x1"
+fn riscv_abi_name(name: &str) -> Option<&'static str> {
+    let index: usize = name.strip_prefix('x')?.parse().ok()?;
+    RISCV_ABI_NAMES.get(index).copied()
+}
+
+/// Every name a register answers to, lowercased.
+///
+/// This is the primary name and role aliases probe-rs reports ("R13/SP"),
+/// plus the ABI name on RISC-V ("x1" is also "ra", "x8" also "fp").
+pub fn register_aliases(register: &CoreRegister, architecture: Architecture) -> Vec<String> {
+    let mut aliases: Vec<String> = register.to_string().split('/').map(str::to_lowercase).collect();
+    if architecture == Architecture::Riscv {
+        if let Some(abi_name) = riscv_abi_name(register.name()) {
+            aliases.push(abi_name.to_string());
+        }
+    }
+    aliases
+}
+
+/// A register name resolved against the core's register file
+enum ResolvedRegister<'a> {
+    Core(&'a CoreRegister),
+    /// A field of the Cortex-M "EXTRA" register, like PRIMASK
+    Packed {
+        extra: &'a CoreRegister,
+        name: &'static str,
+        shift: u32,
+    },
+    /// A Cortex-M double precision register Dn, stored in S(2n) (low) and S(2n+1) (high)
+    Double {
+        name: String,
+        low: &'a CoreRegister,
+        high: &'a CoreRegister,
+    },
+}
+
+/// Whether `name` (lowercase) refers to a Cortex-M FPU register: S0-S31, D0-D15 or FPSCR
+fn is_fpu_name(name: &str) -> bool {
+    let index = |prefix: char, count: usize| {
+        name.strip_prefix(prefix)
+            .and_then(|n| n.parse::<usize>().ok())
+            .is_some_and(|n| n < count)
+    };
+    name == "fpscr" || index('s', 32) || index('d', 16)
+}
+
+/// Whether a register holds floating point data, from the "FPU" role probe-rs reports
+fn is_floating_point(register: &CoreRegister) -> bool {
+    register.to_string().split('/').any(|alias| alias == "FPU")
+}
+
+/// Resolve a register name case-insensitively, including the packed Cortex-M
+/// special registers and the D0-D15 views of the FPU registers.
+///
+/// Fails with a clear error when an FPU register is requested on a core without an FPU.
+fn resolve<'a>(registers: &'a CoreRegisters, name: &str, architecture: Architecture, has_fpu: bool) -> Result<Option<ResolvedRegister<'a>>> {
+    let wanted = name.trim().to_lowercase();
+    let find = |name: &str| {
+        registers
+            .all_registers()
+            .find(|register| register_aliases(register, architecture).iter().any(|alias| alias == name))
+    };
+
+    if architecture == Architecture::Arm {
+        if let Some(&(name, shift)) = PACKED_SPECIAL_REGISTERS.iter().find(|(name, _)| name.eq_ignore_ascii_case(&wanted)) {
+            let Some(extra) = registers.all_registers().find(|register| register.name() == PACKED_REGISTER_NAME) else {
+                return Ok(None);
+            };
+            return Ok(Some(ResolvedRegister::Packed { extra, name, shift }));
+        }
+        if is_fpu_name(&wanted) && !has_fpu {
+            return Err(DebugError::InvalidConfig(format!(
+                "Target has no FPU; register '{}' is not available (S0-S31, D0-D15 and FPSCR need a Cortex-M4F/M7 class core)",
+                name
+            )));
+        }
+        if let Some(index) = wanted.strip_prefix('d').and_then(|n| n.parse::<usize>().ok()).filter(|_| is_fpu_name(&wanted)) {
+            let (Some(low), Some(high)) = (find(&format!("s{}", index * 2)), find(&format!("s{}", index * 2 + 1))) else {
+                return Ok(None);
+            };
+            return Ok(Some(ResolvedRegister::Double { name: format!("D{}", index), low, high }));
+        }
+    }
+
+    Ok(find(&wanted).map(ResolvedRegister::Core))
+}
+
+fn read_value(core: &mut Core<'_>, register: &CoreRegister) -> std::result::Result<u128, String> {
+    let value = core.read_core_reg::<RegisterValue>(register.id()).map_err(|e| e.to_string())?;
+    value.try_into().map_err(|e| format!("{:?}", e))
+}
+
+fn read_resolved(core: &mut Core<'_>, register: &ResolvedRegister<'_>) -> RegisterReading {
+    let (name, bits, floating_point, result) = match register {
+        ResolvedRegister::Core(register) => (
+            register.to_string(),
+            register.size_in_bits(),
+            is_floating_point(register),
+            read_value(core, register),
+        ),
+        ResolvedRegister::Packed { extra, name, shift } => {
+            let result = read_value(core, extra).map(|value| (value >> shift) & 0xFF);
+            (name.to_string(), 8, false, result)
+        }
+        ResolvedRegister::Double { name, low, high } => {
+            let result = read_value(core, low)
+                .and_then(|low| read_value(core, high).map(|high| (high << 32) | low));
+            (name.clone(), 64, true, result)
+        }
+    };
+    match result {
+        Ok(value) => RegisterReading { name, bits, value: Some(value), error: None, floating_point },
+        Err(e) => RegisterReading { name, bits, value: None, error: Some(e), floating_point },
+    }
+}
+
+/// Read a register, splitting the Cortex-M "EXTRA" register into its named fields
+fn read_expanded(core: &mut Core<'_>, register: &CoreRegister, readings: &mut Vec<RegisterReading>) {
+    if core.architecture() == Architecture::Arm && register.name() == PACKED_REGISTER_NAME {
+        for &(name, shift) in &PACKED_SPECIAL_REGISTERS {
+            readings.push(read_resolved(core, &ResolvedRegister::Packed { extra: register, name, shift }));
+        }
+    } else {
+        readings.push(read_resolved(core, &ResolvedRegister::Core(register)));
+    }
+}
+
+/// Read every register of the core's register file.
+///
+/// FPU registers are skipped on cores without an FPU. A register that fails
+/// to read is reported with its error instead of failing the whole read.
+pub fn read_all(core: &mut Core<'_>) -> Vec<RegisterReading> {
+    let has_fpu = core.fpu_support().unwrap_or(false);
+    let registers = core.registers();
+    let descriptions: Vec<_> = if has_fpu {
+        registers.all_registers().collect()
+    } else {
+        registers.core_registers().collect()
+    };
+
+    let mut readings = Vec::with_capacity(descriptions.len());
+    for register in descriptions {
+        read_expanded(core, register, &mut readings);
+    }
+    readings
+}
+
+/// Read registers by name, matched case-insensitively against the core's register file.
+///
+/// With no names the non-FPU register set of the architecture is read,
+/// which covers the general purpose registers, PC, SP and status register.
+/// On Cortex-M, PRIMASK, BASEPRI, FAULTMASK and CONTROL are also accepted,
+/// as are S0-S31, D0-D15 and FPSCR on cores with an FPU.
+pub fn read_selected(core: &mut Core<'_>, names: &[String]) -> Result<RegisterSelection> {
+    let registers = core.registers();
+    let mut selection = RegisterSelection::default();
+    if names.is_empty() {
+        let descriptions: Vec<_> = registers.core_registers().collect();
+        for register in descriptions {
+            read_expanded(core, register, &mut selection.readings);
+        }
+        return Ok(selection);
+    }
+
+    let architecture = core.architecture();
+    let has_fpu = core.fpu_support().unwrap_or(false);
+    for name in names {
+        match resolve(registers, name, architecture, has_fpu)? {
+            Some(register) => selection.readings.push(read_resolved(core, &register)),
+            None => selection.unrecognized.push(name.clone()),
+        }
+    }
+    Ok(selection)
+}
+
+/// Result of a register write
+#[derive(Debug, Clone)]
+pub struct RegisterWrite {
+    /// Canonical name of the written register
+    pub name: String,
+    pub written: u64,
+    /// Value read back after the write, when verification was requested
+    pub read_back: Option<u128>,
+    /// Set when the write changes execution state, e.g. masks interrupts
+    pub warning: Option<String>,
+}
+
+impl RegisterWrite {
+    /// Whether the read-back matched the written value, if it was read back
+    pub fn verified(&self) -> Option<bool> {
+        self.read_back.map(|value| value == self.written as u128)
+    }
+}
+
+/// xPSR execution state bit selecting Thumb state
+const XPSR_THUMB_BIT: u64 = 1 << 24;
+
+/// Check a new PC value against the instruction alignment of the core.
+///
+/// Cortex-M only executes Thumb code, so an odd value is taken as a Thumb
+/// interworking address and bit 0 is cleared. Other cores cannot switch
+/// instruction set through a PC write, so misaligned values are rejected.
+/// Returns the value to write and a note when it was adjusted.
+pub fn align_pc(value: u64, core_type: CoreType) -> Result<(u64, Option<String>)> {
+    let alignment = match core_type {
+        CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+            if value & 1 != 0 {
+                let aligned = value & !1;
+                return Ok((aligned, Some(format!(
+                    "Cleared the Thumb bit: PC set to 0x{:X} (Thumb state is kept in xPSR.T)", aligned
+                ))));
+            }
+            return Ok((value, None));
+        }
+        CoreType::Armv7a | CoreType::Riscv => 2,
+        CoreType::Armv8a => 4,
+        CoreType::Xtensa => 1,
+    };
+    if !value.is_multiple_of(alignment) {
+        return Err(DebugError::InvalidConfig(format!(
+            "PC value 0x{:X} is not {}-byte aligned for a {:?} core", value, alignment, core_type
+        )));
+    }
+    Ok((value, None))
+}
+
+/// Write `value` to the register called `name`, optionally reading it back
+pub fn write(core: &mut Core<'_>, name: &str, value: u64, verify: bool) -> Result<RegisterWrite> {
+    let architecture = core.architecture();
+    let has_fpu = core.fpu_support().unwrap_or(false);
+    let registers = core.registers();
+    let register = resolve(registers, name, architecture, has_fpu)?
+        .ok_or_else(|| DebugError::InvalidConfig(format!("Unknown register '{}'", name)))?;
+
+    let core_type = core.core_type();
+    let is_pc = |register: &CoreRegister| registers.pc().is_some_and(|pc| pc.id() == register.id());
+    let is_psr = |register: &CoreRegister| registers.psr().is_some_and(|psr| psr.id() == register.id());
+
+    let write_error = |e: probe_rs::Error| DebugError::InternalError(format!("Failed to write {}: {}", name, e));
+    let mut value = value;
+    let (canonical_name, warning) = match &register {
+        ResolvedRegister::Core(register) => {
+            let bits = register.size_in_bits();
+            if bits < 64 && value >> bits != 0 {
+                return Err(DebugError::InvalidConfig(format!(
+                    "Value 0x{:X} does not fit in the {}-bit register {}", value, bits, register
+                )));
+            }
+            let mut warning = None;
+            if is_pc(register) {
+                let (aligned, note) = align_pc(value, core_type)?;
+                value = aligned;
+                warning = note;
+            } else if is_psr(register) && core_type.is_cortex_m() && value & XPSR_THUMB_BIT == 0 {
+                warning = Some("xPSR T-bit is clear; the core will take a UsageFault (INVSTATE) when it resumes".to_string());
+            }
+            if bits <= 32 {
+                core.write_core_reg(register.id(), value as u32).map_err(write_error)?;
+            } else {
+                core.write_core_reg(register.id(), value).map_err(write_error)?;
+            }
+            (register.to_string(), warning)
+        }
+        ResolvedRegister::Double { name, low, high } => {
+            core.write_core_reg(low.id(), value as u32).map_err(write_error)?;
+            core.write_core_reg(high.id(), (value >> 32) as u32).map_err(write_error)?;
+            (name.clone(), None)
+        }
+        ResolvedRegister::Packed { extra, name, shift } => {
+            if value > 0xFF {
+                return Err(DebugError::InvalidConfig(format!("Value 0x{:X} does not fit in the 8-bit register {}", value, name)));
+            }
+            let current: u32 = core.read_core_reg(extra.id())
+                .map_err(|e| DebugError::InternalError(format!("Failed to read {}: {}", name, e)))?;
+            let updated = (current & !(0xFF << shift)) | ((value as u32) << shift);
+            core.write_core_reg(extra.id(), updated).map_err(write_error)?;
+            let warning = format!(
+                "{} changes execution state (interrupt masking, privilege or stack selection) once the core resumes",
+                name
+            );
+            (name.to_string(), Some(warning))
+        }
+    };
+
+    let read_back = if verify {
+        let reading = read_resolved(core, &register);
+        match reading.value {
+            Some(value) => Some(value),
+            None => {
+                return Err(DebugError::InternalError(format!(
+                    "Read-back of {} failed: {}", canonical_name, reading.error.unwrap_or_default()
+                )))
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(RegisterWrite { name: canonical_name, written: value, read_back, warning })
+}
+
+/// Maximum number of register snapshots kept per session
+pub const MAX_REGISTER_SNAPSHOTS: usize = 16;
+
+/// Register values captured at one point in time
+#[derive(Debug, Clone)]
+pub struct RegisterSnapshot {
+    pub core_index: usize,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub readings: Vec<RegisterReading>,
+}
+
+/// A register whose value differs between two snapshots
+#[derive(Debug, Clone)]
+pub struct RegisterChange {
+    pub name: String,
+    pub bits: usize,
+    /// `None` when the register was missing or unreadable on that side
+    pub old: Option<u128>,
+    pub new: Option<u128>,
+}
+
+/// Registers whose values differ between `old` and `new`, matched by name
+pub fn diff(old: &[RegisterReading], new: &[RegisterReading]) -> Vec<RegisterChange> {
+    new.iter()
+        .filter_map(|reading| {
+            let previous = old.iter().find(|previous| previous.name == reading.name).and_then(|previous| previous.value);
+            (previous != reading.value).then(|| RegisterChange {
+                name: reading.name.clone(),
+                bits: reading.bits,
+                old: previous,
+                new: reading.value,
+            })
+        })
+        .collect()
+}
+
+/// Cortex-M exception name for an xPSR exception number
+pub(crate) fn exception_name(number: u32) -> String {
+    match number {
+        0 => "Thread mode".to_string(),
+        1 => "Reset".to_string(),
+        2 => "NMI".to_string(),
+        3 => "HardFault".to_string(),
+        4 => "MemManage".to_string(),
+        5 => "BusFault".to_string(),
+        6 => "UsageFault".to_string(),
+        7 => "SecureFault".to_string(),
+        11 => "SVCall".to_string(),
+        12 => "DebugMonitor".to_string(),
+        14 => "PendSV".to_string(),
+        15 => "SysTick".to_string(),
+        n if n >= 16 => format!("IRQ{}", n - 16),
+        n => format!("Reserved({})", n),
+    }
+}
+
+/// Decode a Cortex-M xPSR value into its flags, Thumb bit and active exception
+pub fn decode_xpsr(value: u32) -> String {
+    let bit = |n: u32| (value >> n) & 1;
+    let exception = value & 0x1FF;
+    format!(
+        "N={} Z={} C={} V={} Q={} T={} exception={} ({})",
+        bit(31), bit(30), bit(29), bit(28), bit(27), bit(24),
+        exception, exception_name(exception)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_riscv_abi_name() {
+        assert_eq!(riscv_abi_name("x0"), Some("zero"));
+        assert_eq!(riscv_abi_name("x1"), Some("ra"));
+        assert_eq!(riscv_abi_name("x2"), Some("sp"));
+        assert_eq!(riscv_abi_name("x31"), Some("t6"));
+        assert_eq!(riscv_abi_name("x32"), None);
+        assert_eq!(riscv_abi_name("pc"), None);
+    }
+
+    #[test]
+    fn test_display_value() {
+        let reading = |bits, value, error: Option<&str>| RegisterReading {
+            name: "R0".to_string(),
+            bits,
+            value,
+            error: error.map(str::to_string),
+            floating_point: false,
+        };
+        assert_eq!(reading(32, Some(0x1234), None).display_value(), "0x00001234");
+        assert_eq!(reading(8, Some(0x1), None).display_value(), "0x01");
+        assert_eq!(reading(64, Some(u64::MAX as u128), None).display_value(), "0xFFFFFFFFFFFFFFFF");
+        assert_eq!(reading(32, None, Some("core running")).display_value(), "<unavailable: core running>");
+        assert_eq!(reading(32, None, None).display_value(), "<unavailable>");
+    }
+
+    #[test]
+    fn test_register_write_verified() {
+        let write = |read_back| RegisterWrite { name: "R0".to_string(), written: 0x1234, read_back, warning: None };
+        assert_eq!(write(None).verified(), None);
+        assert_eq!(write(Some(0x1234)).verified(), Some(true));
+        assert_eq!(write(Some(0x1235)).verified(), Some(false));
+    }
+
+    #[test]
+    fn test_is_fpu_name() {
+        assert!(is_fpu_name("s0") && is_fpu_name("s31") && is_fpu_name("d15") && is_fpu_name("fpscr"));
+        assert!(!is_fpu_name("s32") && !is_fpu_name("d16") && !is_fpu_name("sp") && !is_fpu_name("d"));
+    }
+
+    #[test]
+    fn test_align_pc() {
+        let (value, note) = align_pc(0x0800_0101, CoreType::Armv7em).unwrap();
+        assert_eq!(value, 0x0800_0100);
+        assert!(note.is_some());
+        assert_eq!(align_pc(0x0800_0100, CoreType::Armv6m).unwrap(), (0x0800_0100, None));
+
+        assert!(align_pc(0x8000_0001, CoreType::Riscv).is_err());
+        assert!(align_pc(0x8000_0002, CoreType::Riscv).is_ok());
+        assert!(align_pc(0x4000_0002, CoreType::Armv8a).is_err());
+    }
+
+    #[test]
+    fn test_register_diff() {
+        let reading = |name: &str, value: Option<u128>| RegisterReading {
+            name: name.to_string(),
+            bits: 32,
+            value,
+            error: None,
+            floating_point: false,
+        };
+        let old = vec![reading("R0", Some(1)), reading("R1", Some(2)), reading("R15/PC", Some(0x100))];
+        let new = vec![reading("R0", Some(1)), reading("R1", Some(3)), reading("R15/PC", Some(0x104)), reading("R2", None)];
+
+        let changes = diff(&old, &new);
+        let names: Vec<&str> = changes.iter().map(|change| change.name.as_str()).collect();
+        assert_eq!(names, vec!["R1", "R15/PC"]);
+        assert_eq!((changes[0].old, changes[0].new), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn test_decode_xpsr() {
+        assert_eq!(
+            decode_xpsr(0x6100_0003),
+            "N=0 Z=1 C=1 V=0 Q=0 T=1 exception=3 (HardFault)"
+        );
+        assert!(decode_xpsr(0x0100_0010).ends_with("exception=16 (IRQ0)"));
+        assert!(decode_xpsr(0x0100_0000).ends_with("(Thread mode)"));
+    }
+}
@@ -0,0 +1,258 @@
+//! FreeRTOS task awareness: kernel lists and TCBs read through their DWARF types
+
+use crate::error::{DebugError, Result};
+use crate::symbols::{VariableTable, VariableType};
+use super::backtrace::Backtrace;
+use super::stepping;
+
+/// Byte FreeRTOS fills new task stacks with (`tskSTACK_FILL_BYTE`)
+pub const STACK_FILL_BYTE: u8 = 0xA5;
+
+/// Items followed per kernel list, guarding against a corrupted list that loops
+pub const MAX_LIST_ITEMS: usize = 256;
+
+/// Most stack bytes scanned per task for the high-water mark
+pub const MAX_STACK_SCAN: u64 = 64 * 1024;
+
+/// Names the TCB type goes by across FreeRTOS versions
+const TCB_TYPE_NAMES: &[&str] = &["TCB_t", "tskTCB", "tskTaskControlBlock"];
+
+/// Words read at a task's saved top of stack: r4-r11, EXC_RETURN, s16-s31
+/// and the hardware-stacked frame
+pub const SAVED_CONTEXT_WORDS: usize = 9 + 16 + 8;
+
+/// Scheduler state of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    Ready,
+    Blocked,
+    Suspended,
+    /// Deleted, waiting for the idle task to free it
+    Deleted,
+}
+
+impl std::fmt::Display for TaskState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TaskState::Running => "Running",
+            TaskState::Ready => "Ready",
+            TaskState::Blocked => "Blocked",
+            TaskState::Suspended => "Suspended",
+            TaskState::Deleted => "Deleted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Kernel list holding tasks in one state
+#[derive(Debug, Clone)]
+pub struct TaskList {
+    pub name: &'static str,
+    pub address: u64,
+    pub state: TaskState,
+}
+
+/// Offsets into the kernel structures, taken from the DWARF info so they
+/// follow the FreeRTOSConfig.h the firmware was built with
+#[derive(Debug, Clone)]
+pub struct FreeRtosLayout {
+    /// Address of `pxCurrentTCB`
+    pub current_tcb: u64,
+    pub lists: Vec<TaskList>,
+    pub list_item_count: u64,
+    /// Offset of `xListEnd.pxNext` in a list
+    pub list_end: u64,
+    pub list_end_next: u64,
+    pub item_next: u64,
+    pub item_owner: u64,
+    pub item_container: u64,
+    pub tcb_top_of_stack: u64,
+    pub tcb_state_item: u64,
+    pub tcb_event_item: Option<u64>,
+    pub tcb_priority: u64,
+    pub priority_size: u64,
+    pub tcb_stack: u64,
+    /// Only present with `configRECORD_STACK_HIGH_ADDRESS`
+    pub tcb_end_of_stack: Option<u64>,
+    pub tcb_name: u64,
+    pub name_length: u64,
+}
+
+impl FreeRtosLayout {
+    /// Find the kernel lists and the TCB layout in the DWARF info of a FreeRTOS firmware
+    pub fn from_variables(table: &VariableTable) -> Result<Self> {
+        let variable = |name: &str| {
+            table.lookup(name).ok_or_else(|| {
+                DebugError::InvalidConfig(format!(
+                    "'{}' not found in the DWARF info; is this a FreeRTOS firmware built with debug info?",
+                    name
+                ))
+            })
+        };
+        let current_tcb = variable("pxCurrentTCB")?.address;
+
+        let ready = variable("pxReadyTasksLists")?;
+        let VariableType::Array { element: list_type, count: priorities } = &ready.ty else {
+            return Err(DebugError::InvalidConfig("pxReadyTasksLists is not an array of lists".to_string()));
+        };
+        let list_size = list_type.size();
+        let mut lists: Vec<TaskList> = (0..*priorities)
+            .map(|priority| TaskList { name: "pxReadyTasksLists", address: ready.address + priority * list_size, state: TaskState::Ready })
+            .collect();
+        for (name, state) in [
+            ("xPendingReadyList", TaskState::Ready),
+            ("xDelayedTaskList1", TaskState::Blocked),
+            ("xDelayedTaskList2", TaskState::Blocked),
+            ("xSuspendedTaskList", TaskState::Suspended),
+            ("xTasksWaitingTermination", TaskState::Deleted),
+        ] {
+            // The suspended and termination lists depend on INCLUDE_ options
+            if let Some(list) = table.lookup(name) {
+                lists.push(TaskList { name, address: list.address, state });
+            }
+        }
+
+        let tcb = TCB_TYPE_NAMES.iter().find_map(|name| table.lookup_type(name)).ok_or_else(|| {
+            DebugError::InvalidConfig("TCB_t not found in the DWARF info of the firmware".to_string())
+        })?;
+        let tcb_member = |name: &str| tcb.member(name).ok_or_else(|| missing_member("TCB_t", name));
+        let list_member = |name: &str| list_type.member(name).ok_or_else(|| missing_member("List_t", name));
+
+        let list_end = list_member("xListEnd")?;
+        let list_end_next = list_end.ty.member("pxNext").ok_or_else(|| missing_member("MiniListItem_t", "pxNext"))?.offset;
+        let state_item = tcb_member("xStateListItem").or_else(|_| tcb_member("xGenericListItem"))?;
+        let item_member = |name: &str| state_item.ty.member(name).map(|member| member.offset);
+        let name = tcb_member("pcTaskName")?;
+        let priority = tcb_member("uxPriority")?;
+
+        Ok(FreeRtosLayout {
+            current_tcb,
+            lists,
+            list_item_count: list_member("uxNumberOfItems")?.offset,
+            list_end: list_end.offset,
+            list_end_next,
+            item_next: item_member("pxNext").ok_or_else(|| missing_member("ListItem_t", "pxNext"))?,
+            item_owner: item_member("pvOwner").ok_or_else(|| missing_member("ListItem_t", "pvOwner"))?,
+            // Renamed from pvContainer in FreeRTOS 10.1
+            item_container: item_member("pxContainer").or_else(|| item_member("pvContainer"))
+                .ok_or_else(|| missing_member("ListItem_t", "pxContainer"))?,
+            tcb_top_of_stack: tcb_member("pxTopOfStack")?.offset,
+            tcb_state_item: state_item.offset,
+            tcb_event_item: tcb.member("xEventListItem").map(|member| member.offset),
+            tcb_priority: priority.offset,
+            priority_size: priority.ty.size(),
+            tcb_stack: tcb_member("pxStack")?.offset,
+            tcb_end_of_stack: tcb.member("pxEndOfStack").map(|member| member.offset),
+            tcb_name: name.offset,
+            name_length: name.ty.size(),
+        })
+    }
+}
+
+fn missing_member(type_name: &str, member: &str) -> DebugError {
+    DebugError::InvalidConfig(format!("{} has no member '{}' in the DWARF info; unsupported FreeRTOS version", type_name, member))
+}
+
+/// A FreeRTOS task and where its stack stands
+#[derive(Debug, Clone)]
+pub struct RtosTask {
+    pub tcb: u64,
+    pub name: String,
+    pub state: TaskState,
+    pub priority: u64,
+    /// Low end of the stack (`pxStack`)
+    pub stack_base: u64,
+    /// Live SP of the running task, saved SP (`pxTopOfStack`) of the others
+    pub stack_pointer: u64,
+    /// Stack size, when the TCB records the high end
+    pub stack_size: Option<u64>,
+    /// Bytes at the low end of the stack still holding the fill pattern,
+    /// i.e. the headroom left at the deepest point so far
+    pub high_water_bytes: Option<u64>,
+    pub backtrace: Option<Backtrace>,
+    /// Why the backtrace is missing
+    pub note: Option<String>,
+}
+
+/// Registers of a task switched out by a FreeRTOS Cortex-M port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedContext {
+    /// R0-R12, SP, LR, PC and xPSR as (probe-rs register number, value)
+    pub registers: Vec<(u16, u32)>,
+    /// SP of the task once the context is popped
+    pub stack_pointer: u32,
+}
+
+/// Decode the context the ARMv6-M and ARMv7-M ports push on a task switch,
+/// from the words at its saved top of stack: r4-r11, EXC_RETURN on ports
+/// with an FPU, s16-s31 when the task used the FPU, then the exception
+/// frame the hardware stacked.
+///
+/// Returns `None` when the words do not look like a saved context, e.g.
+/// with the ARMv8-M ports that also save PSPLIM and CONTROL.
+pub fn saved_context(words: &[u32], top_of_stack: u32) -> Option<SavedContext> {
+    let (software_words, extended) = match words.get(8) {
+        Some(&exc_return) if stepping::is_exc_return(u64::from(exc_return)) => {
+            let extended = exc_return & (1 << 4) == 0;
+            (if extended { 9 + 16 } else { 9 }, extended)
+        }
+        _ => (8, false),
+    };
+    let frame = words.get(software_words..software_words + 8)?;
+    let [r0, r1, r2, r3, r12, lr, pc, xpsr] = frame.try_into().ok()?;
+    // Task code always runs in Thumb state
+    if xpsr & (1 << 24) == 0 || pc == 0 {
+        return None;
+    }
+
+    let mut registers = vec![(0, r0), (1, r1), (2, r2), (3, r3)];
+    registers.extend(words[..8].iter().enumerate().map(|(index, &value)| (4 + index as u16, value)));
+    let frame_size = if extended { 8 + 18 } else { 8 };
+    // xPSR bit 9 records the padding word added to align the frame
+    let padding = if xpsr & (1 << 9) != 0 { 4 } else { 0 };
+    let stack_pointer = top_of_stack + (software_words as u32 + frame_size) * 4 + padding;
+    registers.extend([(12, r12), (13, stack_pointer), (14, lr), (15, pc), (16, xpsr)]);
+    Some(SavedContext { registers, stack_pointer })
+}
+
+/// Task name from the bytes of `pcTaskName`, up to the first NUL
+pub fn task_name(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_context_cm3() {
+        let top = 0x2000_1000;
+        let mut words: Vec<u32> = (4..12).collect();
+        words.extend([0, 1, 2, 3, 12, 0x0800_0101, 0x0800_0200, 0x0100_0000]);
+        let context = saved_context(&words, top).unwrap();
+        assert_eq!(context.stack_pointer, top + 16 * 4);
+        assert!(context.registers.contains(&(15, 0x0800_0200)));
+        assert!(context.registers.contains(&(11, 11)));
+        assert!(context.registers.contains(&(13, top + 64)));
+    }
+
+    #[test]
+    fn test_saved_context_cm4f() {
+        let top = 0x2000_1000;
+        // EXC_RETURN with FType clear: s16-s31 follow, and the frame holds FP state
+        let mut words: Vec<u32> = (4..12).collect();
+        words.push(0xFFFF_FFED);
+        words.extend([0; 16]);
+        words.extend([0, 1, 2, 3, 12, 0x0800_0101, 0x0800_0200, 0x0100_0200]);
+        let context = saved_context(&words, top).unwrap();
+        assert!(context.registers.contains(&(15, 0x0800_0200)));
+        assert_eq!(context.stack_pointer, top + (25 + 26) * 4 + 4);
+
+        // Not a plausible frame: the Thumb bit is clear
+        let garbage = [0u32; SAVED_CONTEXT_WORDS];
+        assert_eq!(saved_context(&garbage, top), None);
+        assert_eq!(task_name(b"IDLE\0\0\0\0"), "IDLE");
+    }
+}
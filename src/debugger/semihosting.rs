@@ -0,0 +1,135 @@
+//! ARM semihosting: capture target console output and exit requests
+
+use probe_rs::semihosting::SemihostingCommand;
+use probe_rs::{Core, CoreInterface};
+use tracing::debug;
+
+use crate::error::{DebugError, Result};
+
+/// Semihosting file handles the host treats as the console
+const STDOUT_HANDLE: u32 = 1;
+const STDERR_HANDLE: u32 = 2;
+
+/// Path the C library opens to reach the host console
+const CONSOLE_PATH: &str = ":tt";
+
+/// Handle SYS_OPEN of the console returns for `mode`: write modes open
+/// stdout, append modes stderr. Reading the console is not supported.
+pub fn console_handle(path: &str, mode: &str) -> Option<u32> {
+    if path != CONSOLE_PATH {
+        return None;
+    }
+    match mode.chars().next() {
+        Some('w') => Some(STDOUT_HANDLE),
+        Some('a') => Some(STDERR_HANDLE),
+        _ => None,
+    }
+}
+
+/// How the target ended execution through semihosting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemihostingExit {
+    Success,
+    Error {
+        reason: u32,
+        exit_status: Option<u32>,
+        subcode: Option<u32>,
+    },
+}
+
+impl SemihostingExit {
+    /// Process-style exit code: 0 on success, the reported status (or 1) on failure
+    pub fn exit_code(&self) -> u32 {
+        match self {
+            SemihostingExit::Success => 0,
+            SemihostingExit::Error { exit_status, .. } => exit_status.unwrap_or(1),
+        }
+    }
+}
+
+/// Semihosting state of a session
+#[derive(Debug, Default)]
+pub struct SemihostingState {
+    pub enabled: bool,
+    /// Console output not yet returned to a client
+    pub output: String,
+    pub exit: Option<SemihostingExit>,
+}
+
+/// Service one semihosting request the core halted on.
+///
+/// Returns `true` when the core should be resumed, `false` when the target
+/// asked to exit.
+pub fn handle_command(core: &mut Core<'_>, command: SemihostingCommand, state: &mut SemihostingState) -> Result<bool> {
+    let map_err = |e: probe_rs::Error| DebugError::InternalError(format!("Semihosting request failed: {}", e));
+
+    match command {
+        SemihostingCommand::WriteConsole(request) => {
+            state.output.push_str(&request.read(core).map_err(map_err)?);
+            Ok(true)
+        }
+        SemihostingCommand::Write(request) if matches!(request.file_handle(), STDOUT_HANDLE | STDERR_HANDLE) => {
+            let bytes = request.read(core).map_err(map_err)?;
+            state.output.push_str(&String::from_utf8_lossy(&bytes));
+            // SYS_WRITE returns the number of bytes not written
+            request.write_status(core as &mut dyn CoreInterface, 0).map_err(map_err)?;
+            Ok(true)
+        }
+        SemihostingCommand::Open(request) => {
+            let path = request.path(core as &mut dyn CoreInterface).map_err(map_err)?;
+            match console_handle(&path, request.mode()).and_then(std::num::NonZeroU32::new) {
+                Some(handle) => request.respond_with_handle(core as &mut dyn CoreInterface, handle).map_err(map_err)?,
+                None => debug!("Refusing semihosting open of '{}' (mode '{}')", path, request.mode()),
+            }
+            Ok(true)
+        }
+        SemihostingCommand::Close(request) => {
+            let handle = request.file_handle(core as &mut dyn CoreInterface).map_err(map_err)?;
+            if matches!(handle, STDOUT_HANDLE | STDERR_HANDLE) {
+                request.success(core as &mut dyn CoreInterface).map_err(map_err)?;
+            }
+            Ok(true)
+        }
+        SemihostingCommand::ExitSuccess => {
+            state.exit = Some(SemihostingExit::Success);
+            Ok(false)
+        }
+        SemihostingCommand::ExitError(details) => {
+            state.exit = Some(SemihostingExit::Error {
+                reason: details.reason,
+                exit_status: details.exit_status,
+                subcode: details.subcode,
+            });
+            Ok(false)
+        }
+        other => {
+            // probe-rs already reported failure (-1) to the target for these
+            debug!("Ignoring unsupported semihosting request: {:?}", other);
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_handle() {
+        assert_eq!(console_handle(":tt", "w"), Some(STDOUT_HANDLE));
+        assert_eq!(console_handle(":tt", "a+b"), Some(STDERR_HANDLE));
+        assert_eq!(console_handle(":tt", "r"), None);
+        assert_eq!(console_handle("log.txt", "w"), None);
+        assert_eq!(SemihostingExit::Error { reason: 0x20026, exit_status: Some(3), subcode: None }.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(SemihostingExit::Success.exit_code(), 0);
+        assert_eq!(SemihostingExit::Error { reason: 0x20023, exit_status: None, subcode: None }.exit_code(), 1);
+        assert_eq!(SemihostingExit::Error { reason: 0x20026, exit_status: Some(0), subcode: Some(7) }.exit_code(), 0);
+
+        let state = SemihostingState::default();
+        assert!(!state.enabled && state.output.is_empty() && state.exit.is_none());
+    }
+}
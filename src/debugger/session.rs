@@ -0,0 +1,3387 @@
+//! Debug session state shared by the MCP tools
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use probe_rs::config::TargetSelector;
+use probe_rs::probe::DebugProbeInfo;
+use probe_rs::{Core, CoreStatus, HaltReason, MemoryInterface, Permissions, RegisterValue, Session};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+use tracing::{debug, info, warn};
+
+use crate::error::{DebugError, MemoryError, Result};
+use crate::flash::{FileFormat, FlashManager, ProgramResult};
+use crate::flash::progress::{self, ProgressPhase, ProgressSink};
+use crate::rtt::{ChannelDirection, ChannelInfo, RttInfo, RttManager};
+use crate::symbols::{source, variables, InlineFrame, InlineResolver, LineTable, SourceContext, SourceLocation, Symbol, SymbolTable, Variable, VariableTable};
+use crate::utils::{Endianness, ResetType, ScalarType};
+use super::backtrace::{self, Backtrace, FrameLocals};
+use super::batch::{self, BatchOp, BatchOpResult};
+use super::breakpoints::{self, BreakpointCapacity, BreakpointInfo, BreakpointKind};
+use super::coredump::{self, CoreDumpFormat, CoreDumpResult, CoreMachine};
+use super::cpuid::{self, CpuId};
+use super::cycles::{self, CycleMeasurement};
+use super::disasm::{self, DisasmMode, Instruction};
+use super::discovery::{ProbeDiscovery, ProbeInfo};
+use super::dump::DumpFormat;
+use super::events::{self, EventMonitor, EventSubscription, TargetEvent};
+use super::fault::{self, ExceptionFrame, FaultReport, FaultStatus};
+use super::poll::{self, PollHistory, PollInfo, PollSample, VariablePoll};
+use super::registers::{self, RegisterChange, RegisterReading, RegisterSelection, RegisterSnapshot, RegisterWrite};
+use super::rtos::{self, RtosTask};
+use super::semihosting::{self, SemihostingExit, SemihostingState};
+use super::stack::{self, StackPaint, StackUsage};
+use super::stepping;
+use super::testrun::{TestLog, TestOutput};
+use super::vector_catch::{self, VectorCatch};
+use super::watchpoints::{self, WatchAccess, WatchpointInfo};
+use super::memory::{self, BatchWriteStatus, BlankCheckResult, ChecksumAlgorithm, ChecksumResult, CompareResult, DumpResult, FillResult, ReadResult, SearchMatch, TypedReadResult, WatchSample};
+
+/// probe-rs session shared between tools and the RTT manager; `None` while disconnected
+pub type SharedSession = Arc<Mutex<Option<Session>>>;
+
+/// Lock a shared session, failing if it is currently disconnected.
+///
+/// The session is always locked last: paths that also need the RTT manager,
+/// breakpoints, watchpoints, vector catches or semihosting state lock those
+/// first, in that order, so no two paths can wait on each other.
+pub async fn lock_session(session: &SharedSession) -> Result<MappedMutexGuard<'_, Session>> {
+    MutexGuard::try_map(session.lock().await, |slot| slot.as_mut()).map_err(|_| {
+        DebugError::ConnectionFailed("Session is disconnected; use 'reconnect' to re-attach".to_string())
+    })
+}
+
+/// `target_chip` value that asks probe-rs to identify the chip itself
+pub const AUTO_TARGET: &str = "auto";
+
+/// Target selector for a chip name, or probe-rs auto-detection for `AUTO_TARGET`
+pub fn target_selector(target_chip: &str) -> TargetSelector {
+    if target_chip.eq_ignore_ascii_case(AUTO_TARGET) {
+        TargetSelector::Auto
+    } else {
+        TargetSelector::from(target_chip)
+    }
+}
+
+/// Open the probe at `speed_khz` and attach to `target_chip`, holding nRST
+/// asserted during the attach when `under_reset` is set.
+///
+/// Returns the session and the speed the probe actually negotiated, which
+/// may be lower than requested.
+pub fn attach(probe_info: &DebugProbeInfo, target_chip: &str, speed_khz: u32, under_reset: bool) -> Result<(Session, u32)> {
+    let mut probe = probe_info.open()
+        .map_err(|e| DebugError::ConnectionFailed(format!("Failed to open probe {}: {}", probe_info.identifier, e)))?;
+
+    let actual_khz = match probe.set_speed(speed_khz) {
+        Ok(actual_khz) => actual_khz,
+        Err(e) => {
+            warn!("Failed to set probe speed to {} kHz: {}", speed_khz, e);
+            probe.speed_khz()
+        }
+    };
+
+    let session = if under_reset {
+        probe.attach_under_reset(target_selector(target_chip), Permissions::default())
+    } else {
+        probe.attach(target_selector(target_chip), Permissions::default())
+    }
+    .map_err(|e| DebugError::ConnectionFailed(format!("Failed to attach to target '{}': {}", target_chip, e)))?;
+    Ok((session, actual_khz))
+}
+
+/// How long to wait for the core to halt after a reset
+const RESET_HALT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1000);
+
+/// Time the system takes to come out of an AIRCR.SYSRESETREQ reset
+const SYSTEM_RESET_SETTLE: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Farthest a disassembly scans back to the enclosing symbol before giving up on it
+const DISASM_MAX_SCAN: u64 = 4096;
+
+/// How long to wait for a core to acknowledge a halt request
+const HALT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1000);
+
+/// Delay between RTT attach attempts while the firmware sets up its control block
+const RTT_ATTACH_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Cortex-M Application Interrupt and Reset Control Register
+const AIRCR: u64 = 0xE000_ED0C;
+const AIRCR_VECTKEY: u32 = 0x05FA << 16;
+const AIRCR_SYSRESETREQ: u32 = 1 << 2;
+
+/// How often `wait_for_halt` polls the core state
+const HALT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Most breakpoint hits `wait_for_halt` resumes for `skip_count` in one call
+const MAX_AUTO_RESUMES: u32 = 1000;
+
+/// How long `run_tests` waits for output or a halt between checks
+const TEST_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+
+/// Core state observed when a running core stopped
+#[derive(Debug, Clone)]
+pub struct HaltEvent {
+    pub status: CoreStatus,
+    /// Program counter at the halt, if it could be read
+    pub pc: Option<u64>,
+    /// Time spent waiting for the halt
+    pub elapsed_ms: u64,
+    /// Watchpoint that caused the halt, if any
+    pub watchpoint: Option<WatchpointInfo>,
+    /// Breakpoint set at the halted PC, if any
+    pub breakpoint: Option<BreakpointInfo>,
+    /// Exception the core was caught on when halted by a vector catch
+    pub vector_catch: Option<String>,
+    /// Breakpoint hits resumed automatically because of their `skip_count`
+    pub auto_resumed: u32,
+    /// Exit the target requested through semihosting, ending the run
+    pub semihosting_exit: Option<SemihostingExit>,
+}
+
+/// Outcome of `run_with_semihosting`
+#[derive(Debug, Clone)]
+pub struct SemihostingRun {
+    /// Console output the target wrote during the run
+    pub output: String,
+    /// Halt that ended the run; `None` when the core was still running at the timeout
+    pub halt: Option<HaltEvent>,
+}
+
+/// Outcome of `step`, `step_over` or `step_out`
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub status: CoreStatus,
+    /// Program counter afterwards, if it could be read
+    pub pc: Option<u64>,
+    /// Instructions single-stepped; `None` when the core ran to `ran_to`
+    pub instructions: Option<u64>,
+    /// Return address the core was resumed to with a temporary breakpoint
+    pub ran_to: Option<u64>,
+    /// The core did not stop within the timeout and was halted
+    pub timed_out: bool,
+    pub elapsed_ms: u64,
+    /// PC after every single step, when tracing was requested
+    pub trace: Option<Vec<u64>>,
+    /// Breakpoint the core stopped on, if any
+    pub breakpoint: Option<BreakpointInfo>,
+}
+
+/// The ELF loaded for a session, from `symbol_status`
+#[derive(Debug, Clone)]
+pub struct SymbolStatus {
+    pub elf_path: String,
+    pub build_id: Option<String>,
+    pub symbol_count: usize,
+    pub has_line_info: bool,
+}
+
+/// An address mapped to source, from `symbolize`
+#[derive(Debug, Clone)]
+pub struct SymbolizedAddress {
+    pub address: u64,
+    /// Out-of-line function containing the address
+    pub function: Option<String>,
+    /// Offset of the address into `function`, when the symbol table has it
+    pub offset: Option<u64>,
+    /// Source line of the address itself
+    pub location: Option<SourceLocation>,
+    /// Functions inlined at the address, innermost first
+    pub inlined: Vec<InlineFrame>,
+}
+
+/// Result of `disassemble`
+#[derive(Debug, Clone)]
+pub struct Disassembly {
+    pub mode: DisasmMode,
+    /// Program counter of the core when the code was read
+    pub pc: u64,
+    pub instructions: Vec<Instruction>,
+    /// No symbol precedes the start, so the scan back to it may have
+    /// started mid-instruction and decoded garbage before the anchor
+    pub unaligned_scan: bool,
+}
+
+/// Result of `write_variable`
+#[derive(Debug, Clone)]
+pub struct VariableWrite {
+    pub variable: Variable,
+    /// Bytes written, spanning the first to the last patched byte
+    pub bytes_written: usize,
+    /// Offsets into the variable that differ after read-back, when verification was requested
+    pub mismatches: Option<Vec<u64>>,
+}
+
+/// Debug features of a connected core and its probe, from `capabilities`
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub core_type: probe_rs::CoreType,
+    /// Hardware breakpoint comparators, in use or free
+    pub hw_breakpoints: usize,
+    /// DWT watchpoint comparators; 0 on cores without a DWT
+    pub watchpoints: usize,
+    /// Software breakpoint instructions can be patched into RAM
+    pub software_breakpoints: bool,
+    /// Floating point registers, 0 without an FPU
+    pub fpu_registers: usize,
+    /// The core architecture has an ITM for SWO tracing (ARMv7-M/ARMv8-M)
+    pub itm: bool,
+    /// The probe can capture SWO output
+    pub probe_swo: bool,
+    /// Whether the probe drove nRST for a hardware reset; `None` until one was tried
+    pub nrst: Option<bool>,
+}
+
+/// Result of `clear_all_breakpoints`
+#[derive(Debug, Default)]
+pub struct ClearAllResult {
+    /// Addresses of the breakpoints removed
+    pub breakpoints: Vec<u64>,
+    /// Addresses of the watchpoints removed
+    pub watchpoints: Vec<u64>,
+    /// Breakpoints or watchpoints that could not be removed from the target, with the reason
+    pub failed: Vec<(u64, String)>,
+}
+
+/// Requested and negotiated probe clock
+#[derive(Debug, Clone, Copy)]
+pub struct ProbeSpeed {
+    pub requested_khz: u32,
+    pub actual_khz: u32,
+}
+
+impl std::fmt::Display for ProbeSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} kHz (requested {} kHz)", self.actual_khz, self.requested_khz)
+    }
+}
+
+/// Debug session information
+#[derive(Debug)]
+pub struct DebugSession {
+    pub session_id: String,
+    pub probe_identifier: String,
+    pub target_chip: String,
+    /// Probe and speed used to (re)attach
+    probe_info: DebugProbeInfo,
+    speed: std::sync::Mutex<ProbeSpeed>,
+    /// The probe reported an SWO interface when the session was opened
+    probe_swo: bool,
+    /// Outcome of the last attach under reset, `None` before the first one
+    nrst_works: std::sync::Mutex<Option<bool>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    /// Time of the last tool call on this session, used to reap idle sessions
+    last_activity: std::sync::Mutex<chrono::DateTime<chrono::Utc>>,
+    pub session: SharedSession,
+    pub rtt_manager: Arc<tokio::sync::Mutex<RttManager>>,
+    /// Parsed ELF symbol tables keyed by ELF path
+    symbol_tables: Mutex<HashMap<String, Arc<SymbolTable>>>,
+    /// ELF path used when a tool does not name one explicitly
+    default_elf_path: Mutex<Option<String>>,
+    /// Parsed DWARF line tables keyed by ELF path
+    line_tables: Mutex<HashMap<String, Arc<LineTable>>>,
+    /// DWARF inline info keyed by ELF path, built on first symbolization
+    inline_resolvers: Mutex<HashMap<String, Arc<InlineResolver>>>,
+    /// DWARF static variables keyed by ELF path, built on first variable access
+    variable_tables: Mutex<HashMap<String, Arc<VariableTable>>>,
+    /// Background variable polls, keyed by id
+    polls: std::sync::Mutex<BTreeMap<u32, VariablePoll>>,
+    /// Id given to the next variable poll
+    next_poll_id: std::sync::atomic::AtomicU32,
+    /// Breakpoints installed through the tools, keyed by address and core index
+    breakpoints: Mutex<BTreeMap<(u64, usize), BreakpointInfo>>,
+    /// Id given to the next new breakpoint
+    next_breakpoint_id: std::sync::atomic::AtomicU32,
+    /// Cores whose current halt was already counted as a breakpoint hit
+    counted_halts: std::sync::Mutex<BTreeSet<usize>>,
+    /// Watchpoints installed through the tools, keyed by address and core index
+    watchpoints: Mutex<BTreeMap<(u64, usize), WatchpointInfo>>,
+    /// Named register snapshots, kept across halt/run cycles
+    register_snapshots: Mutex<HashMap<String, RegisterSnapshot>>,
+    /// Vector catches enabled through the tools, per core index
+    vector_catches: Mutex<BTreeSet<(usize, VectorCatch)>>,
+    /// Captured semihosting output and exit status
+    semihosting: Mutex<SemihostingState>,
+    /// Background monitor pushing target events to the client
+    event_monitor: std::sync::Mutex<Option<EventMonitor>>,
+}
+
+/// Get core `index` of a probe-rs session, rejecting indices the target does not have
+pub fn get_core(session: &mut Session, index: usize) -> Result<Core<'_>> {
+    let core_count = session.list_cores().len();
+    if index >= core_count {
+        return Err(DebugError::InvalidConfig(format!(
+            "Core index {} is out of range: target has {} core(s)",
+            index, core_count
+        )));
+    }
+    session.core(index)
+        .map_err(|e| DebugError::InternalError(format!("Failed to get core {}: {}", index, e)))
+}
+
+/// Re-apply known breakpoints, watchpoints and vector catches to a session after a reset or re-attach.
+///
+/// Breakpoints that cannot be installed again are disabled rather than dropped.
+fn rearm_all(
+    session: &mut Session,
+    breakpoints: &mut BTreeMap<(u64, usize), BreakpointInfo>,
+    watchpoints: &BTreeMap<(u64, usize), WatchpointInfo>,
+    vector_catches: &BTreeSet<(usize, VectorCatch)>,
+) -> ReconnectResult {
+    let (breakpoints_restored, breakpoints_failed) = breakpoints::rearm_enabled(breakpoints.values_mut(), |breakpoint| {
+        get_core(session, breakpoint.core_index).and_then(|mut core| breakpoints::rearm(&mut core, breakpoint))
+    });
+    let mut result = ReconnectResult {
+        breakpoints_restored,
+        breakpoints_failed,
+        watchpoints_failed: Vec::new(),
+    };
+
+    for watchpoint in watchpoints.values() {
+        if let Err(e) = get_core(session, watchpoint.core_index).and_then(|mut core| watchpoints::program(&mut core, watchpoint)) {
+            warn!("Failed to restore watchpoint at 0x{:08X}: {}", watchpoint.address, e);
+            result.watchpoints_failed.push((watchpoint.address, e.to_string()));
+        }
+    }
+
+    for &(core_index, catch) in vector_catches {
+        if let Err(e) = get_core(session, core_index).and_then(|mut core| vector_catch::apply(&mut core, catch, true)) {
+            warn!("Failed to restore vector catch '{}' on core {}: {}", catch, core_index, e);
+        }
+    }
+    result
+}
+
+/// Fail unless the core is halted, as register access requires
+fn ensure_halted(core: &mut Core<'_>) -> Result<()> {
+    let halted = core.core_halted()
+        .map_err(|e| DebugError::InternalError(format!("Failed to read core state: {}", e)))?;
+    if !halted {
+        return Err(DebugError::InvalidConfig("Core must be halted to read registers; use 'halt' first".to_string()));
+    }
+    Ok(())
+}
+
+/// Result of a memory write
+#[derive(Debug)]
+pub struct WriteResult {
+    pub bytes_written: usize,
+    /// Offsets that differ after read-back, when verification was requested
+    pub mismatches: Option<Vec<usize>>,
+    /// Bytes read back after the write, when verification was requested
+    pub read_back: Option<Vec<u8>>,
+}
+
+/// Result of a reconnect
+#[derive(Debug, Default)]
+pub struct ReconnectResult {
+    /// Addresses of the breakpoints re-applied on the target
+    pub breakpoints_restored: Vec<u64>,
+    /// Breakpoints that could not be re-applied, with the reason
+    pub breakpoints_failed: Vec<(u64, String)>,
+    /// Watchpoints that could not be re-applied, with the reason
+    pub watchpoints_failed: Vec<(u64, String)>,
+}
+
+/// Result of `run_firmware`
+#[derive(Debug)]
+pub struct RunFirmwareResult {
+    pub flash: ProgramResult,
+    /// Core status after the reset, `None` when the target was not reset
+    pub core_status: Option<CoreStatus>,
+    /// Attached RTT control block, `None` when RTT was not requested or not found
+    pub rtt: Option<RttInfo>,
+    /// Why RTT could not be attached within the timeout
+    pub rtt_error: Option<String>,
+}
+
+/// Outcome of `run_tests`
+#[derive(Debug)]
+pub struct TestRunResult {
+    pub flash: ProgramResult,
+    pub log: TestLog,
+    /// Halt that ended the run, carrying the semihosting exit if there was one
+    pub halt: Option<HaltEvent>,
+    /// Whether the run hit its timeout; the core was halted then
+    pub timed_out: bool,
+    pub elapsed_ms: u64,
+}
+
+impl DebugSession {
+    /// Create a new debug session around an attached probe-rs session
+    pub fn new(
+        session_id: String,
+        probe_info: DebugProbeInfo,
+        target_chip: String,
+        speed: ProbeSpeed,
+        probe_swo: bool,
+        session: Session,
+    ) -> Self {
+        Self {
+            session_id,
+            probe_identifier: probe_info.identifier.clone(),
+            target_chip,
+            probe_info,
+            speed: std::sync::Mutex::new(speed),
+            probe_swo,
+            nrst_works: std::sync::Mutex::new(None),
+            created_at: chrono::Utc::now(),
+            last_activity: std::sync::Mutex::new(chrono::Utc::now()),
+            session: Arc::new(Mutex::new(Some(session))),
+            rtt_manager: Arc::new(tokio::sync::Mutex::new(RttManager::new())),
+            symbol_tables: Mutex::new(HashMap::new()),
+            default_elf_path: Mutex::new(None),
+            line_tables: Mutex::new(HashMap::new()),
+            inline_resolvers: Mutex::new(HashMap::new()),
+            variable_tables: Mutex::new(HashMap::new()),
+            polls: std::sync::Mutex::new(BTreeMap::new()),
+            next_poll_id: std::sync::atomic::AtomicU32::new(1),
+            breakpoints: Mutex::new(BTreeMap::new()),
+            next_breakpoint_id: std::sync::atomic::AtomicU32::new(1),
+            counted_halts: std::sync::Mutex::new(BTreeSet::new()),
+            vector_catches: Mutex::new(BTreeSet::new()),
+            register_snapshots: Mutex::new(HashMap::new()),
+            watchpoints: Mutex::new(BTreeMap::new()),
+            semihosting: Mutex::new(SemihostingState::default()),
+            event_monitor: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Lock the probe-rs session, failing if it is currently disconnected
+    pub async fn lock_session(&self) -> Result<MappedMutexGuard<'_, Session>> {
+        lock_session(&self.session).await
+    }
+
+    /// Install a breakpoint and remember it so it can be re-applied after a reconnect.
+    ///
+    /// Software breakpoints patch a breakpoint instruction over the code, so
+    /// they only work in writable memory; the replaced bytes are kept and
+    /// written back when the breakpoint is cleared. A temporary breakpoint is
+    /// removed by `wait_for_halt` once the core halts on it; setting a
+    /// permanent one at the same address makes it permanent.
+    pub async fn set_breakpoint(
+        &self,
+        core_index: usize,
+        address: u64,
+        software: bool,
+        symbol: Option<String>,
+        temporary: bool,
+        skip_count: u32,
+    ) -> Result<BreakpointInfo> {
+        let mut breakpoints = self.breakpoints.lock().await;
+        if let Some(existing) = breakpoints.get_mut(&(address, core_index)) {
+            if existing.kind.is_software() == software {
+                if !existing.enabled {
+                    let mut session = self.lock_session().await?;
+                    breakpoints::rearm(&mut get_core(&mut session, existing.core_index)?, existing)?;
+                    existing.enabled = true;
+                }
+                existing.temporary &= temporary;
+                existing.skip_count = skip_count;
+                return Ok(existing.clone());
+            }
+            return Err(DebugError::InvalidConfig(format!(
+                "A {} breakpoint is already set at 0x{:08X}; clear it first", existing.kind, address
+            )));
+        }
+
+        let mut session = self.lock_session().await?;
+        let kind = if software {
+            if let Some((name, range)) = memory::find_nvm_overlap(&session.target().memory_map, address, 2) {
+                return Err(DebugError::InvalidAddress {
+                    address,
+                    reason: format!(
+                        "inside flash region '{}' (0x{:08X}-0x{:08X}); software breakpoints need code in RAM, use breakpoint_type \"hardware\"",
+                        name, range.start, range.end
+                    ),
+                });
+            }
+            let mut core = get_core(&mut session, core_index)?;
+            let mut head = [0u8; 2];
+            core.read_8(address, &mut head)?;
+            let opcode = breakpoints::breakpoint_instruction(core.core_type(), u16::from_le_bytes(head))
+                .ok_or_else(|| DebugError::InvalidConfig(format!(
+                    "Software breakpoints are not supported on {:?} cores", core.core_type()
+                )))?;
+
+            let mut original = vec![0u8; opcode.len()];
+            core.read_8(address, &mut original)?;
+            core.write_8(address, &opcode)?;
+            let mut read_back = vec![0u8; opcode.len()];
+            core.read_8(address, &mut read_back)?;
+            if read_back != opcode {
+                let _ = core.write_8(address, &original);
+                return Err(MemoryError::WriteFailed {
+                    address,
+                    reason: "memory is not writable; use breakpoint_type \"hardware\"".to_string(),
+                }.into());
+            }
+            BreakpointKind::Software { original }
+        } else {
+            breakpoints::set_hardware_breakpoint(&mut get_core(&mut session, core_index)?, address)?;
+            BreakpointKind::Hardware
+        };
+
+        let id = self.next_breakpoint_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let breakpoint = BreakpointInfo {
+            id,
+            address,
+            core_index,
+            symbol,
+            kind,
+            temporary,
+            enabled: true,
+            hit_count: 0,
+            skip_count,
+        };
+        breakpoints.insert((address, core_index), breakpoint.clone());
+        Ok(breakpoint)
+    }
+
+    /// Install a data watchpoint on a free DWT comparator of a core, replacing one at the same address on that core
+    pub async fn set_watchpoint(&self, core_index: usize, address: u64, size: u32, access: WatchAccess) -> Result<WatchpointInfo> {
+        watchpoints::validate(address, size)?;
+        let mut installed = self.watchpoints.lock().await;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+
+        let total = watchpoints::comparator_count(&mut core)?;
+        let comparator = match installed.get(&(address, core_index)) {
+            Some(existing) => existing.comparator,
+            None => (0..total)
+                .find(|index| !installed.values().any(|w| w.core_index == core_index && w.comparator == *index))
+                .ok_or_else(|| DebugError::InvalidConfig(format!(
+                    "All {} DWT comparators are in use by watchpoints at {}; clear one first",
+                    total,
+                    installed.values()
+                        .filter(|w| w.core_index == core_index)
+                        .map(|w| format!("0x{:08X}", w.address))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))?,
+        };
+
+        let watchpoint = WatchpointInfo { address, size, access, core_index, comparator };
+        watchpoints::program(&mut core, &watchpoint)?;
+        installed.insert((address, core_index), watchpoint.clone());
+        Ok(watchpoint)
+    }
+
+    /// Remove the watchpoint at `address` on a core, freeing its comparator
+    pub async fn clear_watchpoint(&self, core_index: usize, address: u64) -> Result<WatchpointInfo> {
+        let mut installed = self.watchpoints.lock().await;
+        let watchpoint = installed.get(&(address, core_index)).cloned()
+            .ok_or_else(|| DebugError::InvalidConfig(format!("No watchpoint at 0x{:08X} on core {}", address, core_index)))?;
+        let mut session = self.lock_session().await?;
+        watchpoints::disable(&mut get_core(&mut session, core_index)?, watchpoint.comparator)?;
+        installed.remove(&(address, core_index));
+        Ok(watchpoint)
+    }
+
+    /// Watchpoints installed through the tools on a core, ordered by address
+    pub async fn watchpoints(&self, core_index: usize) -> Vec<WatchpointInfo> {
+        self.watchpoints.lock().await
+            .values()
+            .filter(|watchpoint| watchpoint.core_index == core_index)
+            .cloned()
+            .collect()
+    }
+
+    /// Up and down channels of the attached RTT control block, up channels first, by index,
+    /// with their flags read from the target
+    pub async fn list_rtt_channels(&self) -> Result<Vec<ChannelInfo>> {
+        let mut rtt_manager = self.rtt_manager.lock().await;
+        if !rtt_manager.is_attached() {
+            return Err(DebugError::RttNotAvailable);
+        }
+        if let Err(e) = rtt_manager.refresh_channel_flags().await {
+            warn!("Listing RTT channels with the flags read at attach: {}", e);
+        }
+        let mut channels: Vec<ChannelInfo> = rtt_manager.get_channels().into_iter().cloned().collect();
+        channels.sort_by_key(|channel| (matches!(channel.direction, ChannelDirection::Down), channel.id));
+        Ok(channels)
+    }
+
+    /// Used and total hardware breakpoint comparators of a core
+    pub async fn breakpoint_capacity(&self, core_index: usize) -> Result<BreakpointCapacity> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        breakpoints::capacity(&mut core)
+    }
+
+    /// Report which debug features the core and probe support.
+    ///
+    /// Comparator counts are read from the core, so they reflect what this
+    /// particular chip implements rather than the architecture maximum.
+    pub async fn capabilities(&self, core_index: usize) -> Result<Capabilities> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        let core_type = core.core_type();
+        let hw_breakpoints = breakpoints::capacity(&mut core)?.total;
+        let watchpoints = if core_type.is_cortex_m() {
+            watchpoints::comparator_count(&mut core)?
+        } else {
+            0
+        };
+        let fpu_registers = match core.fpu_support() {
+            Ok(true) => core.floating_point_register_count().unwrap_or(0),
+            _ => 0,
+        };
+
+        Ok(Capabilities {
+            core_type,
+            hw_breakpoints,
+            watchpoints,
+            software_breakpoints: breakpoints::breakpoint_instruction(core_type, 0).is_some(),
+            fpu_registers,
+            itm: matches!(core_type, probe_rs::CoreType::Armv7m | probe_rs::CoreType::Armv7em | probe_rs::CoreType::Armv8m),
+            probe_swo: self.probe_swo,
+            nrst: *self.nrst_works.lock().unwrap_or_else(|e| e.into_inner()),
+        })
+    }
+
+    /// Remove a breakpoint, restoring the original instruction of a software
+    /// breakpoint or releasing the hardware comparator
+    pub async fn clear_breakpoint(&self, core_index: usize, address: u64) -> Result<Option<BreakpointInfo>> {
+        let mut breakpoints = self.breakpoints.lock().await;
+        let mut session = self.lock_session().await?;
+        match breakpoints.get(&(address, core_index)) {
+            Some(breakpoint) if !breakpoint.enabled => {}
+            Some(breakpoint) => breakpoints::uninstall(&mut get_core(&mut session, breakpoint.core_index)?, breakpoint)?,
+            None => get_core(&mut session, core_index)?.clear_hw_breakpoint(address)?,
+        }
+        Ok(breakpoints.remove(&(address, core_index)))
+    }
+
+    /// Enable or disable a known breakpoint, keeping its entry either way.
+    ///
+    /// Disabling removes it from the target (freeing a hardware comparator or
+    /// restoring the original instruction); enabling installs it again.
+    pub async fn set_breakpoint_enabled(&self, core_index: usize, address: u64, enabled: bool) -> Result<BreakpointInfo> {
+        let mut breakpoints = self.breakpoints.lock().await;
+        let breakpoint = breakpoints.get_mut(&(address, core_index))
+            .ok_or_else(|| DebugError::InvalidConfig(format!("No breakpoint at 0x{:08X} on core {}", address, core_index)))?;
+        if breakpoint.enabled != enabled {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, breakpoint.core_index)?;
+            if enabled {
+                breakpoints::rearm(&mut core, breakpoint)?;
+            } else {
+                breakpoints::uninstall(&mut core, breakpoint)?;
+            }
+            breakpoint.enabled = enabled;
+        }
+        Ok(breakpoint.clone())
+    }
+
+    /// Remove every breakpoint (and optionally watchpoint) from the target and forget them.
+    ///
+    /// Known breakpoints are uninstalled one by one, restoring the
+    /// instructions under software breakpoints; then every hardware
+    /// comparator on each core is cleared so stale ones set outside the tools
+    /// go too. A breakpoint that fails to uninstall is reported and still
+    /// forgotten. Called when a session is closed so it never leaves the chip
+    /// armed.
+    pub async fn clear_all_breakpoints(&self, include_watchpoints: bool) -> Result<ClearAllResult> {
+        let mut breakpoints = self.breakpoints.lock().await;
+        let mut watchpoints = self.watchpoints.lock().await;
+        let mut session = self.lock_session().await?;
+        let (removed, failed) = breakpoints::uninstall_each(breakpoints.values(), |breakpoint| {
+            get_core(&mut session, breakpoint.core_index).and_then(|mut core| breakpoints::uninstall(&mut core, breakpoint))
+        });
+        let mut result = ClearAllResult { breakpoints: removed, watchpoints: Vec::new(), failed };
+        breakpoints.clear();
+
+        for core_index in 0..session.list_cores().len() {
+            if let Err(e) = get_core(&mut session, core_index).and_then(|mut core| Ok(core.clear_all_hw_breakpoints()?)) {
+                warn!("Failed to clear hardware breakpoints on core {}: {}", core_index, e);
+            }
+        }
+
+        if include_watchpoints {
+            for watchpoint in watchpoints.values() {
+                match get_core(&mut session, watchpoint.core_index).and_then(|mut core| watchpoints::disable(&mut core, watchpoint.comparator)) {
+                    Ok(()) => result.watchpoints.push(watchpoint.address),
+                    Err(e) => {
+                        warn!("Failed to remove watchpoint at 0x{:08X}: {}", watchpoint.address, e);
+                        result.failed.push((watchpoint.address, e.to_string()));
+                    }
+                }
+            }
+            watchpoints.clear();
+        }
+        Ok(result)
+    }
+
+    /// Undo all debug state set through the tools, resume every core and release the probe.
+    ///
+    /// Dropping a session may leave the target halted, or halting again on a
+    /// leftover breakpoint. This removes breakpoints, watchpoints and vector
+    /// catches first so the firmware keeps running on its own, then drops the
+    /// probe-rs session. The session is left disconnected.
+    pub async fn detach_running(&self) -> Result<()> {
+        self.clear_all_breakpoints(true).await?;
+        *self.rtt_manager.lock().await = RttManager::new();
+        let mut vector_catches = self.vector_catches.lock().await;
+
+        let mut slot = self.session.lock().await;
+        let session = slot.as_mut().ok_or_else(|| {
+            DebugError::ConnectionFailed("Session is disconnected; nothing to detach".to_string())
+        })?;
+
+        for &(core_index, catch) in vector_catches.iter() {
+            if let Err(e) = get_core(session, core_index).and_then(|mut core| vector_catch::apply(&mut core, catch, false)) {
+                warn!("Failed to disable vector catch '{}' on core {} before detaching: {}", catch, core_index, e);
+            }
+        }
+        vector_catches.clear();
+
+        for core_index in 0..session.list_cores().len() {
+            let mut core = get_core(session, core_index)?;
+            if core.core_halted()? {
+                core.run()?;
+            }
+        }
+
+        slot.take();
+        info!("Detached session {} with the target running", self.session_id);
+        Ok(())
+    }
+
+    /// Breakpoints installed through the tools, ordered by address
+    pub async fn breakpoints(&self) -> Vec<BreakpointInfo> {
+        self.breakpoints.lock().await.values().cloned().collect()
+    }
+
+    /// Re-open the probe and re-attach to the target, keeping the session id.
+    ///
+    /// The old probe-rs session is dropped first so the probe is released
+    /// before it is opened again. Known breakpoints and vector catches are re-applied and RTT must
+    /// be attached again. If attaching fails the session stays disconnected
+    /// and reconnect can be retried.
+    pub async fn reconnect(&self) -> Result<ReconnectResult> {
+        self.reattach(false, true).await
+    }
+
+    /// Drop and re-open the probe-rs session, optionally pulsing nRST while attaching.
+    ///
+    /// Known breakpoints, watchpoints and vector catches are re-applied unless
+    /// `rearm` is false, in which case the result is empty. If the probe
+    /// cannot attach under reset, the session is re-attached normally so it
+    /// stays usable and the nRST failure is returned.
+    async fn reattach(&self, under_reset: bool, rearm: bool) -> Result<ReconnectResult> {
+        info!("Reconnecting session {} to {}", self.session_id, self.target_chip);
+
+        // Same order as every other path: the RTT manager and the debug state tables before the session
+        let mut rtt_manager = self.rtt_manager.lock().await;
+        let mut breakpoints = self.breakpoints.lock().await;
+        let watchpoints = self.watchpoints.lock().await;
+        let vector_catches = self.vector_catches.lock().await;
+        let mut slot = self.session.lock().await;
+        slot.take();
+        *rtt_manager = RttManager::new();
+
+        let requested_khz = self.get_speed().requested_khz;
+        let mut reset_error = None;
+        let (mut session, actual_khz) = match attach(&self.probe_info, &self.target_chip, requested_khz, under_reset) {
+            Ok(attached) => attached,
+            Err(e) if under_reset => {
+                warn!("Attach under reset failed for session {}: {}", self.session_id, e);
+                reset_error = Some(e);
+                attach(&self.probe_info, &self.target_chip, requested_khz, false)?
+            }
+            Err(e) => return Err(e),
+        };
+        *self.speed.lock().unwrap_or_else(|e| e.into_inner()) = ProbeSpeed { requested_khz, actual_khz };
+        if under_reset {
+            *self.nrst_works.lock().unwrap_or_else(|e| e.into_inner()) = Some(reset_error.is_none());
+        }
+
+        let result = if rearm {
+            rearm_all(&mut session, &mut breakpoints, &watchpoints, &vector_catches)
+        } else {
+            ReconnectResult::default()
+        };
+
+        *slot = Some(session);
+        info!("Reconnected session {} ({} breakpoint(s) restored)", self.session_id, result.breakpoints_restored.len());
+        if let Some(e) = reset_error {
+            return Err(DebugError::ProbeError(format!(
+                "Probe {} could not drive nRST for a hardware reset ({}); the session was re-attached without a reset. Use reset_type \"system\" instead",
+                self.probe_identifier, e
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Reset the target and optionally halt it afterwards.
+    ///
+    /// A hardware reset re-attaches the session with nRST asserted, so RTT
+    /// must be attached again afterwards. A system reset writes AIRCR.SYSRESETREQ
+    /// directly and catches the reset vector when halting. After any reset,
+    /// breakpoints and watchpoints are re-armed unless `rearm` is false, in
+    /// which case `None` is returned. Re-arming is best-effort:
+    /// a breakpoint that cannot be installed again is reported in the result
+    /// and left disabled, so it can be enabled again later.
+    pub async fn reset(&self, core_index: usize, reset_type: ResetType, halt: bool, rearm: bool) -> Result<Option<ReconnectResult>> {
+        self.forget_halt(core_index);
+        match reset_type {
+            ResetType::Hardware => {
+                let result = self.reattach(true, rearm).await?;
+                if halt {
+                    let mut session = self.lock_session().await?;
+                    get_core(&mut session, core_index)?.halt(RESET_HALT_TIMEOUT)
+                        .map_err(|e| DebugError::from_wait("Halting after reset", RESET_HALT_TIMEOUT, e))?;
+                }
+                return Ok(rearm.then_some(result));
+            }
+            ResetType::Software => {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                if halt {
+                    core.reset_and_halt(RESET_HALT_TIMEOUT)
+                        .map_err(|e| DebugError::from_wait("Reset and halt", RESET_HALT_TIMEOUT, e))?;
+                } else {
+                    core.reset()?;
+                }
+            }
+            ResetType::System => {
+                let reset_catch_enabled = self.vector_catches.lock().await.contains(&(core_index, VectorCatch::Reset));
+                {
+                    let mut session = self.lock_session().await?;
+                    let mut core = get_core(&mut session, core_index)?;
+                    if !core.core_type().is_cortex_m() {
+                        return Err(DebugError::InvalidConfig(format!(
+                            "System reset through AIRCR is only available on Cortex-M cores, not {:?}; use reset_type \"software\"",
+                            core.core_type()
+                        )));
+                    }
+
+                    if halt && !reset_catch_enabled {
+                        vector_catch::apply(&mut core, VectorCatch::Reset, true)?;
+                    }
+                    // The debug interface may drop the transaction while the system resets
+                    if let Err(e) = core.write_word_32(AIRCR, AIRCR_VECTKEY | AIRCR_SYSRESETREQ) {
+                        debug!("AIRCR write returned {} during system reset", e);
+                    }
+                }
+                // Let the reset complete without holding the session, so other tools are not stalled
+                tokio::time::sleep(SYSTEM_RESET_SETTLE).await;
+
+                if halt {
+                    let mut session = self.lock_session().await?;
+                    let mut core = get_core(&mut session, core_index)?;
+                    let halted = core.wait_for_core_halted(RESET_HALT_TIMEOUT)
+                        .map_err(|e| DebugError::from_wait("Halting after system reset", RESET_HALT_TIMEOUT, e));
+                    if !reset_catch_enabled {
+                        vector_catch::apply(&mut core, VectorCatch::Reset, false)?;
+                    }
+                    halted?;
+                }
+            }
+        }
+
+        if !rearm {
+            return Ok(None);
+        }
+        let mut breakpoints = self.breakpoints.lock().await;
+        let watchpoints = self.watchpoints.lock().await;
+        let vector_catches = self.vector_catches.lock().await;
+        let mut session = self.lock_session().await?;
+        let result = rearm_all(&mut session, &mut breakpoints, &watchpoints, &vector_catches);
+        info!("Re-armed {} breakpoint(s) after reset of session {}", result.breakpoints_restored.len(), self.session_id);
+        Ok(Some(result))
+    }
+
+    /// Flash a firmware file, then optionally reset the target and attach RTT.
+    ///
+    /// RTT is retried until `rtt_timeout` elapses, since the firmware needs
+    /// time after reset to set up its control block; failing to find it is
+    /// reported in the result rather than as an error.
+    pub async fn run_firmware(
+        &self,
+        file_path: &std::path::Path,
+        format: FileFormat,
+        reset: bool,
+        rtt_timeout: Option<std::time::Duration>,
+    ) -> Result<RunFirmwareResult> {
+        let flash = {
+            let mut session = self.lock_session().await?;
+            FlashManager::program_file(&mut session, file_path, format, None, None).await?
+        };
+
+        let core_status = if reset {
+            self.reset(0, ResetType::Software, false, true).await?;
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, 0)?;
+            Some(core.status()?)
+        } else {
+            None
+        };
+
+        let (mut rtt, mut rtt_error) = (None, None);
+        if let Some(timeout) = rtt_timeout {
+            match self.attach_rtt_retrying(file_path, timeout).await {
+                Ok(info) => rtt = info,
+                Err(e) => rtt_error = Some(e.to_string()),
+            }
+        }
+
+        Ok(RunFirmwareResult { flash, core_status, rtt, rtt_error })
+    }
+
+    /// Attach RTT through the control block symbol of `file_path`, retrying
+    /// until `timeout` while the freshly started firmware sets it up
+    async fn attach_rtt_retrying(&self, file_path: &std::path::Path, timeout: std::time::Duration) -> Result<Option<RttInfo>> {
+        let start_time = std::time::Instant::now();
+        let mut rtt_manager = self.rtt_manager.lock().await;
+        loop {
+            match rtt_manager.attach_with_elf(self.session.clone(), file_path).await {
+                Ok(()) => return Ok(rtt_manager.info()),
+                Err(e) if start_time.elapsed() >= timeout => return Err(e),
+                Err(e) => debug!("RTT not found yet after {:?}: {}", start_time.elapsed(), e),
+            }
+            tokio::time::sleep(RTT_ATTACH_RETRY_INTERVAL).await;
+        }
+    }
+
+    /// Flash a test binary, reset it and collect its log until it finishes.
+    ///
+    /// The run ends when the core halts (a semihosting exit, or a breakpoint
+    /// in the panic handler), when the log announces the end of the run, or
+    /// at `timeout`. A timed out or failed core is halted, and RTT stays
+    /// attached, so the failing test can be debugged from where it stopped.
+    pub async fn run_tests(&self, elf_path: &std::path::Path, output: TestOutput, timeout: std::time::Duration) -> Result<TestRunResult> {
+        let start_time = std::time::Instant::now();
+        let flash = {
+            let mut session = self.lock_session().await?;
+            FlashManager::program_file(&mut session, elf_path, FileFormat::Elf, None, None).await?
+        };
+
+        if output == TestOutput::Semihosting {
+            self.enable_semihosting().await;
+        }
+        self.reset(0, ResetType::Software, false, true).await?;
+        if output == TestOutput::Rtt {
+            if let Err(e) = self.attach_rtt_retrying(elf_path, timeout.saturating_sub(start_time.elapsed())).await {
+                self.set_core_halted(0, true).await?;
+                return Err(e);
+            }
+        }
+
+        let mut log = TestLog::default();
+        let (halt, timed_out) = loop {
+            if output == TestOutput::Rtt {
+                let (lines, _) = self.rtt_manager.lock().await.read_lines(0, TEST_POLL_INTERVAL).await?;
+                lines.iter().for_each(|line| log.push_line(line));
+            }
+            let wait = if output == TestOutput::Rtt { std::time::Duration::ZERO } else { TEST_POLL_INTERVAL };
+            let halt = self.wait_for_halt(0, wait).await?;
+            if output == TestOutput::Semihosting {
+                log.push_text(&std::mem::take(&mut self.semihosting.lock().await.output));
+            }
+
+            if halt.is_some() || log.finished == Some(true) {
+                break (halt, false);
+            }
+            // A test panicked but the firmware kept running: stop it where it is
+            if log.finished == Some(false) {
+                self.set_core_halted(0, true).await?;
+                break (None, false);
+            }
+            if start_time.elapsed() >= timeout {
+                self.set_core_halted(0, true).await?;
+                break (None, true);
+            }
+        };
+
+        // Pick up what the target logged right before it stopped
+        if output == TestOutput::Rtt {
+            let mut rtt_manager = self.rtt_manager.lock().await;
+            let (lines, fragment) = rtt_manager.read_lines(0, std::time::Duration::ZERO).await?;
+            lines.iter().for_each(|line| log.push_line(line));
+            log.push_text(&fragment);
+        }
+        log.flush();
+
+        Ok(TestRunResult {
+            flash,
+            log,
+            halt,
+            timed_out,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Current requested and negotiated probe speed
+    pub fn get_speed(&self) -> ProbeSpeed {
+        *self.speed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Change the probe clock.
+    ///
+    /// probe-rs gives no access to the probe once a session is attached, so
+    /// the session is re-attached at the new speed through `reconnect`;
+    /// breakpoints are restored and RTT must be attached again.
+    pub async fn set_speed(&self, speed_khz: u32) -> Result<(ProbeSpeed, ReconnectResult)> {
+        if speed_khz == 0 {
+            return Err(DebugError::InvalidConfig("Speed must be greater than 0 kHz".to_string()));
+        }
+        self.speed.lock().unwrap_or_else(|e| e.into_inner()).requested_khz = speed_khz;
+        let result = self.reconnect().await?;
+        Ok((self.get_speed(), result))
+    }
+
+    /// Enable vector catches so the core halts when it takes these exceptions
+    pub async fn set_vector_catch(&self, core_index: usize, conditions: &[VectorCatch]) -> Result<()> {
+        let mut enabled = self.vector_catches.lock().await;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        for &catch in conditions {
+            vector_catch::apply(&mut core, catch, true)?;
+            enabled.insert((core_index, catch));
+        }
+        Ok(())
+    }
+
+    /// Disable vector catches; with no conditions every catch enabled on the core is cleared
+    pub async fn clear_vector_catch(&self, core_index: usize, conditions: &[VectorCatch]) -> Result<Vec<VectorCatch>> {
+        let mut enabled = self.vector_catches.lock().await;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        let conditions: Vec<VectorCatch> = if conditions.is_empty() {
+            enabled.iter().filter(|(core, _)| *core == core_index).map(|&(_, catch)| catch).collect()
+        } else {
+            conditions.to_vec()
+        };
+        for &catch in &conditions {
+            vector_catch::apply(&mut core, catch, false)?;
+            enabled.remove(&(core_index, catch));
+        }
+        Ok(conditions)
+    }
+
+    /// Vector catches currently enabled on a core
+    pub async fn vector_catches(&self, core_index: usize) -> Vec<VectorCatch> {
+        self.vector_catches.lock().await
+            .iter()
+            .filter(|(core, _)| *core == core_index)
+            .map(|&(_, catch)| catch)
+            .collect()
+    }
+
+    /// Start capturing semihosting requests, clearing earlier output and exit status
+    pub async fn enable_semihosting(&self) {
+        *self.semihosting.lock().await = SemihostingState { enabled: true, ..Default::default() };
+    }
+
+    /// Service semihosting halts for up to `timeout`, then drain the captured output.
+    ///
+    /// Each time the core is found halted on a semihosting request the request
+    /// is handled and the core resumed. Servicing stops early when the core
+    /// halts for another reason or the target exits.
+    pub async fn read_semihosting_output(&self, core_index: usize, timeout: std::time::Duration) -> Result<(String, Option<SemihostingExit>)> {
+        let mut state = self.semihosting.lock().await;
+        if !state.enabled {
+            return Err(DebugError::InvalidConfig("Semihosting is not enabled for this session".to_string()));
+        }
+
+        let deadline = std::time::Instant::now() + timeout;
+        while state.exit.is_none() {
+            let running = {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                let status = core.status()
+                    .map_err(|e| DebugError::InternalError(format!("Failed to read core status: {}", e)))?;
+                match status {
+                    probe_rs::CoreStatus::Halted(probe_rs::HaltReason::Breakpoint(
+                        probe_rs::BreakpointCause::Semihosting(command),
+                    )) => {
+                        if semihosting::handle_command(&mut core, command, &mut state)? {
+                            core.run()
+                                .map_err(|e| DebugError::InternalError(format!("Failed to resume after semihosting: {}", e)))?;
+                        }
+                        true
+                    }
+                    probe_rs::CoreStatus::Running => true,
+                    _ => false,
+                }
+            };
+
+            if !running || std::time::Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        Ok((std::mem::take(&mut state.output), state.exit))
+    }
+
+    /// Enable semihosting, resume the core and service its requests until it
+    /// exits, halts for another reason or `timeout` passes.
+    ///
+    /// Output captured earlier is discarded; the output of this run is drained
+    /// into the result.
+    pub async fn run_with_semihosting(&self, core_index: usize, timeout: std::time::Duration) -> Result<SemihostingRun> {
+        self.enable_semihosting().await;
+        self.resume(core_index).await?;
+        let halt = self.wait_for_halt(core_index, timeout).await?;
+        let output = std::mem::take(&mut self.semihosting.lock().await.output);
+        Ok(SemihostingRun { output, halt })
+    }
+
+    /// USB identity, firmware version and negotiated speed of the probe this
+    /// session was opened on
+    pub fn probe_info(&self) -> ProbeInfo {
+        ProbeInfo::new(&self.probe_info, self.get_speed().actual_khz, ProbeDiscovery::firmware_version(&self.probe_info))
+    }
+
+    /// Time the session was created
+    pub fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+
+    /// Record activity on this session
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = chrono::Utc::now();
+    }
+
+    /// Time of the last recorded activity
+    pub fn last_activity(&self) -> chrono::DateTime<chrono::Utc> {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the symbol table for `elf_path`, loading and caching it on first use.
+    ///
+    /// Without a path, the most recently used ELF of this session is used.
+    pub async fn symbols(&self, elf_path: Option<&str>) -> Result<Arc<SymbolTable>> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; provide elf_path".to_string())
+            })?,
+        };
+
+        let table = {
+            let mut tables = self.symbol_tables.lock().await;
+            match tables.get(&elf_path) {
+                Some(table) => table.clone(),
+                None => {
+                    let table = Arc::new(SymbolTable::load(std::path::Path::new(&elf_path))?);
+                    tables.insert(elf_path.clone(), table.clone());
+                    table
+                }
+            }
+        };
+
+        *self.default_elf_path.lock().await = Some(elf_path);
+        Ok(table)
+    }
+
+    /// Parse `elf_path` and make it the session's default ELF.
+    ///
+    /// Unlike `symbols`, this always re-reads the file so a rebuilt firmware
+    /// is picked up. Both tables are fully built before they replace the
+    /// cached ones, so concurrent lookups see either the old or the new ELF.
+    /// A missing line table (stripped ELF) is not an error.
+    pub async fn load_symbols(&self, elf_path: &str) -> Result<(Arc<SymbolTable>, Option<Arc<LineTable>>)> {
+        let path = std::path::Path::new(elf_path);
+        let symbols = Arc::new(SymbolTable::load(path)?);
+        let lines = match LineTable::load(path) {
+            Ok(table) => Some(Arc::new(table)),
+            Err(e) => {
+                warn!("No line info in {}: {}", elf_path, e);
+                None
+            }
+        };
+
+        let mut symbol_tables = self.symbol_tables.lock().await;
+        let mut line_tables = self.line_tables.lock().await;
+        let mut default_elf_path = self.default_elf_path.lock().await;
+        // Rebuilt lazily from the new file on the next symbolization
+        self.inline_resolvers.lock().await.remove(elf_path);
+        self.variable_tables.lock().await.remove(elf_path);
+        symbol_tables.insert(elf_path.to_string(), symbols.clone());
+        match &lines {
+            Some(table) => line_tables.insert(elf_path.to_string(), table.clone()),
+            None => line_tables.remove(elf_path),
+        };
+        *default_elf_path = Some(elf_path.to_string());
+        Ok((symbols, lines))
+    }
+
+    /// Forget every cached ELF of this session, returning the default ELF path if one was loaded
+    pub async fn unload_symbols(&self) -> Option<String> {
+        let mut symbol_tables = self.symbol_tables.lock().await;
+        let mut line_tables = self.line_tables.lock().await;
+        symbol_tables.clear();
+        line_tables.clear();
+        self.inline_resolvers.lock().await.clear();
+        self.variable_tables.lock().await.clear();
+        self.default_elf_path.lock().await.take()
+    }
+
+    /// Describe the default ELF of this session, if one is loaded
+    pub async fn symbol_status(&self) -> Option<SymbolStatus> {
+        let elf_path = self.default_elf_path.lock().await.clone()?;
+        let symbols = self.symbol_tables.lock().await.get(&elf_path).cloned();
+        let has_line_info = self.line_tables.lock().await.contains_key(&elf_path);
+        Some(SymbolStatus {
+            build_id: symbols.as_ref().and_then(|table| table.build_id().map(str::to_string)),
+            symbol_count: symbols.as_ref().map_or(0, |table| table.len()),
+            has_line_info,
+            elf_path,
+        })
+    }
+
+    /// Look up a symbol by raw or demangled name, suggesting close matches when it is unknown
+    pub async fn resolve_symbol(&self, name: &str, elf_path: Option<&str>) -> Result<Symbol> {
+        let symbols = self.symbols(elf_path).await?;
+        if let Some(symbol) = symbols.lookup(name) {
+            return Ok(symbol.clone());
+        }
+
+        let suggestions = symbols.suggest(name, 3);
+        let hint = if suggestions.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "; did you mean {}?",
+                suggestions.iter().map(|s| format!("`{}`", s.display_name())).collect::<Vec<_>>().join(", ")
+            )
+        };
+        Err(DebugError::InvalidConfig(format!("Symbol '{}' not found in ELF symbol table{}", name, hint)))
+    }
+
+    /// Read `size` bytes in `chunk_size` pieces.
+    ///
+    /// The session lock is released between chunks so other tools such as
+    /// `halt` or `get_status` can interleave with large reads.
+    ///
+    /// With `allow_running` a running core is read through the debug port
+    /// without halting it. Targets whose bus faults on running access fall
+    /// back to halting for the rest of the read, which is reported in
+    /// `ReadResult::note`. Without `allow_running` a running core is halted
+    /// for the read. Either way the core is resumed afterwards.
+    pub async fn read_memory(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        chunk_size: usize,
+        allow_unmapped: bool,
+        allow_running: bool,
+    ) -> Result<ReadResult> {
+        self.read_memory_with_progress(core_index, address, size, chunk_size, allow_unmapped, allow_running, None).await
+    }
+
+    /// [`Self::read_memory`], sending the read progress to `progress` after every chunk
+    #[allow(clippy::too_many_arguments)]
+    pub async fn read_memory_with_progress(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        chunk_size: usize,
+        allow_unmapped: bool,
+        allow_running: bool,
+        progress: Option<&ProgressSink>,
+    ) -> Result<ReadResult> {
+        let start_time = std::time::Instant::now();
+
+        if chunk_size == 0 {
+            return Err(DebugError::InvalidConfig("Chunk size must be greater than 0".to_string()));
+        }
+        if !allow_unmapped {
+            self.validate_range(address, size).await?;
+        }
+
+        let was_running = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            !core.core_halted()
+                .map_err(|e| DebugError::InternalError(format!("Failed to read core state: {}", e)))?
+        };
+        let mut halted_for_read = false;
+        if was_running && !allow_running {
+            self.set_core_halted(core_index, true).await?;
+            halted_for_read = true;
+        }
+
+        let mut note = None;
+        let mut phase = progress::start_phase(progress, ProgressPhase::Read, size as u64);
+        let mut data = vec![0u8; size];
+        let mut read = 0;
+        let outcome: Result<()> = async {
+            while read < size {
+                let len = chunk_size.min(size - read);
+                let chunk_address = address + read as u64;
+                let outcome = {
+                    let mut session = self.lock_session().await?;
+                    let mut core = get_core(&mut session, core_index)?;
+                    core.read(chunk_address, &mut data[read..read + len])
+                };
+
+                if let Err(e) = outcome {
+                    if was_running && !halted_for_read {
+                        warn!("Running read at 0x{:08X} failed ({}), halting core {} for the read", chunk_address, e, core_index);
+                        self.set_core_halted(core_index, true).await?;
+                        halted_for_read = true;
+                        note = Some(format!(
+                            "Reading while running faulted at 0x{:08X} ({}); the core was halted for the read and resumed afterwards",
+                            chunk_address, e
+                        ));
+                        continue;
+                    }
+                    return Err(DebugError::MemoryAccessFailed(format!(
+                        "Read failed at 0x{:08X} after {} bytes: {}", chunk_address, read, e
+                    )));
+                }
+                read += len;
+                if let Some(phase) = phase.as_mut() {
+                    phase.advance(len as u64);
+                }
+            }
+            Ok(())
+        }.await;
+
+        // Resume a core halted for the read also when the read failed, then report the read error first
+        if halted_for_read {
+            let resumed = self.set_core_halted(core_index, false).await;
+            outcome?;
+            resumed?;
+        } else {
+            outcome?;
+        }
+
+        debug!("Read {} bytes from address 0x{:08X}", size, address);
+        Ok(ReadResult {
+            data,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+            was_running,
+            note,
+        })
+    }
+
+    /// Sample `size` bytes at `address` `samples` times, `interval` apart, without halting the core
+    pub async fn watch_memory(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        samples: usize,
+        interval: std::time::Duration,
+    ) -> Result<Vec<WatchSample>> {
+        let start_time = std::time::Instant::now();
+        let mut series = Vec::with_capacity(samples);
+        for index in 0..samples {
+            if index > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            let result = self.read_memory(core_index, address, size, memory::DEFAULT_CHUNK_SIZE, false, true).await?;
+            series.push(WatchSample {
+                elapsed_ms: start_time.elapsed().as_millis() as u64,
+                data: result.data,
+                was_running: result.was_running,
+                note: result.note,
+            });
+        }
+        Ok(series)
+    }
+
+    /// Start sampling a `scalar_type` value at `address` every `interval` in the background.
+    ///
+    /// Samples are plain debug-port reads, so the core keeps running. The
+    /// newest `capacity` samples are kept. The sampler stops with
+    /// `stop_variable_poll` or when the session is closed.
+    pub async fn start_variable_poll(
+        self: &Arc<Self>,
+        core_index: usize,
+        label: String,
+        address: u64,
+        scalar_type: ScalarType,
+        interval: std::time::Duration,
+        capacity: usize,
+    ) -> Result<PollInfo> {
+        if interval < poll::MIN_POLL_INTERVAL {
+            return Err(DebugError::InvalidConfig(format!(
+                "Poll interval must be at least {}ms", poll::MIN_POLL_INTERVAL.as_millis()
+            )));
+        }
+        if capacity == 0 || capacity > memory::MAX_WATCH_SAMPLES {
+            return Err(DebugError::InvalidConfig(format!(
+                "Poll history must hold between 1 and {} samples", memory::MAX_WATCH_SAMPLES
+            )));
+        }
+        self.validate_range(address, scalar_type.size()).await?;
+        {
+            let mut session = self.lock_session().await?;
+            get_core(&mut session, core_index)?;
+        }
+
+        let mut polls = self.polls.lock().unwrap_or_else(|e| e.into_inner());
+        if polls.len() >= poll::MAX_POLLS {
+            return Err(DebugError::InvalidConfig(format!(
+                "At most {} variable polls can run at once; stop one with stop_variable_poll", poll::MAX_POLLS
+            )));
+        }
+
+        let info = PollInfo {
+            id: self.next_poll_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            core_index,
+            label,
+            address,
+            scalar_type,
+            interval,
+            capacity,
+        };
+        let history = Arc::new(std::sync::Mutex::new(PollHistory::new(capacity)));
+
+        // The sampler only holds a weak reference so it ends with the session
+        let session = Arc::downgrade(self);
+        let sampler_history = history.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let Some(session) = session.upgrade() else { break };
+                let value = session.read_memory_typed(core_index, address, scalar_type, 1, None, true).await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result.values.first().copied().ok_or_else(|| "Empty read".to_string()));
+                drop(session);
+                sampler_history.lock().unwrap_or_else(|e| e.into_inner()).push(PollSample {
+                    timestamp: chrono::Utc::now(),
+                    value,
+                });
+            }
+        });
+
+        info!("Started variable poll #{} of {} at 0x{:08X} every {}ms", info.id, info.label, address, interval.as_millis());
+        polls.insert(info.id, VariablePoll { info: info.clone(), history, task });
+        Ok(info)
+    }
+
+    /// Stop a variable poll, discarding its history
+    pub fn stop_variable_poll(&self, id: u32) -> Result<PollInfo> {
+        let poll = self.polls.lock().unwrap_or_else(|e| e.into_inner()).remove(&id)
+            .ok_or_else(|| DebugError::InvalidConfig(format!("No variable poll #{}", id)))?;
+        Ok(poll.info.clone())
+    }
+
+    /// The `count` most recent samples of poll `id`, or of every poll when `id` is `None`,
+    /// with the number of samples taken so far
+    pub fn variable_poll_history(&self, id: Option<u32>, count: usize) -> Result<Vec<(PollInfo, u64, Vec<PollSample>)>> {
+        let polls = self.polls.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(id) = id.filter(|id| !polls.contains_key(id)) {
+            return Err(DebugError::InvalidConfig(format!("No variable poll #{}", id)));
+        }
+        Ok(polls
+            .values()
+            .filter(|poll| id.is_none_or(|id| poll.info.id == id))
+            .map(|poll| {
+                let history = poll.history.lock().unwrap_or_else(|e| e.into_inner());
+                (poll.info.clone(), history.total, history.recent(count))
+            })
+            .collect())
+    }
+
+    /// Start pushing target events into `sender`, replacing an earlier monitor.
+    ///
+    /// The monitor checks the core every `interval` and reports when it goes
+    /// from running to halted; with `rtt` set it also drains the attached
+    /// text up channels. A check is skipped while a tool call holds the probe
+    /// or RTT, so the monitor never delays one. It ends with the session or
+    /// once `sender` is closed.
+    pub async fn subscribe_events(
+        self: &Arc<Self>,
+        core_index: usize,
+        rtt: bool,
+        interval: std::time::Duration,
+        sender: tokio::sync::mpsc::UnboundedSender<TargetEvent>,
+    ) -> Result<EventSubscription> {
+        if interval < events::MIN_EVENT_INTERVAL {
+            return Err(DebugError::InvalidConfig(format!(
+                "Event interval must be at least {}ms", events::MIN_EVENT_INTERVAL.as_millis()
+            )));
+        }
+        {
+            let mut session = self.lock_session().await?;
+            get_core(&mut session, core_index)?;
+        }
+
+        let subscription = EventSubscription { core_index, rtt, interval };
+        // The monitor only holds a weak reference so it ends with the session
+        let session = Arc::downgrade(self);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut halted = None;
+            loop {
+                ticker.tick().await;
+                let Some(session) = session.upgrade() else { break };
+                let events = session.poll_events(core_index, rtt, &mut halted).await;
+                drop(session);
+                if events.into_iter().any(|event| sender.send(event).is_err()) || sender.is_closed() {
+                    break;
+                }
+            }
+        });
+
+        info!("Started event monitor for core {} every {}ms (RTT: {})", core_index, interval.as_millis(), rtt);
+        *self.event_monitor.lock().unwrap_or_else(|e| e.into_inner()) = Some(EventMonitor { subscription: subscription.clone(), task });
+        Ok(subscription)
+    }
+
+    /// Stop the event monitor, returning what it watched
+    pub fn unsubscribe_events(&self) -> Option<EventSubscription> {
+        self.event_monitor.lock().unwrap_or_else(|e| e.into_inner()).take()
+            .map(|monitor| monitor.subscription.clone())
+    }
+
+    /// One check of the event monitor. Every lock is only tried, never
+    /// awaited: when a tool call holds the probe or RTT the check is cut
+    /// short and picked up again on the next tick.
+    ///
+    /// `halted` carries the core state seen by the previous check; no event
+    /// is reported for the state found by the first one.
+    async fn poll_events(&self, core_index: usize, rtt: bool, halted: &mut Option<bool>) -> Vec<TargetEvent> {
+        let mut found = Vec::new();
+        {
+            let Ok(mut slot) = self.session.try_lock() else { return found };
+            let Some(session) = slot.as_mut() else { return found };
+            let Ok(mut core) = get_core(session, core_index) else { return found };
+            let status = match core.status() {
+                Ok(status) => status,
+                Err(e) => {
+                    debug!("Event monitor could not read core {} state: {}", core_index, e);
+                    return found;
+                }
+            };
+            let is_halted = matches!(status, CoreStatus::Halted(_) | CoreStatus::LockedUp);
+            if is_halted && *halted == Some(false) {
+                let pc = core.read_core_reg::<RegisterValue>(core.program_counter())
+                    .ok()
+                    .and_then(|value| value.try_into().ok());
+                let reason = match status {
+                    CoreStatus::Halted(reason) => format!("{:?}", reason),
+                    _ => "LockedUp".to_string(),
+                };
+                let fault = matches!(status, CoreStatus::Halted(HaltReason::Exception) | CoreStatus::LockedUp);
+                found.push(TargetEvent::Halted { core_index, reason, pc, fault });
+            }
+            *halted = Some(is_halted);
+        }
+
+        if rtt {
+            let Ok(mut rtt_manager) = self.rtt_manager.try_lock() else { return found };
+            if !rtt_manager.is_attached() {
+                return found;
+            }
+            // Binary frame channels are left to rtt_read_frames
+            let channels: Vec<u32> = rtt_manager.get_channels().into_iter()
+                .filter(|channel| channel.direction == ChannelDirection::Up && rtt_manager.frame_header(channel.id).is_none())
+                .map(|channel| channel.id)
+                .collect();
+            for channel in channels {
+                match rtt_manager.try_read_channel(channel) {
+                    Ok(Some(data)) if !data.is_empty() => {
+                        found.push(TargetEvent::RttData { channel, text: String::from_utf8_lossy(&data).into_owned() });
+                    }
+                    Ok(Some(_)) => {}
+                    // A tool call took the probe; the remaining channels wait for the next check
+                    Ok(None) => break,
+                    Err(e) => debug!("Event monitor could not read RTT channel {}: {}", channel, e),
+                }
+            }
+        }
+        found
+    }
+
+    /// Halt or resume a core around a memory access
+    async fn set_core_halted(&self, core_index: usize, halted: bool) -> Result<()> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        if halted {
+            core.halt(HALT_TIMEOUT)
+                .map(|_| ())
+                .map_err(|e| DebugError::from_wait(&format!("Halting core {}", core_index), HALT_TIMEOUT, e))
+        } else {
+            core.run()
+                .map_err(|e| DebugError::InternalError(format!("Failed to resume core {}: {}", core_index, e)))
+        }
+    }
+
+    /// Native byte order of the attached target's architecture
+    pub async fn target_endianness(&self) -> Result<Endianness> {
+        Ok(Endianness::for_architecture(self.lock_session().await?.target().architecture()))
+    }
+
+    /// Read `count` values of `scalar_type` starting at `address`.
+    ///
+    /// Without an explicit `endianness` the target architecture's native byte order is used.
+    pub async fn read_memory_typed(
+        &self,
+        core_index: usize,
+        address: u64,
+        scalar_type: ScalarType,
+        count: usize,
+        endianness: Option<Endianness>,
+        allow_unmapped: bool,
+    ) -> Result<TypedReadResult> {
+        let endianness = match endianness {
+            Some(endianness) => endianness,
+            None => self.target_endianness().await?,
+        };
+
+        let size = count.checked_mul(scalar_type.size())
+            .ok_or_else(|| DebugError::InvalidConfig(format!("{} x {} overflows the read size", count, scalar_type)))?;
+        let data = self.read_memory(core_index, address, size, memory::DEFAULT_CHUNK_SIZE, allow_unmapped, true).await?.data;
+
+        Ok(TypedReadResult {
+            endianness,
+            values: data
+                .chunks_exact(scalar_type.size())
+                .map(|bytes| scalar_type.decode(bytes, endianness))
+                .collect(),
+        })
+    }
+
+    /// Check a memory range against the target's memory map
+    pub async fn validate_range(&self, address: u64, size: usize) -> Result<()> {
+        let session = self.lock_session().await?;
+        memory::validate_range(&session.target().memory_map, address, size as u64)
+    }
+
+    /// Wait until the core halts (or locks up), polling its state.
+    ///
+    /// The session lock is released between polls so other tools keep working
+    /// while a long wait is in progress. Returns `None` when the core is still
+    /// running once `timeout` has passed.
+    ///
+    /// A halt on a breakpoint counts as a hit once, however often it is
+    /// observed. While a breakpoint's hit count is within its `skip_count` the
+    /// core is stepped past it and resumed, at most `MAX_AUTO_RESUMES` times
+    /// per call.
+    ///
+    /// With semihosting enabled, halts on semihosting requests are serviced
+    /// and the core resumed; an exit request ends the wait like any other halt.
+    pub async fn wait_for_halt(&self, core_index: usize, timeout: std::time::Duration) -> Result<Option<HaltEvent>> {
+        let start_time = std::time::Instant::now();
+        let mut auto_resumed = 0;
+        loop {
+            {
+                let watchpoints = self.watchpoints(core_index).await;
+                let mut breakpoints = self.breakpoints.lock().await;
+                let mut semihosting = self.semihosting.lock().await;
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                let status = core.status()
+                    .map_err(|e| DebugError::InternalError(format!("Failed to read core state: {}", e)))?;
+                let mut semihosting_exit = None;
+                // A serviced request resumes the core; the wait goes on through the deadline check below
+                let mut serviced = false;
+                if let CoreStatus::Halted(HaltReason::Breakpoint(probe_rs::BreakpointCause::Semihosting(command))) = status {
+                    if semihosting.enabled {
+                        if semihosting::handle_command(&mut core, command, &mut semihosting)? {
+                            core.run()?;
+                            serviced = true;
+                        } else {
+                            semihosting_exit = semihosting.exit;
+                        }
+                    }
+                }
+                if !serviced && matches!(status, CoreStatus::Halted(_) | CoreStatus::LockedUp) {
+                    let pc: Option<u64> = core.read_core_reg::<RegisterValue>(core.program_counter())
+                        .ok()
+                        .and_then(|value| value.try_into().ok());
+                    let watchpoint = match status {
+                        CoreStatus::Halted(HaltReason::Watchpoint) => watchpoints::fired(&mut core, &watchpoints).cloned(),
+                        _ => None,
+                    };
+                    let new_halt = self.counted_halts.lock().unwrap_or_else(|e| e.into_inner()).insert(core_index);
+                    let breakpoint = pc
+                        .and_then(|pc| breakpoints.get_mut(&(pc, core_index)))
+                        .filter(|breakpoint| breakpoint.enabled)
+                        .map(|breakpoint| {
+                            if new_halt {
+                                breakpoint.hit_count += 1;
+                            }
+                            breakpoint.clone()
+                        });
+
+                    let skipped = breakpoint.as_ref().filter(|breakpoint| {
+                        new_halt && watchpoint.is_none() && breakpoint.skips_hit()
+                    });
+                    if let Some(hit) = skipped {
+                        if auto_resumed < MAX_AUTO_RESUMES {
+                            breakpoints::step_past(&mut core, hit)?;
+                            core.run()?;
+                            self.forget_halt(core_index);
+                            auto_resumed += 1;
+                            continue;
+                        }
+                        warn!("Stopped auto-resuming breakpoint 0x{:08X} after {} hits in one wait", hit.address, auto_resumed);
+                    }
+
+                    if let Some(hit) = breakpoint.as_ref().filter(|breakpoint| breakpoint.temporary) {
+                        breakpoints::uninstall(&mut core, hit)?;
+                        breakpoints.remove(&(hit.address, hit.core_index));
+                    }
+                    let vector_catch = match status {
+                        CoreStatus::Halted(HaltReason::Exception) => vector_catch::describe_caught(&mut core),
+                        _ => None,
+                    };
+                    return Ok(Some(HaltEvent {
+                        status,
+                        pc,
+                        elapsed_ms: start_time.elapsed().as_millis() as u64,
+                        watchpoint,
+                        breakpoint,
+                        vector_catch,
+                        auto_resumed,
+                        semihosting_exit,
+                    }));
+                }
+            }
+
+            if start_time.elapsed() >= timeout {
+                return Ok(None);
+            }
+            tokio::time::sleep(HALT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Resume a halted core
+    pub async fn resume(&self, core_index: usize) -> Result<()> {
+        let mut session = self.lock_session().await?;
+        get_core(&mut session, core_index)?.run()
+            .map_err(|e| DebugError::InternalError(format!("Failed to resume core {}: {}", core_index, e)))?;
+        self.forget_halt(core_index);
+        Ok(())
+    }
+
+    /// Read and decode the CPUID register of a Cortex-M core.
+    ///
+    /// Unlike the probe-rs core type this names the exact core, also for
+    /// targets registered with generic metadata.
+    pub async fn read_cpuid(&self, core_index: usize) -> Result<CpuId> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        if !core.core_type().is_cortex_m() {
+            return Err(DebugError::TargetNotSupported(format!(
+                "CPUID is a Cortex-M register; core {} is {:?}", core_index, core.core_type()
+            )));
+        }
+        let raw = core.read_word_32(cpuid::CPUID)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("CPUID read at 0x{:08X} failed: {}", cpuid::CPUID, e)))?;
+        Ok(CpuId::decode(raw))
+    }
+
+    /// Enable the DWT cycle counter of a Cortex-M core and return its count
+    pub async fn enable_cycle_counter(&self, core_index: usize) -> Result<u32> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        cycles::enable(&mut core)
+    }
+
+    /// Read the DWT cycle counter and whether it is enabled
+    pub async fn read_cycle_counter(&self, core_index: usize) -> Result<(u32, bool)> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        cycles::read(&mut core)
+    }
+
+    /// Set the DWT cycle counter back to zero
+    pub async fn reset_cycle_counter(&self, core_index: usize) -> Result<()> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        cycles::reset(&mut core)
+    }
+
+    /// Count the cycles the core takes from `start` to `end`.
+    ///
+    /// The counter is enabled, then the core runs to `start` and on to `end`
+    /// with temporary breakpoints, sampling the counter at each halt. Both
+    /// runs share `timeout`. The core is left halted at `end`.
+    pub async fn measure_cycles(&self, core_index: usize, start: u64, end: u64, timeout: std::time::Duration, cpu_hz: Option<u64>) -> Result<CycleMeasurement> {
+        let start_time = std::time::Instant::now();
+        self.enable_cycle_counter(core_index).await?;
+
+        let check = |event: Option<HaltEvent>, address: u64, label: &str, elapsed: std::time::Duration| match event {
+            Some(event) if event.pc == Some(address) => Ok(()),
+            Some(event) => Err(DebugError::InternalError(format!(
+                "Core halted at {} ({:?}) before reaching the {} address 0x{:08X}",
+                event.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "an unknown PC".to_string()),
+                event.status, label, address
+            ))),
+            None => Err(DebugError::timeout(format!("Running to the {} address 0x{:08X}", label, address), elapsed)),
+        };
+
+        let event = self.run_to_address(core_index, start, timeout).await?;
+        check(event, start, "start", start_time.elapsed())?;
+        let (start_cycles, _) = self.read_cycle_counter(core_index).await?;
+
+        let run_start = std::time::Instant::now();
+        let remaining = timeout.saturating_sub(start_time.elapsed());
+        let event = self.run_to_address(core_index, end, remaining).await?;
+        let host_elapsed = run_start.elapsed();
+        check(event, end, "end", start_time.elapsed())?;
+        let (end_cycles, _) = self.read_cycle_counter(core_index).await?;
+
+        Ok(CycleMeasurement::new(start_cycles, end_cycles, host_elapsed, cpu_hz))
+    }
+
+    /// Work out why a Cortex-M core faulted: decode the SCB fault registers,
+    /// find the exception frame through EXC_RETURN and symbolize its PC and LR
+    /// with the ELF loaded through `load_symbols`.
+    ///
+    /// The frame can only be located while LR still holds EXC_RETURN, i.e.
+    /// before the handler has overwritten it, such as right after a vector catch.
+    pub async fn analyze_fault(&self, core_index: usize) -> Result<FaultReport> {
+        let mut report = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            if !core.core_type().is_cortex_m() {
+                return Err(DebugError::TargetNotSupported(format!(
+                    "Fault analysis reads Cortex-M SCB registers; core {} is {:?}", core_index, core.core_type()
+                )));
+            }
+            ensure_halted(&mut core)?;
+
+            let read = |core: &mut Core<'_>, address: u64| {
+                core.read_word_32(address)
+                    .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of 0x{:08X} failed: {}", address, e)))
+            };
+            let active_exception = read(&mut core, fault::ICSR)? & 0x1FF;
+            let exception = fault::fault_exception_name(active_exception);
+            let mut notes = Vec::new();
+
+            // ARMv6-M has no configurable faults and none of the status registers
+            let status = if core.core_type() == probe_rs::CoreType::Armv6m {
+                notes.push("ARMv6-M has no fault status registers; every fault is a HardFault".to_string());
+                None
+            } else {
+                Some(FaultStatus {
+                    cfsr: read(&mut core, fault::CFSR)?,
+                    hfsr: read(&mut core, fault::HFSR)?,
+                    mmfar: read(&mut core, fault::MMFAR)?,
+                    bfar: read(&mut core, fault::BFAR)?,
+                })
+            };
+            let faults = status.as_ref().map(FaultStatus::describe).unwrap_or_default();
+
+            let lr: u64 = core.read_core_reg(core.return_address())?;
+            let exc_return = stepping::is_exc_return(lr).then_some(lr as u32);
+            let frame = match (exception, exc_return) {
+                (Some(_), Some(exc_return)) => {
+                    let (stack, extended) = fault::frame_stack(exc_return);
+                    let register = match stack {
+                        "PSP" => core.registers().psp(),
+                        _ => core.registers().msp(),
+                    }
+                    .map(|register| register.id())
+                    .ok_or_else(|| DebugError::InternalError(format!("Core {} has no {} register", core_index, stack)))?;
+                    let address: u64 = core.read_core_reg(register)?;
+                    let mut words = [0u32; 8];
+                    core.read_32(address, &mut words)
+                        .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of the exception frame at 0x{:08X} failed: {}", address, e)))?;
+                    Some(ExceptionFrame::from_words(&words, stack, address, extended))
+                }
+                (Some(_), None) => {
+                    notes.push(format!(
+                        "LR (0x{:08X}) no longer holds EXC_RETURN, so the stacked frame cannot be located; \
+                        catch the fault on entry with vector_catch for a full analysis",
+                        lr
+                    ));
+                    None
+                }
+                (None, _) => None,
+            };
+
+            if exception.is_none() {
+                notes.push(match active_exception {
+                    0 => "The core is not in a fault handler (thread mode)".to_string(),
+                    number => format!("The core is not in a fault handler (active exception {})", number),
+                });
+                if !faults.is_empty() {
+                    notes.push("Fault status bits are still set from an earlier fault".to_string());
+                }
+            }
+
+            FaultReport {
+                in_fault: exception.is_some(),
+                active_exception,
+                exception,
+                status,
+                faults,
+                exc_return,
+                frame,
+                pc_symbol: None,
+                lr_symbol: None,
+                notes,
+            }
+        };
+
+        if let Some(frame) = &report.frame {
+            if self.default_elf_path.lock().await.is_some() {
+                let addresses = [frame.pc as u64, frame.lr as u64 & !1];
+                match self.symbolize(&addresses, None).await {
+                    Ok(symbolized) => {
+                        let describe = |symbolized: &SymbolizedAddress| {
+                            let function = symbolized.function.as_deref()?;
+                            let mut text = match symbolized.offset {
+                                Some(offset) => format!("{}+0x{:X}", function, offset),
+                                None => function.to_string(),
+                            };
+                            if let Some(location) = &symbolized.location {
+                                text.push_str(&format!(" at {}", location));
+                            }
+                            Some(text)
+                        };
+                        report.pc_symbol = describe(&symbolized[0]);
+                        report.lr_symbol = describe(&symbolized[1]);
+                    }
+                    Err(e) => report.notes.push(format!("Symbolization failed: {}", e)),
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Bring core 0 into the state requested at connect: halted when `halt` is
+    /// set, running otherwise, whatever state the attach left it in
+    pub async fn settle_after_connect(&self, halt: bool) -> Result<CoreStatus> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, 0)?;
+        let halted = core.core_halted()?;
+        if halt && !halted {
+            core.halt(HALT_TIMEOUT).map_err(|e| DebugError::from_wait("Halt", HALT_TIMEOUT, e))?;
+        } else if !halt && halted {
+            core.run().map_err(|e| DebugError::InternalError(format!("Failed to resume core 0: {}", e)))?;
+        }
+        Ok(core.status()?)
+    }
+
+    /// Mark the core as moved on, so its next halt counts as a new breakpoint hit
+    fn forget_halt(&self, core_index: usize) {
+        self.counted_halts.lock().unwrap_or_else(|e| e.into_inner()).remove(&core_index);
+    }
+
+    /// Run `f` on a core while holding the session lock for the whole call.
+    ///
+    /// No other tool can touch the target until `f` returns, so a sequence of
+    /// operations inside it is not interleaved with concurrent requests.
+    pub async fn with_core<T>(&self, core_index: usize, f: impl FnOnce(&mut Core<'_>) -> Result<T>) -> Result<T> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        f(&mut core)
+    }
+
+    /// Execute a sequence of halt/read/write/run operations as one transaction.
+    ///
+    /// Unless `allow_unmapped` is set, every memory range is checked against
+    /// the target's memory map before anything runs; a bad range rejects the
+    /// whole batch.
+    pub async fn run_batch(&self, core_index: usize, ops: &[BatchOp], stop_on_error: bool, allow_unmapped: bool) -> Result<Vec<BatchOpResult>> {
+        if !allow_unmapped {
+            for (address, size) in ops.iter().filter_map(BatchOp::memory_range) {
+                self.validate_range(address, size).await?;
+            }
+        }
+        let results = self.with_core(core_index, |core| Ok(batch::execute(core, ops, stop_on_error))).await?;
+        if results.iter().any(|result| matches!(result, BatchOpResult::Resumed)) {
+            self.forget_halt(core_index);
+        }
+        Ok(results)
+    }
+
+    /// Resume the core until it reaches `address`, using a temporary hardware breakpoint.
+    ///
+    /// The temporary breakpoint is removed whether the core reaches `address`,
+    /// halts elsewhere first or is still running after `timeout`, which is
+    /// reported as `None`. An existing breakpoint at `address` is left as is.
+    pub async fn run_to_address(&self, core_index: usize, address: u64, timeout: std::time::Duration) -> Result<Option<HaltEvent>> {
+        let existing = self.breakpoints.lock().await.get(&(address, core_index)).map(|breakpoint| breakpoint.enabled);
+        match existing {
+            None => {
+                self.set_breakpoint(core_index, address, false, None, true, 0).await?;
+            }
+            Some(false) => {
+                self.set_breakpoint_enabled(core_index, address, true).await?;
+            }
+            Some(true) => {}
+        }
+
+        let event = match self.resume(core_index).await {
+            Ok(()) => self.wait_for_halt(core_index, timeout).await,
+            Err(e) => Err(e),
+        };
+
+        match existing {
+            None => {
+                let pending = self.breakpoints.lock().await
+                    .get(&(address, core_index))
+                    .is_some_and(|breakpoint| breakpoint.temporary);
+                if pending {
+                    self.clear_breakpoint(core_index, address).await?;
+                }
+            }
+            Some(false) => {
+                self.set_breakpoint_enabled(core_index, address, false).await?;
+            }
+            Some(true) => {}
+        }
+        event
+    }
+
+    /// Single-step up to `count` instructions while holding the core.
+    ///
+    /// Stops early when the PC reaches a breakpoint or the core faults (locks
+    /// up or halts on an exception). With `trace` the PC after every step is
+    /// recorded.
+    pub async fn step(&self, core_index: usize, count: u32, trace: bool) -> Result<StepResult> {
+        let start_time = std::time::Instant::now();
+        let breakpoints = self.breakpoints.lock().await;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        ensure_halted(&mut core)?;
+
+        let mut pcs = Vec::new();
+        let mut executed = 0;
+        let mut breakpoint = None;
+        let mut status = core.status()?;
+        let mut pc = None;
+        while executed < count {
+            let info = core.step()?;
+            self.forget_halt(core_index);
+            executed += 1;
+            pc = Some(info.pc);
+            if trace {
+                pcs.push(info.pc);
+            }
+
+            status = core.status()?;
+            if matches!(status, CoreStatus::LockedUp | CoreStatus::Halted(HaltReason::Exception)) {
+                break;
+            }
+            breakpoint = breakpoints.get(&(info.pc, core_index))
+                .filter(|breakpoint| breakpoint.enabled)
+                .cloned();
+            if breakpoint.is_some() {
+                break;
+            }
+        }
+
+        Ok(StepResult {
+            status,
+            pc,
+            instructions: Some(executed as u64),
+            ran_to: None,
+            timed_out: false,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+            trace: trace.then_some(pcs),
+            breakpoint,
+        })
+    }
+
+    /// Run exactly `count` instructions by single-stepping, then stay halted.
+    ///
+    /// Cortex-M has no hardware step counter, so the core is stepped in
+    /// chunks, releasing the session between them so other tools are not
+    /// starved. Stops early at a breakpoint or fault, once `timeout` has
+    /// passed (`timed_out`) or when `cancelled` returns true, which is also
+    /// returned.
+    pub async fn run_instructions(&self, core_index: usize, count: u64, timeout: std::time::Duration, cancelled: impl Fn() -> bool) -> Result<(StepResult, bool)> {
+        let start_time = std::time::Instant::now();
+        let mut executed = 0;
+        loop {
+            let chunk = stepping::instruction_chunk(count, executed);
+            let mut result = self.step(core_index, chunk as u32, false).await?;
+            let stepped = result.instructions.unwrap_or_default();
+            executed += stepped;
+            result.instructions = Some(executed);
+            result.elapsed_ms = start_time.elapsed().as_millis() as u64;
+
+            if stepping::run_finished(count, executed, chunk, stepped) {
+                return Ok((result, false));
+            }
+            if cancelled() {
+                return Ok((result, true));
+            }
+            if start_time.elapsed() >= timeout {
+                result.timed_out = true;
+                return Ok((result, false));
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Step one instruction, running over calls.
+    ///
+    /// When the instruction at the PC is a call, a temporary breakpoint is set
+    /// at its return address and the core resumed until it halts there or
+    /// elsewhere (a fault, another breakpoint). The breakpoint is removed in
+    /// every case; on timeout the core is halted.
+    pub async fn step_over(&self, core_index: usize, timeout: std::time::Duration) -> Result<StepResult> {
+        let start_time = std::time::Instant::now();
+        let return_address = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            ensure_halted(&mut core)?;
+            let pc: u64 = core.read_core_reg(core.program_counter())?;
+            let mut code = [0u8; 4];
+            core.read_8(pc, &mut code)?;
+            match stepping::call_length(core.core_type(), &code) {
+                Some(length) => pc + length,
+                None => {
+                    let pc = core.step()?.pc;
+                    let status = core.status()?;
+                    return Ok(StepResult {
+                        status,
+                        pc: Some(pc),
+                        instructions: Some(1),
+                        ran_to: None,
+                        timed_out: false,
+                        elapsed_ms: start_time.elapsed().as_millis() as u64,
+                        trace: None,
+                        breakpoint: None,
+                    });
+                }
+            }
+        };
+
+        self.run_until_return(core_index, return_address, start_time, timeout).await
+    }
+
+    /// Run until the current function returns to its caller.
+    ///
+    /// The return address comes from unwinding one frame with the ELF loaded
+    /// through `load_symbols`, falling back to the link register. A temporary
+    /// breakpoint there is removed however the run ends; on timeout the core
+    /// is halted.
+    pub async fn step_out(&self, core_index: usize, timeout: std::time::Duration) -> Result<StepResult> {
+        let start_time = std::time::Instant::now();
+        let elf_path = self.default_elf_path.lock().await.clone();
+        let return_address = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            ensure_halted(&mut core)?;
+            let pc: u64 = core.read_core_reg(core.program_counter())?;
+
+            let unwound = elf_path
+                .and_then(|path| backtrace::unwind(&mut core, Some(std::path::Path::new(&path)), 8).ok())
+                .filter(|trace| !trace.lr_fallback)
+                .and_then(|trace| trace.frames.iter().skip(1).find(|frame| frame.address != pc).map(|frame| frame.address));
+            let address = match unwound {
+                Some(address) => address,
+                None => {
+                    let lr: u64 = core.read_core_reg(core.return_address())?;
+                    if core.core_type().is_cortex_m() && stepping::is_exc_return(lr) {
+                        return Err(DebugError::InvalidConfig(format!(
+                            "The core is in an exception handler (LR=0x{:08X}); load symbols with load_symbols to unwind it",
+                            lr
+                        )));
+                    }
+                    lr
+                }
+            };
+            registers::align_pc(address, core.core_type())?.0
+        };
+
+        self.run_until_return(core_index, return_address, start_time, timeout).await
+    }
+
+    /// Run to `address` for `step_over`/`step_out`, halting the core if it is still running at the timeout
+    async fn run_until_return(
+        &self,
+        core_index: usize,
+        address: u64,
+        start_time: std::time::Instant,
+        timeout: std::time::Duration,
+    ) -> Result<StepResult> {
+        let (status, pc, timed_out, breakpoint) = match self.run_to_address(core_index, address, timeout).await? {
+            Some(event) => (event.status, event.pc, false, event.breakpoint.filter(|breakpoint| !breakpoint.temporary)),
+            None => {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                let pc = core.halt(HALT_TIMEOUT)
+                    .map_err(|e| DebugError::from_wait(&format!("Halting core {}", core_index), HALT_TIMEOUT, e))?
+                    .pc;
+                (core.status()?, Some(pc), true, None)
+            }
+        };
+
+        Ok(StepResult {
+            status,
+            pc,
+            instructions: None,
+            ran_to: Some(address),
+            timed_out,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+            trace: None,
+            breakpoint,
+        })
+    }
+
+    /// Read the full register file of a halted core
+    pub async fn read_all_registers(&self, core_index: usize) -> Result<Vec<RegisterReading>> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        ensure_halted(&mut core)?;
+
+        Ok(registers::read_all(&mut core))
+    }
+
+    /// Capture all registers of a halted core under `name`, replacing a snapshot of the same name.
+    ///
+    /// When the cap is reached the oldest snapshot is evicted and its name returned.
+    pub async fn take_register_snapshot(&self, core_index: usize, name: &str) -> Result<(RegisterSnapshot, Option<String>)> {
+        let readings = self.read_all_registers(core_index).await?;
+        let snapshot = RegisterSnapshot { core_index, taken_at: chrono::Utc::now(), readings };
+
+        let mut snapshots = self.register_snapshots.lock().await;
+        let evicted = if !snapshots.contains_key(name) && snapshots.len() >= registers::MAX_REGISTER_SNAPSHOTS {
+            let oldest = snapshots.iter()
+                .min_by_key(|(_, snapshot)| snapshot.taken_at)
+                .map(|(name, _)| name.clone());
+            if let Some(oldest) = &oldest {
+                snapshots.remove(oldest);
+            }
+            oldest
+        } else {
+            None
+        };
+        snapshots.insert(name.to_string(), snapshot.clone());
+        Ok((snapshot, evicted))
+    }
+
+    /// Changed registers between snapshot `from` and snapshot `to`, or the live registers of the
+    /// snapshot's core when `to` is `None`
+    pub async fn register_diff(&self, from: &str, to: Option<&str>) -> Result<Vec<RegisterChange>> {
+        let find = |snapshots: &HashMap<String, RegisterSnapshot>, name: &str| {
+            snapshots.get(name).cloned().ok_or_else(|| {
+                DebugError::InvalidConfig(format!("No register snapshot named '{}'", name))
+            })
+        };
+
+        let (old, new) = {
+            let snapshots = self.register_snapshots.lock().await;
+            (find(&snapshots, from)?, to.map(|to| find(&snapshots, to)).transpose()?)
+        };
+        let new = match new {
+            Some(snapshot) => snapshot.readings,
+            None => self.read_all_registers(old.core_index).await?,
+        };
+        Ok(registers::diff(&old.readings, &new))
+    }
+
+    /// Delete a register snapshot, or all snapshots when `name` is `None`; returns how many were removed
+    pub async fn delete_register_snapshot(&self, name: Option<&str>) -> Result<usize> {
+        let mut snapshots = self.register_snapshots.lock().await;
+        match name {
+            Some(name) => snapshots.remove(name)
+                .map(|_| 1)
+                .ok_or_else(|| DebugError::InvalidConfig(format!("No register snapshot named '{}'", name))),
+            None => {
+                let count = snapshots.len();
+                snapshots.clear();
+                Ok(count)
+            }
+        }
+    }
+
+    /// Names of the stored register snapshots, oldest first
+    pub async fn register_snapshot_names(&self) -> Vec<String> {
+        let snapshots = self.register_snapshots.lock().await;
+        let mut names: Vec<_> = snapshots.iter().map(|(name, snapshot)| (snapshot.taken_at, name.clone())).collect();
+        names.sort();
+        names.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Read registers of a halted core by name, or the core register set when `names` is empty
+    pub async fn read_registers(&self, core_index: usize, names: &[String]) -> Result<RegisterSelection> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        ensure_halted(&mut core)?;
+
+        registers::read_selected(&mut core, names)
+    }
+
+    /// Write a register of a halted core by name, reading it back when `verify` is set
+    pub async fn write_register(&self, core_index: usize, name: &str, value: u64, verify: bool) -> Result<RegisterWrite> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        ensure_halted(&mut core)?;
+
+        registers::write(&mut core, name, value, verify)
+    }
+
+    /// Write bytes to target memory, optionally reading them back for verification
+    pub async fn write_memory(&self, core_index: usize, address: u64, data: &[u8], verify: bool, allow_unmapped: bool) -> Result<WriteResult> {
+        if !allow_unmapped {
+            self.validate_range(address, data.len()).await?;
+        }
+
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+
+        core.write(address, data)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("Write at 0x{:08X} failed: {}", address, e)))?;
+
+        let (mismatches, read_back) = if verify {
+            let mut read_back = vec![0u8; data.len()];
+            core.read(address, &mut read_back)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Read-back at 0x{:08X} failed: {}", address, e)))?;
+            (Some(memory::mismatched_offsets(data, &read_back)), Some(read_back))
+        } else {
+            (None, None)
+        };
+
+        Ok(WriteResult {
+            bytes_written: data.len(),
+            mismatches,
+            read_back,
+        })
+    }
+
+    /// Read a 32-bit word with a single word-sized bus access, as MMIO registers require
+    pub async fn read_word32(&self, core_index: usize, address: u64, allow_unmapped: bool) -> Result<u32> {
+        self.check_word_access(address, 4, allow_unmapped).await?;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        core.read_word_32(address)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("32-bit read at 0x{:08X} failed: {}", address, e)))
+    }
+
+    /// Write a 32-bit word with a single word-sized bus access, as MMIO registers require
+    pub async fn write_word32(&self, core_index: usize, address: u64, value: u32, allow_unmapped: bool) -> Result<()> {
+        self.check_word_access(address, 4, allow_unmapped).await?;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        core.write_word_32(address, value)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("32-bit write at 0x{:08X} failed: {}", address, e)))
+    }
+
+    /// Read a 16-bit halfword with a single halfword-sized bus access
+    pub async fn read_word16(&self, core_index: usize, address: u64, allow_unmapped: bool) -> Result<u16> {
+        self.check_word_access(address, 2, allow_unmapped).await?;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        core.read_word_16(address)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("16-bit read at 0x{:08X} failed: {}", address, e)))
+    }
+
+    /// Write a 16-bit halfword with a single halfword-sized bus access
+    pub async fn write_word16(&self, core_index: usize, address: u64, value: u16, allow_unmapped: bool) -> Result<()> {
+        self.check_word_access(address, 2, allow_unmapped).await?;
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        core.write_word_16(address, value)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("16-bit write at 0x{:08X} failed: {}", address, e)))
+    }
+
+    /// Reject unaligned word accesses, and unmapped ones unless `allow_unmapped` is set
+    async fn check_word_access(&self, address: u64, width: usize, allow_unmapped: bool) -> Result<()> {
+        memory::check_alignment(address, width as u64)?;
+        if !allow_unmapped {
+            self.validate_range(address, width).await?;
+        }
+        Ok(())
+    }
+
+    /// Read several `(address, size)` regions while holding the core once.
+    ///
+    /// Results are returned in request order; a failed read, including one
+    /// outside the memory map unless `allow_unmapped` is set, only fails its
+    /// own entry.
+    pub async fn read_memory_batch(&self, core_index: usize, requests: &[(u64, usize)], allow_unmapped: bool) -> Result<Vec<Result<Vec<u8>>>> {
+        let mut session = self.lock_session().await?;
+        let memory_map = session.target().memory_map.clone();
+        let mut core = get_core(&mut session, core_index)?;
+
+        let memory_map = (!allow_unmapped).then_some(memory_map.as_slice());
+        Ok(memory::read_batch(requests, memory_map, |address, data| core.read(address, data).map_err(|e| e.to_string())))
+    }
+
+    /// Apply `writes` in order while holding the core once.
+    ///
+    /// With `halt_during_write` a running core is halted for the batch and
+    /// resumed afterwards so the firmware never observes a partial update.
+    /// A write outside the memory map fails unless `allow_unmapped` is set.
+    /// With `stop_on_error` the entries after a failed write are skipped.
+    pub async fn write_memory_batch(
+        &self,
+        core_index: usize,
+        writes: &[memory::Segment],
+        halt_during_write: bool,
+        stop_on_error: bool,
+        allow_unmapped: bool,
+    ) -> Result<Vec<BatchWriteStatus>> {
+        let mut session = self.lock_session().await?;
+        let memory_map = session.target().memory_map.clone();
+        let mut core = get_core(&mut session, core_index)?;
+
+        let resume = if halt_during_write && !core.core_halted()
+            .map_err(|e| DebugError::InternalError(format!("Failed to read core state: {}", e)))?
+        {
+            core.halt(HALT_TIMEOUT)
+                .map_err(|e| DebugError::from_wait("Halting core before batch write", HALT_TIMEOUT, e))?;
+            true
+        } else {
+            false
+        };
+
+        let statuses = memory::write_batch(writes, stop_on_error, |address, data| {
+            if !allow_unmapped {
+                memory::validate_range(&memory_map, address, data.len() as u64).map_err(|e| e.to_string())?;
+            }
+            core.write(address, data).map_err(|e| format!("Write at 0x{:08X} failed: {}", address, e))
+        });
+
+        if resume {
+            core.run()
+                .map_err(|e| DebugError::InternalError(format!("Failed to resume core after batch write: {}", e)))?;
+        }
+
+        Ok(statuses)
+    }
+
+    /// Fill a memory region with a repeating 1/2/4 byte pattern.
+    ///
+    /// The pattern is written in chunks, releasing the session lock between
+    /// chunks so large fills do not starve other tools. The range is checked
+    /// against the memory map unless `allow_unmapped` is set.
+    pub async fn fill_memory(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        pattern: &[u8],
+        allow_flash: bool,
+        allow_unmapped: bool,
+    ) -> Result<FillResult> {
+        let start_time = std::time::Instant::now();
+
+        if pattern.is_empty() {
+            return Err(DebugError::InvalidConfig("Fill pattern must not be empty".to_string()));
+        }
+        if !allow_unmapped {
+            self.validate_range(address, size).await?;
+        }
+
+        if !allow_flash {
+            let session = self.lock_session().await?;
+            if let Some((name, range)) = memory::find_nvm_overlap(&session.target().memory_map, address, size as u64) {
+                return Err(DebugError::MemoryAccessFailed(format!(
+                    "Fill range 0x{:08X}..0x{:08X} overlaps flash region {} (0x{:08X}..0x{:08X}); set allow_flash to override",
+                    address, address + size as u64, name, range.start, range.end
+                )));
+            }
+        }
+
+        // Chunk length is a multiple of the pattern width so every chunk starts in phase
+        let chunk_size = memory::DEFAULT_CHUNK_SIZE - memory::DEFAULT_CHUNK_SIZE % pattern.len();
+        let chunk: Vec<u8> = pattern.iter().copied().cycle().take(chunk_size.min(size)).collect();
+
+        let mut written = 0;
+        while written < size {
+            let len = chunk.len().min(size - written);
+            let chunk_address = address + written as u64;
+            {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                core.write(chunk_address, &chunk[..len])
+                    .map_err(|e| DebugError::MemoryAccessFailed(format!(
+                        "Fill failed at 0x{:08X} after {} bytes: {}", chunk_address, written, e
+                    )))?;
+            }
+            written += len;
+        }
+
+        debug!("Filled {} bytes at 0x{:08X}", written, address);
+        Ok(FillResult {
+            bytes_written: written,
+            pattern: pattern.to_vec(),
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Search `[address, address + size)` for `pattern`, returning at most `max_results` matches.
+    ///
+    /// The region is read in chunks with the session lock released in between;
+    /// the last `pattern.len() - 1` bytes of each chunk are carried over so
+    /// matches spanning a chunk boundary are still found. The range is checked
+    /// against the memory map unless `allow_unmapped` is set.
+    pub async fn search_memory(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        pattern: &[u8],
+        max_results: usize,
+        allow_unmapped: bool,
+    ) -> Result<Vec<SearchMatch>> {
+        if pattern.is_empty() {
+            return Err(DebugError::InvalidConfig("Search pattern must not be empty".to_string()));
+        }
+        if !allow_unmapped {
+            self.validate_range(address, size).await?;
+        }
+
+        let mut hits = Vec::new();
+        let mut carry: Vec<u8> = Vec::new();
+        let mut scanned = 0;
+        while scanned < size && hits.len() < max_results {
+            let len = memory::DEFAULT_CHUNK_SIZE.min(size - scanned);
+            let chunk_address = address + scanned as u64;
+            let mut buffer = std::mem::take(&mut carry);
+            let buffer_address = chunk_address - buffer.len() as u64;
+            {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                let mut chunk = vec![0u8; len];
+                core.read(chunk_address, &mut chunk)
+                    .map_err(|e| DebugError::MemoryAccessFailed(format!(
+                        "Search read failed at 0x{:08X} after {} bytes: {}", chunk_address, scanned, e
+                    )))?;
+                buffer.extend_from_slice(&chunk);
+            }
+
+            // The carried tail is shorter than the pattern, so no match is reported twice
+            for offset in memory::find_pattern(&buffer, pattern) {
+                if hits.len() >= max_results {
+                    break;
+                }
+                hits.push(buffer_address + offset as u64);
+            }
+
+            carry = buffer[buffer.len().saturating_sub(pattern.len() - 1)..].to_vec();
+            scanned += len;
+        }
+
+        // Read a little context around each hit, clamped to the searched region
+        let end = address + size as u64;
+        let mut matches = Vec::with_capacity(hits.len());
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        for hit in hits {
+            let context_address = hit.saturating_sub(memory::SEARCH_CONTEXT_BYTES as u64).max(address);
+            let context_end = (hit + (pattern.len() + memory::SEARCH_CONTEXT_BYTES) as u64).min(end);
+            let mut context = vec![0u8; (context_end - context_address) as usize];
+            core.read(context_address, &mut context)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Context read at 0x{:08X} failed: {}", context_address, e)))?;
+            matches.push(SearchMatch { address: hit, context_address, context });
+        }
+
+        debug!("Found {} match(es) in {} bytes at 0x{:08X}", matches.len(), scanned, address);
+        Ok(matches)
+    }
+
+    /// Compare target memory against reference `segments` of `(address, bytes)`.
+    ///
+    /// Memory is read in chunks; a read error stops the comparison and is
+    /// reported in the result alongside the bytes compared so far.
+    pub async fn compare_memory(&self, core_index: usize, segments: &[memory::Segment], max_diffs: usize) -> Result<CompareResult> {
+        let mut result = CompareResult::default();
+
+        'segments: for (address, expected) in segments {
+            let mut offset = 0;
+            while offset < expected.len() {
+                let len = memory::DEFAULT_CHUNK_SIZE.min(expected.len() - offset);
+                let chunk_address = address + offset as u64;
+                let mut actual = vec![0u8; len];
+                let read = {
+                    let mut session = self.lock_session().await?;
+                    let mut core = get_core(&mut session, core_index)?;
+                    core.read(chunk_address, &mut actual)
+                };
+                if let Err(e) = read {
+                    result.error = Some(format!("Read failed at 0x{:08X}: {}", chunk_address, e));
+                    break 'segments;
+                }
+
+                result.add_chunk(chunk_address, offset, &expected[offset..offset + len], &actual, max_diffs);
+                offset += len;
+            }
+        }
+
+        debug!("Compared {} bytes, {} mismatch(es)", result.bytes_compared, result.mismatch_count);
+        Ok(result)
+    }
+
+    /// Checksum `[address, address + size)` by streaming chunked reads through the hasher.
+    ///
+    /// Fails before reading anything if the region is not fully covered by the
+    /// target's memory map.
+    pub async fn checksum_memory(&self, core_index: usize, address: u64, size: usize, algorithm: ChecksumAlgorithm) -> Result<ChecksumResult> {
+        let start_time = std::time::Instant::now();
+
+        self.validate_range(address, size).await?;
+
+        let mut hasher = algorithm.hasher();
+        let mut chunk = vec![0u8; memory::DEFAULT_CHUNK_SIZE.min(size)];
+        let mut read = 0;
+        while read < size {
+            let len = chunk.len().min(size - read);
+            let chunk_address = address + read as u64;
+            {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                core.read(chunk_address, &mut chunk[..len])
+                    .map_err(|e| DebugError::MemoryAccessFailed(format!(
+                        "Checksum read failed at 0x{:08X} after {} bytes: {}", chunk_address, read, e
+                    )))?;
+            }
+            hasher.update(&chunk[..len]);
+            read += len;
+        }
+
+        Ok(ChecksumResult {
+            digest: hasher.finalize(),
+            bytes_read: read,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Check whether `[address, address + size)` holds only the erased flash value.
+    ///
+    /// The erased value comes from the flash algorithm covering `address`,
+    /// falling back to 0xFF. The region is read in chunks and the check stops
+    /// at the first byte that differs.
+    pub async fn is_blank(&self, core_index: usize, address: u64, size: usize) -> Result<BlankCheckResult> {
+        let start_time = std::time::Instant::now();
+
+        let erased_value = {
+            let session = self.lock_session().await?;
+            memory::validate_range(&session.target().memory_map, address, size as u64)?;
+            memory::erased_byte_value(&session.target().flash_algorithms, address)
+        };
+
+        let mut chunk = vec![0u8; memory::DEFAULT_CHUNK_SIZE.min(size)];
+        let mut read = 0;
+        let mut first_non_blank = None;
+        while read < size && first_non_blank.is_none() {
+            let len = chunk.len().min(size - read);
+            let chunk_address = address + read as u64;
+            {
+                let mut session = self.lock_session().await?;
+                let mut core = get_core(&mut session, core_index)?;
+                core.read(chunk_address, &mut chunk[..len])
+                    .map_err(|e| DebugError::MemoryAccessFailed(format!(
+                        "Blank check read failed at 0x{:08X} after {} bytes: {}", chunk_address, read, e
+                    )))?;
+            }
+            first_non_blank = chunk[..len].iter()
+                .position(|&byte| byte != erased_value)
+                .map(|offset| chunk_address + offset as u64);
+            read += len;
+        }
+
+        Ok(BlankCheckResult {
+            first_non_blank,
+            erased_value,
+            bytes_checked: read,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Read `[address, address + size)` in `chunk_size` pieces and write it to `file_path`.
+    ///
+    /// An existing file is only replaced when `overwrite` is set. The read
+    /// progress goes to `progress`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dump_memory_to_file(
+        &self,
+        core_index: usize,
+        address: u64,
+        size: usize,
+        file_path: &str,
+        format: DumpFormat,
+        chunk_size: usize,
+        overwrite: bool,
+        progress: Option<&ProgressSink>,
+    ) -> Result<DumpResult> {
+        let start_time = std::time::Instant::now();
+
+        if !overwrite && std::path::Path::new(file_path).exists() {
+            return Err(DebugError::InvalidConfig(format!(
+                "File {} already exists; set overwrite to replace it",
+                file_path
+            )));
+        }
+
+        let data = self.read_memory_with_progress(core_index, address, size, chunk_size, false, true, progress).await?.data;
+
+        let encoded = format.encode(address, &data)?;
+        std::fs::write(file_path, &encoded)?;
+
+        debug!("Dumped {} bytes at 0x{:08X} to {} ({})", data.len(), address, file_path, format);
+        Ok(DumpResult {
+            bytes_read: data.len(),
+            file_size: encoded.len(),
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Capture the state of a core to `file_path` for post-mortem analysis.
+    ///
+    /// The core is halted, every register is read, and `regions` are read in
+    /// `chunk_size` pieces; without regions all RAM of the memory map is
+    /// dumped. A core that was running is resumed afterwards, also when the
+    /// dump fails, unless `stay_halted` is set. An existing file is only
+    /// replaced when `overwrite` is set.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn core_dump(
+        &self,
+        core_index: usize,
+        file_path: &str,
+        regions: Option<Vec<(u64, usize)>>,
+        format: CoreDumpFormat,
+        chunk_size: usize,
+        stay_halted: bool,
+        overwrite: bool,
+    ) -> Result<CoreDumpResult> {
+        let start_time = std::time::Instant::now();
+
+        if !overwrite && std::path::Path::new(file_path).exists() {
+            return Err(DebugError::InvalidConfig(format!(
+                "File {} already exists; set overwrite to replace it",
+                file_path
+            )));
+        }
+
+        let (regions, was_running) = {
+            let mut session = self.lock_session().await?;
+            let regions = regions.unwrap_or_else(|| {
+                session.target().memory_map.iter()
+                    .filter_map(|region| match region {
+                        probe_rs::config::MemoryRegion::Ram(ram) => Some((ram.range.start, (ram.range.end - ram.range.start) as usize)),
+                        _ => None,
+                    })
+                    .collect()
+            });
+            let mut core = get_core(&mut session, core_index)?;
+            if format == CoreDumpFormat::Elf && CoreMachine::for_core_type(core.core_type()).is_none() {
+                return Err(DebugError::TargetNotSupported(format!(
+                    "ELF core files are not supported for {:?} cores; use the 'probe-rs' format",
+                    core.core_type()
+                )));
+            }
+            (regions, !core.core_halted()?)
+        };
+        if regions.is_empty() {
+            return Err(DebugError::InvalidConfig("No memory regions to dump; the memory map lists no RAM".to_string()));
+        }
+
+        if was_running {
+            self.set_core_halted(core_index, true).await?;
+        }
+        let outcome = self.write_core_dump(core_index, file_path, &regions, format, chunk_size).await;
+        let resumed = was_running && !stay_halted;
+        if resumed {
+            self.set_core_halted(core_index, false).await?;
+        }
+        let (registers, file_size) = outcome?;
+
+        Ok(CoreDumpResult {
+            regions,
+            registers,
+            file_size,
+            resumed,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Read the registers and `regions` of a halted core and write them as a
+    /// core file, returning the register count and the file size
+    async fn write_core_dump(
+        &self,
+        core_index: usize,
+        file_path: &str,
+        regions: &[(u64, usize)],
+        format: CoreDumpFormat,
+        chunk_size: usize,
+    ) -> Result<(usize, usize)> {
+        let (registers, instruction_set, core_type, supports_native_64bit_access, fpu_support, floating_point_register_count) = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            let mut registers: HashMap<probe_rs::RegisterId, RegisterValue> = HashMap::new();
+            for register in core.registers().all_registers() {
+                registers.insert(register.id(), core.read_core_reg(register.id())?);
+            }
+            (
+                registers,
+                core.instruction_set()?,
+                core.core_type(),
+                core.supports_native_64bit_access(),
+                core.fpu_support()?,
+                core.floating_point_register_count()?,
+            )
+        };
+
+        let total: usize = regions.iter().map(|(_, size)| size).sum();
+        let mut data = Vec::with_capacity(regions.len());
+        let mut done = 0;
+        for (index, &(address, size)) in regions.iter().enumerate() {
+            let bytes = self.read_memory(core_index, address, size, chunk_size, false, false).await?.data;
+            done += size;
+            info!(
+                "Core dump of session {}: region {}/{} at 0x{:08X} read ({}/{} bytes)",
+                self.session_id, index + 1, regions.len(), address, done, total
+            );
+            data.push((address, bytes));
+        }
+
+        let contents = match format {
+            CoreDumpFormat::Elf => {
+                let machine = CoreMachine::for_core_type(core_type)
+                    .ok_or_else(|| DebugError::TargetNotSupported(format!("ELF core files are not supported for {:?} cores", core_type)))?;
+                let values: Vec<u32> = machine.prstatus_registers().iter()
+                    .map(|id| registers.get(id).and_then(|&value| value.try_into().ok()).unwrap_or(0))
+                    .collect();
+                let metadata = [
+                    ("chip", self.target_chip.clone()),
+                    ("session_id", self.session_id.clone()),
+                    ("core_index", core_index.to_string()),
+                    ("timestamp", chrono::Utc::now().to_rfc3339()),
+                ];
+                coredump::elf_core(machine, &values, &data, &metadata)?
+            }
+            CoreDumpFormat::ProbeRs => {
+                let dump = probe_rs::CoreDump {
+                    registers: registers.clone(),
+                    data: data.into_iter().map(|(address, bytes)| (address..address + bytes.len() as u64, bytes)).collect(),
+                    instruction_set,
+                    supports_native_64bit_access,
+                    core_type,
+                    fpu_support,
+                    floating_point_register_count: Some(floating_point_register_count),
+                };
+                let path = std::path::Path::new(file_path);
+                dump.store(path)
+                    .map_err(|e| DebugError::InternalError(format!("Failed to write core dump {}: {}", file_path, e)))?;
+                return Ok((registers.len(), std::fs::metadata(path)?.len() as usize));
+            }
+        };
+        std::fs::write(file_path, &contents)?;
+        debug!("Wrote {} core dump of {} bytes to {}", format, contents.len(), file_path);
+        Ok((registers.len(), contents.len()))
+    }
+
+    /// Stack region to paint or measure: `region` when given, otherwise the one
+    /// the linker symbols of `elf_path`, or of the ELF loaded with
+    /// `load_symbols`, define
+    async fn stack_region(&self, region: Option<(u64, usize)>, elf_path: Option<&str>) -> Result<std::ops::Range<u64>> {
+        if let Some((address, size)) = region {
+            if size == 0 {
+                return Err(DebugError::InvalidConfig("Stack region size must be greater than 0".to_string()));
+            }
+            return Ok(address..address + size as u64);
+        }
+        let symbols = self.symbols(elf_path).await?;
+        stack::stack_region(&symbols).ok_or_else(|| DebugError::InvalidConfig(format!(
+            "The ELF does not define the stack region (top: {}; bottom: {}); pass address and size",
+            stack::STACK_TOP_SYMBOLS.join(", "),
+            stack::STACK_BOTTOM_SYMBOLS.join(", ")
+        )))
+    }
+
+    /// Read the main stack pointer (MSP on Cortex-M, SP elsewhere).
+    ///
+    /// A running core is halted for the read and resumed; the returned flag
+    /// says whether it was running.
+    async fn read_stack_pointer(&self, core_index: usize) -> Result<(u64, bool)> {
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        let was_running = !core.core_halted()?;
+        if was_running {
+            core.halt(HALT_TIMEOUT).map_err(|e| DebugError::from_wait("Halt", HALT_TIMEOUT, e))?;
+        }
+        let register = match core.core_type().is_cortex_m() {
+            true => core.registers().msp().unwrap_or(core.stack_pointer()).id(),
+            false => core.stack_pointer().id(),
+        };
+        let stack_pointer = core.read_core_reg(register);
+        if was_running {
+            core.run().map_err(|e| DebugError::InternalError(format!("Failed to resume core {}: {}", core_index, e)))?;
+        }
+        Ok((stack_pointer?, was_running))
+    }
+
+    /// Fill the unused part of the stack with `pattern` so `measure_stack_usage`
+    /// can find how deep it grows.
+    ///
+    /// Only the region below the stack pointer is painted. On a running core a
+    /// further margin below the SP is left alone, since the stack keeps moving
+    /// while the paint is written.
+    pub async fn paint_stack(&self, core_index: usize, region: Option<(u64, usize)>, pattern: &[u8], elf_path: Option<&str>) -> Result<StackPaint> {
+        let start_time = std::time::Instant::now();
+        if pattern.is_empty() {
+            return Err(DebugError::InvalidConfig("Stack paint pattern must not be empty".to_string()));
+        }
+        let region = self.stack_region(region, elf_path).await?;
+        let (stack_pointer, was_running) = self.read_stack_pointer(core_index).await?;
+
+        let margin = if was_running { stack::RUNNING_SP_MARGIN } else { 0 };
+        let painted = region.start..stack::paint_end(&region, stack_pointer, margin, pattern.len());
+        if !painted.is_empty() {
+            self.fill_memory(core_index, painted.start, (painted.end - painted.start) as usize, pattern, false, false).await?;
+        }
+
+        debug!("Painted stack 0x{:08X}..0x{:08X} (SP 0x{:08X})", painted.start, painted.end, stack_pointer);
+        Ok(StackPaint {
+            region,
+            painted,
+            stack_pointer,
+            was_running,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Find the peak stack usage since `paint_stack`: the first byte from the
+    /// low end of the region that no longer holds the paint pattern
+    pub async fn measure_stack_usage(&self, core_index: usize, region: Option<(u64, usize)>, pattern: &[u8], elf_path: Option<&str>) -> Result<StackUsage> {
+        if pattern.is_empty() {
+            return Err(DebugError::InvalidConfig("Stack paint pattern must not be empty".to_string()));
+        }
+        let region = self.stack_region(region, elf_path).await?;
+        let size = (region.end - region.start) as usize;
+        let data = self.read_memory(core_index, region.start, size, memory::DEFAULT_CHUNK_SIZE, false, true).await?.data;
+        let untouched = stack::untouched_bytes(&data, pattern) as u64;
+        let (stack_pointer, _) = self.read_stack_pointer(core_index).await?;
+
+        Ok(StackUsage {
+            peak_bytes: size as u64 - untouched,
+            current_bytes: region.end.saturating_sub(stack_pointer.max(region.start)),
+            stack_pointer,
+            region,
+        })
+    }
+
+    /// Unwind the call stack using the DWARF info of `elf_path`, or of the ELF loaded with `load_symbols`.
+    ///
+    /// A running core is an error unless `halt` is set, in which case it is
+    /// halted first and left halted. Also returns whether this call halted it.
+    pub async fn backtrace(&self, core_index: usize, elf_path: Option<&str>, max_frames: usize, halt: bool) -> Result<(Backtrace, bool)> {
+        let elf_path = match elf_path {
+            Some(path) => Some(path.to_string()),
+            None => self.default_elf_path.lock().await.clone(),
+        };
+        debug!("Unwinding stack for session {} using {:?}", self.session_id, elf_path);
+
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+
+        let halted_now = halt && !core.core_halted()?;
+        if halted_now {
+            let timeout = std::time::Duration::from_millis(500);
+            core.halt(timeout).map_err(|e| DebugError::from_wait("Halt", timeout, e))?;
+        }
+
+        let trace = backtrace::unwind(&mut core, elf_path.as_deref().map(std::path::Path::new), max_frames)?;
+        Ok((trace, halted_now))
+    }
+
+    /// Read the parameters and local variables of backtrace frame `frame` (0 is the innermost)
+    /// through the DWARF info of `elf_path`, or of the ELF loaded with `load_symbols`
+    pub async fn read_locals(&self, core_index: usize, frame: usize, elf_path: Option<&str>) -> Result<FrameLocals> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; use load_symbols first".to_string())
+            })?,
+        };
+
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        backtrace::locals(&mut core, std::path::Path::new(&elf_path), frame)
+    }
+
+    /// Walk the FreeRTOS kernel lists and TCBs described by the DWARF info of
+    /// `elf_path`, or of the ELF loaded with `load_symbols`.
+    ///
+    /// Each task gets its state, priority and stack high-water mark, and with
+    /// `backtraces` a short call stack: unwound from the live registers for
+    /// the running task and from the context saved on the stack of the
+    /// others. A running core is an error unless `halt` is set, in which case
+    /// it is halted first and left halted. Also returns whether this call
+    /// halted it.
+    pub async fn rtos_threads(&self, core_index: usize, elf_path: Option<&str>, backtraces: bool, max_frames: usize, halt: bool) -> Result<(Vec<RtosTask>, bool)> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; use load_symbols first".to_string())
+            })?,
+        };
+        let table = self.variable_table(&elf_path).await?;
+        let layout = rtos::FreeRtosLayout::from_variables(&table)?;
+        let little_endian = table.little_endian();
+
+        let mut session = self.lock_session().await?;
+        // DebugInfo is not Send, so it is only loaded once no more awaits follow
+        let debug_info = match backtraces {
+            true => Some(backtrace::load_debug_info(std::path::Path::new(&elf_path))?),
+            false => None,
+        };
+        let mut core = get_core(&mut session, core_index)?;
+        let halted_now = halt && !core.core_halted()?;
+        if halted_now {
+            core.halt(HALT_TIMEOUT).map_err(|e| DebugError::from_wait("Halt", HALT_TIMEOUT, e))?;
+        }
+        if !core.core_halted()? {
+            return Err(DebugError::InvalidConfig("Core must be halted to walk the task lists; use 'halt' first or set halt".to_string()));
+        }
+
+        let read = |core: &mut Core<'_>, address: u64, size: u64| -> Result<u64> {
+            let mut bytes = vec![0u8; size.min(8) as usize];
+            core.read(address, &mut bytes)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of kernel data at 0x{:08X} failed: {}", address, e)))?;
+            if !little_endian {
+                bytes.reverse();
+            }
+            Ok(bytes.iter().rev().fold(0, |value, &byte| value << 8 | u64::from(byte)))
+        };
+
+        let current = read(&mut core, layout.current_tcb, 4)?;
+        let mut found: Vec<(u64, rtos::TaskState)> = Vec::new();
+        for list in &layout.lists {
+            let count = read(&mut core, list.address + layout.list_item_count, 4)? as usize;
+            let end = list.address + layout.list_end;
+            let mut item = read(&mut core, end + layout.list_end_next, 4)?;
+            for _ in 0..count.min(rtos::MAX_LIST_ITEMS) {
+                if item == end || item == 0 {
+                    break;
+                }
+                let tcb = read(&mut core, item + layout.item_owner, 4)?;
+                let state = match list.state {
+                    _ if tcb == current => rtos::TaskState::Running,
+                    // Tasks blocked without a timeout sit in the suspended list, waiting on an event
+                    rtos::TaskState::Suspended => match layout.tcb_event_item {
+                        Some(event_item) if read(&mut core, tcb + event_item + layout.item_container, 4)? != 0 => rtos::TaskState::Blocked,
+                        _ => rtos::TaskState::Suspended,
+                    },
+                    state => state,
+                };
+                if tcb != 0 && !found.iter().any(|(seen, _)| *seen == tcb) {
+                    found.push((tcb, state));
+                }
+                item = read(&mut core, item + layout.item_next, 4)?;
+            }
+        }
+
+        let is_cortex_m = core.core_type().is_cortex_m();
+        let mut tasks = Vec::with_capacity(found.len());
+        for (tcb, state) in found {
+            let mut name = vec![0u8; layout.name_length as usize];
+            core.read(tcb + layout.tcb_name, &mut name)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of the task name at 0x{:08X} failed: {}", tcb + layout.tcb_name, e)))?;
+            let stack_base = read(&mut core, tcb + layout.tcb_stack, 4)?;
+            let top_of_stack = read(&mut core, tcb + layout.tcb_top_of_stack, 4)?;
+            let stack_end = match layout.tcb_end_of_stack {
+                Some(offset) => Some(read(&mut core, tcb + offset, 4)?),
+                None => None,
+            };
+
+            // Tasks run on the process stack; the live SP is the MSP inside an interrupt
+            let stack_pointer = match state {
+                rtos::TaskState::Running => {
+                    let register = match is_cortex_m {
+                        true => core.registers().psp().unwrap_or(core.stack_pointer()).id(),
+                        false => core.stack_pointer().id(),
+                    };
+                    core.read_core_reg(register)?
+                }
+                _ => top_of_stack,
+            };
+
+            let scan_end = stack_pointer.clamp(stack_base, stack_base + rtos::MAX_STACK_SCAN);
+            let mut stack = vec![0u8; (scan_end - stack_base) as usize];
+            let high_water_bytes = match core.read(stack_base, &mut stack) {
+                Ok(()) => Some(stack::untouched_bytes(&stack, &[rtos::STACK_FILL_BYTE]) as u64),
+                Err(e) => {
+                    warn!("Failed to read the stack of task at 0x{:08X}: {}", tcb, e);
+                    None
+                }
+            };
+
+            let (backtrace, note) = match &debug_info {
+                None => (None, None),
+                Some(debug_info) if state == rtos::TaskState::Running => {
+                    let registers = backtrace::registers_with(&mut core, &[]);
+                    (Some(backtrace::unwind_registers(&mut core, Some(debug_info), registers, max_frames)), None)
+                }
+                Some(_) if !is_cortex_m => (None, Some("Saved contexts can only be unwound on Cortex-M".to_string())),
+                Some(debug_info) => {
+                    let mut words = [0u32; rtos::SAVED_CONTEXT_WORDS];
+                    let context = core.read_32(top_of_stack, &mut words).ok().and_then(|()| rtos::saved_context(&words, top_of_stack as u32));
+                    match context {
+                        Some(context) => {
+                            let registers = backtrace::registers_with(&mut core, &context.registers);
+                            (Some(backtrace::unwind_registers(&mut core, Some(debug_info), registers, max_frames)), None)
+                        }
+                        None => (None, Some(format!("No saved context recognized at 0x{:08X}", top_of_stack))),
+                    }
+                }
+            };
+
+            tasks.push(RtosTask {
+                tcb,
+                name: rtos::task_name(&name),
+                state,
+                priority: read(&mut core, tcb + layout.tcb_priority, layout.priority_size)?,
+                stack_base,
+                stack_pointer,
+                stack_size: stack_end.map(|end| end.saturating_sub(stack_base) + 4),
+                high_water_bytes,
+                backtrace,
+                note,
+            });
+        }
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.name.cmp(&b.name)));
+
+        debug!("Found {} FreeRTOS task(s) in session {}", tasks.len(), self.session_id);
+        Ok((tasks, halted_now))
+    }
+
+    /// Disassemble `count` instructions around `address`, or around the PC when no address is given.
+    ///
+    /// `before` of them precede the anchor. Thumb code mixes 2 and 4 byte
+    /// instructions, so the code is decoded from the start of the enclosing
+    /// symbol to find where those earlier instructions begin. Instructions and
+    /// direct branch targets are annotated with symbols of `elf_path`, or of
+    /// the ELF loaded with `load_symbols`, when one is available.
+    pub async fn disassemble(&self, core_index: usize, address: Option<u64>, count: usize, before: usize, elf_path: Option<&str>) -> Result<Disassembly> {
+        let symbols = match elf_path {
+            Some(path) => Some(self.symbols(Some(path)).await?),
+            None if self.default_elf_path.lock().await.is_some() => self.symbols(None).await.ok(),
+            None => None,
+        };
+
+        let mut session = self.lock_session().await?;
+        let mut core = get_core(&mut session, core_index)?;
+        let mode = disasm::mode_for(&mut core)?;
+        let pc: u64 = core.read_core_reg(core.program_counter())?;
+        let anchor = address.unwrap_or(pc);
+
+        let boundary = symbols.as_ref()
+            .and_then(|table| table.address_to_symbol(anchor))
+            .map(|(_, offset)| offset)
+            .filter(|&offset| offset <= DISASM_MAX_SCAN);
+        let start = match (before, boundary) {
+            (0, _) => anchor,
+            (_, Some(offset)) => anchor - offset,
+            (_, None) => anchor.saturating_sub(before as u64 * disasm::MAX_INSTRUCTION_SIZE),
+        };
+        let length = (anchor - start) + count.saturating_sub(before) as u64 * disasm::MAX_INSTRUCTION_SIZE;
+        let mut code = vec![0u8; length as usize];
+        core.read(start, &mut code)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of code at 0x{:08X} failed: {}", start, e)))?;
+        drop(core);
+        drop(session);
+
+        let decoded = disasm::decode(mode, &code, start)?;
+        let first = decoded.iter().position(|insn| insn.address >= anchor).unwrap_or(decoded.len());
+        let mut instructions: Vec<Instruction> = decoded
+            .into_iter()
+            .skip(first.saturating_sub(before))
+            .take(count)
+            .collect();
+
+        if let Some(table) = &symbols {
+            for insn in &mut instructions {
+                insn.symbol = table.address_to_symbol(insn.address)
+                    .map(|(symbol, offset)| (symbol.display_name().to_string(), offset));
+                if let Some((target, name)) = &mut insn.branch_target {
+                    *name = table.address_to_symbol(*target).map(|(symbol, offset)| match offset {
+                        0 => symbol.display_name().to_string(),
+                        offset => format!("{}+{}", symbol.display_name(), offset),
+                    });
+                }
+            }
+        }
+
+        Ok(Disassembly { mode, pc, instructions, unaligned_scan: before > 0 && boundary.is_none() })
+    }
+
+    /// Get the DWARF line table for `elf_path`, parsing and caching it on first use
+    pub async fn line_table(&self, elf_path: &str) -> Result<Arc<LineTable>> {
+        let mut tables = self.line_tables.lock().await;
+        if let Some(table) = tables.get(elf_path) {
+            return Ok(table.clone());
+        }
+
+        let table = Arc::new(LineTable::load(std::path::Path::new(elf_path))?);
+        tables.insert(elf_path.to_string(), table.clone());
+        Ok(table)
+    }
+
+    /// Get the DWARF inline info for `elf_path`, parsing and caching it on first use
+    pub async fn inline_resolver(&self, elf_path: &str) -> Result<Arc<InlineResolver>> {
+        let mut resolvers = self.inline_resolvers.lock().await;
+        if let Some(resolver) = resolvers.get(elf_path) {
+            return Ok(resolver.clone());
+        }
+
+        let resolver = Arc::new(InlineResolver::load(std::path::Path::new(elf_path))?);
+        resolvers.insert(elf_path.to_string(), resolver.clone());
+        Ok(resolver)
+    }
+
+    /// Get the DWARF static variables of `elf_path`, parsing and caching them on first use
+    pub async fn variable_table(&self, elf_path: &str) -> Result<Arc<VariableTable>> {
+        let mut tables = self.variable_tables.lock().await;
+        if let Some(table) = tables.get(elf_path) {
+            return Ok(table.clone());
+        }
+
+        let table = Arc::new(VariableTable::load(std::path::Path::new(elf_path))?);
+        tables.insert(elf_path.to_string(), table.clone());
+        Ok(table)
+    }
+
+    /// Write a static variable by name, encoding `value` for its DWARF type.
+    ///
+    /// Objects may name only some struct fields: the variable is read, the
+    /// named fields patched and the span between the first and last patched
+    /// byte written back, so the other fields keep their values. Variables in
+    /// flash are refused.
+    pub async fn write_variable(&self, core_index: usize, name: &str, value: &serde_json::Value, elf_path: Option<&str>, verify: bool) -> Result<VariableWrite> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; use load_symbols first".to_string())
+            })?,
+        };
+        let table = self.variable_table(&elf_path).await?;
+        let variable = table.lookup(name).cloned().ok_or_else(|| {
+            DebugError::InvalidConfig(format!("Static variable '{}' not found in the DWARF info of {}", name, elf_path))
+        })?;
+
+        let size = variable.ty.size();
+        let patches = variables::encode(&variable.ty, value, table.little_endian())?;
+        if let Some((offset, bytes)) = patches.iter().find(|(offset, bytes)| offset + bytes.len() as u64 > size) {
+            return Err(DebugError::InvalidConfig(format!(
+                "Writing {} bytes at offset {} exceeds {} ({} bytes of type {})",
+                bytes.len(), offset, variable.name, size, variable.ty.name()
+            )));
+        }
+        let (Some(start), Some(end)) = (
+            patches.iter().map(|(offset, _)| *offset).min(),
+            patches.iter().map(|(offset, bytes)| offset + bytes.len() as u64).max(),
+        ) else {
+            return Ok(VariableWrite { variable, bytes_written: 0, mismatches: verify.then(Vec::new) });
+        };
+
+        let mut session = self.lock_session().await?;
+        if let Some((region, range)) = memory::find_nvm_overlap(&session.target().memory_map, variable.address, size) {
+            return Err(DebugError::InvalidAddress {
+                address: variable.address,
+                reason: format!(
+                    "{} lives in flash region '{}' (0x{:08X}-0x{:08X}) and cannot be written",
+                    variable.name, region, range.start, range.end
+                ),
+            });
+        }
+        let mut core = get_core(&mut session, core_index)?;
+
+        let address = variable.address + start;
+        let mut image = vec![0u8; (end - start) as usize];
+        core.read(address, &mut image)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("Read of {} at 0x{:08X} failed: {}", variable.name, address, e)))?;
+        for (offset, bytes) in &patches {
+            let at = (offset - start) as usize;
+            image[at..at + bytes.len()].copy_from_slice(bytes);
+        }
+        core.write(address, &image)
+            .map_err(|e| DebugError::MemoryAccessFailed(format!("Write of {} at 0x{:08X} failed: {}", variable.name, address, e)))?;
+
+        let mismatches = if verify {
+            let mut read_back = vec![0u8; image.len()];
+            core.read(address, &mut read_back)
+                .map_err(|e| DebugError::MemoryAccessFailed(format!("Read-back at 0x{:08X} failed: {}", address, e)))?;
+            Some(memory::mismatched_offsets(&image, &read_back).into_iter().map(|offset| start + offset as u64).collect())
+        } else {
+            None
+        };
+
+        Ok(VariableWrite { variable, bytes_written: image.len(), mismatches })
+    }
+
+    /// Map addresses to their function and source line through the ELF
+    /// symbol table and DWARF info, including functions inlined at them.
+    ///
+    /// Uses `elf_path`, or the ELF loaded with `load_symbols`. Without DWARF
+    /// info only the symbol table is consulted.
+    pub async fn symbolize(&self, addresses: &[u64], elf_path: Option<&str>) -> Result<Vec<SymbolizedAddress>> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; use load_symbols first".to_string())
+            })?,
+        };
+        let symbols = self.symbols(Some(&elf_path)).await?;
+        let resolver = match self.inline_resolver(&elf_path).await {
+            Ok(resolver) => Some(resolver),
+            Err(e) => {
+                warn!("No DWARF info for symbolization: {}", e);
+                None
+            }
+        };
+
+        Ok(addresses
+            .iter()
+            .map(|&address| {
+                let mut inlined = resolver.as_ref().map(|resolver| resolver.frames(address)).unwrap_or_default();
+                let outer = inlined.pop();
+                let (function, offset) = match symbols.address_to_symbol(address) {
+                    Some((symbol, offset)) => (Some(symbol.display_name().to_string()), Some(offset)),
+                    None => (outer.as_ref().and_then(|frame| frame.function.clone()), None),
+                };
+                let location = inlined.first().or(outer.as_ref()).and_then(|frame| frame.location.clone());
+                SymbolizedAddress { address, function, offset, location, inlined }
+            })
+            .collect())
+    }
+
+    /// Resolve `file:line` to a breakpoint address through the DWARF line info
+    /// of `elf_path`, or of the ELF loaded with `load_symbols`
+    pub async fn resolve_source_line(&self, file: &str, line: u64, elf_path: Option<&str>) -> Result<(u64, SourceLocation)> {
+        let elf_path = match elf_path {
+            Some(path) => path.to_string(),
+            None => self.default_elf_path.lock().await.clone().ok_or_else(|| {
+                DebugError::InvalidConfig("No ELF file loaded for this session; use load_symbols first".to_string())
+            })?,
+        };
+        self.line_table(&elf_path).await?.find_line_address(file, line)
+    }
+
+    /// Source lines around `address` through the line info of the ELF loaded
+    /// with `load_symbols`, looking for the file in `search_paths` when its
+    /// DWARF path does not exist on the host.
+    ///
+    /// `None` when no ELF is loaded or it has no line info for the address.
+    pub async fn source_context(&self, address: u64, search_paths: &[std::path::PathBuf], radius: u64) -> Option<SourceContext> {
+        let elf_path = self.default_elf_path.lock().await.clone()?;
+        let location = self.line_table(&elf_path).await.ok()?.find_location(address)?;
+        Some(source::source_context(&location, search_paths, radius))
+    }
+
+    /// Map the current program counter to a source location.
+    ///
+    /// Returns the PC together with its location, or `None` when the ELF has no
+    /// line info covering it.
+    pub async fn pc_to_source(&self, core_index: usize, elf_path: &str) -> Result<(u64, Option<SourceLocation>)> {
+        let table = self.line_table(elf_path).await?;
+
+        let pc = {
+            let mut session = self.lock_session().await?;
+            let mut core = get_core(&mut session, core_index)?;
+            let pc_reg = core.program_counter();
+            let value: u64 = core.read_core_reg(pc_reg)
+                .map_err(|e| DebugError::InternalError(format!("Failed to read PC: {}", e)))?;
+            value
+        };
+
+        Ok((pc, table.find_location(pc)))
+    }
+}
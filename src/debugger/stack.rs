@@ -0,0 +1,94 @@
+//! Stack usage measurement by painting the stack with a known pattern
+
+use std::ops::Range;
+
+use crate::symbols::SymbolTable;
+
+/// Symbols marking the top of the stack, where it starts growing down from
+pub const STACK_TOP_SYMBOLS: &[&str] = &["_stack_start", "__StackTop", "_estack", "__stack"];
+
+/// Symbols marking the lowest address the stack may grow to, falling back
+/// to the end of the static data below it
+pub const STACK_BOTTOM_SYMBOLS: &[&str] = &[
+    "_stack_end", "__stack_end", "__StackLimit", "_sstack", "__sheap", "__euninit", "__ebss", "_ebss",
+];
+
+/// Bytes below the SP of a running core left unpainted, since the stack
+/// keeps moving while the paint is written
+pub const RUNNING_SP_MARGIN: u64 = 256;
+
+/// Result of `paint_stack`
+#[derive(Debug, Clone)]
+pub struct StackPaint {
+    pub region: Range<u64>,
+    /// Part of the region that was painted, from its low end up to the live stack
+    pub painted: Range<u64>,
+    pub stack_pointer: u64,
+    pub was_running: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Result of `measure_stack_usage`
+#[derive(Debug, Clone)]
+pub struct StackUsage {
+    pub region: Range<u64>,
+    /// Bytes from the top of the region down to the deepest overwritten byte
+    pub peak_bytes: u64,
+    pub stack_pointer: u64,
+    /// Bytes currently in use, from the top of the region down to the SP
+    pub current_bytes: u64,
+}
+
+impl StackUsage {
+    /// Peak usage as a percentage of the region
+    pub fn peak_percent(&self) -> f64 {
+        self.peak_bytes as f64 * 100.0 / (self.region.end - self.region.start).max(1) as f64
+    }
+}
+
+/// Stack region from the linker symbols of an ELF, when it defines both ends
+pub fn stack_region(symbols: &SymbolTable) -> Option<Range<u64>> {
+    let top = STACK_TOP_SYMBOLS.iter().find_map(|name| symbols.resolve_symbol(name))?;
+    let bottom = STACK_BOTTOM_SYMBOLS.iter().find_map(|name| symbols.resolve_symbol(name))?;
+    (bottom < top).then_some(bottom..top)
+}
+
+/// End of the part of `region` that can be painted without touching live
+/// stack at or above `stack_pointer`, keeping whole pattern repeats
+pub fn paint_end(region: &Range<u64>, stack_pointer: u64, margin: u64, pattern_len: usize) -> u64 {
+    let end = stack_pointer.saturating_sub(margin).clamp(region.start, region.end);
+    end - (end - region.start) % pattern_len as u64
+}
+
+/// Bytes at the low end of `data` still holding the repeating `pattern`
+pub fn untouched_bytes(data: &[u8], pattern: &[u8]) -> usize {
+    data.iter()
+        .zip(pattern.iter().cycle())
+        .take_while(|(byte, expected)| byte == expected)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint_end_spares_live_stack() {
+        let region = 0x2000_0000..0x2000_1000;
+        assert_eq!(paint_end(&region, 0x2000_0F00, 0, 4), 0x2000_0F00);
+        assert_eq!(paint_end(&region, 0x2000_0F02, 0, 4), 0x2000_0F00);
+        assert_eq!(paint_end(&region, 0x2000_0F00, RUNNING_SP_MARGIN, 4), 0x2000_0E00);
+        // An SP outside the region either spares all of it or none of it
+        assert_eq!(paint_end(&region, 0x1FFF_FF00, 0, 4), 0x2000_0000);
+        assert_eq!(paint_end(&region, 0x2000_2000, 0, 4), 0x2000_1000);
+    }
+
+    #[test]
+    fn test_untouched_bytes() {
+        let pattern = [0xA5, 0x5A];
+        let mut data = [0xA5, 0x5A].repeat(8);
+        assert_eq!(untouched_bytes(&data, &pattern), 16);
+        data[11] = 0x00;
+        assert_eq!(untouched_bytes(&data, &pattern), 11);
+    }
+}
@@ -0,0 +1,120 @@
+//! Instruction decoding for stepping over calls and out of functions, and chunking of long step runs
+
+use probe_rs::CoreType;
+
+/// Instructions `run_instructions` steps per hold of the session lock
+const RUN_INSTRUCTIONS_CHUNK: u64 = 256;
+
+/// Instructions to step in the next chunk of a run of `count`, `executed` of which are done
+pub fn instruction_chunk(count: u64, executed: u64) -> u64 {
+    count.saturating_sub(executed).min(RUN_INSTRUCTIONS_CHUNK)
+}
+
+/// Whether a run of `count` is over after a chunk of `chunk` stepped `stepped`:
+/// all instructions ran, or a breakpoint or fault cut the chunk short
+pub fn run_finished(count: u64, executed: u64, chunk: u64, stepped: u64) -> bool {
+    stepped < chunk || executed >= count
+}
+
+/// Length of the call instruction at the start of `code`, `None` if it is not a call.
+///
+/// Recognises `BL`/`BLX` on Cortex-M and Cortex-A, `BL`/`BLR` on AArch64 and
+/// `jal`/`jalr`/`c.jal`/`c.jalr` linking through `ra` on RISC-V.
+pub fn call_length(core_type: CoreType, code: &[u8]) -> Option<u64> {
+    let halfword = |offset: usize| code.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let word = || code.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    match core_type {
+        CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+            let first = halfword(0)?;
+            // BLX <Rm>
+            if first & 0xFF87 == 0x4780 {
+                return Some(2);
+            }
+            // BL / BLX <label>: 11110... followed by 11x1x... / 11x0x...
+            let second = halfword(2)?;
+            (first & 0xF800 == 0xF000 && second & 0xC000 == 0xC000).then_some(4)
+        }
+        CoreType::Armv7a => {
+            let instruction = word()?;
+            let is_bl = instruction >> 28 != 0xF && instruction & 0x0F00_0000 == 0x0B00_0000;
+            let is_blx_label = instruction & 0xFE00_0000 == 0xFA00_0000;
+            let is_blx_register = instruction & 0x0FFF_FFF0 == 0x012F_FF30;
+            (is_bl || is_blx_label || is_blx_register).then_some(4)
+        }
+        CoreType::Armv8a => {
+            let instruction = word()?;
+            let is_bl = instruction & 0xFC00_0000 == 0x9400_0000;
+            let is_blr = instruction & 0xFFFF_FC1F == 0xD63F_0000;
+            (is_bl || is_blr).then_some(4)
+        }
+        CoreType::Riscv => {
+            let first = halfword(0)?;
+            if first & 0b11 != 0b11 {
+                // c.jalr ra, 0(rs1) with rs1 != 0, and c.jal (RV32 only, which
+                // is all probe-rs debugs)
+                let is_c_jalr = first & 0xF07F == 0x9002 && (first >> 7) & 0x1F != 0;
+                let is_c_jal = first & 0xE003 == 0x2001;
+                return (is_c_jalr || is_c_jal).then_some(2);
+            }
+            let instruction = word()?;
+            let rd = (instruction >> 7) & 0x1F;
+            let opcode = instruction & 0x7F;
+            // jal / jalr writing the return address to ra
+            (rd == 1 && (opcode == 0x6F || opcode == 0x67)).then_some(4)
+        }
+        CoreType::Xtensa => None,
+    }
+}
+
+/// Whether `value` is a Cortex-M EXC_RETURN code rather than a return address
+pub fn is_exc_return(value: u64) -> bool {
+    (value as u32) >> 24 == 0xFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_length() {
+        // bl 0x08000400 and blx r3 on Thumb, a plain movs
+        assert_eq!(call_length(CoreType::Armv7em, &[0x00, 0xF0, 0x2E, 0xF8]), Some(4));
+        assert_eq!(call_length(CoreType::Armv6m, &[0x98, 0x47, 0x00, 0x00]), Some(2));
+        assert_eq!(call_length(CoreType::Armv7em, &[0x01, 0x20, 0x00, 0xBF]), None);
+        // b.w is a branch, not a call
+        assert_eq!(call_length(CoreType::Armv7em, &[0x00, 0xF0, 0x2E, 0xB8]), None);
+
+        // jal ra, ...; c.jalr a5; jal zero (a jump)
+        assert_eq!(call_length(CoreType::Riscv, &0x0100_00EFu32.to_le_bytes()), Some(4));
+        assert_eq!(call_length(CoreType::Riscv, &[0x82, 0x97, 0x00, 0x00]), Some(2));
+        assert_eq!(call_length(CoreType::Riscv, &0x0100_006Fu32.to_le_bytes()), None);
+
+        assert_eq!(call_length(CoreType::Armv8a, &0x9400_0010u32.to_le_bytes()), Some(4));
+        assert!(is_exc_return(0xFFFF_FFF9));
+        assert!(!is_exc_return(0x0800_0401));
+    }
+
+    #[test]
+    fn test_instruction_chunks() {
+        // (chunk, stepped) of each chunk of a run of `count`, where `stop_after` instructions hit a breakpoint
+        let run = |count: u64, stop_after: u64| {
+            let mut executed = 0;
+            let mut chunks = Vec::new();
+            loop {
+                let chunk = instruction_chunk(count, executed);
+                let stepped = chunk.min(stop_after - executed);
+                executed += stepped;
+                chunks.push((chunk, stepped));
+                if run_finished(count, executed, chunk, stepped) {
+                    return (chunks, executed);
+                }
+            }
+        };
+
+        assert_eq!(run(600, u64::MAX), (vec![(256, 256), (256, 256), (88, 88)], 600));
+        assert_eq!(run(512, u64::MAX), (vec![(256, 256), (256, 256)], 512));
+        assert_eq!(run(600, 300), (vec![(256, 256), (256, 44)], 300));
+        assert_eq!(run(1, u64::MAX), (vec![(1, 1)], 1));
+    }
+}
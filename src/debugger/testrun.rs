@@ -0,0 +1,206 @@
+//! Result detection for on-target test binaries such as defmt-test and embedded-test
+
+use crate::error::{DebugError, Result};
+use super::semihosting::SemihostingExit;
+
+/// Where a test binary writes its log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOutput {
+    /// Text on RTT up channel 0
+    Rtt,
+    /// Semihosting console writes, ending with SYS_EXIT
+    Semihosting,
+}
+
+impl TestOutput {
+    /// Parse an output name: "rtt" or "semihosting"
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "rtt" => Ok(TestOutput::Rtt),
+            "semihosting" => Ok(TestOutput::Semihosting),
+            _ => Err(DebugError::InvalidConfig(format!(
+                "Unsupported test output '{}'. Use 'rtt' or 'semihosting'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Outcome of a single test as reported in the log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestCaseStatus {
+    Passed,
+    Failed,
+    Ignored,
+    /// Started but no result seen yet
+    Running,
+}
+
+impl std::fmt::Display for TestCaseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TestCaseStatus::Passed => "passed",
+            TestCaseStatus::Failed => "FAILED",
+            TestCaseStatus::Ignored => "ignored",
+            TestCaseStatus::Running => "running",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestCaseStatus,
+}
+
+/// Overall result of a test run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRunStatus {
+    Passed,
+    Failed,
+    TimedOut,
+}
+
+/// Test log collected from the target, with the per-test results found in it.
+///
+/// Understands the defmt-test progress lines (`(1/3) running `name`...`,
+/// `all tests passed!`), libtest-style result lines (`test name ... ok`,
+/// `test result: FAILED.`) and panic messages.
+#[derive(Debug, Default)]
+pub struct TestLog {
+    pub lines: Vec<String>,
+    pub tests: Vec<TestCase>,
+    /// Set once the log announced the end of the run: whether all tests passed
+    pub finished: Option<bool>,
+    fragment: String,
+}
+
+impl TestLog {
+    /// Append raw text, handling each line it completes
+    pub fn push_text(&mut self, text: &str) {
+        self.fragment.push_str(text);
+        while let Some(end) = self.fragment.find('\n') {
+            let line: String = self.fragment.drain(..=end).collect();
+            self.push_line(line.trim_end_matches(['\r', '\n']));
+        }
+    }
+
+    /// Handle the incomplete last line once no more output will come
+    pub fn flush(&mut self) {
+        if !self.fragment.is_empty() {
+            let line = std::mem::take(&mut self.fragment);
+            self.push_line(&line);
+        }
+    }
+
+    /// Record one complete line of the log
+    pub fn push_line(&mut self, line: &str) {
+        self.lines.push(line.to_string());
+
+        if let Some(name) = running_test(line) {
+            // defmt-test only moves on to the next test once the previous one passed
+            self.settle_running(TestCaseStatus::Passed);
+            self.tests.push(TestCase { name, status: TestCaseStatus::Running });
+        } else if let Some((name, status)) = test_result(line) {
+            match self.tests.iter_mut().find(|test| test.name == name) {
+                Some(test) => test.status = status,
+                None => self.tests.push(TestCase { name, status }),
+            }
+        } else if line.contains("all tests passed") {
+            self.settle_running(TestCaseStatus::Passed);
+            self.finished = Some(true);
+        } else if let Some(summary) = line.trim_start().strip_prefix("test result: ") {
+            self.finished = Some(summary.starts_with("ok"));
+        } else if line.contains("panicked at") {
+            self.settle_running(TestCaseStatus::Failed);
+            self.finished = Some(false);
+        }
+    }
+
+    fn settle_running(&mut self, status: TestCaseStatus) {
+        for test in self.tests.iter_mut().filter(|test| test.status == TestCaseStatus::Running) {
+            test.status = status;
+        }
+    }
+
+    /// Number of tests that ended with `status`
+    pub fn count(&self, status: TestCaseStatus) -> usize {
+        self.tests.iter().filter(|test| test.status == status).count()
+    }
+
+    /// Overall status of a run that ended with this log.
+    ///
+    /// A semihosting exit code decides over the log; a halt without a passing
+    /// log (e.g. a breakpoint in the panic handler) counts as a failure, and
+    /// a run that neither halted nor finished its log timed out.
+    pub fn status(&self, exit: Option<&SemihostingExit>, halted: bool) -> TestRunStatus {
+        let failed = self.count(TestCaseStatus::Failed) > 0;
+        match (exit, self.finished) {
+            (Some(exit), _) if exit.exit_code() == 0 && !failed => TestRunStatus::Passed,
+            (Some(_), _) => TestRunStatus::Failed,
+            (None, Some(true)) if !failed => TestRunStatus::Passed,
+            (None, Some(_)) => TestRunStatus::Failed,
+            (None, None) if halted || failed => TestRunStatus::Failed,
+            (None, None) => TestRunStatus::TimedOut,
+        }
+    }
+}
+
+/// Test name of a defmt-test progress line: "(1/3) running `name`..."
+fn running_test(line: &str) -> Option<String> {
+    let rest = &line[line.find("running `")? + "running `".len()..];
+    Some(rest[..rest.find('`')?].to_string())
+}
+
+/// Test name and outcome of a libtest-style line: "test name ... ok"
+fn test_result(line: &str) -> Option<(String, TestCaseStatus)> {
+    let rest = line.trim_start().strip_prefix("test ")?;
+    let (name, outcome) = rest.split_once(" ... ")?;
+    let status = match outcome.trim() {
+        "ok" => TestCaseStatus::Passed,
+        "FAILED" => TestCaseStatus::Failed,
+        outcome if outcome.starts_with("ignored") => TestCaseStatus::Ignored,
+        _ => return None,
+    };
+    Some((name.trim().to_string(), status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defmt_test_log() {
+        let mut log = TestLog::default();
+        log.push_text("(1/2) running `it_works`...\n(2/2) running `it_fails`...\n");
+        log.push_text("panicked at src/tests.rs:12:5: assertion failed");
+        assert_eq!(log.finished, None);
+        log.flush();
+
+        assert_eq!(log.tests.len(), 2);
+        assert_eq!(log.tests[0].status, TestCaseStatus::Passed);
+        assert_eq!(log.tests[1].status, TestCaseStatus::Failed);
+        assert_eq!(log.status(None, true), TestRunStatus::Failed);
+
+        let mut passing = TestLog::default();
+        passing.push_text("(1/1) running `it_works`...\r\nall tests passed!\r\n");
+        assert_eq!(passing.finished, Some(true));
+        assert_eq!(passing.status(None, false), TestRunStatus::Passed);
+    }
+
+    #[test]
+    fn test_libtest_log_and_exit_code() {
+        let mut log = TestLog::default();
+        log.push_text("test adc::reads ... ok\ntest dma::slow ... ignored, needs hardware\ntest result: ok. 1 passed\n");
+        assert_eq!(log.count(TestCaseStatus::Passed), 1);
+        assert_eq!(log.count(TestCaseStatus::Ignored), 1);
+
+        // The exit code decides over the log
+        let failure = SemihostingExit::Error { reason: 0x20026, exit_status: Some(1), subcode: None };
+        assert_eq!(log.status(Some(&failure), true), TestRunStatus::Failed);
+        assert_eq!(log.status(Some(&SemihostingExit::Success), true), TestRunStatus::Passed);
+        assert_eq!(TestLog::default().status(None, false), TestRunStatus::TimedOut);
+        assert!(TestOutput::parse("swo").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Vector catch: halting the core when it takes a reset or fault exception
+
+use probe_rs::{Architecture, Core, MemoryInterface, VectorCatchCondition};
+
+use super::registers;
+use crate::error::{DebugError, Result};
+
+/// Cortex-M Debug Exception and Monitor Control Register
+const DEMCR: u64 = 0xE000_EDFC;
+
+/// Exception a vector catch can halt on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VectorCatch {
+    Reset,
+    HardFault,
+    SecureFault,
+    BusError,
+    MemManage,
+    UsageFault,
+    InterruptError,
+    All,
+}
+
+impl VectorCatch {
+    /// Parse a condition name like "hard_fault", "reset" or "bus_err"
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "reset" | "core_reset" | "corereset" => Ok(VectorCatch::Reset),
+            "hard_fault" | "hardfault" | "hard_err" => Ok(VectorCatch::HardFault),
+            "secure_fault" | "securefault" => Ok(VectorCatch::SecureFault),
+            "bus_err" | "bus_error" | "bus_fault" => Ok(VectorCatch::BusError),
+            "mm_err" | "mem_manage" | "memmanage" => Ok(VectorCatch::MemManage),
+            "usage_err" | "usage_fault" => Ok(VectorCatch::UsageFault),
+            "int_err" | "interrupt_error" => Ok(VectorCatch::InterruptError),
+            "all" => Ok(VectorCatch::All),
+            _ => Err(format!(
+                "Unknown vector catch condition '{}'. Use reset, hard_fault, secure_fault, bus_err, mm_err, usage_err, int_err or all",
+                name
+            )),
+        }
+    }
+
+    /// Matching probe-rs condition, for catches probe-rs handles itself
+    fn condition(&self) -> Option<VectorCatchCondition> {
+        match self {
+            VectorCatch::Reset => Some(VectorCatchCondition::CoreReset),
+            VectorCatch::HardFault => Some(VectorCatchCondition::HardFault),
+            VectorCatch::SecureFault => Some(VectorCatchCondition::SecureFault),
+            VectorCatch::All => Some(VectorCatchCondition::All),
+            _ => None,
+        }
+    }
+
+    /// DEMCR vector catch bits for the ARMv7-M/ARMv8-M catches probe-rs does not expose
+    fn demcr_mask(&self) -> u32 {
+        match self {
+            VectorCatch::MemManage => 1 << 4,
+            // VC_NOCPERR, VC_CHKERR and VC_STATERR
+            VectorCatch::UsageFault => (1 << 5) | (1 << 6) | (1 << 7),
+            VectorCatch::BusError => 1 << 8,
+            VectorCatch::InterruptError => 1 << 9,
+            _ => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for VectorCatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            VectorCatch::Reset => "reset",
+            VectorCatch::HardFault => "hard_fault",
+            VectorCatch::SecureFault => "secure_fault",
+            VectorCatch::BusError => "bus_err",
+            VectorCatch::MemManage => "mm_err",
+            VectorCatch::UsageFault => "usage_err",
+            VectorCatch::InterruptError => "int_err",
+            VectorCatch::All => "all",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enable or disable one vector catch on a core
+pub fn apply(core: &mut Core<'_>, catch: VectorCatch, enable: bool) -> Result<()> {
+    let vector_catch_error = |e: probe_rs::Error| {
+        DebugError::InternalError(format!("Failed to {} vector catch '{}': {}", if enable { "enable" } else { "disable" }, catch, e))
+    };
+
+    if let Some(condition) = catch.condition() {
+        return if enable {
+            core.enable_vector_catch(condition).map_err(vector_catch_error)
+        } else {
+            core.disable_vector_catch(condition).map_err(vector_catch_error)
+        };
+    }
+
+    if core.architecture() != Architecture::Arm || matches!(core.core_type(), probe_rs::CoreType::Armv6m) {
+        return Err(DebugError::InvalidConfig(format!(
+            "Vector catch '{}' needs an ARMv7-M or ARMv8-M core", catch
+        )));
+    }
+    let demcr = core.read_word_32(DEMCR).map_err(vector_catch_error)?;
+    let demcr = if enable { demcr | catch.demcr_mask() } else { demcr & !catch.demcr_mask() };
+    core.write_word_32(DEMCR, demcr).map_err(vector_catch_error)
+}
+
+/// Describe the exception a halted Cortex-M core was caught on, like "Vector catch: HardFault".
+///
+/// Meant for cores halted with `HaltReason::Exception`. A reset catch halts in
+/// Thread mode before the first instruction, so an IPSR of 0 reads as Reset.
+pub fn describe_caught(core: &mut Core<'_>) -> Option<String> {
+    if !core.core_type().is_cortex_m() {
+        return None;
+    }
+    let psr = core.registers().psr()?.id();
+    let xpsr: u32 = core.read_core_reg(psr).ok()?;
+    Some(caught_exception(xpsr))
+}
+
+/// Halt reason for a core caught with `xpsr`, e.g. "Vector catch: HardFault"
+fn caught_exception(xpsr: u32) -> String {
+    let exception = match xpsr & 0x1FF {
+        0 => "Reset".to_string(),
+        number => registers::exception_name(number),
+    };
+    format!("Vector catch: {}", exception)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vector_catch() {
+        assert_eq!(VectorCatch::parse("hard_fault"), Ok(VectorCatch::HardFault));
+        assert_eq!(VectorCatch::parse("Reset"), Ok(VectorCatch::Reset));
+        assert_eq!(VectorCatch::parse("corereset"), Ok(VectorCatch::Reset));
+        assert_eq!(VectorCatch::parse("bus-err"), Ok(VectorCatch::BusError));
+        assert!(VectorCatch::parse("nmi").is_err());
+
+        for catch in [VectorCatch::BusError, VectorCatch::MemManage, VectorCatch::UsageFault, VectorCatch::InterruptError] {
+            assert_eq!(VectorCatch::parse(&catch.to_string()), Ok(catch));
+        }
+    }
+
+    #[test]
+    fn test_describe_caught_exceptions() {
+        assert_eq!(caught_exception(0x0100_0000), "Vector catch: Reset");
+        assert_eq!(caught_exception(0x0100_0003), "Vector catch: HardFault");
+        assert_eq!(caught_exception(0x2100_0005), "Vector catch: BusFault");
+    }
+}
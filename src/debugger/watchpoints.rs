@@ -0,0 +1,168 @@
+//! Data watchpoints backed by the Cortex-M DWT comparators
+
+use std::fmt;
+
+use probe_rs::{Core, CoreType, MemoryInterface};
+
+use crate::error::{DebugError, Result};
+
+/// Debug Exception and Monitor Control Register, TRCENA powers the DWT
+pub(super) const DEMCR: u64 = 0xE000_EDFC;
+pub(super) const DEMCR_TRCENA: u32 = 1 << 24;
+
+pub(super) const DWT_CTRL: u64 = 0xE000_1000;
+const DWT_COMP_BASE: u64 = 0xE000_1020;
+const DWT_COMPARATOR_STRIDE: u64 = 0x10;
+/// FUNCTION.MATCHED, set when the comparator fired and cleared by reading
+const DWT_FUNCTION_MATCHED: u32 = 1 << 24;
+
+/// Kind of data access a watchpoint halts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl WatchAccess {
+    /// Parse an access name ("read", "write" or "readwrite")
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "read" | "r" => Ok(WatchAccess::Read),
+            "write" | "w" => Ok(WatchAccess::Write),
+            "readwrite" | "read_write" | "access" | "rw" => Ok(WatchAccess::ReadWrite),
+            other => Err(format!("Unknown watchpoint access '{}'. Use 'read', 'write' or 'readwrite'", other)),
+        }
+    }
+}
+
+impl fmt::Display for WatchAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchAccess::Read => write!(f, "read"),
+            WatchAccess::Write => write!(f, "write"),
+            WatchAccess::ReadWrite => write!(f, "readwrite"),
+        }
+    }
+}
+
+/// A watchpoint installed on the target
+#[derive(Debug, Clone)]
+pub struct WatchpointInfo {
+    pub address: u64,
+    pub size: u32,
+    pub access: WatchAccess,
+    pub core_index: usize,
+    /// DWT comparator holding the watchpoint
+    pub comparator: usize,
+}
+
+/// Check that a watched range is 1, 2 or 4 bytes and naturally aligned
+pub fn validate(address: u64, size: u32) -> Result<()> {
+    if !matches!(size, 1 | 2 | 4) {
+        return Err(DebugError::InvalidConfig(format!("Watchpoint size must be 1, 2 or 4 bytes, not {}", size)));
+    }
+    if !address.is_multiple_of(size as u64) {
+        return Err(DebugError::InvalidAddress {
+            address,
+            reason: format!("a {}-byte watchpoint must be {}-byte aligned", size, size),
+        });
+    }
+    Ok(())
+}
+
+/// DWT_FUNCTION value that raises a debug event for `access` on a `size`-byte data address
+pub fn function_value(core_type: CoreType, access: WatchAccess, size: u32) -> u32 {
+    if core_type == CoreType::Armv8m {
+        // MATCH selects the access type, ACTION=0b01 halts, DATAVSIZE is log2(size)
+        let match_field = match access {
+            WatchAccess::ReadWrite => 0b0100,
+            WatchAccess::Write => 0b0101,
+            WatchAccess::Read => 0b0110,
+        };
+        match_field | (0b01 << 4) | (size.trailing_zeros() << 10)
+    } else {
+        match access {
+            WatchAccess::Read => 0b0101,
+            WatchAccess::Write => 0b0110,
+            WatchAccess::ReadWrite => 0b0111,
+        }
+    }
+}
+
+fn dwt_error(e: probe_rs::Error) -> DebugError {
+    DebugError::InternalError(format!("DWT access failed: {}", e))
+}
+
+fn comparator_address(comparator: usize) -> u64 {
+    DWT_COMP_BASE + comparator as u64 * DWT_COMPARATOR_STRIDE
+}
+
+fn ensure_cortex_m(core: &Core<'_>) -> Result<()> {
+    if core.core_type().is_cortex_m() {
+        Ok(())
+    } else {
+        Err(DebugError::InvalidConfig(format!(
+            "Watchpoints need the Cortex-M DWT unit; not available on {:?} cores", core.core_type()
+        )))
+    }
+}
+
+/// Number of DWT comparators implemented by the core
+pub fn comparator_count(core: &mut Core<'_>) -> Result<usize> {
+    ensure_cortex_m(core)?;
+    let demcr = core.read_word_32(DEMCR).map_err(dwt_error)?;
+    if demcr & DEMCR_TRCENA == 0 {
+        core.write_word_32(DEMCR, demcr | DEMCR_TRCENA).map_err(dwt_error)?;
+    }
+    let ctrl = core.read_word_32(DWT_CTRL).map_err(dwt_error)?;
+    Ok((ctrl >> 28) as usize)
+}
+
+/// Program a DWT comparator with the watchpoint
+pub fn program(core: &mut Core<'_>, watchpoint: &WatchpointInfo) -> Result<()> {
+    ensure_cortex_m(core)?;
+    let base = comparator_address(watchpoint.comparator);
+    let function = function_value(core.core_type(), watchpoint.access, watchpoint.size);
+
+    // Disable the comparator while it is reconfigured
+    core.write_word_32(base + 8, 0).map_err(dwt_error)?;
+    core.write_word_32(base, watchpoint.address as u32).map_err(dwt_error)?;
+    if core.core_type() != CoreType::Armv8m {
+        core.write_word_32(base + 4, watchpoint.size.trailing_zeros()).map_err(dwt_error)?;
+    }
+    core.write_word_32(base + 8, function).map_err(dwt_error)
+}
+
+/// Disable a DWT comparator
+pub fn disable(core: &mut Core<'_>, comparator: usize) -> Result<()> {
+    core.write_word_32(comparator_address(comparator) + 8, 0).map_err(dwt_error)
+}
+
+/// Find which of `watchpoints` fired, using the comparators' MATCHED flags
+pub fn fired<'a>(core: &mut Core<'_>, watchpoints: &'a [WatchpointInfo]) -> Option<&'a WatchpointInfo> {
+    watchpoints.iter().find(|watchpoint| {
+        core.read_word_32(comparator_address(watchpoint.comparator) + 8)
+            .is_ok_and(|function| function & DWT_FUNCTION_MATCHED != 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_value() {
+        assert_eq!(function_value(CoreType::Armv7em, WatchAccess::Write, 4), 0b0110);
+        assert_eq!(function_value(CoreType::Armv6m, WatchAccess::ReadWrite, 1), 0b0111);
+        // MATCH=write, ACTION=debug event, DATAVSIZE=word
+        assert_eq!(function_value(CoreType::Armv8m, WatchAccess::Write, 4), 0b1000_0001_0101);
+    }
+
+    #[test]
+    fn test_validate_watchpoint() {
+        assert!(validate(0x2000_0004, 4).is_ok());
+        assert!(validate(0x2000_0002, 4).is_err());
+        assert!(validate(0x2000_0000, 8).is_err());
+    }
+}
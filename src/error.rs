@@ -20,17 +20,20 @@ pub enum DebugError {
     #[error("Session limit exceeded (max: {0})")]
     SessionLimitExceeded(usize),
 
-    #[error("Operation timeout")]
-    OperationTimeout,
+    #[error("{operation} timed out after {elapsed_ms}ms")]
+    Timeout { operation: String, elapsed_ms: u64 },
 
-    #[error("Invalid address: 0x{0:08x}")]
-    InvalidAddress(u64),
+    #[error("Invalid address 0x{address:08x}: {reason}")]
+    InvalidAddress { address: u64, reason: String },
 
     #[error("Memory access failed: {0}")]
     MemoryAccessFailed(String),
 
-    #[error("Breakpoint limit exceeded")]
-    BreakpointLimitExceeded,
+    #[error(
+        "All {max} hardware breakpoint units are in use ({used} used, at {}); clear one or use a software breakpoint in RAM",
+        format_addresses(.occupied)
+    )]
+    BreakpointLimitReached { used: usize, max: usize, occupied: Vec<u64> },
 
     #[error("RTT not available")]
     RttNotAvailable,
@@ -57,6 +60,53 @@ pub enum DebugError {
     InternalError(String),
 }
 
+impl DebugError {
+    /// Stable kebab-case identifier of the error kind, for clients that branch on errors
+    pub fn code(&self) -> &'static str {
+        match self {
+            DebugError::ProbeNotFound(_) => "probe-not-found",
+            DebugError::TargetNotSupported(_) => "target-not-supported",
+            DebugError::ConnectionFailed(_) => "connection-failed",
+            DebugError::InvalidSession(_) => "invalid-session",
+            DebugError::SessionLimitExceeded(_) => "session-limit-exceeded",
+            DebugError::Timeout { .. } => "timeout",
+            DebugError::InvalidAddress { .. } => "invalid-address",
+            DebugError::MemoryAccessFailed(_) => "memory-access-failed",
+            DebugError::BreakpointLimitReached { .. } => "breakpoint-limit-reached",
+            DebugError::RttNotAvailable => "rtt-not-available",
+            DebugError::RttError(_) => "rtt-error",
+            DebugError::FlashOperationFailed(_) => "flash-operation-failed",
+            DebugError::InvalidConfig(_) => "invalid-config",
+            DebugError::ProbeError(_) => "probe-error",
+            DebugError::IoError(_) => "io-error",
+            DebugError::SerializationError(_) => "serialization-error",
+            DebugError::InternalError(_) => "internal-error",
+        }
+    }
+
+    /// Timeout of `operation` after waiting `elapsed`
+    pub fn timeout(operation: impl Into<String>, elapsed: std::time::Duration) -> Self {
+        DebugError::Timeout { operation: operation.into(), elapsed_ms: elapsed.as_millis() as u64 }
+    }
+
+    /// Map a probe-rs error from a wait bounded by `timeout`, keeping probe timeouts distinct
+    pub fn from_wait(operation: &str, timeout: std::time::Duration, error: probe_rs::Error) -> Self {
+        match error {
+            probe_rs::Error::Timeout => DebugError::timeout(operation, timeout),
+            error => DebugError::ProbeError(format!("{} failed: {}", operation, error)),
+        }
+    }
+
+    /// Structured error data with the `code` and `message` of this error
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "code": self.code(), "message": self.to_string() })
+    }
+}
+
+fn format_addresses(addresses: &[u64]) -> String {
+    addresses.iter().map(|address| format!("0x{:08X}", address)).collect::<Vec<_>>().join(", ")
+}
+
 impl From<probe_rs::Error> for DebugError {
     fn from(error: probe_rs::Error) -> Self {
         DebugError::ProbeError(error.to_string())
@@ -167,4 +217,46 @@ impl From<FlashError> for DebugError {
     fn from(error: FlashError) -> Self {
         DebugError::FlashOperationFailed(error.to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_unique_and_stable() {
+        let errors = [
+            (DebugError::ProbeNotFound(String::new()), "probe-not-found"),
+            (DebugError::TargetNotSupported(String::new()), "target-not-supported"),
+            (DebugError::ConnectionFailed(String::new()), "connection-failed"),
+            (DebugError::InvalidSession(String::new()), "invalid-session"),
+            (DebugError::SessionLimitExceeded(1), "session-limit-exceeded"),
+            (DebugError::Timeout { operation: String::new(), elapsed_ms: 0 }, "timeout"),
+            (DebugError::InvalidAddress { address: 0, reason: String::new() }, "invalid-address"),
+            (DebugError::MemoryAccessFailed(String::new()), "memory-access-failed"),
+            (DebugError::BreakpointLimitReached { used: 0, max: 0, occupied: Vec::new() }, "breakpoint-limit-reached"),
+            (DebugError::RttNotAvailable, "rtt-not-available"),
+            (DebugError::RttError(String::new()), "rtt-error"),
+            (DebugError::FlashOperationFailed(String::new()), "flash-operation-failed"),
+            (DebugError::InvalidConfig(String::new()), "invalid-config"),
+            (DebugError::ProbeError(String::new()), "probe-error"),
+            (DebugError::IoError(std::io::Error::other("")), "io-error"),
+            (DebugError::SerializationError(serde_json::from_str::<u8>("").unwrap_err()), "serialization-error"),
+            (DebugError::InternalError(String::new()), "internal-error"),
+        ];
+
+        let mut seen = std::collections::HashSet::new();
+        for (error, code) in &errors {
+            assert_eq!(error.code(), *code);
+            assert!(seen.insert(error.code()), "duplicate code {}", code);
+        }
+
+        let timeout = DebugError::from_wait("Halting core 0", std::time::Duration::from_millis(500), probe_rs::Error::Timeout);
+        assert_eq!(timeout.code(), "timeout");
+        assert_eq!(timeout.to_string(), "Halting core 0 timed out after 500ms");
+
+        let json = DebugError::SessionLimitExceeded(4).to_json();
+        assert_eq!(json["code"], "session-limit-exceeded");
+        assert_eq!(json["message"], "Session limit exceeded (max: 4)");
+    }
+}
@@ -1,6 +1,7 @@
 //! Flash programming manager - Real probe-rs integration
 
 use crate::error::{Result, DebugError};
+use super::progress::{self, ProgressPhase, ProgressSink};
 use std::path::Path;
 use std::time::Instant;
 use tracing::{debug, info, warn};
@@ -8,6 +9,9 @@ use tracing::{debug, info, warn};
 // Probe-rs imports  
 use probe_rs::{flashing::{self, FlashProgress}, Session, MemoryInterface};
 
+/// Bytes read back per step of a verification
+pub const VERIFY_CHUNK_SIZE: usize = 4096;
+
 /// Erase operation types
 #[derive(Debug, Clone)]
 pub enum EraseType {
@@ -18,7 +22,7 @@ pub enum EraseType {
 }
 
 /// File format types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileFormat {
     Auto,
     Elf,
@@ -26,6 +30,19 @@ pub enum FileFormat {
     Bin,
 }
 
+impl FileFormat {
+    /// Parse a format name ("auto", "elf", "hex" or "bin")
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "auto" => Ok(FileFormat::Auto),
+            "elf" => Ok(FileFormat::Elf),
+            "hex" => Ok(FileFormat::Hex),
+            "bin" => Ok(FileFormat::Bin),
+            other => Err(format!("Unsupported format: {}", other)),
+        }
+    }
+}
+
 /// Erase operation result
 #[derive(Debug)]
 pub struct EraseResult {
@@ -90,7 +107,7 @@ impl FlashManager {
                 
                 // Calculate sector range - this is target-specific, using approximation
                 let sector_size = 4096; // Common sector size, should be target-specific
-                let sector_count = (size + sector_size - 1) / sector_size;
+                let sector_count = size.div_ceil(sector_size);
                 
                 // Use probe-rs flashing API for sector erase
                 let mut core = session.core(0)
@@ -111,12 +128,13 @@ impl FlashManager {
         }
     }
 
-    /// Program file to flash
+    /// Program file to flash, sending erase, program and verify progress to `progress`
     pub async fn program_file(
         session: &mut Session,
         file_path: &Path,
         format: FileFormat,
         base_address: Option<u64>,
+        progress: Option<ProgressSink>,
     ) -> Result<ProgramResult> {
         let start_time = Instant::now();
         
@@ -146,7 +164,11 @@ impl FlashManager {
         // Setup download options - use default and override what we need
         let mut options = flashing::DownloadOptions::default();
         options.verify = true;
-        options.progress = None;
+        let finish_progress = progress.map(|sink| {
+            let (handler, finish) = progress::flash_progress(sink);
+            options.progress = Some(handler);
+            finish
+        });
 
         // Set base address for BIN files - this might need to be handled differently
         if matches!(probe_format, flashing::Format::Bin(_)) {
@@ -159,6 +181,9 @@ impl FlashManager {
         // Execute programming
         flashing::download_file_with_options(session, file_path, probe_format, options)
             .map_err(|e| DebugError::FlashOperationFailed(format!("Programming failed: {}", e)))?;
+        if let Some(finish) = finish_progress {
+            finish();
+        }
 
         let elapsed = start_time.elapsed().as_millis() as u64;
         
@@ -205,11 +230,13 @@ impl FlashManager {
         })
     }
 
-    /// Verify flash contents
+    /// Verify flash contents, reading in `VERIFY_CHUNK_SIZE` pieces and
+    /// sending the verify progress to `progress`
     pub async fn verify_flash(
         session: &mut Session,
         expected_data: &[u8],
         address: u64,
+        progress: Option<&ProgressSink>,
     ) -> Result<VerifyResult> {
         debug!("Verifying {} bytes at address 0x{:08X}", expected_data.len(), address);
 
@@ -217,9 +244,15 @@ impl FlashManager {
             .map_err(|e| DebugError::FlashOperationFailed(format!("Failed to get core: {}", e)))?;
         
         // Read actual data from flash
+        let mut phase = progress::start_phase(progress, ProgressPhase::Verify, expected_data.len() as u64);
         let mut actual_data = vec![0u8; expected_data.len()];
-        core.read(address, &mut actual_data)
-            .map_err(|e| DebugError::FlashOperationFailed(format!("Failed to read flash: {}", e)))?;
+        for (index, chunk) in actual_data.chunks_mut(VERIFY_CHUNK_SIZE).enumerate() {
+            core.read(address + (index * VERIFY_CHUNK_SIZE) as u64, chunk)
+                .map_err(|e| DebugError::FlashOperationFailed(format!("Failed to read flash: {}", e)))?;
+            if let Some(phase) = phase.as_mut() {
+                phase.advance(chunk.len() as u64);
+            }
+        }
 
         // Compare data and find mismatches
         let mut mismatches = Vec::new();
@@ -253,4 +286,18 @@ impl Default for FlashManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_format() {
+        assert_eq!(FileFormat::parse("auto").unwrap(), FileFormat::Auto);
+        assert_eq!(FileFormat::parse("elf").unwrap(), FileFormat::Elf);
+        assert_eq!(FileFormat::parse("hex").unwrap(), FileFormat::Hex);
+        assert_eq!(FileFormat::parse("bin").unwrap(), FileFormat::Bin);
+        assert_eq!(FileFormat::parse("uf2").unwrap_err(), "Unsupported format: uf2");
+    }
+}
@@ -1,6 +1,7 @@
 //! Flash programming and management
 
 pub mod manager;
+pub mod progress;
 
 pub use manager::{
     FlashManager, 
@@ -10,4 +11,5 @@ pub use manager::{
     ProgramResult, 
     VerifyResult, 
     VerifyMismatch
-};
\ No newline at end of file
+};
+pub use progress::{ProgressPhase, ProgressSink, ProgressUpdate};
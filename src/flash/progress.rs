@@ -0,0 +1,189 @@
+//! Progress of long flash and memory operations, reported to the client as it happens
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use probe_rs::flashing::{FlashProgress, ProgressEvent};
+
+/// Shortest interval between two progress notifications of one operation
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Receiving end of the updates of an operation
+pub type ProgressSink = tokio::sync::mpsc::UnboundedSender<ProgressUpdate>;
+
+/// Stage of a long operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressPhase {
+    Erase,
+    Program,
+    Verify,
+    Read,
+}
+
+impl std::fmt::Display for ProgressPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ProgressPhase::Erase => "erase",
+            ProgressPhase::Program => "program",
+            ProgressPhase::Verify => "verify",
+            ProgressPhase::Read => "read",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Bytes done in the current phase of an operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressUpdate {
+    pub phase: ProgressPhase,
+    pub done: u64,
+    pub total: u64,
+    /// Time spent in the phase so far
+    pub elapsed: Duration,
+}
+
+impl ProgressUpdate {
+    /// Throughput of the phase so far
+    pub fn rate_kbps(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.done as f64 / 1024.0 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether the phase is complete
+    pub fn finished(&self) -> bool {
+        self.done >= self.total
+    }
+
+    /// One-line description, e.g. "program: 4096/65536 bytes, 12.5 kB/s"
+    pub fn describe(&self) -> String {
+        format!("{}: {}/{} bytes, {:.1} kB/s", self.phase, self.done, self.total, self.rate_kbps())
+    }
+}
+
+/// Progress of one phase, sending an update for every step
+#[derive(Debug)]
+pub struct PhaseProgress {
+    sink: ProgressSink,
+    phase: ProgressPhase,
+    done: u64,
+    total: u64,
+    started: Instant,
+}
+
+impl PhaseProgress {
+    /// Start a phase of `total` bytes and announce it
+    pub fn start(sink: &ProgressSink, phase: ProgressPhase, total: u64) -> Self {
+        let progress = PhaseProgress { sink: sink.clone(), phase, done: 0, total, started: Instant::now() };
+        progress.send();
+        progress
+    }
+
+    /// Count `bytes` more as done
+    pub fn advance(&mut self, bytes: u64) {
+        self.done = (self.done + bytes).min(self.total);
+        self.send();
+    }
+
+    /// Mark the whole phase done
+    pub fn finish(&mut self) {
+        self.done = self.total;
+        self.send();
+    }
+
+    fn send(&self) {
+        // The receiver is gone once the client stopped listening; the operation goes on
+        let _ = self.sink.send(ProgressUpdate {
+            phase: self.phase,
+            done: self.done,
+            total: self.total,
+            elapsed: self.started.elapsed(),
+        });
+    }
+}
+
+/// Start a phase when `sink` is set
+pub fn start_phase(sink: Option<&ProgressSink>, phase: ProgressPhase, total: u64) -> Option<PhaseProgress> {
+    sink.map(|sink| PhaseProgress::start(sink, phase, total))
+}
+
+/// Bytes of the erase and program phases, known once probe-rs laid out the flash
+#[derive(Debug, Default)]
+struct FlashTotals {
+    erase: u64,
+    program: u64,
+}
+
+/// Flash progress handler translating probe-rs events into updates on `sink`.
+///
+/// probe-rs does not report its readback verification, so the verify phase
+/// starts when programming finishes and is left for the caller to finish.
+pub fn flash_progress(sink: ProgressSink) -> (FlashProgress, impl FnOnce()) {
+    let state = std::rc::Rc::new(RefCell::new((FlashTotals::default(), None::<PhaseProgress>)));
+    let handler_state = state.clone();
+    let handler = FlashProgress::new(move |event| {
+        let (totals, phase) = &mut *handler_state.borrow_mut();
+        match event {
+            ProgressEvent::Initialized { phases, .. } => {
+                totals.erase = phases.iter().flat_map(|layout| layout.sectors()).map(|sector| sector.size()).sum();
+                totals.program = phases.iter().flat_map(|layout| layout.pages()).map(|page| u64::from(page.size())).sum();
+            }
+            ProgressEvent::StartedErasing => {
+                *phase = Some(PhaseProgress::start(&sink, ProgressPhase::Erase, totals.erase));
+            }
+            ProgressEvent::SectorErased { size, .. } => {
+                if let Some(phase) = phase {
+                    phase.advance(size);
+                }
+            }
+            ProgressEvent::StartedProgramming { length } => {
+                *phase = Some(PhaseProgress::start(&sink, ProgressPhase::Program, length));
+            }
+            ProgressEvent::PageProgrammed { size, .. } => {
+                if let Some(phase) = phase {
+                    phase.advance(u64::from(size));
+                }
+            }
+            ProgressEvent::FinishedErasing => {
+                if let Some(phase) = phase {
+                    phase.finish();
+                }
+            }
+            ProgressEvent::FinishedProgramming => {
+                if let Some(phase) = phase {
+                    phase.finish();
+                }
+                *phase = Some(PhaseProgress::start(&sink, ProgressPhase::Verify, totals.program));
+            }
+            _ => {}
+        }
+    });
+    let finish = move || {
+        if let Some(phase) = state.borrow_mut().1.as_mut().filter(|phase| phase.phase == ProgressPhase::Verify) {
+            phase.finish();
+        }
+    };
+    (handler, finish)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_progress_updates() {
+        let (sink, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut phase = start_phase(Some(&sink), ProgressPhase::Read, 1024).unwrap();
+        phase.advance(512);
+        phase.advance(1024);
+
+        let updates: Vec<ProgressUpdate> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(updates.iter().map(|update| update.done).collect::<Vec<_>>(), vec![0, 512, 1024]);
+        assert!(updates[2].finished());
+        assert!(updates[1].describe().starts_with("read: 512/1024 bytes"));
+        assert!(start_phase(None, ProgressPhase::Read, 1024).is_none());
+    }
+}
@@ -11,6 +11,7 @@ pub mod debugger;
 pub mod rtt;
 pub mod flash;
 pub mod tools;
+pub mod symbols;
 
 pub use error::{DebugError, Result};
 pub use config::Config;
@@ -60,7 +60,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Configuration loaded and validated successfully");
 
     // Create and serve the handler using rust-sdk standard pattern
-    let service = EmbeddedDebuggerToolHandler::new(config.server.max_sessions)
+    let service = EmbeddedDebuggerToolHandler::from_config(&config)
         .serve(stdio()).await.inspect_err(|e| {
             error!("Serving error: {:?}", e);
         })?;
@@ -118,7 +118,7 @@ mod tests {
 
     #[test]
     fn test_args_parsing() {
-        let args = Args::parse_from(&[
+        let args = Args::parse_from([
             "debugger-mcp-rs",
             "--log-level", "debug",
             "--max-sessions", "10",
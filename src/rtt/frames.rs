@@ -0,0 +1,129 @@
+//! Reassembly of length-prefixed binary frames from an RTT up channel
+
+use crate::error::{DebugError, Result};
+use crate::utils::Endianness;
+
+/// Default limit on a frame payload, guarding against a desynchronized stream
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// Length prefix in front of every frame payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// Prefix size in bytes, 2 or 4
+    pub size: usize,
+    pub endianness: Endianness,
+}
+
+impl FrameHeader {
+    /// Parse a header description: "u16le", "u16be", "u32le" or "u32be"
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        let (size, endianness) = match name.to_lowercase().as_str() {
+            "u16le" => (2, Endianness::Little),
+            "u16be" => (2, Endianness::Big),
+            "u32le" => (4, Endianness::Little),
+            "u32be" => (4, Endianness::Big),
+            _ => return Err(format!("Unsupported frame header '{}'. Use 'u16le', 'u16be', 'u32le' or 'u32be'", name)),
+        };
+        Ok(FrameHeader { size, endianness })
+    }
+}
+
+impl std::fmt::Display for FrameHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let suffix = match self.endianness {
+            Endianness::Little => "le",
+            Endianness::Big => "be",
+        };
+        write!(f, "u{}{}", self.size * 8, suffix)
+    }
+}
+
+/// Result of a framed read
+#[derive(Debug, Default)]
+pub struct FrameRead {
+    /// Payloads of the complete frames, prefix removed
+    pub frames: Vec<Vec<u8>>,
+    /// Bytes of the incomplete next frame, kept for the next read
+    pub pending: usize,
+    /// Why buffered bytes were dropped, when the stream lost sync
+    pub desync: Option<String>,
+}
+
+/// Bytes of an up channel that have not yet formed a complete frame
+#[derive(Debug)]
+pub struct FrameBuffer {
+    pub header: FrameHeader,
+    pub max_frame_size: usize,
+    pending: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub fn new(header: FrameHeader, max_frame_size: usize) -> Self {
+        Self { header, max_frame_size, pending: Vec::new() }
+    }
+
+    /// Append raw channel bytes and return the payloads of the frames they complete.
+    ///
+    /// Bytes of an incomplete frame stay buffered for the next push. A length
+    /// prefix above `max_frame_size` means the stream lost sync; the buffer
+    /// is dropped and an error returned, after the frames completed before it.
+    pub fn push(&mut self, data: &[u8]) -> (Vec<Vec<u8>>, Result<()>) {
+        self.pending.extend_from_slice(data);
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        while self.pending.len() - offset >= self.header.size {
+            let length = self.header.endianness.word_from_bytes(&self.pending[offset..offset + self.header.size]) as usize;
+            if length > self.max_frame_size {
+                let dropped = self.pending.len() - offset;
+                self.pending.clear();
+                return (frames, Err(DebugError::RttError(format!(
+                    "Frame length {} exceeds the {} byte limit; the stream is out of sync, dropped {} buffered bytes",
+                    length, self.max_frame_size, dropped
+                ))));
+            }
+            let end = offset + self.header.size + length;
+            if end > self.pending.len() {
+                break;
+            }
+            frames.push(self.pending[offset + self.header.size..end].to_vec());
+            offset = end;
+        }
+        self.pending.drain(..offset);
+        (frames, Ok(()))
+    }
+
+    /// Number of buffered bytes of the incomplete next frame
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_buffer_keeps_partial_frames() {
+        let header = FrameHeader::parse("u16le").unwrap();
+        let mut buffer = FrameBuffer::new(header, DEFAULT_MAX_FRAME_SIZE);
+
+        // One whole frame, then the prefix and half the payload of the next
+        let (frames, result) = buffer.push(&[3, 0, 0xAA, 0xBB, 0xCC, 2, 0, 0x11]);
+        assert!(result.is_ok());
+        assert_eq!(frames, vec![vec![0xAA, 0xBB, 0xCC]]);
+        assert_eq!(buffer.pending(), 3);
+
+        let (frames, _) = buffer.push(&[0x22, 0, 0]);
+        assert_eq!(frames, vec![vec![0x11, 0x22], vec![]]);
+        assert_eq!(buffer.pending(), 0);
+
+        let mut big_endian = FrameBuffer::new(FrameHeader::parse("u32be").unwrap(), 4);
+        assert_eq!(big_endian.push(&[0, 0, 0, 1, 0x55]).0, vec![vec![0x55]]);
+        let (frames, result) = big_endian.push(&[0, 0, 1, 0]);
+        assert!(frames.is_empty() && result.is_err());
+        assert_eq!(big_endian.pending(), 0);
+
+        assert!(FrameHeader::parse("u24le").is_err());
+        assert_eq!(header.to_string(), "u16le");
+    }
+}
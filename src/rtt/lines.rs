@@ -0,0 +1,52 @@
+//! Reassembly of RTT output into text lines
+
+/// Bytes of an up channel that have not yet formed a complete line
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Append raw channel bytes and return the lines they complete.
+    ///
+    /// Lines are split on `\n` with a trailing `\r` removed; invalid UTF-8 is
+    /// replaced with U+FFFD. Bytes after the last newline stay buffered, so
+    /// multi-byte characters split across reads are decoded correctly.
+    pub fn push(&mut self, data: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(data);
+        let Some(last_newline) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+
+        let complete: Vec<u8> = self.pending.drain(..=last_newline).collect();
+        complete[..complete.len() - 1]
+            .split(|&b| b == b'\n')
+            .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned())
+            .collect()
+    }
+
+    /// Incomplete trailing line, decoded lossily
+    pub fn fragment(&self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer_carries_fragments() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"boot").is_empty());
+        assert_eq!(buffer.fragment(), "boot");
+
+        assert_eq!(buffer.push(b"ing\r\nready\ntemp="), vec!["booting", "ready"]);
+        assert_eq!(buffer.fragment(), "temp=");
+
+        // A multi-byte character split across reads survives, invalid bytes are replaced
+        assert!(buffer.push(&[0x32, 0x30, 0xC2]).is_empty());
+        assert_eq!(buffer.push(&[0xB0, b'C', b'\n', 0xFF, b'\n']), vec!["temp=20°C", "\u{FFFD}"]);
+        assert_eq!(buffer.fragment(), "");
+    }
+}
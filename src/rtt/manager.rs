@@ -2,11 +2,24 @@
 
 use crate::error::{DebugError, Result};
 use std::collections::HashMap;
-use std::sync::Arc;
 use std::path::Path;
-use tokio::sync::Mutex;
 use tracing::{debug, info, error, warn};
-use probe_rs::{Session, rtt::{Rtt, ScanRegion}, MemoryInterface};
+use probe_rs::{rtt::{ChannelMode, Rtt, ScanRegion}, Core, MemoryInterface, Session};
+use crate::debugger::session::{lock_session, SharedSession};
+use crate::rtt::{FrameBuffer, FrameHeader, FrameRead, LineBuffer};
+
+/// Delay between polls of a line-oriented RTT read waiting for a complete line
+const READ_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Delay between attempts of a blocking RTT write while the down buffer is full
+const WRITE_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Channels of an attached RTT control block
+#[derive(Debug, Clone, Copy)]
+pub struct RttInfo {
+    pub up_channels: usize,
+    pub down_channels: usize,
+}
 
 /// RTT manager for hardware communication with embedded targets  
 #[derive(Debug)]
@@ -16,13 +29,17 @@ pub struct RttManager {
     /// Real RTT instance from probe-rs
     rtt: Option<Rtt>,
     /// Session reference for RTT operations
-    session: Option<Arc<Mutex<Session>>>,
+    session: Option<SharedSession>,
     /// Cached channel information from RTT
     channels: HashMap<u32, ChannelInfo>,
     /// Number of up channels discovered
     up_channel_count: usize,
     /// Number of down channels discovered
     down_channel_count: usize,
+    /// Partial lines per up channel for line-oriented reads
+    line_buffers: HashMap<u32, LineBuffer>,
+    /// Partial frames per up channel switched to framed reads
+    frame_buffers: HashMap<u32, FrameBuffer>,
 }
 
 #[derive(Debug, Clone)]
@@ -30,11 +47,69 @@ pub struct ChannelInfo {
     pub id: u32,
     pub name: String,
     pub direction: ChannelDirection,
-    pub mode: String,
     pub buffer_size: usize,
+    /// Mode bits of the channel's flags word, deciding what the target does
+    /// when the buffer is full. Only read for up channels, where the target
+    /// writes; `None` for down channels and when the read failed.
+    pub flags: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+impl ChannelInfo {
+    /// What the target does when the channel buffer is full
+    pub fn mode_name(&self) -> &'static str {
+        match self.flags.map(|flags| flags & CHANNEL_MODE_MASK) {
+            Some(0) => "non-blocking, skip",
+            Some(1) => "non-blocking, trim",
+            Some(2) => "blocking",
+            Some(_) => "invalid",
+            None => "unknown",
+        }
+    }
+}
+
+/// Bits of the channel flags word holding the mode
+const CHANNEL_MODE_MASK: u32 = 0b11;
+
+/// Mode bits of a channel, read from the control block on the target
+fn channel_flags(mode: std::result::Result<ChannelMode, probe_rs::rtt::Error>, name: &str) -> Option<u32> {
+    match mode {
+        Ok(mode) => Some(mode as u32),
+        Err(e) => {
+            warn!("Failed to read the mode of RTT channel {}: {}", name, e);
+            None
+        }
+    }
+}
+
+/// Read whatever an up channel has buffered, through core 0 of a locked session
+fn read_up_channel(rtt: &mut Rtt, session: &mut Session, channel: u32) -> Result<Vec<u8>> {
+    let mut core = session.core(0).map_err(|e| {
+        DebugError::RttError(format!("Failed to get core: {}", e))
+    })?;
+
+    // Get the up channel (mutable reference)
+    let up_channels = rtt.up_channels();
+    let up_channel = up_channels.get_mut(channel as usize)
+        .ok_or_else(|| DebugError::RttError(format!("Up channel {} not found", channel)))?;
+
+    // Read from RTT channel
+    let mut buffer = vec![0u8; 1024]; // Buffer for reading
+    match up_channel.read(&mut core, &mut buffer) {
+        Ok(bytes_read) => {
+            buffer.truncate(bytes_read);
+            if bytes_read > 0 {
+                debug!("Read {} bytes from RTT up channel {}", bytes_read, channel);
+            }
+            Ok(buffer)
+        }
+        Err(e) => {
+            error!("Failed to read from RTT up channel {}: {}", channel, e);
+            Err(DebugError::RttError(format!("RTT read failed: {}", e)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChannelDirection {
     Up,   // Target to Host
     Down, // Host to Target
@@ -56,6 +131,8 @@ impl RttManager {
             channels: HashMap::new(),
             up_channel_count: 0,
             down_channel_count: 0,
+            line_buffers: HashMap::new(),
+            frame_buffers: HashMap::new(),
         }
     }
 
@@ -63,7 +140,7 @@ impl RttManager {
     /// This is the recommended method that follows probe-rs best practices
     pub async fn attach_with_elf(
         &mut self,
-        session: Arc<Mutex<Session>>,
+        session: SharedSession,
         firmware_path: &Path,
     ) -> Result<()> {
         info!("Starting enhanced RTT attachment with ELF symbol detection first");
@@ -100,7 +177,7 @@ impl RttManager {
     /// Try RTT connection at specific address (used for ELF symbol detection)
     async fn try_rtt_at_address(
         &mut self,
-        session: Arc<Mutex<Session>>,
+        session: SharedSession,
         address: u64,
     ) -> Result<()> {
         debug!("Attempting RTT connection at specific address: 0x{:08X}", address);
@@ -108,7 +185,7 @@ impl RttManager {
         // Store session reference
         self.session = Some(session.clone());
 
-        let mut session_guard = session.lock().await;
+        let mut session_guard = lock_session(&session).await?;
         let mut core = session_guard.core(0).map_err(|e| {
             error!("Failed to get core for RTT attachment: {}", e);
             DebugError::RttError(format!("Failed to get core: {}", e))
@@ -133,7 +210,7 @@ impl RttManager {
         match rtt_result {
             Ok(rtt) => {
                 info!("Successfully attached RTT at ELF symbol address 0x{:08X}!", address);
-                self.complete_attachment_sync(rtt)
+                self.complete_attachment_sync(&mut core, rtt)
             }
             Err(e) => {
                 error!("RTT attachment failed at address 0x{:08X}: {}", address, e);
@@ -176,7 +253,7 @@ impl RttManager {
     /// Priority: ELF symbol detection first, then memory scanning fallback
     pub async fn attach(
         &mut self, 
-        session: Arc<Mutex<Session>>,
+        session: SharedSession,
         control_block_address: Option<u64>,
         memory_ranges: Option<Vec<(u64, u64)>>
     ) -> Result<()> {
@@ -188,7 +265,7 @@ impl RttManager {
         // Note: memory_map not needed for probe-rs 0.25 attach_region API
         
         // Get the session and core to perform RTT attachment
-        let mut session_guard = session.lock().await;
+        let mut session_guard = lock_session(&session).await?;
         let mut core = session_guard.core(0).map_err(|e| {
             error!("Failed to get core for RTT attachment: {}", e);
             DebugError::RttError(format!("Failed to get core: {}", e))
@@ -223,7 +300,7 @@ impl RttManager {
         match rtt_result {
             Ok(rtt) => {
                 info!("Successfully attached to RTT control block!");
-                self.complete_attachment_sync(rtt)
+                self.complete_attachment_sync(&mut core, rtt)
             }
             Err(e) => {
                 error!("RTT attachment failed: {}", e);
@@ -253,7 +330,7 @@ impl RttManager {
     }
     
     /// Complete RTT attachment by discovering channels (synchronous version)
-    fn complete_attachment_sync(&mut self, mut rtt: Rtt) -> Result<()> {
+    fn complete_attachment_sync(&mut self, core: &mut Core<'_>, mut rtt: Rtt) -> Result<()> {
         // Clear any previous state
         self.channels.clear();
         
@@ -262,11 +339,12 @@ impl RttManager {
         self.up_channel_count = up_channels.len();
         for i in 0..up_channels.len() {
             if let Some(up_channel) = up_channels.get(i) {
+                let name = up_channel.name().unwrap_or(&format!("Up{}", i)).to_string();
                 let channel_info = ChannelInfo {
                     id: i as u32,
-                    name: up_channel.name().unwrap_or(&format!("Up{}", i)).to_string(),
+                    flags: channel_flags(up_channel.mode(core), &name),
+                    name,
                     direction: ChannelDirection::Up,
-                    mode: "RTT".to_string(), // Simplified as mode() requires &mut Core
                     buffer_size: up_channel.buffer_size(),
                 };
                 self.channels.insert(i as u32, channel_info);
@@ -280,11 +358,12 @@ impl RttManager {
         self.down_channel_count = down_channels.len();
         for i in 0..down_channels.len() {
             if let Some(down_channel) = down_channels.get(i) {
+                let name = down_channel.name().unwrap_or(&format!("Down{}", i)).to_string();
                 let channel_info = ChannelInfo {
                     id: i as u32,
-                    name: down_channel.name().unwrap_or(&format!("Down{}", i)).to_string(),
+                    flags: None,
+                    name,
                     direction: ChannelDirection::Down,
-                    mode: "RTT".to_string(), // Simplified as mode() requires &mut Core
                     buffer_size: down_channel.buffer_size(),
                 };
                 // Use offset for down channels to avoid ID conflicts
@@ -311,6 +390,8 @@ impl RttManager {
         self.rtt = None;
         self.session = None;
         self.channels.clear();
+        self.line_buffers.clear();
+        self.frame_buffers.clear();
         self.up_channel_count = 0;
         self.down_channel_count = 0;
         
@@ -331,33 +412,131 @@ impl RttManager {
             .ok_or_else(|| DebugError::RttError("No RTT instance available".to_string()))?;
         
         // Lock session and get core
-        let mut session_guard = session.lock().await;
-        let mut core = session_guard.core(0).map_err(|e| {
-            DebugError::RttError(format!("Failed to get core: {}", e))
+        let mut session_guard = lock_session(session).await?;
+        read_up_channel(rtt, &mut session_guard, channel)
+    }
+
+    /// Read from an up channel without waiting for the session.
+    ///
+    /// Returns `None` when a tool call holds the session, so callers that
+    /// must never stall one (the event monitor) can try again later.
+    pub fn try_read_channel(&mut self, channel: u32) -> Result<Option<Vec<u8>>> {
+        if !self.attached {
+            return Err(DebugError::RttError("RTT not attached".to_string()));
+        }
+
+        let session = self.session.as_ref()
+            .ok_or_else(|| DebugError::RttError("No session available".to_string()))?;
+
+        let rtt = self.rtt.as_mut()
+            .ok_or_else(|| DebugError::RttError("No RTT instance available".to_string()))?;
+
+        let Ok(mut slot) = session.try_lock() else { return Ok(None) };
+        let session_guard = slot.as_mut().ok_or_else(|| {
+            DebugError::ConnectionFailed("Session is disconnected; use 'reconnect' to re-attach".to_string())
         })?;
-        
-        // Get the up channel (mutable reference)
-        let up_channels = rtt.up_channels();
-        let up_channel = up_channels.get_mut(channel as usize)
-            .ok_or_else(|| DebugError::RttError(format!("Up channel {} not found", channel)))?;
-        
-        // Read from RTT channel
-        let mut buffer = vec![0u8; 1024]; // Buffer for reading
-        match up_channel.read(&mut core, &mut buffer) {
-            Ok(bytes_read) => {
-                buffer.truncate(bytes_read);
-                if bytes_read > 0 {
-                    debug!("Read {} bytes from RTT up channel {}", bytes_read, channel);
-                }
-                Ok(buffer)
+        read_up_channel(rtt, session_guard, channel).map(Some)
+    }
+
+    /// Read from an up channel, polling until data arrives.
+    ///
+    /// Fails with `DebugError::Timeout` when nothing arrives within a non-zero
+    /// `timeout`; a zero timeout reads once and may return no data.
+    pub async fn read_channel_timeout(&mut self, channel: u32, timeout: std::time::Duration) -> Result<Vec<u8>> {
+        let start_time = std::time::Instant::now();
+        loop {
+            let data = self.read_channel(channel).await?;
+            if !data.is_empty() || timeout.is_zero() {
+                return Ok(data);
             }
-            Err(e) => {
-                error!("Failed to read from RTT up channel {}: {}", channel, e);
-                Err(DebugError::RttError(format!("RTT read failed: {}", e)))
+            if start_time.elapsed() >= timeout {
+                return Err(DebugError::timeout(format!("RTT read on channel {}", channel), start_time.elapsed()));
+            }
+            tokio::time::sleep(READ_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Read complete text lines from an up channel.
+    ///
+    /// Polls until at least one line is complete or `timeout` elapses. Returns
+    /// the lines and the incomplete trailing fragment, which is kept and
+    /// continued by the next read.
+    pub async fn read_lines(&mut self, channel: u32, timeout: std::time::Duration) -> Result<(Vec<String>, String)> {
+        let start_time = std::time::Instant::now();
+        let mut lines = Vec::new();
+        loop {
+            let data = self.read_channel(channel).await?;
+            let buffer = self.line_buffers.entry(channel).or_default();
+            lines.extend(buffer.push(&data));
+
+            // Keep draining while the target has more data buffered
+            if !data.is_empty() {
+                continue;
+            }
+            if !lines.is_empty() || start_time.elapsed() >= timeout {
+                return Ok((lines, buffer.fragment()));
             }
+            tokio::time::sleep(READ_POLL_INTERVAL).await;
         }
     }
 
+    /// Switch an up channel to length-prefixed binary frames for `read_frames`.
+    ///
+    /// Bytes buffered under an earlier framing of the channel are dropped.
+    /// Raw or line reads of the same channel take bytes out of the frame
+    /// stream, so the channel should only be read through `read_frames`.
+    pub fn attach_framed(&mut self, channel: u32, header: FrameHeader, max_frame_size: usize) -> Result<()> {
+        if !self.attached {
+            return Err(DebugError::RttError("RTT not attached".to_string()));
+        }
+        if !self.channels.get(&channel).is_some_and(|info| info.direction == ChannelDirection::Up) {
+            return Err(DebugError::RttError(format!("Up channel {} not found", channel)));
+        }
+        self.frame_buffers.insert(channel, FrameBuffer::new(header, max_frame_size));
+        info!("RTT up channel {} switched to {} framed reads", channel, header);
+        Ok(())
+    }
+
+    /// Read complete frames from an up channel set up with `attach_framed`.
+    ///
+    /// Polls until at least one frame is complete or `timeout` elapses. The
+    /// bytes of an incomplete frame are kept and continued by the next read.
+    pub async fn read_frames(&mut self, channel: u32, timeout: std::time::Duration) -> Result<FrameRead> {
+        if !self.frame_buffers.contains_key(&channel) {
+            return Err(DebugError::RttError(format!(
+                "RTT up channel {} is not framed; use rtt_attach_framed first", channel
+            )));
+        }
+        let start_time = std::time::Instant::now();
+        let mut read = FrameRead::default();
+        loop {
+            let data = self.read_channel(channel).await?;
+            let buffer = self.frame_buffers.get_mut(&channel)
+                .ok_or_else(|| DebugError::RttError(format!("RTT up channel {} is not framed", channel)))?;
+            let (frames, result) = buffer.push(&data);
+            read.frames.extend(frames);
+            read.pending = buffer.pending();
+            if let Err(e) = result {
+                warn!("RTT up channel {}: {}", channel, e);
+                read.desync = Some(e.to_string());
+            }
+
+            // Keep draining while the target has more data buffered
+            if !data.is_empty() {
+                continue;
+            }
+            if !read.frames.is_empty() || start_time.elapsed() >= timeout {
+                return Ok(read);
+            }
+            tokio::time::sleep(READ_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Framing of an up channel, if it was switched to framed reads
+    pub fn frame_header(&self, channel: u32) -> Option<FrameHeader> {
+        self.frame_buffers.get(&channel).map(|buffer| buffer.header)
+    }
+
     /// Write to RTT down channel using probe-rs RTT API
     pub async fn write_channel(&mut self, channel: u32, data: &[u8]) -> Result<usize> {
         if !self.attached {
@@ -371,7 +550,7 @@ impl RttManager {
             .ok_or_else(|| DebugError::RttError("No RTT instance available".to_string()))?;
         
         // Lock session and get core
-        let mut session_guard = session.lock().await;
+        let mut session_guard = lock_session(session).await?;
         let mut core = session_guard.core(0).map_err(|e| {
             DebugError::RttError(format!("Failed to get core: {}", e))
         })?;
@@ -395,6 +574,78 @@ impl RttManager {
         }
     }
 
+    /// Write all of `data` to a down channel, retrying while the target drains the buffer.
+    ///
+    /// The session lock is released between attempts so the target keeps
+    /// running normally. Returns the bytes written, which is less than
+    /// `data.len()` only when `timeout` elapsed first.
+    pub async fn write_channel_blocking(&mut self, channel: u32, data: &[u8], timeout: std::time::Duration) -> Result<usize> {
+        let start_time = std::time::Instant::now();
+        let mut written = 0;
+        while written < data.len() {
+            written += self.write_channel(channel, &data[written..]).await?;
+            if written == data.len() || start_time.elapsed() >= timeout {
+                break;
+            }
+            tokio::time::sleep(WRITE_RETRY_INTERVAL).await;
+        }
+        if written < data.len() {
+            warn!("RTT write to channel {} timed out after {} of {} bytes", channel, written, data.len());
+        }
+        Ok(written)
+    }
+
+    /// Resolve a channel name such as "Terminal" or "defmt" to its index.
+    ///
+    /// Names match case-insensitively; the error lists the channels that exist
+    /// in `direction`.
+    pub fn find_channel(&self, name: &str, direction: ChannelDirection) -> Result<u32> {
+        let mut candidates: Vec<&ChannelInfo> = self.channels
+            .values()
+            .filter(|channel| channel.direction == direction)
+            .collect();
+        candidates.sort_by_key(|channel| channel.id);
+
+        if let Some(channel) = candidates.iter().find(|channel| channel.name.eq_ignore_ascii_case(name)) {
+            return Ok(channel.id);
+        }
+
+        let available = candidates
+            .iter()
+            .map(|channel| format!("{} ({})", channel.id, channel.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(DebugError::RttError(format!(
+            "No {} channel named '{}'; available: {}",
+            if direction == ChannelDirection::Up { "up" } else { "down" },
+            name,
+            if available.is_empty() { "none".to_string() } else { available }
+        )))
+    }
+
+    /// Read the up channel flags again; the firmware may change a channel's
+    /// mode after the control block was found, e.g. once its logger starts
+    pub async fn refresh_channel_flags(&mut self) -> Result<()> {
+        let session = self.session.as_ref()
+            .ok_or_else(|| DebugError::RttError("No session available".to_string()))?;
+        let rtt = self.rtt.as_mut()
+            .ok_or_else(|| DebugError::RttError("No RTT instance available".to_string()))?;
+
+        let mut session_guard = lock_session(session).await?;
+        let mut core = session_guard.core(0).map_err(|e| {
+            DebugError::RttError(format!("Failed to get core: {}", e))
+        })?;
+        for channel in self.channels.values_mut() {
+            if channel.direction != ChannelDirection::Up {
+                continue;
+            }
+            if let Some(up_channel) = rtt.up_channels().get(channel.id as usize) {
+                channel.flags = channel_flags(up_channel.mode(&mut core), &channel.name);
+            }
+        }
+        Ok(())
+    }
+
     /// Get information about all RTT channels
     pub fn get_channels(&self) -> Vec<&ChannelInfo> {
         self.channels.values().collect()
@@ -405,6 +656,14 @@ impl RttManager {
         self.attached
     }
 
+    /// Summary of the attached control block, `None` when not attached
+    pub fn info(&self) -> Option<RttInfo> {
+        self.attached.then_some(RttInfo {
+            up_channels: self.up_channel_count,
+            down_channels: self.down_channel_count,
+        })
+    }
+
     /// Get the number of available up channels
     pub fn up_channel_count(&self) -> usize {
         self.up_channel_count
@@ -414,4 +673,61 @@ impl RttManager {
     pub fn down_channel_count(&self) -> usize {
         self.down_channel_count
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_channel() {
+        let mut manager = RttManager::new();
+        let channel = |id: u32, name: &str, direction| ChannelInfo {
+            id,
+            name: name.to_string(),
+            direction,
+            buffer_size: 1024,
+            flags: Some(2),
+        };
+        manager.channels.insert(0, channel(0, "Terminal", ChannelDirection::Up));
+        manager.channels.insert(1, channel(1, "defmt", ChannelDirection::Up));
+        manager.channels.insert(1000, channel(0, "Terminal", ChannelDirection::Down));
+
+        assert_eq!(manager.find_channel("defmt", ChannelDirection::Up).unwrap(), 1);
+        assert_eq!(manager.find_channel("terminal", ChannelDirection::Down).unwrap(), 0);
+
+        let message = manager.find_channel("defmt", ChannelDirection::Down).unwrap_err().to_string();
+        assert!(message.contains("No down channel named 'defmt'"));
+        assert!(message.contains("0 (Terminal)"));
+    }
+
+    #[test]
+    fn test_channel_mode() {
+        let channel = |flags| ChannelInfo { id: 0, name: "Terminal".to_string(), direction: ChannelDirection::Up, buffer_size: 1024, flags };
+
+        // The flags word as read from the control block
+        assert_eq!(channel_flags(Ok(ChannelMode::NoBlockSkip), "Terminal"), Some(0));
+        assert_eq!(channel_flags(Ok(ChannelMode::BlockIfFull), "Terminal"), Some(2));
+        assert_eq!(channel_flags(Err(probe_rs::rtt::Error::ControlBlockNotFound), "Terminal"), None);
+
+        assert_eq!(channel(channel_flags(Ok(ChannelMode::NoBlockSkip), "Terminal")).mode_name(), "non-blocking, skip");
+        assert_eq!(channel(channel_flags(Ok(ChannelMode::NoBlockTrim), "Terminal")).mode_name(), "non-blocking, trim");
+        assert_eq!(channel(channel_flags(Ok(ChannelMode::BlockIfFull), "Terminal")).mode_name(), "blocking");
+        assert_eq!(channel(Some(3)).mode_name(), "invalid");
+        // Only the low two bits hold the mode
+        assert_eq!(channel(Some(0x100 | 2)).mode_name(), "blocking");
+        assert_eq!(channel(None).mode_name(), "unknown");
+    }
+
+    #[test]
+    fn test_info_only_when_attached() {
+        let mut manager = RttManager::new();
+        manager.up_channel_count = 3;
+        manager.down_channel_count = 1;
+        assert!(manager.info().is_none());
+
+        manager.attached = true;
+        let info = manager.info().unwrap();
+        assert_eq!((info.up_channels, info.down_channels), (3, 1));
+    }
+}
@@ -5,7 +5,11 @@
 
 pub mod manager;
 pub mod elf_parser;
+pub mod frames;
+pub mod lines;
 
 // Export RTT components
-pub use manager::{RttManager, ChannelInfo, ChannelDirection};
+pub use manager::{RttManager, RttInfo, ChannelInfo, ChannelDirection};
+pub use frames::{FrameBuffer, FrameHeader, FrameRead};
+pub use lines::LineBuffer;
 pub use elf_parser::{get_rtt_symbol_from_elf, get_elf_debug_info, ElfDebugInfo, SymbolInfo};
\ No newline at end of file
@@ -0,0 +1,125 @@
+//! Address symbolization through DWARF debug info, including inlined functions
+
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::error::{DebugError, Result};
+use super::SourceLocation;
+
+type Reader = gimli::EndianArcSlice<gimli::RunTimeEndian>;
+
+/// One function of the inline chain at an address
+#[derive(Debug, Clone)]
+pub struct InlineFrame {
+    /// Demangled function name, if DWARF records one
+    pub function: Option<String>,
+    /// Source location: the address itself for the innermost frame, the call site of the inlined callee otherwise
+    pub location: Option<SourceLocation>,
+}
+
+/// DWARF debug info of an ELF file for resolving addresses to functions and lines
+pub struct InlineResolver {
+    /// addr2line parses units lazily and is not `Sync`, so lookups are serialized
+    context: std::sync::Mutex<addr2line::Context<Reader>>,
+}
+
+impl std::fmt::Debug for InlineResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InlineResolver").finish_non_exhaustive()
+    }
+}
+
+impl InlineResolver {
+    /// Load the DWARF info of an ELF file
+    pub fn load(elf_path: &Path) -> Result<Self> {
+        debug!("Loading DWARF debug info from {}", elf_path.display());
+
+        let elf_data = std::fs::read(elf_path).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to read ELF file {}: {}", elf_path.display(), e))
+        })?;
+
+        let resolver = Self::parse(&elf_data).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to parse DWARF info in {}: {}", elf_path.display(), e))
+        })?;
+
+        info!("Loaded DWARF debug info from {}", elf_path.display());
+        Ok(resolver)
+    }
+
+    /// Parse the DWARF info from raw ELF bytes
+    pub fn parse(elf_data: &[u8]) -> std::result::Result<Self, String> {
+        let elf = goblin::elf::Elf::parse(elf_data).map_err(|e| e.to_string())?;
+        let endian = if elf.little_endian { gimli::RunTimeEndian::Little } else { gimli::RunTimeEndian::Big };
+
+        let load_section = |id: gimli::SectionId| -> std::result::Result<Reader, gimli::Error> {
+            let data = elf
+                .section_headers
+                .iter()
+                .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(id.name()))
+                .filter(|sh| sh.sh_type != goblin::elf::section_header::SHT_NOBITS)
+                .and_then(|sh| elf_data.get(sh.sh_offset as usize..(sh.sh_offset + sh.sh_size) as usize))
+                .unwrap_or(&[]);
+            Ok(gimli::EndianArcSlice::new(Arc::from(data), endian))
+        };
+
+        let dwarf = gimli::Dwarf::load(load_section).map_err(|e| e.to_string())?;
+        let context = addr2line::Context::from_dwarf(dwarf).map_err(|e| e.to_string())?;
+        Ok(Self { context: std::sync::Mutex::new(context) })
+    }
+
+    /// Functions at `address`, innermost inlined function first and the
+    /// out-of-line function containing them last. Empty when DWARF does not
+    /// cover the address.
+    pub fn frames(&self, address: u64) -> Vec<InlineFrame> {
+        let context = self.context.lock().unwrap_or_else(|e| e.into_inner());
+        let mut frames = Vec::new();
+        let Ok(mut iter) = context.find_frames(address).skip_all_loads() else {
+            return frames;
+        };
+
+        while let Ok(Some(frame)) = iter.next() {
+            let function = frame.function
+                .as_ref()
+                .and_then(|name| name.demangle().ok())
+                .map(|name| name.into_owned());
+            let location = frame.location.and_then(|location| {
+                Some(SourceLocation {
+                    file: location.file?.to_string(),
+                    line: u64::from(location.line?),
+                    column: location.column.filter(|&column| column != 0).map(u64::from),
+                })
+            });
+            frames.push(InlineFrame { function, location });
+        }
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ELF32 header of an ARM executable with no sections
+    fn empty_elf() -> Vec<u8> {
+        let mut elf = vec![0x7F, b'E', b'L', b'F', 1, 1, 1];
+        elf.resize(16, 0);
+        for half in [2u16, 40] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        for word in [1u32, 0, 0, 0, 0] {
+            elf.extend_from_slice(&word.to_le_bytes());
+        }
+        for half in [52u16, 32, 0, 40, 0, 0] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        elf
+    }
+
+    #[test]
+    fn test_frames_without_dwarf() {
+        let resolver = InlineResolver::parse(&empty_elf()).unwrap();
+        assert!(resolver.frames(0x0800_0100).is_empty());
+        assert!(InlineResolver::parse(b"not an elf").is_err());
+    }
+}
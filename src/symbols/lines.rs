@@ -0,0 +1,286 @@
+//! DWARF line table parsing for address <-> source line mapping
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::error::{DebugError, Result};
+
+/// A resolved source location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u64,
+    pub column: Option<u64>,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}:{}", self.file, self.line, column),
+            None => write!(f, "{}:{}", self.file, self.line),
+        }
+    }
+}
+
+/// One row of the flattened line program
+#[derive(Debug, Clone)]
+struct LineRow {
+    address: u64,
+    file: u32,
+    line: u64,
+    column: u64,
+    /// Recommended breakpoint location for the line
+    is_stmt: bool,
+    /// Marks the first address past the end of a sequence
+    end_sequence: bool,
+}
+
+/// Flattened DWARF line table of an ELF file, sorted by address
+#[derive(Debug, Default)]
+pub struct LineTable {
+    files: Vec<String>,
+    rows: Vec<LineRow>,
+}
+
+type Reader<'a> = gimli::EndianSlice<'a, gimli::RunTimeEndian>;
+
+impl LineTable {
+    /// Load the line table from the `.debug_line` info of an ELF file
+    pub fn load(elf_path: &Path) -> Result<Self> {
+        debug!("Loading DWARF line table from {}", elf_path.display());
+
+        let elf_data = std::fs::read(elf_path).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to read ELF file {}: {}", elf_path.display(), e))
+        })?;
+
+        let table = Self::parse(&elf_data).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to parse DWARF line info in {}: {}", elf_path.display(), e))
+        })?;
+
+        info!("Loaded {} line table rows from {}", table.rows.len(), elf_path.display());
+        Ok(table)
+    }
+
+    /// Parse the line table from raw ELF bytes
+    pub fn parse(elf_data: &[u8]) -> std::result::Result<Self, String> {
+        let elf = goblin::elf::Elf::parse(elf_data).map_err(|e| e.to_string())?;
+        let endian = if elf.little_endian { gimli::RunTimeEndian::Little } else { gimli::RunTimeEndian::Big };
+
+        let load_section = |id: gimli::SectionId| -> std::result::Result<Reader<'_>, gimli::Error> {
+            let data = elf
+                .section_headers
+                .iter()
+                .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(id.name()))
+                .filter(|sh| sh.sh_type != goblin::elf::section_header::SHT_NOBITS)
+                .and_then(|sh| elf_data.get(sh.sh_offset as usize..(sh.sh_offset + sh.sh_size) as usize))
+                .unwrap_or(&[]);
+            Ok(gimli::EndianSlice::new(data, endian))
+        };
+
+        let dwarf = gimli::Dwarf::load(load_section).map_err(|e| e.to_string())?;
+
+        let mut table = LineTable::default();
+        let mut file_indices: HashMap<String, u32> = HashMap::new();
+
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().map_err(|e| e.to_string())? {
+            let unit = dwarf.unit(header).map_err(|e| e.to_string())?;
+            let Some(program) = unit.line_program.clone() else { continue };
+
+            let comp_dir = unit.comp_dir.map(|dir| PathBuf::from(dir.to_string_lossy().into_owned()));
+
+            let mut rows = program.rows();
+            while let Some((header, row)) = rows.next_row().map_err(|e| e.to_string())? {
+                if row.end_sequence() {
+                    table.rows.push(LineRow {
+                        address: row.address(),
+                        file: 0,
+                        line: 0,
+                        column: 0,
+                        is_stmt: false,
+                        end_sequence: true,
+                    });
+                    continue;
+                }
+
+                let Some(line) = row.line() else { continue };
+                let Some(file) = row.file(header) else { continue };
+
+                let mut path = comp_dir.clone().unwrap_or_default();
+                if let Some(dir) = file.directory(header) {
+                    if let Ok(dir) = dwarf.attr_string(&unit, dir) {
+                        path.push(dir.to_string_lossy().as_ref() as &str);
+                    }
+                }
+                if let Ok(name) = dwarf.attr_string(&unit, file.path_name()) {
+                    path.push(name.to_string_lossy().as_ref() as &str);
+                }
+                let path = path.display().to_string();
+
+                let next_index = file_indices.len() as u32;
+                let file_index = *file_indices.entry(path.clone()).or_insert_with(|| {
+                    table.files.push(path);
+                    next_index
+                });
+
+                table.rows.push(LineRow {
+                    address: row.address(),
+                    file: file_index,
+                    line: line.get(),
+                    column: match row.column() {
+                        gimli::ColumnType::LeftEdge => 0,
+                        gimli::ColumnType::Column(column) => column.get(),
+                    },
+                    is_stmt: row.is_stmt(),
+                    end_sequence: false,
+                });
+            }
+        }
+
+        // Stable sort keeps sequence terminators ahead of rows starting a new sequence
+        table.rows.sort_by_key(|row| (row.address, !row.end_sequence));
+        Ok(table)
+    }
+
+    /// Whether the table contains any line information
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Map an address to the source location of the line program row covering it
+    pub fn find_location(&self, address: u64) -> Option<SourceLocation> {
+        let index = self.rows.partition_point(|row| row.address <= address).checked_sub(1)?;
+        let row = &self.rows[index];
+        if row.end_sequence {
+            return None;
+        }
+        Some(self.location_of(row))
+    }
+
+    /// Find the address to break at for `line` of `file`.
+    ///
+    /// `file` matches any compiled file whose path ends with it, so
+    /// "src/main.rs" and "main.rs" both work. The first statement address of
+    /// the first line at or after `line` that has code is chosen; the returned
+    /// location carries the line actually used. Fails with the nearest lines
+    /// that have code when nothing follows `line`.
+    pub fn find_line_address(&self, file: &str, line: u64) -> Result<(u64, SourceLocation)> {
+        let requested = file.replace('\\', "/");
+        let matches: Vec<usize> = self.files
+            .iter()
+            .enumerate()
+            .filter(|(_, path)| Path::new(&path.replace('\\', "/")).ends_with(&requested))
+            .map(|(index, _)| index)
+            .collect();
+
+        let file_index = match matches.as_slice() {
+            [] => {
+                return Err(DebugError::InvalidConfig(format!("No line info for source file '{}' in the ELF", file)));
+            }
+            [index] => *index as u32,
+            _ => {
+                let candidates: Vec<&str> = matches.iter().take(5).map(|&i| self.files[i].as_str()).collect();
+                return Err(DebugError::InvalidConfig(format!(
+                    "Source file '{}' is ambiguous; matches {}. Use a longer path",
+                    file, candidates.join(", ")
+                )));
+            }
+        };
+
+        let statements: Vec<&LineRow> = self.rows
+            .iter()
+            .filter(|row| !row.end_sequence && row.is_stmt && row.file == file_index && row.line > 0)
+            .collect();
+
+        if let Some(row) = statements.iter().filter(|row| row.line >= line).min_by_key(|row| (row.line, row.address)) {
+            return Ok((row.address, self.location_of(row)));
+        }
+
+        let mut code_lines: Vec<u64> = statements.iter().map(|row| row.line).collect();
+        code_lines.sort_unstable();
+        code_lines.dedup();
+        code_lines.sort_by_key(|code_line| code_line.abs_diff(line));
+        code_lines.truncate(5);
+        code_lines.sort_unstable();
+        Err(DebugError::InvalidConfig(format!(
+            "No code at or after {}:{}; nearest lines with code: {}",
+            self.files[file_index as usize],
+            line,
+            code_lines.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ")
+        )))
+    }
+
+    fn location_of(&self, row: &LineRow) -> SourceLocation {
+        SourceLocation {
+            file: self.files[row.file as usize].clone(),
+            line: row.line,
+            column: (row.column > 0).then_some(row.column),
+        }
+    }
+}
+
+/// Read `context` lines around `line` (1-based) from a source file on the host
+pub fn read_source_context(file: &str, line: u64, context: usize) -> Option<Vec<(u64, String)>> {
+    let content = std::fs::read_to_string(file).ok()?;
+    let first = line.saturating_sub(context as u64).max(1);
+    let last = line + context as u64;
+    Some(
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, text)| (i as u64 + 1, text.to_string()))
+            .filter(|(number, _)| *number >= first && *number <= last)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(address: u64, line: u64, end_sequence: bool) -> LineRow {
+        LineRow { address, file: 0, line, column: 0, is_stmt: !end_sequence, end_sequence }
+    }
+
+    #[test]
+    fn test_find_location() {
+        let table = LineTable {
+            files: vec!["src/main.rs".to_string()],
+            rows: vec![row(0x100, 10, false), row(0x108, 11, false), row(0x110, 0, true)],
+        };
+
+        assert_eq!(table.find_location(0x104).unwrap().line, 10);
+        assert_eq!(table.find_location(0x10C).unwrap().to_string(), "src/main.rs:11");
+        assert!(table.find_location(0x110).is_none());
+        assert!(table.find_location(0x0FF).is_none());
+    }
+
+    #[test]
+    fn test_find_line_address() {
+        let mut prologue = row(0x100, 42, false);
+        prologue.is_stmt = false;
+        let table = LineTable {
+            files: vec!["/work/app/src/main.rs".to_string(), "/work/app/src/lib.rs".to_string()],
+            rows: vec![
+                prologue,
+                row(0x104, 42, false),
+                row(0x10C, 40, false),
+                row(0x110, 45, false),
+                row(0x114, 0, true),
+            ],
+        };
+
+        let (address, location) = table.find_line_address("src/main.rs", 42).unwrap();
+        assert_eq!((address, location.line), (0x104, 42));
+
+        // A line without code moves to the next line that has some
+        let (address, location) = table.find_line_address("main.rs", 43).unwrap();
+        assert_eq!((address, location.line), (0x110, 45));
+
+        let err = table.find_line_address("src/main.rs", 50).unwrap_err().to_string();
+        assert!(err.contains("40, 42, 45"), "{}", err);
+        assert!(table.find_line_address("src/other.rs", 1).is_err());
+        assert!(table.find_line_address("ain.rs", 42).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+//! Firmware symbol information
+//!
+//! Loads the ELF symbol table so tools can accept symbol names in place of raw
+//! addresses and annotate addresses with the enclosing function, and the DWARF
+//! line table to map addresses to source lines, following inlined functions.
+//! DWARF variable info lets statics be read and written by name with their types.
+
+pub mod inline;
+pub mod lines;
+pub mod source;
+pub mod table;
+pub mod variables;
+
+pub use inline::{InlineFrame, InlineResolver};
+pub use lines::{LineTable, SourceLocation};
+pub use source::SourceContext;
+pub use table::{SymbolTable, Symbol, SymbolKind};
+pub use variables::{Variable, VariableTable, VariableType};
@@ -0,0 +1,85 @@
+//! Source text around a location, for showing where the core stopped
+
+use std::path::{Path, PathBuf};
+
+use super::SourceLocation;
+
+/// Lines of source around a location
+#[derive(Debug, Clone)]
+pub struct SourceContext {
+    pub location: SourceLocation,
+    /// Host path the source was read from, `None` when the file was not found
+    pub path: Option<PathBuf>,
+    /// Line numbers and text of the lines around `location.line`
+    pub lines: Vec<(u64, String)>,
+}
+
+/// Find the host copy of a source file named in DWARF.
+///
+/// The path is used as is when it exists. Otherwise each search path is
+/// tried with ever shorter trailing parts of the DWARF path, so a file built
+/// as `/ci/build/app/src/main.rs` is found as `<search path>/src/main.rs`.
+pub fn find_source_file(file: &str, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    let original = Path::new(file);
+    if original.is_file() {
+        return Some(original.to_path_buf());
+    }
+
+    let components: Vec<_> = original
+        .components()
+        .filter(|component| matches!(component, std::path::Component::Normal(_)))
+        .collect();
+    for search_path in search_paths {
+        for skip in 0..components.len() {
+            let candidate = components[skip..].iter().fold(search_path.clone(), |path, part| path.join(part));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+/// Read `radius` lines either side of `location`, degrading to the bare
+/// location when the file cannot be found or read
+pub fn source_context(location: &SourceLocation, search_paths: &[PathBuf], radius: u64) -> SourceContext {
+    let path = find_source_file(&location.file, search_paths);
+    let text = path.as_ref().and_then(|path| std::fs::read_to_string(path).ok());
+    let first = location.line.saturating_sub(radius).max(1);
+    let lines = text
+        .map(|text| {
+            text.lines()
+                .enumerate()
+                .map(|(index, line)| (index as u64 + 1, line.to_string()))
+                .skip_while(|(number, _)| *number < first)
+                .take_while(|(number, _)| *number <= location.line + radius)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    SourceContext { location: location.clone(), path, lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_context_search_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let text = (1..=10).map(|n| format!("line {}", n)).collect::<Vec<_>>().join("\n");
+        std::fs::write(dir.path().join("src/main.rs"), text).unwrap();
+
+        // A CI path baked into DWARF resolves through the search path
+        let location = SourceLocation { file: "/ci/build/app/src/main.rs".to_string(), line: 2, column: None };
+        let context = source_context(&location, &[dir.path().to_path_buf()], 2);
+        assert_eq!(context.path, Some(dir.path().join("src/main.rs")));
+        assert_eq!(context.lines.first(), Some(&(1, "line 1".to_string())));
+        assert_eq!(context.lines.last(), Some(&(4, "line 4".to_string())));
+
+        let missing = source_context(&location, &[], 2);
+        assert!(missing.path.is_none());
+        assert!(missing.lines.is_empty());
+    }
+}
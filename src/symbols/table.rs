@@ -0,0 +1,265 @@
+//! ELF symbol table parsing and lookup
+
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::error::{DebugError, Result};
+
+/// Kind of an ELF symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Object,
+    Other,
+}
+
+/// A named symbol from the ELF symbol table
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    /// Raw (possibly mangled) symbol name
+    pub name: String,
+    /// Demangled name without the trailing hash, if the name was mangled
+    pub demangled: Option<String>,
+    /// Symbol address with the Thumb bit cleared for ARM functions
+    pub address: u64,
+    pub size: u64,
+    pub kind: SymbolKind,
+    /// Name of the ELF section the symbol is defined in
+    pub section: Option<String>,
+}
+
+impl Symbol {
+    /// Human readable name, preferring the demangled form
+    pub fn display_name(&self) -> &str {
+        self.demangled.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Parsed ELF symbol table with lookups in both directions
+#[derive(Debug)]
+pub struct SymbolTable {
+    /// Symbols sorted by address
+    symbols: Vec<Symbol>,
+    /// Raw and demangled names to indices into `symbols`
+    by_name: HashMap<String, usize>,
+    /// GNU build ID of the ELF as lowercase hex, if it has one
+    build_id: Option<String>,
+}
+
+impl SymbolTable {
+    /// Load the symbol table of an ELF file
+    pub fn load(elf_path: &Path) -> Result<Self> {
+        debug!("Loading symbol table from {}", elf_path.display());
+
+        let elf_data = std::fs::read(elf_path).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to read ELF file {}: {}", elf_path.display(), e))
+        })?;
+
+        let table = Self::parse(&elf_data).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to parse ELF file {}: {}", elf_path.display(), e))
+        })?;
+
+        info!("Loaded {} symbols from {}", table.symbols.len(), elf_path.display());
+        Ok(table)
+    }
+
+    /// Parse the symbol table from raw ELF bytes
+    pub fn parse(elf_data: &[u8]) -> std::result::Result<Self, goblin::error::Error> {
+        let elf = goblin::elf::Elf::parse(elf_data)?;
+        let is_arm = elf.header.e_machine == goblin::elf::header::EM_ARM;
+
+        let mut symbols = Vec::new();
+        for sym in elf.syms.iter() {
+            let name = match elf.strtab.get_at(sym.st_name) {
+                Some(name) if !name.is_empty() && !name.starts_with('$') => name,
+                _ => continue,
+            };
+
+            let kind = match sym.st_type() {
+                goblin::elf::sym::STT_FUNC => SymbolKind::Function,
+                goblin::elf::sym::STT_OBJECT => SymbolKind::Object,
+                _ => SymbolKind::Other,
+            };
+
+            // ARM Thumb functions carry the interworking bit in their address
+            let address = if is_arm && kind == SymbolKind::Function {
+                sym.st_value & !1
+            } else {
+                sym.st_value
+            };
+
+            let demangled = rustc_demangle::try_demangle(name)
+                .ok()
+                .map(|d| format!("{:#}", d));
+
+            let section = elf.section_headers
+                .get(sym.st_shndx)
+                .and_then(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+
+            symbols.push(Symbol {
+                name: name.to_string(),
+                demangled,
+                address,
+                size: sym.st_size,
+                kind,
+                section,
+            });
+        }
+
+        symbols.sort_by_key(|s| s.address);
+
+        let mut by_name = HashMap::new();
+        for (index, symbol) in symbols.iter().enumerate() {
+            by_name.entry(symbol.name.clone()).or_insert(index);
+            if let Some(demangled) = &symbol.demangled {
+                by_name.entry(demangled.clone()).or_insert(index);
+            }
+        }
+
+        let build_id = elf
+            .iter_note_sections(elf_data, Some(".note.gnu.build-id"))
+            .into_iter()
+            .flatten()
+            .filter_map(|note| note.ok())
+            .find(|note| note.n_type == goblin::elf::note::NT_GNU_BUILD_ID)
+            .map(|note| note.desc.iter().map(|byte| format!("{:02x}", byte)).collect());
+
+        Ok(Self { symbols, by_name, build_id })
+    }
+
+    /// GNU build ID of the ELF as lowercase hex, if it has one
+    pub fn build_id(&self) -> Option<&str> {
+        self.build_id.as_deref()
+    }
+
+    /// Number of symbols in the table
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Whether the table has no symbols
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Look up a symbol by raw or demangled name
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.by_name.get(name).map(|&index| &self.symbols[index])
+    }
+
+    /// Resolve a symbol name to its address
+    pub fn resolve_symbol(&self, name: &str) -> Option<u64> {
+        self.lookup(name).map(|s| s.address)
+    }
+
+    /// Symbols whose names resemble `name`, best match first.
+    ///
+    /// Names containing `name` (ignoring case) rank before names within a
+    /// small edit distance of it.
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<&Symbol> {
+        let wanted = name.to_lowercase();
+        let max_distance = (wanted.len() / 3).max(2);
+
+        let mut scored: Vec<(usize, &Symbol)> = self.symbols
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Function || s.kind == SymbolKind::Object)
+            .filter_map(|symbol| {
+                let candidate = symbol.display_name().to_lowercase();
+                let short = candidate.rsplit("::").next().unwrap_or(&candidate);
+                if candidate.contains(&wanted) {
+                    Some((candidate.len() - wanted.len(), symbol))
+                } else {
+                    let distance = edit_distance(&wanted, short).min(edit_distance(&wanted, &candidate));
+                    (distance <= max_distance).then_some((1000 + distance, symbol))
+                }
+            })
+            .collect();
+
+        scored.sort_by_key(|(score, symbol)| (*score, symbol.display_name().to_string()));
+        let mut suggestions: Vec<&Symbol> = Vec::new();
+        for (_, symbol) in scored {
+            if !suggestions.iter().any(|s| s.display_name() == symbol.display_name()) {
+                suggestions.push(symbol);
+            }
+            if suggestions.len() == limit {
+                break;
+            }
+        }
+        suggestions
+    }
+
+    /// Find the symbol containing `addr`, returning it with the offset into it
+    pub fn address_to_symbol(&self, addr: u64) -> Option<(&Symbol, u64)> {
+        let end = self.symbols.partition_point(|s| s.address <= addr);
+        self.symbols[..end]
+            .iter()
+            .rev()
+            .filter(|s| s.kind != SymbolKind::Other)
+            .find(|s| addr < s.address + s.size.max(1))
+            .map(|s| (s, addr - s.address))
+    }
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(symbols: Vec<Symbol>) -> SymbolTable {
+        let by_name = symbols.iter().enumerate().map(|(i, s)| (s.name.clone(), i)).collect();
+        SymbolTable { symbols, by_name, build_id: None }
+    }
+
+    fn function(name: &str, address: u64, size: u64) -> Symbol {
+        Symbol { name: name.to_string(), demangled: None, address, size, kind: SymbolKind::Function, section: None }
+    }
+
+    #[test]
+    fn test_address_to_symbol() {
+        let table = table(vec![
+            function("Reset", 0x0800_0100, 0x40),
+            function("main", 0x0800_0200, 0x80),
+        ]);
+
+        let (symbol, offset) = table.address_to_symbol(0x0800_0210).unwrap();
+        assert_eq!(symbol.name, "main");
+        assert_eq!(offset, 0x10);
+
+        assert!(table.address_to_symbol(0x0800_0180).is_none());
+        assert!(table.address_to_symbol(0x0800_0000).is_none());
+        assert_eq!(table.resolve_symbol("Reset"), Some(0x0800_0100));
+    }
+
+    #[test]
+    fn test_suggest() {
+        let mut app_main = function("_ZN3app4main17h0123456789abcdefE", 0x0800_0300, 0x20);
+        app_main.demangled = Some("app::main".to_string());
+        let table = table(vec![
+            function("HardFault_Handler", 0x0800_0100, 0x10),
+            function("DefaultHandler", 0x0800_0110, 0x10),
+            app_main,
+        ]);
+
+        let names = |name| table.suggest(name, 3).iter().map(|s| s.display_name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names("mian"), vec!["app::main"]);
+        assert_eq!(names("hardfault"), vec!["HardFault_Handler"]);
+        assert!(names("xyzzy_unrelated").is_empty());
+    }
+}
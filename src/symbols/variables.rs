@@ -0,0 +1,575 @@
+//! Static variables and their DWARF types, for typed access by name
+
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::error::{DebugError, Result};
+
+/// Nesting limit when resolving a type, guarding against cyclic type references
+const MAX_TYPE_DEPTH: usize = 16;
+
+type Reader<'a> = gimli::EndianSlice<'a, gimli::RunTimeEndian>;
+
+/// How the bytes of a base type are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseEncoding {
+    Signed,
+    Unsigned,
+    Float,
+    Bool,
+    Char,
+}
+
+/// DWARF type of a variable, reduced to what is needed to encode and decode values
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableType {
+    Base { name: String, encoding: BaseEncoding, size: u64 },
+    Pointer { name: String, size: u64 },
+    Struct { name: String, size: u64, members: Vec<Member> },
+    Enum { name: String, size: u64, variants: Vec<(String, i64)> },
+    Array { element: Box<VariableType>, count: u64 },
+    /// A type values cannot be encoded for, such as a Rust enum with data or a bitfield
+    Opaque { name: String, size: u64 },
+}
+
+/// A field of a struct or union
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    /// Byte offset from the start of the enclosing struct
+    pub offset: u64,
+    pub ty: VariableType,
+}
+
+impl VariableType {
+    /// Type name as written in the source, e.g. "u32" or "[u8; 16]"
+    pub fn name(&self) -> String {
+        match self {
+            VariableType::Base { name, .. }
+            | VariableType::Pointer { name, .. }
+            | VariableType::Struct { name, .. }
+            | VariableType::Enum { name, .. }
+            | VariableType::Opaque { name, .. } => name.clone(),
+            VariableType::Array { element, count } => format!("[{}; {}]", element.name(), count),
+        }
+    }
+
+    /// Size in bytes
+    pub fn size(&self) -> u64 {
+        match self {
+            VariableType::Base { size, .. }
+            | VariableType::Pointer { size, .. }
+            | VariableType::Struct { size, .. }
+            | VariableType::Enum { size, .. }
+            | VariableType::Opaque { size, .. } => *size,
+            VariableType::Array { element, count } => element.size() * count,
+        }
+    }
+
+    /// Struct member by name
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        match self {
+            VariableType::Struct { members, .. } => members.iter().find(|member| member.name == name),
+            _ => None,
+        }
+    }
+}
+
+/// A static variable with a fixed address
+#[derive(Debug, Clone)]
+pub struct Variable {
+    /// Name qualified with its enclosing namespaces, e.g. "app::COUNTER"
+    pub name: String,
+    pub address: u64,
+    pub ty: VariableType,
+}
+
+/// Bytes to write at an offset into a variable
+pub type Patch = (u64, Vec<u8>);
+
+/// Static variables of an ELF file found through its DWARF info
+#[derive(Debug, Default)]
+pub struct VariableTable {
+    variables: Vec<Variable>,
+    /// Qualified and unqualified names to indices into `variables`
+    by_name: HashMap<String, usize>,
+    /// Named C structs and typedefs declared at compile unit scope
+    types: HashMap<String, VariableType>,
+    little_endian: bool,
+}
+
+impl VariableTable {
+    /// Load the static variables from the DWARF info of an ELF file
+    pub fn load(elf_path: &Path) -> Result<Self> {
+        debug!("Loading DWARF variables from {}", elf_path.display());
+
+        let elf_data = std::fs::read(elf_path).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to read ELF file {}: {}", elf_path.display(), e))
+        })?;
+
+        let table = Self::parse(&elf_data).map_err(|e| {
+            DebugError::InvalidConfig(format!("Failed to parse DWARF variables in {}: {}", elf_path.display(), e))
+        })?;
+
+        info!("Loaded {} static variables from {}", table.variables.len(), elf_path.display());
+        Ok(table)
+    }
+
+    /// Parse the static variables from raw ELF bytes
+    pub fn parse(elf_data: &[u8]) -> std::result::Result<Self, String> {
+        let elf = goblin::elf::Elf::parse(elf_data).map_err(|e| e.to_string())?;
+        let endian = if elf.little_endian { gimli::RunTimeEndian::Little } else { gimli::RunTimeEndian::Big };
+
+        let load_section = |id: gimli::SectionId| -> std::result::Result<Reader<'_>, gimli::Error> {
+            let data = elf
+                .section_headers
+                .iter()
+                .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(id.name()))
+                .filter(|sh| sh.sh_type != goblin::elf::section_header::SHT_NOBITS)
+                .and_then(|sh| elf_data.get(sh.sh_offset as usize..(sh.sh_offset + sh.sh_size) as usize))
+                .unwrap_or(&[]);
+            Ok(gimli::EndianSlice::new(data, endian))
+        };
+
+        let dwarf = gimli::Dwarf::load(load_section).map_err(|e| e.to_string())?;
+        let mut table = VariableTable { little_endian: elf.little_endian, ..Default::default() };
+
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().map_err(|e| e.to_string())? {
+            let unit = dwarf.unit(header).map_err(|e| e.to_string())?;
+            table.parse_unit(&dwarf, &unit).map_err(|e| e.to_string())?;
+        }
+        Ok(table)
+    }
+
+    fn parse_unit(&mut self, dwarf: &gimli::Dwarf<Reader<'_>>, unit: &gimli::Unit<Reader<'_>>) -> gimli::Result<()> {
+        // Namespace names by DIE depth, used to qualify variable names
+        let mut scopes: Vec<Option<String>> = Vec::new();
+        let mut depth: isize = 0;
+        let mut entries = unit.entries();
+        while let Some((delta, entry)) = entries.next_dfs()? {
+            depth += delta;
+            scopes.truncate(depth.max(0) as usize);
+            let name = entry_name(dwarf, unit, entry);
+
+            if entry.tag() == gimli::DW_TAG_variable {
+                if let Some(address) = static_address(unit, entry)? {
+                    // A definition outside its declaration carries the name on the declaration
+                    let name = match name.clone() {
+                        Some(name) => Some(name),
+                        None => match entry.attr_value(gimli::DW_AT_specification)? {
+                            Some(gimli::AttributeValue::UnitRef(offset)) => entry_name(dwarf, unit, &unit.entry(offset)?),
+                            _ => None,
+                        },
+                    };
+                    if let Some(name) = name {
+                        let ty = match entry.attr_value(gimli::DW_AT_type)? {
+                            Some(gimli::AttributeValue::UnitRef(offset)) => resolve_type(dwarf, unit, offset, 0)?,
+                            _ => VariableType::Opaque { name: "<unknown>".to_string(), size: 0 },
+                        };
+                        let qualified = scopes.iter().flatten().cloned().chain(std::iter::once(name.clone())).collect::<Vec<_>>().join("::");
+                        self.insert(Variable { name: qualified, address, ty }, &name);
+                    }
+                }
+            }
+
+            // Rust types live in namespaces, so only C types are picked up here
+            if depth == 1 && matches!(entry.tag(), gimli::DW_TAG_structure_type | gimli::DW_TAG_typedef) {
+                if let Some(type_name) = name.as_ref().filter(|type_name| !self.types.contains_key(*type_name)) {
+                    if entry.attr_value(gimli::DW_AT_declaration)?.is_none() {
+                        if let ty @ VariableType::Struct { .. } = resolve_type(dwarf, unit, entry.offset(), 0)? {
+                            self.types.insert(type_name.clone(), ty);
+                        }
+                    }
+                }
+            }
+
+            scopes.push(if entry.tag() == gimli::DW_TAG_namespace { name } else { None });
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, variable: Variable, short_name: &str) {
+        let index = self.variables.len();
+        self.by_name.entry(variable.name.clone()).or_insert(index);
+        self.by_name.entry(short_name.to_string()).or_insert(index);
+        self.variables.push(variable);
+    }
+
+    /// Look up a static by qualified ("app::COUNTER") or plain ("COUNTER") name
+    pub fn lookup(&self, name: &str) -> Option<&Variable> {
+        self.by_name.get(name).map(|&index| &self.variables[index])
+    }
+
+    /// Look up a C struct type by its tag or typedef name, e.g. "TCB_t"
+    pub fn lookup_type(&self, name: &str) -> Option<&VariableType> {
+        self.types.get(name)
+    }
+
+    /// Number of static variables in the table
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+
+    /// Whether the table has no variables
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Byte order of the ELF the table was parsed from
+    pub fn little_endian(&self) -> bool {
+        self.little_endian
+    }
+}
+
+/// Name attribute of a DIE
+fn entry_name(dwarf: &gimli::Dwarf<Reader<'_>>, unit: &gimli::Unit<Reader<'_>>, entry: &gimli::DebuggingInformationEntry<Reader<'_>>) -> Option<String> {
+    let value = entry.attr_value(gimli::DW_AT_name).ok()??;
+    dwarf.attr_string(unit, value).ok().map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Address of a variable whose location is a plain `DW_OP_addr`
+fn static_address(unit: &gimli::Unit<Reader<'_>>, entry: &gimli::DebuggingInformationEntry<Reader<'_>>) -> gimli::Result<Option<u64>> {
+    let Some(gimli::AttributeValue::Exprloc(expression)) = entry.attr_value(gimli::DW_AT_location)? else {
+        return Ok(None);
+    };
+    let mut operations = expression.operations(unit.encoding());
+    match operations.next()? {
+        // Address 0 marks a variable the linker discarded
+        Some(gimli::Operation::Address { address }) if address != 0 && operations.next()?.is_none() => Ok(Some(address)),
+        _ => Ok(None),
+    }
+}
+
+fn udata(entry: &gimli::DebuggingInformationEntry<Reader<'_>>, attr: gimli::DwAt) -> gimli::Result<Option<u64>> {
+    Ok(entry.attr_value(attr)?.and_then(|value| value.udata_value()))
+}
+
+/// Byte offset of a struct member, in either the constant or the expression form
+fn member_offset(unit: &gimli::Unit<Reader<'_>>, entry: &gimli::DebuggingInformationEntry<Reader<'_>>) -> gimli::Result<u64> {
+    match entry.attr_value(gimli::DW_AT_data_member_location)? {
+        Some(gimli::AttributeValue::Exprloc(expression)) => {
+            match expression.operations(unit.encoding()).next()? {
+                Some(gimli::Operation::PlusConstant { value }) => Ok(value),
+                _ => Ok(0),
+            }
+        }
+        Some(value) => Ok(value.udata_value().unwrap_or(0)),
+        None => Ok(0),
+    }
+}
+
+fn resolve_type(dwarf: &gimli::Dwarf<Reader<'_>>, unit: &gimli::Unit<Reader<'_>>, offset: gimli::UnitOffset, depth: usize) -> gimli::Result<VariableType> {
+    let entry = unit.entry(offset)?;
+    let name = entry_name(dwarf, unit, &entry);
+    let size = udata(&entry, gimli::DW_AT_byte_size)?;
+    let opaque = |name: Option<String>, size: Option<u64>| VariableType::Opaque {
+        name: name.unwrap_or_else(|| "<unknown>".to_string()),
+        size: size.unwrap_or(0),
+    };
+    if depth > MAX_TYPE_DEPTH {
+        return Ok(opaque(name, size));
+    }
+
+    let target = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(gimli::AttributeValue::UnitRef(target)) => Some(target),
+        _ => None,
+    };
+
+    match entry.tag() {
+        gimli::DW_TAG_base_type => {
+            let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+                Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_signed)) => BaseEncoding::Signed,
+                Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_float)) => BaseEncoding::Float,
+                Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_boolean)) => BaseEncoding::Bool,
+                Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_signed_char))
+                | Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_unsigned_char))
+                | Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_UTF)) => BaseEncoding::Char,
+                _ => BaseEncoding::Unsigned,
+            };
+            Ok(VariableType::Base { name: name.unwrap_or_default(), encoding, size: size.unwrap_or(0) })
+        }
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type | gimli::DW_TAG_rvalue_reference_type => Ok(VariableType::Pointer {
+            name: name.unwrap_or_else(|| "pointer".to_string()),
+            size: size.unwrap_or(u64::from(unit.encoding().address_size)),
+        }),
+        gimli::DW_TAG_typedef
+        | gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_atomic_type
+        | gimli::DW_TAG_restrict_type => match target {
+            Some(target) => resolve_type(dwarf, unit, target, depth + 1),
+            None => Ok(opaque(name, size)),
+        },
+        gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
+            let mut members = Vec::new();
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                match child.tag() {
+                    gimli::DW_TAG_member => {
+                        let member_name = entry_name(dwarf, unit, child).unwrap_or_default();
+                        let ty = match child.attr_value(gimli::DW_AT_type)? {
+                            _ if child.attr_value(gimli::DW_AT_bit_size)?.is_some() => opaque(Some("bitfield".to_string()), None),
+                            Some(gimli::AttributeValue::UnitRef(member_type)) => resolve_type(dwarf, unit, member_type, depth + 1)?,
+                            _ => opaque(None, None),
+                        };
+                        members.push(Member { name: member_name, offset: member_offset(unit, child)?, ty });
+                    }
+                    // Rust enums with data describe their variants in a variant part
+                    gimli::DW_TAG_variant_part => return Ok(opaque(name, size)),
+                    _ => {}
+                }
+            }
+            Ok(VariableType::Struct { name: name.unwrap_or_default(), size: size.unwrap_or(0), members })
+        }
+        gimli::DW_TAG_enumeration_type => {
+            let mut variants = Vec::new();
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_enumerator {
+                    continue;
+                }
+                let value = match child.attr_value(gimli::DW_AT_const_value)? {
+                    Some(gimli::AttributeValue::Sdata(value)) => Some(value),
+                    Some(value) => value.udata_value().map(|value| value as i64),
+                    None => None,
+                };
+                if let (Some(variant), Some(value)) = (entry_name(dwarf, unit, child), value) {
+                    variants.push((variant, value));
+                }
+            }
+            let size = match (size, target) {
+                (Some(size), _) => size,
+                (None, Some(target)) => resolve_type(dwarf, unit, target, depth + 1)?.size(),
+                (None, None) => 0,
+            };
+            Ok(VariableType::Enum { name: name.unwrap_or_default(), size, variants })
+        }
+        gimli::DW_TAG_array_type => {
+            let Some(target) = target else { return Ok(opaque(name, size)) };
+            let element = resolve_type(dwarf, unit, target, depth + 1)?;
+            // Multi-dimensional arrays are flattened into one element count
+            let mut count: u64 = 1;
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_subrange_type {
+                    continue;
+                }
+                let length = match (udata(child, gimli::DW_AT_count)?, udata(child, gimli::DW_AT_upper_bound)?) {
+                    (Some(length), _) => length,
+                    (None, Some(upper_bound)) => upper_bound + 1,
+                    (None, None) => 0,
+                };
+                count *= length;
+            }
+            Ok(VariableType::Array { element: Box::new(element), count })
+        }
+        _ => Ok(opaque(name, size)),
+    }
+}
+
+/// Encode a JSON value for a variable of type `ty` as byte patches relative to its start.
+///
+/// Objects only patch the struct members they name, so a partial object
+/// leaves the other fields untouched. Arrays patch a prefix of the elements.
+pub fn encode(ty: &VariableType, value: &serde_json::Value, little_endian: bool) -> Result<Vec<Patch>> {
+    let mut patches = Vec::new();
+    encode_at(ty, value, 0, little_endian, &mut patches)?;
+    Ok(patches)
+}
+
+fn encode_at(ty: &VariableType, value: &serde_json::Value, offset: u64, little_endian: bool, patches: &mut Vec<Patch>) -> Result<()> {
+    let invalid = |expected: &str| DebugError::InvalidConfig(format!("Expected {} for type {}, got {}", expected, ty.name(), value));
+
+    match ty {
+        VariableType::Base { encoding, size, .. } => {
+            let bytes = match encoding {
+                BaseEncoding::Float => {
+                    let number = value.as_f64().ok_or_else(|| invalid("a number"))?;
+                    match size {
+                        4 => (number as f32).to_le_bytes().to_vec(),
+                        8 => number.to_le_bytes().to_vec(),
+                        _ => return Err(invalid("a 4 or 8 byte float")),
+                    }
+                }
+                BaseEncoding::Bool => {
+                    let flag = match value {
+                        serde_json::Value::Bool(flag) => *flag,
+                        serde_json::Value::Number(number) if number.as_u64() == Some(0) => false,
+                        serde_json::Value::Number(number) if number.as_u64() == Some(1) => true,
+                        _ => return Err(invalid("true or false")),
+                    };
+                    integer_bytes(u64::from(flag), *size)
+                }
+                BaseEncoding::Char => {
+                    let code = match value {
+                        serde_json::Value::String(text) if text.chars().count() == 1 => text.chars().next().map(u64::from),
+                        _ => json_integer(value).map(|code| code as u64),
+                    }
+                    .ok_or_else(|| invalid("a single character or a character code"))?;
+                    check_unsigned(code, *size).map_err(|_| invalid("a character that fits the type"))?;
+                    integer_bytes(code, *size)
+                }
+                BaseEncoding::Signed => {
+                    let number = json_integer(value).ok_or_else(|| invalid("an integer"))?;
+                    let bits = size * 8;
+                    if bits < 128 && (number < -(1i128 << (bits - 1)) || number >= (1i128 << (bits - 1))) {
+                        return Err(invalid(&format!("an integer within {} bits", bits)));
+                    }
+                    integer_bytes(number as u64, *size)
+                }
+                BaseEncoding::Unsigned => {
+                    let number = json_integer(value).filter(|number| *number >= 0).ok_or_else(|| invalid("a non-negative integer"))?;
+                    check_unsigned(number as u64, *size).map_err(|_| invalid(&format!("an integer within {} bits", size * 8)))?;
+                    integer_bytes(number as u64, *size)
+                }
+            };
+            patches.push((offset, order(bytes, little_endian)));
+        }
+        VariableType::Pointer { size, .. } => {
+            let number = json_integer(value).filter(|number| *number >= 0).ok_or_else(|| invalid("an address"))?;
+            check_unsigned(number as u64, *size).map_err(|_| invalid("an address that fits the pointer"))?;
+            patches.push((offset, order(integer_bytes(number as u64, *size), little_endian)));
+        }
+        VariableType::Enum { variants, size, .. } => {
+            let number = match value {
+                serde_json::Value::String(variant) => variants
+                    .iter()
+                    .find(|(name, _)| name == variant)
+                    .map(|(_, number)| *number)
+                    .ok_or_else(|| DebugError::InvalidConfig(format!(
+                        "{} has no variant '{}'; variants: {}",
+                        ty.name(), variant,
+                        variants.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+                    )))?,
+                _ => json_integer(value).ok_or_else(|| invalid("a variant name"))? as i64,
+            };
+            patches.push((offset, order(integer_bytes(number as u64, *size), little_endian)));
+        }
+        VariableType::Struct { members, .. } => {
+            let fields = value.as_object().ok_or_else(|| invalid("an object"))?;
+            for (field, field_value) in fields {
+                let member = members.iter().find(|member| &member.name == field).ok_or_else(|| {
+                    DebugError::InvalidConfig(format!(
+                        "{} has no field '{}'; fields: {}",
+                        ty.name(), field,
+                        members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>().join(", ")
+                    ))
+                })?;
+                encode_at(&member.ty, field_value, offset + member.offset, little_endian, patches)?;
+            }
+        }
+        VariableType::Array { element, count } => {
+            let items = value.as_array().ok_or_else(|| invalid("an array"))?;
+            if items.len() as u64 > *count {
+                return Err(DebugError::InvalidConfig(format!(
+                    "{} holds {} elements, got {}", ty.name(), count, items.len()
+                )));
+            }
+            for (index, item) in items.iter().enumerate() {
+                encode_at(element, item, offset + index as u64 * element.size(), little_endian, patches)?;
+            }
+        }
+        VariableType::Opaque { name, .. } => {
+            return Err(DebugError::InvalidConfig(format!("Writing values of type {} is not supported", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Integer from a JSON number or a "0x" hex / decimal string
+fn json_integer(value: &serde_json::Value) -> Option<i128> {
+    match value {
+        serde_json::Value::Number(number) => number.as_i64().map(i128::from).or_else(|| number.as_u64().map(i128::from)),
+        serde_json::Value::String(text) => match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).ok().map(i128::from),
+            None => text.parse::<i128>().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn check_unsigned(value: u64, size: u64) -> std::result::Result<(), ()> {
+    if size < 8 && value >> (size * 8) != 0 {
+        return Err(());
+    }
+    Ok(())
+}
+
+/// Low `size` bytes of `value`, least significant first
+fn integer_bytes(value: u64, size: u64) -> Vec<u8> {
+    value.to_le_bytes()[..size.min(8) as usize].to_vec()
+}
+
+fn order(mut bytes: Vec<u8>, little_endian: bool) -> Vec<u8> {
+    if !little_endian {
+        bytes.reverse();
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base(name: &str, encoding: BaseEncoding, size: u64) -> VariableType {
+        VariableType::Base { name: name.to_string(), encoding, size }
+    }
+
+    #[test]
+    fn test_encode_scalars() {
+        assert_eq!(encode(&base("u16", BaseEncoding::Unsigned, 2), &json!(0x1234), true).unwrap(), vec![(0, vec![0x34, 0x12])]);
+        assert_eq!(encode(&base("u16", BaseEncoding::Unsigned, 2), &json!("0x1234"), false).unwrap(), vec![(0, vec![0x12, 0x34])]);
+        assert_eq!(encode(&base("i8", BaseEncoding::Signed, 1), &json!(-1), true).unwrap(), vec![(0, vec![0xFF])]);
+        assert_eq!(encode(&base("bool", BaseEncoding::Bool, 1), &json!(true), true).unwrap(), vec![(0, vec![1])]);
+        assert_eq!(encode(&base("f32", BaseEncoding::Float, 4), &json!(1.5), true).unwrap(), vec![(0, 1.5f32.to_le_bytes().to_vec())]);
+
+        assert!(encode(&base("u8", BaseEncoding::Unsigned, 1), &json!(256), true).is_err());
+        assert!(encode(&base("u8", BaseEncoding::Unsigned, 1), &json!(-1), true).is_err());
+        assert!(encode(&base("i8", BaseEncoding::Signed, 1), &json!(128), true).is_err());
+        assert!(encode(&base("bool", BaseEncoding::Bool, 1), &json!("yes"), true).is_err());
+    }
+
+    #[test]
+    fn test_encode_enum_struct_and_array() {
+        let mode = VariableType::Enum {
+            name: "Mode".to_string(),
+            size: 1,
+            variants: vec![("Idle".to_string(), 0), ("Run".to_string(), 2)],
+        };
+        assert_eq!(encode(&mode, &json!("Run"), true).unwrap(), vec![(0, vec![2])]);
+        assert!(encode(&mode, &json!("Sleep"), true).unwrap_err().to_string().contains("Idle, Run"));
+
+        let config = VariableType::Struct {
+            name: "Config".to_string(),
+            size: 12,
+            members: vec![
+                Member { name: "mode".to_string(), offset: 0, ty: mode },
+                Member { name: "period".to_string(), offset: 4, ty: base("u32", BaseEncoding::Unsigned, 4) },
+                Member {
+                    name: "gains".to_string(),
+                    offset: 8,
+                    ty: VariableType::Array { element: Box::new(base("u16", BaseEncoding::Unsigned, 2)), count: 2 },
+                },
+            ],
+        };
+        assert_eq!(config.size(), 12);
+
+        // A partial object only patches the named fields
+        assert_eq!(encode(&config, &json!({"period": 10}), true).unwrap(), vec![(4, vec![10, 0, 0, 0])]);
+        assert_eq!(
+            encode(&config, &json!({"gains": [1, 2]}), true).unwrap(),
+            vec![(8, vec![1, 0]), (10, vec![2, 0])]
+        );
+        assert!(encode(&config, &json!({"gains": [1, 2, 3]}), true).is_err());
+        assert!(encode(&config, &json!({"missing": 1}), true).unwrap_err().to_string().contains("mode, period, gains"));
+    }
+}
@@ -1,6 +1,6 @@
 //! Complete RMCP 0.3.2 implementation for embedded debugger MCP tools
 //! 
-//! This implementation provides all 18 debugging tools (13 base + 5 RTT) using real probe-rs integration
+//! This implementation provides the debugging, memory, breakpoint, RTT and flash tools using real probe-rs integration
 
 use rmcp::{
     tool, tool_handler, tool_router, ServerHandler,
@@ -17,31 +17,37 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::types::*;
+use crate::config::{Config, DebuggerConfig, MemoryConfig};
+use crate::utils::{align_to_words, format_memory_data, word_width, AttachMode, Endianness, ResetType, ScalarType};
 // Flash types will be used through crate::flash:: prefix
-use crate::rtt::RttManager;
+use crate::error::DebugError;
+use crate::flash::{ProgressPhase, ProgressSink, ProgressUpdate};
+use crate::flash::progress::PROGRESS_INTERVAL;
+use crate::debugger::{get_core, target_selector, BatchOp, BatchOpResult, DebugSession, ProbeSpeed, ReconnectResult, StepResult, AUTO_TARGET};
+use crate::debugger::breakpoints::{parse_breakpoint_type, BreakpointInfo, BreakpointKind};
+use crate::debugger::chips;
+use crate::debugger::cycles;
+use crate::debugger::discovery::{ProbeDiscovery, PROBE_RESET_SETTLE, UNKNOWN_VERSION};
+use crate::debugger::events::TargetEvent;
+use crate::debugger::memory;
+use crate::debugger::registers::{self, decode_xpsr};
+use crate::debugger::vector_catch::{self, VectorCatch};
+use crate::debugger::watchpoints::{self, WatchAccess, WatchpointInfo};
+use crate::rtt::{ChannelDirection, FrameHeader};
 
 // Probe-rs imports
 use probe_rs::probe::list::Lister;
-use probe_rs::{Session, Permissions, CoreStatus, MemoryInterface, RegisterValue};
-
-/// Debug session information
-#[derive(Debug)]
-pub struct DebugSession {
-    pub session_id: String,
-    pub probe_identifier: String,
-    pub target_chip: String,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub session: Arc<tokio::sync::Mutex<Session>>,
-    pub rtt_manager: Arc<tokio::sync::Mutex<RttManager>>,
-}
+use probe_rs::{Permissions, CoreStatus, HaltReason, RegisterValue};
 
-/// Complete embedded debugger tool handler with all 18 tools
+/// Complete embedded debugger tool handler serving every tool of the server
 #[derive(Clone)]
 pub struct EmbeddedDebuggerToolHandler {
     #[allow(dead_code)]
     tool_router: ToolRouter<EmbeddedDebuggerToolHandler>,
     sessions: Arc<RwLock<HashMap<String, Arc<DebugSession>>>>,
     max_sessions: usize,
+    memory_config: MemoryConfig,
+    debugger_config: DebuggerConfig,
 }
 
 impl EmbeddedDebuggerToolHandler {
@@ -50,6 +56,165 @@ impl EmbeddedDebuggerToolHandler {
             tool_router: Self::tool_router(),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             max_sessions,
+            memory_config: MemoryConfig::default(),
+            debugger_config: DebuggerConfig::default(),
+        }
+    }
+
+    /// Source lines around `pc` for a halt response, or an empty string when
+    /// no ELF with line info for it is loaded
+    async fn source_section(&self, session: &DebugSession, pc: Option<u64>) -> String {
+        let search_paths: Vec<std::path::PathBuf> = self.debugger_config.source_search_paths
+            .iter()
+            .map(std::path::PathBuf::from)
+            .collect();
+        let Some(pc) = pc else { return String::new() };
+        let Some(context) = session.source_context(pc, &search_paths, self.debugger_config.source_context_lines).await else {
+            return String::new();
+        };
+
+        let mut section = format!("\n\nSource: {}", context.location);
+        if context.path.is_none() {
+            section.push_str(" (file not found on host; add its directory to debugger.source_search_paths)");
+        }
+        for (number, text) in &context.lines {
+            let marker = if *number == context.location.line { ">" } else { " " };
+            section.push_str(&format!("\n{} {:>5} | {}", marker, number, text));
+        }
+        section
+    }
+
+    /// Session counts, core states, breakpoints and uptime across all sessions.
+    ///
+    /// A session busy with another operation for longer than a short wait is
+    /// counted as busy rather than blocking on it.
+    pub async fn statistics(&self) -> SessionStatistics {
+        let sessions: Vec<Arc<DebugSession>> = self.sessions.read().await.values().cloned().collect();
+        let now = chrono::Utc::now();
+        let mut statistics = SessionStatistics {
+            total_sessions: sessions.len(),
+            max_sessions: self.max_sessions,
+            ..Default::default()
+        };
+
+        for session in sessions {
+            statistics.total_breakpoints += session.breakpoints().await.len();
+            statistics.total_uptime_seconds += (now - session.created_at()).num_seconds();
+
+            let status = match tokio::time::timeout(std::time::Duration::from_millis(250), session.lock_session()).await {
+                Err(_) => {
+                    statistics.busy_sessions += 1;
+                    continue;
+                }
+                Ok(Err(_)) => None,
+                Ok(Ok(mut probe_session)) => probe_session.core(0).ok().and_then(|mut core| core.status().ok()),
+            };
+            count_core_state(&mut statistics, status);
+        }
+        statistics
+    }
+
+    /// Create a handler using the limits from the server configuration.
+    ///
+    /// Must be called from within a Tokio runtime when an idle session timeout is
+    /// configured, as it spawns the session reaper task.
+    pub fn from_config(config: &Config) -> Self {
+        let handler = Self {
+            memory_config: config.memory.clone(),
+            debugger_config: config.debugger.clone(),
+            ..Self::new(config.server.max_sessions)
+        };
+
+        if config.server.session_timeout_seconds > 0 {
+            handler.spawn_session_reaper(std::time::Duration::from_secs(config.server.session_timeout_seconds));
+        }
+
+        handler
+    }
+
+    /// Periodically close sessions that have been idle for longer than `timeout`.
+    ///
+    /// Dropping the last reference to a session drops the probe-rs `Session`,
+    /// which releases the probe so a new client can connect. The task exits once
+    /// the handler is dropped.
+    fn spawn_session_reaper(&self, timeout: std::time::Duration) {
+        let sessions = Arc::downgrade(&self.sessions);
+        let scan_interval = reaper_scan_interval(timeout);
+        let timeout = chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(scan_interval);
+            loop {
+                interval.tick().await;
+                let Some(sessions) = sessions.upgrade() else { break };
+
+                let now = chrono::Utc::now();
+                let mut expired = Vec::new();
+                sessions.write().await.retain(|session_id, session| {
+                    let idle = now - session.last_activity();
+                    if idle > timeout {
+                        warn!(
+                            "Closing idle session {} (probe: {}, target: {}, idle for {}s)",
+                            session_id, session.probe_identifier, session.target_chip, idle.num_seconds()
+                        );
+                        expired.push(session.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                for session in expired {
+                    if let Err(e) = session.clear_all_breakpoints(true).await {
+                        warn!("Failed to clear breakpoints of idle session {}: {}", session.session_id, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Shared body of `enable_breakpoint` and `disable_breakpoint`
+    async fn toggle_breakpoint(&self, session_id: &str, core_index: usize, breakpoint: &str, enabled: bool) -> Result<CallToolResult, McpError> {
+        debug!("{} breakpoint {} for session: {}", if enabled { "Enabling" } else { "Disabling" }, breakpoint, session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(session_id.to_string())));
+                }
+            }
+        };
+
+        let (address, core_index) = resolve_breakpoint(breakpoint, core_index, &session_arc.breakpoints().await)
+            .map_err(invalid_argument)?;
+
+        match session_arc.set_breakpoint_enabled(core_index, address, enabled).await {
+            Ok(info) => {
+                let message = format!(
+                    "🎯 Breakpoint {}\n\n\
+                    Session ID: {}\n\
+                    Breakpoint: #{}\n\
+                    Address: 0x{:08X}{}\n\
+                    Type: {}",
+                    if enabled { "enabled" } else { "disabled" },
+                    session_id, info.id, info.address,
+                    info.symbol.as_ref().map(|symbol| format!(" <{}>", symbol)).unwrap_or_default(),
+                    info.kind
+                );
+
+                info!("Breakpoint #{} {} for session: {}", info.id, if enabled { "enabled" } else { "disabled" }, session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to update breakpoint for session {}: {}", session_id, e);
+                Err(tool_error(format!("Failed to {} breakpoint: {}", if enabled { "enable" } else { "disable" }, e), &e))
+            }
         }
     }
 }
@@ -63,7 +228,7 @@ impl Default for EmbeddedDebuggerToolHandler {
 #[tool_router]
 impl EmbeddedDebuggerToolHandler {
     // =============================================================================
-    // Debugger Management Tools (4 tools)
+    // Debugger Management Tools
     // =============================================================================
 
     #[tool(description = "List all available debug probes (J-Link, ST-Link, DAPLink, etc.)")]
@@ -86,6 +251,10 @@ impl EmbeddedDebuggerToolHandler {
                 }
                 
                 result.push_str(&format!("   Probe Type: {:?}\n", probe.probe_type()));
+                result.push_str(&format!(
+                    "   Firmware: {}\n",
+                    ProbeDiscovery::firmware_version(probe).as_deref().unwrap_or(UNKNOWN_VERSION)
+                ));
                 result.push('\n');
             }
             
@@ -96,16 +265,110 @@ impl EmbeddedDebuggerToolHandler {
         Ok(CallToolResult::success(vec![Content::text(message)]))
     }
 
+    #[tool(description = "List the chip families built into probe-rs with how many chips each contains")]
+    async fn list_supported_chips(&self, Parameters(args): Parameters<ListSupportedChipsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing supported chip families (filter: {:?})", args.filter);
+
+        let families = chips::list_families(args.filter.as_deref());
+        let message = if families.is_empty() {
+            format!(
+                "No chip families match '{}'.\n\nCall 'list_supported_chips' without a filter to see all families.",
+                args.filter.unwrap_or_default()
+            )
+        } else {
+            let mut result = format!(
+                "Found {} chip families ({} chips):\n\n",
+                families.len(),
+                families.iter().map(|family| family.chip_count).sum::<usize>()
+            );
+            for family in &families {
+                result.push_str(&format!("- {} ({} chips)\n", family.name, family.chip_count));
+            }
+            result.push_str("\nUse 'search_chips' to find the exact target_chip name for 'connect'.");
+            result
+        };
+
+        info!("Listed {} chip families", families.len());
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Search the probe-rs chip registry by name and show core types and flash/RAM sizes")]
+    async fn search_chips(&self, Parameters(args): Parameters<SearchChipsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Searching chips matching '{}'", args.query);
+
+        let (chips, total) = chips::search_chips(&args.query, args.max_results);
+        let message = if chips.is_empty() {
+            format!(
+                "No chips match '{}'.\n\nTry a shorter query such as the family prefix (e.g. \"stm32f4\").",
+                args.query
+            )
+        } else {
+            let mut result = format!("Found {} chip(s) matching '{}':\n\n", total, args.query);
+            for chip in &chips {
+                let cores: Vec<String> = chip.cores.iter().map(|core| format!("{:?}", core)).collect();
+                result.push_str(&format!(
+                    "{}{}\n   Family: {}\n   Cores: {}\n   Flash: {} KiB, RAM: {} KiB\n",
+                    chip.name,
+                    if chip.package_variants.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  (also: {})", chip.package_variants.join(", "))
+                    },
+                    chip.family,
+                    cores.join(", "),
+                    chip.flash_bytes / 1024,
+                    chip.ram_bytes / 1024
+                ));
+            }
+            if total > chips.len() {
+                result.push_str(&format!("\n... and {} more; refine the query or raise max_results.", total - chips.len()));
+            }
+            result
+        };
+
+        info!("Chip search '{}' returned {} of {} matches", args.query, chips.len(), total);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Register custom chips from a probe-rs target YAML file so 'connect' accepts their names")]
+    async fn load_target(&self, Parameters(args): Parameters<LoadTargetArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Loading target description from {}", args.file_path);
+
+        match chips::load_target_family(std::path::Path::new(&args.file_path)) {
+            Ok((family, chip_names)) => {
+                let message = format!(
+                    "✅ Target family registered\n\n\
+                    File: {}\n\
+                    Family: {}\n\
+                    Chips ({}): {}\n\n\
+                    Use one of these names as target_chip with 'connect'.",
+                    args.file_path, family, chip_names.len(), chip_names.join(", ")
+                );
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to load target description {}: {}", args.file_path, e);
+                Err(tool_error(format!("❌ Failed to load target description\n\n{}", e), &e))
+            }
+        }
+    }
+
     #[tool(description = "Connect to a debug probe and target chip")]
     async fn connect(&self, Parameters(args): Parameters<ConnectArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Connecting to probe '{}' and target '{}'", args.probe_selector, args.target_chip);
+        let (probe_selector, target_chip_arg) = connect_target(&args, &self.debugger_config)?;
+        let attach_mode = match args.attach_mode.as_deref() {
+            Some(name) => AttachMode::parse(name).map_err(invalid_argument)?,
+            None if args.connect_under_reset || self.debugger_config.connect_under_reset => AttachMode::UnderReset,
+            None => AttachMode::Normal,
+        };
+        debug!("Connecting to probe '{}' and target '{}' ({} attach)", probe_selector, target_chip_arg, attach_mode);
         
         // Check session limit
         {
             let sessions = self.sessions.read().await;
             if sessions.len() >= self.max_sessions {
                 let error_msg = format!("Session limit exceeded. Maximum {} sessions allowed.", self.max_sessions);
-                return Err(McpError::internal_error(error_msg, None));
+                return Err(tool_error(error_msg, &DebugError::SessionLimitExceeded(self.max_sessions)));
             }
         }
         
@@ -113,87 +376,173 @@ impl EmbeddedDebuggerToolHandler {
         let probes = Lister::new().list_all();
         
         if probes.is_empty() {
-            return Err(McpError::internal_error(
-                "❌ No debug probes found\n\nPlease connect a supported probe (J-Link, ST-Link, DAPLink, etc.)".to_string(),
-                None
-            ));
+            let error_msg = "❌ No debug probes found\n\nPlease connect a supported probe (J-Link, ST-Link, DAPLink, etc.)".to_string();
+            return Err(tool_error(error_msg, &DebugError::ProbeNotFound("no probes connected".to_string())));
         }
         
-        let selected_probe = if args.probe_selector.to_lowercase() == "auto" {
+        let selected_probe = if probe_selector.to_lowercase() == "auto" {
             probes.first()
         } else {
-            probes.iter().find(|p| p.identifier.contains(&args.probe_selector))
+            probes.iter().find(|p| p.identifier.contains(&probe_selector))
         };
 
         match selected_probe {
             Some(probe_info) => {
-                info!("Opening probe: {}", probe_info.identifier);
-                match probe_info.open() {
-                    Ok(probe) => {
-                        info!("Attaching to target: {}", args.target_chip);
-                        match probe.attach(&args.target_chip, Permissions::default()) {
-                            Ok(session) => {
-                                let session_id = format!("session_{}", chrono::Utc::now().timestamp_millis());
-                                
-                                let debug_session = DebugSession {
-                                    session_id: session_id.clone(),
-                                    probe_identifier: probe_info.identifier.clone(),
-                                    target_chip: args.target_chip.clone(),
-                                    created_at: chrono::Utc::now(),
-                                    session: Arc::new(tokio::sync::Mutex::new(session)),
-                                    rtt_manager: Arc::new(tokio::sync::Mutex::new(RttManager::new())),
-                                };
-                                
-                                // Store session
-                                {
-                                    let mut sessions = self.sessions.write().await;
-                                    sessions.insert(session_id.clone(), Arc::new(debug_session));
-                                }
-                                
-                                let message = format!(
-                                    "✅ Debug session established!\n\n\
-                                    Session ID: {}\n\
-                                    Probe: {} (VID:PID = {:04X}:{:04X})\n\
-                                    Target: {}\n\
-                                    Connected at: {}\n\n\
-                                    Target connection established and ready for debugging.\n\
-                                    Use this session ID for all debug operations.",
-                                    session_id,
-                                    probe_info.identifier,
-                                    probe_info.vendor_id, probe_info.product_id,
-                                    args.target_chip,
-                                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-                                );
-                                
-                                info!("Created debug session: {}", session_id);
-                                Ok(CallToolResult::success(vec![Content::text(message)]))
-                            }
-                            Err(e) => {
-                                error!("Failed to attach to target '{}': {}", args.target_chip, e);
+                // Open and attach again after transient failures such as USB enumeration timing
+                let attempts = args.connect_retries.unwrap_or(self.debugger_config.retry_count) + 1;
+                let retry_delay = std::time::Duration::from_millis(
+                    args.connect_retry_delay_ms.unwrap_or(self.debugger_config.connect_retry_delay_ms)
+                );
+                let mut attempt = 1;
+                let attached = loop {
+                    match open_and_attach(probe_info, &target_chip_arg, args.speed_khz, attach_mode, args.force).await {
+                        Err(e) if attempt < attempts && e.is_transient() => {
+                            warn!("Connect attempt {}/{} to '{}' failed: {}; retrying in {}ms", attempt, attempts, probe_info.identifier, e, retry_delay.as_millis());
+                            tokio::time::sleep(retry_delay).await;
+                            attempt += 1;
+                        }
+                        outcome => break outcome,
+                    }
+                };
+
+                match attached {
+                    Ok((session, actual_speed_khz, probe_swo)) => {
+                        // With auto-detection the session keeps the detected name so reconnects attach to the same chip
+                        let auto_detected = target_chip_arg.eq_ignore_ascii_case(AUTO_TARGET);
+                        let target_chip = session.target().name.clone();
+                        let session_id = format!("session_{}", chrono::Utc::now().timestamp_millis());
+                        
+                        let speed = ProbeSpeed { requested_khz: args.speed_khz, actual_khz: actual_speed_khz };
+                        let debug_session = DebugSession::new(
+                            session_id.clone(),
+                            probe_info.clone(),
+                            target_chip.clone(),
+                            speed,
+                            probe_swo,
+                            session,
+                        );
+                        
+                        // A running target is left exactly as found
+                        let settled = if attach_mode == AttachMode::AttachRunning {
+                            debug_session.with_core(0, |core| Ok(core.status()?)).await
+                        } else {
+                            debug_session.settle_after_connect(args.halt_after_connect).await
+                        };
+                        let core_state = match settled {
+                            Ok(CoreStatus::Halted(reason)) => format!("Halted ({:?})", reason),
+                            Ok(CoreStatus::Running) => "Running".to_string(),
+                            Ok(other) => format!("{:?}", other),
+                            Err(e) if attach_mode == AttachMode::AttachRunning => {
+                                error!("Attached to '{}' but could not read the running core: {}", target_chip, e);
                                 let error_msg = format!(
-                                    "❌ Failed to attach to target '{}'\n\n\
+                                    "❌ Cannot attach to the running core of '{}'\n\n\
                                     Error: {}\n\n\
-                                    Suggestions:\n\
-                                    - Check target chip name (try: STM32F407VGTx, nRF52840_xxAA)\n\
-                                    - Ensure target is powered and connected\n\
-                                    - Verify SWD/JTAG connections",
-                                    args.target_chip, e
+                                    The probe attached but could not access the core without halting it. \
+                                    Some probes and targets need the core halted for debug access; \
+                                    use attach_mode \"normal\" if stopping the target is acceptable.",
+                                    target_chip, e
                                 );
-                                Err(McpError::internal_error(error_msg, None))
+                                return Err(tool_error(error_msg, &e));
+                            }
+                            Err(e) => {
+                                warn!("Failed to {} core after connect: {}", if args.halt_after_connect { "halt" } else { "resume" }, e);
+                                format!("Unknown ({})", e)
                             }
+                        };
+
+                        let breakpoint_units = match debug_session.breakpoint_capacity(0).await {
+                            Ok(capacity) => capacity.total.to_string(),
+                            Err(e) => {
+                                warn!("Failed to read breakpoint units: {}", e);
+                                "unknown".to_string()
+                            }
+                        };
+
+                        // Store session
+                        {
+                            let mut sessions = self.sessions.write().await;
+                            sessions.insert(session_id.clone(), Arc::new(debug_session));
                         }
+                        
+                        let message = format!(
+                            "✅ Debug session established!\n\n\
+                            Session ID: {}\n\
+                            Probe: {} (VID:PID = {:04X}:{:04X})\n\
+                            Target: {}\n\
+                            Speed: {}\n\
+                            Attach mode: {}\n\
+                            Hardware breakpoints: {}\n\
+                            Core state: {}\n\
+                            Connected at: {}\n\n\
+                            Target connection established and ready for debugging.\n\
+                            Use this session ID for all debug operations.",
+                            session_id,
+                            probe_info.identifier,
+                            probe_info.vendor_id, probe_info.product_id,
+                            if auto_detected { format!("{} (auto-detected)", target_chip) } else { target_chip },
+                            speed,
+                            attach_mode,
+                            breakpoint_units,
+                            core_state,
+                            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+                        );
+                        
+                        info!("Created debug session: {}", session_id);
+                        Ok(CallToolResult::success(vec![Content::text(message)]))
                     }
-                    Err(e) => {
+                    Err(ConnectFailure::Attach(e)) if target_chip_arg.eq_ignore_ascii_case(AUTO_TARGET) => {
+                        error!("Failed to auto-detect target: {}", e);
+                        let error_msg = format!(
+                            "❌ Failed to auto-detect the target chip\n\n\
+                            Error: {}\n\n\
+                            Auto-detection only works for chip families probe-rs can identify from the \
+                            debug port and is not supported by every probe.\n\n\
+                            Specify the chip manually instead:\n\
+                            - Find its name with 'search_chips' (e.g. \"STM32F4\" or \"nRF52840\")\n\
+                            - Pass that name as target_chip to 'connect' (e.g. STM32F407VGTx, nRF52840_xxAA)\n\
+                            - Register chips missing from probe-rs with 'load_target'",
+                            e
+                        );
+                        Err(tool_error(error_msg, &attach_error(&e)))
+                    }
+                    Err(ConnectFailure::Attach(e)) => {
+                        error!("Failed to attach to target '{}': {}", target_chip_arg, e);
+                        let error_msg = format!(
+                            "❌ Failed to attach to target '{}'\n\n\
+                            Error: {}\n\n\
+                            Suggestions:\n\
+                            - Check target chip name with 'search_chips' (e.g. STM32F407VGTx, nRF52840_xxAA)\n\
+                            - Register chips missing from probe-rs with 'load_target'\n\
+                            - Ensure target is powered and connected\n\
+                            - Verify SWD/JTAG connections",
+                            target_chip_arg, e
+                        );
+                        Err(tool_error(error_msg, &attach_error(&e)))
+                    }
+                    Err(ConnectFailure::Reclaim { open, reset }) => {
+                        let error_msg = format!(
+                            "❌ Probe '{}' is in use and could not be reclaimed\n\nOpen error: {}\nRecovery: {}\n\n\
+                            Close the other debugger holding the probe, or unplug and replug it",
+                            probe_info.identifier, open, reset
+                        );
+                        Err(tool_error(error_msg, &reset))
+                    }
+                    Err(ConnectFailure::Open(e)) => {
                         error!("Failed to open probe '{}': {}", probe_info.identifier, e);
+                        let busy_hint = if ProbeDiscovery::is_probe_busy(&e) && !args.force {
+                            "- The probe is in use; if the process holding it crashed, retry with force: true\n"
+                        } else {
+                            ""
+                        };
                         let error_msg = format!(
                             "❌ Failed to open probe '{}'\n\nError: {}\n\n\
-                            Suggestions:\n\
+                            Suggestions:\n{}\
                             - Check probe drivers installation\n\
                             - Verify USB connection\n\
                             - Try disconnecting and reconnecting probe",
-                            probe_info.identifier, e
+                            probe_info.identifier, e, busy_hint
                         );
-                        Err(McpError::internal_error(error_msg, None))
+                        Err(tool_error(error_msg, &DebugError::ProbeError(e.to_string())))
                     }
                 }
             }
@@ -207,17 +556,28 @@ impl EmbeddedDebuggerToolHandler {
                     "❌ Probe '{}' not found\n\n\
                     Available probes:\n{}\n\n\
                     Use 'auto' to connect to first available probe.",
-                    args.probe_selector,
+                    probe_selector,
                     available_probes.join("\n")
                 );
-                Err(McpError::internal_error(error_msg, None))
+                Err(tool_error(error_msg, &DebugError::ProbeNotFound(probe_selector)))
             }
         }
     }
 
-    #[tool(description = "Disconnect from a debug session")]
+    #[tool(description = "Disconnect from a debug session, optionally leaving the target running with all debug state removed")]
     async fn disconnect(&self, Parameters(args): Parameters<DisconnectArgs>) -> Result<CallToolResult, McpError> {
         debug!("Disconnecting session: {}", args.session_id);
+
+        // Detach first so a failure keeps the session around for another try
+        if args.leave_running {
+            let session = self.sessions.read().await.get(&args.session_id).cloned();
+            if let Some(session) = session {
+                if let Err(e) = session.detach_running().await {
+                    error!("Failed to detach session {} with the target running: {}", args.session_id, e);
+                    return Err(tool_error(format!("Failed to leave the target running: {}", e), &e));
+                }
+            }
+        }
         
         // Remove session from storage
         let removed_session = {
@@ -227,17 +587,25 @@ impl EmbeddedDebuggerToolHandler {
         
         match removed_session {
             Some(session) => {
+                session.unsubscribe_events();
+                if !args.leave_running {
+                    if let Err(e) = session.clear_all_breakpoints(true).await {
+                        warn!("Failed to clear breakpoints for session {}: {}", args.session_id, e);
+                    }
+                }
                 let message = format!(
                     "✅ Debug session disconnected successfully\n\n\
                     Session ID: {}\n\
                     Probe: {}\n\
                     Target: {}\n\
+                    Target state: {}\n\
                     Duration: {:.1} minutes\n\n\
                     probe-rs Session resources have been cleaned up.",
                     args.session_id,
                     session.probe_identifier,
                     session.target_chip,
-                    (chrono::Utc::now() - session.created_at).num_seconds() as f64 / 60.0
+                    if args.leave_running { "running (debug state removed)" } else { "unchanged, may be halted" },
+                    (chrono::Utc::now() - session.created_at()).num_seconds() as f64 / 60.0
                 );
                 
                 info!("Disconnected debug session: {}", args.session_id);
@@ -245,7 +613,7 @@ impl EmbeddedDebuggerToolHandler {
             }
             None => {
                 let error_msg = format!("❌ Session '{}' not found\n\nUse 'list_sessions' to see active sessions", args.session_id);
-                Err(McpError::internal_error(error_msg, None))
+                Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())))
             }
         }
     }
@@ -258,21 +626,30 @@ impl EmbeddedDebuggerToolHandler {
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
         
         // Calculate session duration
-        let duration_minutes = (chrono::Utc::now() - session_arc.created_at).num_seconds() as f64 / 60.0;
+        let duration_minutes = (chrono::Utc::now() - session_arc.created_at()).num_seconds() as f64 / 60.0;
         
+        let probe = session_arc.probe_info();
         let message = format!(
             "📊 Debug Session Information\n\n\
             Probe Information:\n\
             - Identifier: {}\n\
+            - Type: {}\n\
+            - VID:PID: {:04X}:{:04X}\n\
+            - Serial: {}\n\
+            - Firmware: {}\n\
+            - Speed: {}\n\
             - Connected: true\n\n\
             Target Information:\n\
             - Chip: {}\n\n\
@@ -281,10 +658,15 @@ impl EmbeddedDebuggerToolHandler {
             - Created: {}\n\
             - Duration: {:.1} minutes\n\n\
             Session is active and ready for operations.",
-            session_arc.probe_identifier,
+            probe.identifier,
+            probe.probe_type,
+            probe.vendor_id, probe.product_id,
+            probe.serial_number.as_deref().unwrap_or("N/A"),
+            probe.version.as_deref().unwrap_or(UNKNOWN_VERSION),
+            session_arc.get_speed(),
             session_arc.target_chip,
             args.session_id,
-            session_arc.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            session_arc.created_at().format("%Y-%m-%d %H:%M:%S UTC"),
             duration_minutes
         );
         
@@ -292,8 +674,183 @@ impl EmbeddedDebuggerToolHandler {
         Ok(CallToolResult::success(vec![Content::text(message)]))
     }
 
+    #[tool(description = "Get session metadata: connection state, target chip, creation and last activity times")]
+    async fn get_session_status(&self, Parameters(args): Parameters<SessionStatusArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Getting session status for session: {}", args.session_id);
+
+        // Looked up without touching, so the reported last activity reflects real use
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => session.clone(),
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // A disconnected probe is reported rather than failed, and a long-running operation does not block the query
+        let (connected, target_state) = match tokio::time::timeout(std::time::Duration::from_millis(250), session_arc.lock_session()).await {
+            Err(_) => (true, "Busy (another operation is in progress)".to_string()),
+            Ok(Err(_)) => (false, "Disconnected (use 'reconnect' to re-attach)".to_string()),
+            Ok(Ok(mut session)) => {
+                let target_state = session.core(0).ok().and_then(|mut core| core.status().ok());
+                (target_state.is_some(), match target_state {
+                    Some(CoreStatus::Halted(reason)) => format!("Halted ({:?})", reason),
+                    Some(CoreStatus::Running) => "Running".to_string(),
+                    Some(other) => format!("{:?}", other),
+                    None => "Unknown".to_string(),
+                })
+            }
+        };
+
+        let status = SessionStatus {
+            session_id: args.session_id.clone(),
+            target_chip: session_arc.target_chip.clone(),
+            connected,
+            target_state,
+            created_at: session_arc.created_at().to_rfc3339(),
+            last_activity: session_arc.last_activity().to_rfc3339(),
+        };
+
+        let idle_seconds = (chrono::Utc::now() - session_arc.last_activity()).num_seconds();
+        let message = format!(
+            "📊 Session Status\n\n\
+            - Session ID: {}\n\
+            - Target: {}\n\
+            - Connected: {}\n\
+            - Target state: {}\n\
+            - Created: {}\n\
+            - Last activity: {} ({}s ago)",
+            status.session_id, status.target_chip, status.connected, status.target_state,
+            status.created_at, status.last_activity, idle_seconds
+        );
+
+        info!("Retrieved session status for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Get server health metrics: session count and limit, halted vs running sessions, total breakpoints and aggregate session uptime")]
+    async fn get_statistics(&self, Parameters(_args): Parameters<GetStatisticsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Collecting session statistics");
+
+        let statistics = self.statistics().await;
+        let message = format!(
+            "📈 Server Statistics\n\n\
+            - Sessions: {} of {}\n\
+            - Halted: {}\n\
+            - Running: {}\n\
+            - Busy: {}\n\
+            - Disconnected: {}\n\
+            - Breakpoints: {}\n\
+            - Total session uptime: {:.1} minutes",
+            statistics.total_sessions, statistics.max_sessions,
+            statistics.halted_sessions, statistics.running_sessions,
+            statistics.busy_sessions, statistics.disconnected_sessions,
+            statistics.total_breakpoints,
+            statistics.total_uptime_seconds as f64 / 60.0
+        );
+
+        info!("Collected statistics for {} session(s)", statistics.total_sessions);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Re-open the probe and re-attach to the target after a reset or USB glitch, keeping the session ID and breakpoints")]
+    async fn reconnect(&self, Parameters(args): Parameters<ReconnectArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reconnecting session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.reconnect().await {
+            Ok(result) => {
+                let mut message = format!(
+                    "🔄 Session reconnected successfully!\n\n\
+                    Session ID: {}\n\
+                    Probe: {}\n\
+                    Target: {}\n\
+                    Breakpoints restored: {}\n",
+                    args.session_id, session_arc.probe_identifier, session_arc.target_chip,
+                    result.breakpoints_restored.len()
+                );
+                message.push_str(&describe_rearm_failures(&result));
+                message.push_str("\nRTT has been detached; use 'rtt_attach' to re-attach if needed.");
+
+                info!("Reconnected session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to reconnect session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Reconnect failed\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    The session is kept but disconnected. Check the probe and target power, then retry 'reconnect'.",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Change the probe clock speed of a session, e.g. to slow down a flaky wire. Re-attaches the session at the new speed")]
+    async fn set_speed(&self, Parameters(args): Parameters<SetSpeedArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Setting probe speed to {} kHz for session: {}", args.speed_khz, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let previous = session_arc.get_speed();
+        match session_arc.set_speed(args.speed_khz).await {
+            Ok((speed, result)) => {
+                let mut message = format!(
+                    "⏱️ Probe speed changed\n\n\
+                    Session ID: {}\n\
+                    Previous: {} kHz\n\
+                    Requested: {} kHz\n\
+                    Actual: {} kHz\n\
+                    Breakpoints restored: {}\n",
+                    args.session_id, previous.actual_khz, speed.requested_khz, speed.actual_khz,
+                    result.breakpoints_restored.len()
+                );
+                message.push_str(&describe_rearm_failures(&result));
+                message.push_str("\nThe session was re-attached at the new speed; use 'rtt_attach' to re-attach RTT if needed.");
+
+                info!("Probe speed for session {} set to {} kHz", args.session_id, speed.actual_khz);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to set probe speed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to set probe speed: {}", e), &e))
+            }
+        }
+    }
+
     // =============================================================================
-    // Target Control Tools (5 tools)
+    // Target Control Tools
     // =============================================================================
 
     #[tool(description = "Halt the target CPU execution")]
@@ -303,26 +860,31 @@ impl EmbeddedDebuggerToolHandler {
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
         
         // Halt the target
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
+        let (mut message, pc) = {
+            let mut session = session_arc.lock_session().await
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            let mut core = match get_core(&mut session, args.core_index) {
                 Ok(core) => core,
                 Err(e) => {
                     error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
+                    return Err(tool_error(format!("Failed to get core: {}", e), &e));
                 }
             };
             
-            match core.halt(std::time::Duration::from_millis(1000)) {
+            let timeout = std::time::Duration::from_millis(1000);
+            match core.halt(timeout) {
                 Ok(_) => {
                     // Get status after halt
                     match core.status() {
@@ -338,9 +900,7 @@ impl EmbeddedDebuggerToolHandler {
                                 State: Halted\n",
                                 args.session_id, pc, sp
                             );
-                            
-                            info!("Halt completed for session: {}", args.session_id);
-                            Ok(CallToolResult::success(vec![Content::text(message)]))
+                            (message, Some(u64::from(pc)))
                         }
                         Err(e) => {
                             warn!("Failed to get status after halt: {}", e);
@@ -350,16 +910,23 @@ impl EmbeddedDebuggerToolHandler {
                                 State: Halted\n",
                                 args.session_id
                             );
-                            Ok(CallToolResult::success(vec![Content::text(message)]))
+                            (message, None)
                         }
                     }
                 }
                 Err(e) => {
                     error!("Failed to halt target for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to halt target: {}", e), None))
+                    let e = DebugError::from_wait("Halting the target", timeout, e);
+                    return Err(tool_error(format!("Failed to halt target: {}", e), &e));
                 }
             }
+        };
+
+        if args.include_source {
+            message.push_str(&self.source_section(&session_arc, pc).await);
         }
+        info!("Halt completed for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
     }
 
     #[tool(description = "Resume target CPU execution")]
@@ -369,1298 +936,5148 @@ impl EmbeddedDebuggerToolHandler {
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
         
-        // Resume the target
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            match core.run() {
-                Ok(_) => {
-                    let message = format!(
-                        "✅ Target resumed execution successfully!\n\n\
-                        Session ID: {}\n\
-                        Status: Running\n\n\
-                        The target is now executing code. Use 'halt' to stop execution.",
-                        args.session_id
-                    );
-                    
-                    info!("Run completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to run target for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to run target: {}", e), None))
-                }
+        match session_arc.resume(args.core_index).await {
+            Ok(()) => {
+                let message = format!(
+                    "✅ Target resumed execution successfully!\n\n\
+                    Session ID: {}\n\
+                    Status: Running\n\n\
+                    The target is now executing code. Use 'halt' to stop execution.",
+                    args.session_id
+                );
+                
+                info!("Run completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to run target for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to run target: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Reset the target CPU")]
-    async fn reset(&self, Parameters(args): Parameters<ResetArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Resetting target for session: {}", args.session_id);
-        
+    #[tool(description = "Resume the target until it reaches an address or function, using a temporary breakpoint that is always removed")]
+    async fn run_to_address(&self, Parameters(args): Parameters<RunToAddressArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Running to {} for session: {}", args.address, args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
-        
-        // Reset the target
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            match core.reset() {
-                Ok(_) => {
-                    if args.halt_after_reset {
-                        match core.halt(std::time::Duration::from_millis(1000)) {
-                            Ok(_) => {},
-                            Err(e) => warn!("Failed to halt after reset: {}", e),
-                        }
-                    }
-                    
-                    let pc = core.read_core_reg(core.program_counter()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    let sp = core.read_core_reg(core.stack_pointer()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    
-                    let message = format!(
-                        "✅ Target reset completed successfully!\n\n\
-                        Session ID: {}\n\
-                        Reset type: {}\n\
-                        Halted after reset: {}\n\
-                        PC: 0x{:08X}\n\
-                        SP: 0x{:08X}\n\
-                        State: {}\n",
-                        args.session_id,
-                        args.reset_type,
-                        args.halt_after_reset,
-                        pc, sp,
-                        if args.halt_after_reset { "Halted" } else { "Running" }
-                    );
-                    
-                    info!("Reset completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to reset target for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to reset target: {}", e), None))
-                }
+
+        // An address that does not parse as a number is treated as a symbol name
+        let (address, symbol) = match parse_address(&args.address) {
+            Ok(address) => (address, None),
+            Err(_) => {
+                let symbol = session_arc.resolve_symbol(&args.address, args.elf_path.as_deref()).await
+                    .map_err(|e| tool_error(format!("Failed to resolve symbol '{}': {}", args.address, e), &e))?;
+                (symbol.address, Some(symbol.display_name().to_string()))
+            }
+        };
+        let target = format!(
+            "0x{:08X}{}",
+            address,
+            symbol.as_ref().map(|symbol| format!(" <{}>", symbol)).unwrap_or_default()
+        );
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.run_to_address(args.core_index, address, timeout).await {
+            Ok(Some(event)) => {
+                let reached = event.pc == Some(address);
+                let reason = match (&event.status, &event.watchpoint) {
+                    (_, Some(watchpoint)) => format!("Watchpoint hit: {}", describe_watchpoint(watchpoint)),
+                    (CoreStatus::Halted(reason), None) => event.vector_catch.clone().unwrap_or_else(|| format!("{:?}", reason)),
+                    (other, None) => format!("{:?}", other),
+                };
+                let pc = event.pc
+                    .map(|pc| format!("0x{:08X}", pc))
+                    .unwrap_or_else(|| "unavailable".to_string());
+                let message = format!(
+                    "{}\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Target: {}\n\
+                    Status: {:?}\n\
+                    Halt reason: {}\n\
+                    PC: {}\n\
+                    Waited: {}ms",
+                    if reached { "🎯 Reached target address" } else { "🛑 Target halted before reaching the address" },
+                    args.session_id, args.core_index, target, event.status, reason, pc, event.elapsed_ms
+                );
+
+                info!("Run to {} on session {} halted at {}", target, args.session_id, pc);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Ok(None) => {
+                let message = format!(
+                    "⏳ Target still running\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Target: {}\n\
+                    Waited: {}ms\n\n\
+                    The temporary breakpoint was removed. Use 'halt' to stop the core.",
+                    args.session_id, args.core_index, target, args.timeout_ms
+                );
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Run to {} failed for session {}: {}", target, args.session_id, e);
+                Err(tool_error(format!("Failed to run to {}: {}", target, e), &e))
             }
         }
     }
 
-    #[tool(description = "Execute a single instruction step")]
-    async fn step(&self, Parameters(args): Parameters<StepArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Single stepping target for session: {}", args.session_id);
-        
+    #[tool(description = "Wait until the target halts (e.g. at a breakpoint after 'run') or the timeout expires")]
+    async fn wait_for_halt(&self, Parameters(args): Parameters<WaitForHaltArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Waiting up to {}ms for halt on session: {}", args.timeout_ms, args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
-        
-        // Single step the target
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.wait_for_halt(args.core_index, timeout).await {
+            Ok(Some(event)) => {
+                let reason = match (&event.status, &event.watchpoint) {
+                    (_, Some(watchpoint)) => format!("Watchpoint hit: {}", describe_watchpoint(watchpoint)),
+                    (CoreStatus::Halted(reason), None) => event.vector_catch.clone().unwrap_or_else(|| format!("{:?}", reason)),
+                    (CoreStatus::LockedUp, None) => "Core locked up".to_string(),
+                    (other, None) => format!("{:?}", other),
+                };
+                let pc = event.pc
+                    .map(|pc| format!("0x{:08X}", pc))
+                    .unwrap_or_else(|| "unavailable".to_string());
+                let mut breakpoint = match &event.breakpoint {
+                    Some(breakpoint) => format!(
+                        "\nBreakpoint: #{} 0x{:08X}{} ({}, hit {} time(s))",
+                        breakpoint.id,
+                        breakpoint.address,
+                        breakpoint.symbol.as_ref().map(|symbol| format!(" <{}>", symbol)).unwrap_or_default(),
+                        breakpoint.kind,
+                        breakpoint.hit_count
+                    ),
+                    None => String::new(),
+                };
+                if event.auto_resumed > 0 {
+                    breakpoint.push_str(&format!("\nAuto-resumed: {} skipped hit(s)", event.auto_resumed));
                 }
-            };
-            
-            match core.step() {
-                Ok(_) => {
-                    let pc = core.read_core_reg(core.program_counter()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    let sp = core.read_core_reg(core.stack_pointer()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    
-                    let message = format!(
-                        "✅ Single step completed successfully!\n\n\
-                        Session ID: {}\n\
-                        PC: 0x{:08X}\n\
-                        SP: 0x{:08X}\n\
-                        State: Halted\n",
-                        args.session_id, pc, sp
-                    );
-                    
-                    info!("Step completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                if let Some(exit) = &event.semihosting_exit {
+                    breakpoint.push_str(&format!("\nSemihosting: {}", describe_semihosting_exit(exit)));
                 }
-                Err(e) => {
-                    error!("Failed to step target for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to step target: {}", e), None))
+                if let Some(hit) = event.breakpoint.as_ref().filter(|hit| hit.skips_hit()) {
+                    breakpoint.push_str(&format!(
+                        "\n⚠️ Stopped before the skip count ({}) was reached: auto-resume limit per call hit. Call 'run' and 'wait_for_halt' to continue",
+                        hit.skip_count
+                    ));
                 }
+                let message = format!(
+                    "🛑 Target halted\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Status: {:?}\n\
+                    Halt reason: {}\n\
+                    PC: {}{}\n\
+                    Waited: {}ms{}",
+                    args.session_id, args.core_index, event.status, reason, pc, breakpoint, event.elapsed_ms,
+                    if args.include_source { self.source_section(&session_arc, event.pc).await } else { String::new() }
+                );
+
+                info!("Core {} halted after {}ms for session: {}", args.core_index, event.elapsed_ms, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Ok(None) => {
+                let message = format!(
+                    "⏳ Target still running\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Waited: {}ms\n\n\
+                    Call 'wait_for_halt' again to keep waiting, or 'halt' to stop the core.",
+                    args.session_id, args.core_index, args.timeout_ms
+                );
+
+                debug!("Core {} still running after {}ms for session: {}", args.core_index, args.timeout_ms, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                warn!("Wait for halt failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed waiting for halt: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Get current status of the target CPU and debug session")]
-    async fn get_status(&self, Parameters(args): Parameters<GetStatusArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Getting status for session: {}", args.session_id);
-        
+    #[tool(description = "Enable or disable vector catch so the core halts on reset or fault exceptions (hard_fault, bus_err, ...)")]
+    async fn vector_catch(&self, Parameters(args): Parameters<VectorCatchArgs>) -> Result<CallToolResult, McpError> {
+        debug!("{} vector catch {:?} for session: {}", if args.enable { "Enabling" } else { "Disabling" }, args.conditions, args.session_id);
+
+        let conditions = args.conditions.iter()
+            .map(|name| VectorCatch::parse(name))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_argument)?;
+        if args.enable && conditions.is_empty() {
+            return Err(invalid_argument("No vector catch conditions given".to_string()));
+        }
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
-        
-        // Get target status
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            match core.status() {
-                Ok(status) => {
-                    let pc = core.read_core_reg(core.program_counter()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    let sp = core.read_core_reg(core.stack_pointer()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
-                    
-                    let is_halted = matches!(status, CoreStatus::Halted(_));
-                    let halt_reason = match status {
-                        CoreStatus::Halted(reason) => format!("{:?}", reason),
-                        CoreStatus::Running => "N/A".to_string(),
-                        _ => "Unknown".to_string(),
-                    };
-                    
-                    let message = format!(
-                        "📊 Debug Session Status\n\n\
-                        Core Information:\n\
-                        - PC: 0x{:08X}\n\
-                        - SP: 0x{:08X}\n\
-                        - State: {}\n\
-                        - Halt reason: {}\n\n\
-                        Session Information:\n\
-                        - ID: {}\n\
-                        - Connected: true\n\
-                        - Target: {}\n\
-                        - Probe: {}\n\
-                        - Duration: {:.1} minutes\n",
-                        pc, sp,
-                        if is_halted { "Halted" } else { "Running" },
-                        halt_reason,
-                        args.session_id,
-                        session_arc.target_chip,
-                        session_arc.probe_identifier,
-                        (chrono::Utc::now() - session_arc.created_at).num_seconds() as f64 / 60.0
-                    );
-                    
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to get core status for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to get core status: {}", e), None))
-                }
-            }
-        }
-    }
 
-    // =============================================================================
-    // Memory Operation Tools (2 tools)
-    // =============================================================================
+        let outcome = if args.enable {
+            session_arc.set_vector_catch(args.core_index, &conditions).await.map(|_| conditions)
+        } else {
+            session_arc.clear_vector_catch(args.core_index, &conditions).await
+        };
 
-    #[tool(description = "Read memory from the target")]
-    async fn read_memory(&self, Parameters(args): Parameters<ReadMemoryArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Reading memory for session: {} at address {}", args.session_id, args.address);
-        
-        // Parse address
-        let address = match parse_address(&args.address) {
-            Ok(addr) => addr,
+        match outcome {
+            Ok(changed) => {
+                let active = session_arc.vector_catches(args.core_index).await;
+                let join = |catches: &[VectorCatch]| if catches.is_empty() {
+                    "none".to_string()
+                } else {
+                    catches.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ")
+                };
+                let message = format!(
+                    "🪤 Vector catch {}\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Changed: {}\n\
+                    Active: {}\n\n\
+                    Use 'wait_for_halt' after 'run' to catch the exception.",
+                    if args.enable { "enabled" } else { "disabled" },
+                    args.session_id, args.core_index, join(&changed), join(&active)
+                );
+
+                info!("Vector catch updated for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
             Err(e) => {
-                error!("Invalid address '{}': {}", args.address, e);
-                return Err(McpError::internal_error(format!("Invalid address '{}': {}", args.address, e), None));
+                error!("Failed to update vector catch for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to update vector catch: {}", e), &e))
             }
-        };
+        }
+    }
+
+    #[tool(description = "Capture all registers of a halted core into a named snapshot kept on the session")]
+    async fn register_snapshot(&self, Parameters(args): Parameters<RegisterSnapshotArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Taking register snapshot '{}' for session: {}", args.name, args.session_id);
 
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Read memory
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            let mut data = vec![0u8; args.size as usize];
-            match core.read(address, &mut data) {
-                Ok(_) => {
-                    debug!("Read {} bytes from address 0x{:08X}", data.len(), address);
-                    
-                    let formatted_data = format_memory_data(&data, &args.format, address);
-                    let message = format!(
-                        "📖 Memory read completed successfully!\n\n\
-                        Session ID: {}\n\
-                        Address: 0x{:08X}\n\
-                        Size: {} bytes\n\
-                        Format: {}\n\n\
-                        Data:\n{}",
-                        args.session_id, address, args.size, args.format, formatted_data
-                    );
-                    
-                    info!("Memory read completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to read memory for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to read memory: {}", e), None))
+        match session_arc.take_register_snapshot(args.core_index, &args.name).await {
+            Ok((snapshot, evicted)) => {
+                let mut message = format!(
+                    "📸 Register snapshot '{}' saved\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Registers: {}\n\
+                    Taken at: {}\n",
+                    args.name, args.session_id, snapshot.core_index, snapshot.readings.len(),
+                    snapshot.taken_at.format("%H:%M:%S%.3f")
+                );
+                if let Some(evicted) = evicted {
+                    message.push_str(&format!(
+                        "\n⚠️ Snapshot limit ({}) reached, removed oldest snapshot '{}'\n",
+                        registers::MAX_REGISTER_SNAPSHOTS, evicted
+                    ));
                 }
-            }
-        }
-    }
+                message.push_str(&format!(
+                    "\nStored snapshots: {}",
+                    session_arc.register_snapshot_names().await.join(", ")
+                ));
 
-    #[tool(description = "Write memory to the target")]
-    async fn write_memory(&self, Parameters(args): Parameters<WriteMemoryArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Writing memory for session: {} at address {}", args.session_id, args.address);
-        
-        // Parse address
-        let address = match parse_address(&args.address) {
-            Ok(addr) => addr,
-            Err(e) => {
-                error!("Invalid address '{}': {}", args.address, e);
-                return Err(McpError::internal_error(format!("Invalid address '{}': {}", args.address, e), None));
+                info!("Register snapshot '{}' saved for session: {}", args.name, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
             }
-        };
-
-        // Parse data based on format
-        let data = match parse_data(&args.data, &args.format) {
-            Ok(data) => data,
             Err(e) => {
-                error!("Invalid data '{}': {}", args.data, e);
-                return Err(McpError::internal_error(format!("Invalid data '{}': {}", args.data, e), None));
+                error!("Failed to take register snapshot for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to take register snapshot: {}", e), &e))
             }
-        };
+        }
+    }
+
+    #[tool(description = "Compare a register snapshot with the current registers or another snapshot, listing only changed registers")]
+    async fn register_diff(&self, Parameters(args): Parameters<RegisterDiffArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Diffing register snapshot '{}' for session: {}", args.from, args.session_id);
 
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Write memory
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            match core.write(address, &data) {
-                Ok(_) => {
-                    let message = format!(
-                        "✏️ Memory write completed successfully!\n\n\
-                        Session ID: {}\n\
-                        Address: 0x{:08X}\n\
-                        Data: {}\n\
-                        Format: {}\n\
-                        Bytes written: {}",
-                        args.session_id, address, args.data, args.format, data.len()
-                    );
-                    
-                    info!("Memory write completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+        match session_arc.register_diff(&args.from, args.to.as_deref()).await {
+            Ok(changes) => {
+                let mut message = format!(
+                    "🔀 Register diff: {} → {}\n\n\
+                    Session ID: {}\n\
+                    Changed: {}\n",
+                    args.from, args.to.as_deref().unwrap_or("current"), args.session_id, changes.len()
+                );
+                if !changes.is_empty() {
+                    message.push('\n');
                 }
-                Err(e) => {
-                    error!("Failed to write memory for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to write memory: {}", e), None))
+                let hex = |value: Option<u128>, bits: usize| match value {
+                    Some(value) => format!("0x{:0width$X}", value, width = bits.div_ceil(4)),
+                    None => "unavailable".to_string(),
+                };
+                for change in &changes {
+                    message.push_str(&format!(
+                        "{:<12} {} → {}\n",
+                        change.name, hex(change.old, change.bits), hex(change.new, change.bits)
+                    ));
                 }
+
+                info!("Register diff computed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to diff registers for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to diff registers: {}", e), &e))
             }
         }
     }
 
-    // =============================================================================
-    // Breakpoint Tools (2 tools)
-    // =============================================================================
-
-    #[tool(description = "Set a breakpoint at the specified address")]
-    async fn set_breakpoint(&self, Parameters(args): Parameters<SetBreakpointArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Setting breakpoint for session: {} at address {}", args.session_id, args.address);
-        
-        // Parse address
-        let address = match parse_address(&args.address) {
-            Ok(addr) => addr,
-            Err(e) => {
-                error!("Invalid address '{}': {}", args.address, e);
-                return Err(McpError::internal_error(format!("Invalid address '{}': {}", args.address, e), None));
-            }
-        };
+    #[tool(description = "Delete a named register snapshot, or all snapshots of the session")]
+    async fn delete_register_snapshot(&self, Parameters(args): Parameters<DeleteRegisterSnapshotArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Deleting register snapshot {:?} for session: {}", args.name, args.session_id);
 
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Set breakpoint
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
-                Ok(core) => core,
-                Err(e) => {
-                    error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
-                }
-            };
-            
-            match core.set_hw_breakpoint(address) {
-                Ok(_) => {
-                    let message = format!(
-                        "🎯 Breakpoint set successfully!\n\n\
-                        Session ID: {}\n\
-                        Address: 0x{:08X}\n\
-                        Type: Hardware breakpoint\n\n\
-                        The target will halt when execution reaches this address.",
-                        args.session_id, address
-                    );
-                    
-                    info!("Breakpoint set for session: {} at 0x{:08X}", args.session_id, address);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to set breakpoint for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to set breakpoint: {}", e), None))
-                }
+        match session_arc.delete_register_snapshot(args.name.as_deref()).await {
+            Ok(removed) => {
+                let message = format!(
+                    "🗑️ Removed {} register snapshot(s)\n\n\
+                    Session ID: {}\n\
+                    Remaining: {}",
+                    removed, args.session_id, session_arc.register_snapshot_names().await.len()
+                );
+
+                info!("Register snapshots deleted for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to delete register snapshot for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to delete register snapshot: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Clear a breakpoint at the specified address")]
-    async fn clear_breakpoint(&self, Parameters(args): Parameters<ClearBreakpointArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Clearing breakpoint for session: {} at address {}", args.session_id, args.address);
-        
-        // Parse address
-        let address = match parse_address(&args.address) {
-            Ok(addr) => addr,
-            Err(e) => {
-                error!("Invalid address '{}': {}", args.address, e);
-                return Err(McpError::internal_error(format!("Invalid address '{}': {}", args.address, e), None));
-            }
-        };
+    #[tool(description = "Reset the target CPU")]
+    async fn reset(&self, Parameters(args): Parameters<ResetArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Resetting target for session: {}", args.session_id);
 
+        let reset_type = ResetType::parse(&args.reset_type)
+            .map_err(invalid_argument)?;
+        
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
+        
+        let reset_result = match session_arc.reset(args.core_index, reset_type, args.halt_after_reset, rearm_after_reset(&args)).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to reset target for session {}: {}", args.session_id, e);
+                return Err(tool_error(format!("Failed to reset target: {}", e), &e));
+            }
+        };
 
-        // Clear breakpoint
-        {
-            let mut session = session_arc.session.lock().await;
-            let mut core = match session.core(0) {
+        let (pc, sp) = {
+            let mut session = session_arc.lock_session().await
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            let mut core = match get_core(&mut session, args.core_index) {
                 Ok(core) => core,
                 Err(e) => {
                     error!("Failed to get core for session {}: {}", args.session_id, e);
-                    return Err(McpError::internal_error(format!("Failed to get core: {}", e), None));
+                    return Err(tool_error(format!("Failed to get core: {}", e), &e));
                 }
             };
-            
-            match core.clear_hw_breakpoint(address) {
-                Ok(_) => {
-                    let message = format!(
-                        "🎯 Breakpoint cleared successfully!\n\n\
-                        Session ID: {}\n\
-                        Address: 0x{:08X}\n\n\
-                        The breakpoint has been removed.",
-                        args.session_id, address
-                    );
-                    
-                    info!("Breakpoint cleared for session: {} at 0x{:08X}", args.session_id, address);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to clear breakpoint for session {}: {}", args.session_id, e);
-                    Err(McpError::internal_error(format!("Failed to clear breakpoint: {}", e), None))
-                }
+            let pc = core.read_core_reg(core.program_counter()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
+            let sp = core.read_core_reg(core.stack_pointer()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
+            (pc, sp)
+        };
+
+        let mut message = format!(
+            "✅ Target reset completed successfully!\n\n\
+            Session ID: {}\n\
+            Reset type: {}\n\
+            Halted after reset: {}\n\
+            PC: 0x{:08X}\n\
+            SP: 0x{:08X}\n\
+            State: {}\n",
+            args.session_id,
+            reset_type,
+            args.halt_after_reset,
+            pc, sp,
+            if args.halt_after_reset { "Halted" } else { "Running" }
+        );
+        if let Some(result) = reset_result {
+            let restored: Vec<String> = result.breakpoints_restored.iter().map(|address| format!("0x{:08X}", address)).collect();
+            message.push_str(&format!(
+                "\nBreakpoints re-armed: {}\n",
+                if restored.is_empty() { "none".to_string() } else { restored.join(", ") }
+            ));
+            message.push_str(&describe_rearm_failures(&result));
+            if reset_type == ResetType::Hardware {
+                message.push_str("\nThe session was re-attached. Use 'rtt_attach' to resume RTT.\n");
             }
+        } else {
+            message.push_str("\nBreakpoints were not re-armed; 'list_breakpoints' may show some the reset cleared.\n");
         }
-    }
 
-    // =============================================================================
-    // RTT Communication Tools (5 tools)
-    // =============================================================================
+        info!("Reset completed for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
 
-    #[tool(description = "Attach to RTT (Real-Time Transfer) for communication with target")]
-    async fn rtt_attach(&self, Parameters(args): Parameters<RttAttachArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Attaching RTT for session: {}", args.session_id);
+    #[tool(description = "Single-step one or more instructions, optionally returning a PC trace")]
+    async fn step(&self, Parameters(args): Parameters<StepArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Single stepping target for session: {}", args.session_id);
         
-        // Get session from storage
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
-                }
-            }
-        };
-
-        // Parse control block address if provided
-        let control_block_address = if let Some(addr_str) = args.control_block_address {
-            match parse_address(&addr_str) {
-                Ok(addr) => Some(addr),
-                Err(e) => {
-                    let error_msg = format!("❌ Invalid control block address '{}': {}", addr_str, e);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
-        } else {
-            None
-        };
-
-        // Parse memory ranges if provided
-        let memory_ranges = if let Some(ranges) = args.memory_ranges {
-            let mut parsed_ranges = Vec::new();
-            for range in ranges {
-                let start = parse_address(&range.start).map_err(|e| {
-                    McpError::internal_error(format!("Invalid start address '{}': {}", range.start, e), None)
-                })?;
-                let end = parse_address(&range.end).map_err(|e| {
-                    McpError::internal_error(format!("Invalid end address '{}': {}", range.end, e), None)
-                })?;
-                parsed_ranges.push((start, end));
-            }
-            Some(parsed_ranges)
-        } else {
-            None
         };
+        
+        if args.count == 0 || args.count > self.debugger_config.max_step_count {
+            return Err(invalid_argument(format!(
+                "Step count must be between 1 and {}", self.debugger_config.max_step_count
+            )));
+        }
 
-        // Attach RTT
-        {
-            let mut rtt_manager = session_arc.rtt_manager.lock().await;
-            match rtt_manager.attach(session_arc.session.clone(), control_block_address, memory_ranges).await {
-                Ok(_) => {
-                    let up_channels = rtt_manager.up_channel_count();
-                    let down_channels = rtt_manager.down_channel_count();
-                    
-                    let message = format!(
-                        "✅ RTT attached successfully!\n\n\
-                        Session ID: {}\n\
-                        Up Channels (Target→Host): {}\n\
-                        Down Channels (Host→Target): {}\n\n\
-                        RTT is now ready for real-time communication with the target.\n\
-                        Use 'rtt_read' to read from target and 'rtt_write' to send data to target.",
-                        args.session_id, up_channels, down_channels
-                    );
-                    
-                    info!("RTT attached successfully for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+        match session_arc.step(args.core_index, args.count, args.trace).await {
+            Ok(result) => {
+                let executed = result.instructions.unwrap_or_default();
+                let stop = step_stop_reason(&result).unwrap_or_else(|| "Completed".to_string());
+                let mut message = format!(
+                    "✅ Stepped {} of {} instruction(s)\n\n\
+                    Session ID: {}\n\
+                    PC: {}\n\
+                    Status: {:?}\n\
+                    Stopped by: {}\n\
+                    Duration: {}ms",
+                    executed, args.count, args.session_id,
+                    result.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "unavailable".to_string()),
+                    result.status, stop, result.elapsed_ms
+                );
+                if args.include_source {
+                    message.push_str(&self.source_section(&session_arc, result.pc).await);
                 }
-                Err(e) => {
-                    error!("Failed to attach RTT for session {}: {}", args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Failed to attach RTT\n\n\
-                        Session ID: {}\n\
-                        Error: {}\n\n\
-                        Suggestions:\n\
-                        - Ensure the target firmware has RTT enabled and initialized\n\
-                        - Check that the target is halted\n\
-                        - Verify memory ranges if specified\n\
-                        - Try different control block address if known",
-                        args.session_id, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
+                if let Some(trace) = &result.trace {
+                    message.push_str("\n\nTrace:\n");
+                    for (i, pc) in trace.iter().enumerate() {
+                        message.push_str(&format!("{:>5}  0x{:08X}\n", i + 1, pc));
+                    }
                 }
+
+                info!("Stepped {} instructions for session: {}", executed, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to step target for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to step target: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Detach from RTT communication")]
-    async fn rtt_detach(&self, Parameters(args): Parameters<RttDetachArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Detaching RTT for session: {}", args.session_id);
-        
-        // Get session from storage
+    #[tool(description = "Run exactly N instructions by single-stepping in interruptible chunks, then stay halted; deterministic instruction counts for comparing code paths (stops early at a breakpoint, fault, timeout or cancellation)")]
+    async fn run_instructions(&self, Parameters(args): Parameters<RunInstructionsArgs>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        debug!("Running {} instructions for session: {}", args.count, args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Detach RTT
-        {
-            let mut rtt_manager = session_arc.rtt_manager.lock().await;
-            match rtt_manager.detach().await {
-                Ok(_) => {
-                    let message = format!(
-                        "✅ RTT detached successfully\n\n\
-                        Session ID: {}\n\n\
-                        RTT communication has been closed.",
-                        args.session_id
-                    );
-                    
-                    info!("RTT detached successfully for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Failed to detach RTT for session {}: {}", args.session_id, e);
-                    let error_msg = format!("❌ Failed to detach RTT: {}", e);
-                    Err(McpError::internal_error(error_msg, None))
-                }
+        if args.count == 0 {
+            return Err(invalid_argument("Instruction count must be greater than 0".to_string()));
+        }
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.run_instructions(args.core_index, args.count, timeout, || context.ct.is_cancelled()).await {
+            Ok((result, cancelled)) => {
+                let executed = result.instructions.unwrap_or_default();
+                let stop = match step_stop_reason(&result) {
+                    Some(reason) => reason,
+                    None if cancelled => "Cancelled".to_string(),
+                    None if result.timed_out => format!("Timeout after {}ms", args.timeout_ms),
+                    None => "Completed".to_string(),
+                };
+                let message = format!(
+                    "{} Ran {} of {} instruction(s)\n\n\
+                    Session ID: {}\n\
+                    PC: {}\n\
+                    Status: {:?}\n\
+                    Stopped by: {}\n\
+                    Duration: {}ms",
+                    if executed == args.count { "✅" } else { "⏸️" },
+                    executed, args.count, args.session_id,
+                    result.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "unavailable".to_string()),
+                    result.status, stop, result.elapsed_ms
+                );
+
+                info!("Ran {} instructions for session: {}", executed, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Running instructions failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to run instructions: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Read data from RTT up channel (target to host)")]
-    async fn rtt_read(&self, Parameters(args): Parameters<RttReadArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Reading from RTT channel {} for session: {}", args.channel, args.session_id);
-        
-        // Get session from storage
+    #[tool(description = "Step one instruction, running over function calls with a temporary breakpoint at the return address")]
+    async fn step_over(&self, Parameters(args): Parameters<StepOverArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Stepping over for session: {}", args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Read from RTT
-        {
-            let mut rtt_manager = session_arc.rtt_manager.lock().await;
-            if !rtt_manager.is_attached() {
-                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
-                return Err(McpError::internal_error(error_msg, None));
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.step_over(args.core_index, timeout).await {
+            Ok(result) => {
+                info!("Step over completed for session: {} in {}ms", args.session_id, result.elapsed_ms);
+                Ok(CallToolResult::success(vec![Content::text(describe_step("Step over", &args.session_id, &result))]))
+            }
+            Err(e) => {
+                error!("Step over failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to step over: {}", e), &e))
             }
+        }
+    }
 
-            match rtt_manager.read_channel(args.channel).await {
-                Ok(data) => {
-                    let data_len = data.len();
-                    let data_str = if data.is_empty() {
-                        "No data available".to_string()
-                    } else {
-                        // Try to decode as UTF-8, fall back to hex if not valid
-                        match String::from_utf8(data.clone()) {
-                            Ok(text) => {
-                                if text.chars().all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace()) {
-                                    format!("Text: {}", text)
-                                } else {
-                                    format!("Mixed: {} (hex: {})", text, hex::encode(&data))
-                                }
-                            }
-                            Err(_) => format!("Binary data (hex): {}", hex::encode(&data))
-                        }
-                    };
+    #[tool(description = "Run until the current function returns to its caller")]
+    async fn step_out(&self, Parameters(args): Parameters<StepOutArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Stepping out for session: {}", args.session_id);
 
-                    let message = format!(
-                        "📥 RTT Read from Channel {}\n\n\
-                        Session ID: {}\n\
-                        Bytes Read: {}\n\n\
-                        Data:\n{}",
-                        args.channel, args.session_id, data_len, data_str
-                    );
-                    
-                    debug!("Read {} bytes from RTT channel {} for session: {}", data_len, args.channel, args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
                 }
-                Err(e) => {
-                    error!("Failed to read from RTT channel {} for session {}: {}", args.channel, args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Failed to read from RTT channel {}\n\n\
-                        Session ID: {}\n\
-                        Error: {}",
-                        args.channel, args.session_id, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
+        };
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.step_out(args.core_index, timeout).await {
+            Ok(result) => {
+                info!("Step out completed for session: {} in {}ms", args.session_id, result.elapsed_ms);
+                Ok(CallToolResult::success(vec![Content::text(describe_step("Step out", &args.session_id, &result))]))
+            }
+            Err(e) => {
+                error!("Step out failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to step out: {}", e), &e))
+            }
         }
     }
 
-    #[tool(description = "Write data to RTT down channel (host to target)")]
-    async fn rtt_write(&self, Parameters(args): Parameters<RttWriteArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Writing to RTT channel {} for session: {}", args.channel, args.session_id);
+    #[tool(description = "Get current status of the target CPU and debug session")]
+    async fn get_status(&self, Parameters(args): Parameters<GetStatusArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Getting status for session: {}", args.session_id);
         
-        // Get session from storage
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
+        
+        // Symbols are optional here: only annotate the PC when an ELF is available
+        let symbols = if args.elf_path.is_some() {
+            Some(session_arc.symbols(args.elf_path.as_deref()).await
+                .map_err(|e| tool_error(format!("Failed to load symbols: {}", e), &e))?)
+        } else {
+            session_arc.symbols(None).await.ok()
+        };
 
-        // Parse data based on encoding
-        let data_bytes = match args.encoding.as_str() {
-            "utf8" => args.data.as_bytes().to_vec(),
-            "hex" => {
-                match hex::decode(&args.data) {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        let error_msg = format!("❌ Invalid hex data '{}': {}", args.data, e);
-                        return Err(McpError::internal_error(error_msg, None));
-                    }
-                }
-            }
-            "binary" => {
-                // Parse binary string like "10110011 11001100"
-                let binary_str = args.data.replace(' ', "");
-                if binary_str.len() % 8 != 0 {
-                    let error_msg = format!("❌ Binary data must be multiple of 8 bits: '{}'", args.data);
-                    return Err(McpError::internal_error(error_msg, None));
+        let symbol_status = match session_arc.symbol_status().await {
+            Some(status) => format!(
+                "{} ({} symbols, build ID {})",
+                status.elf_path, status.symbol_count, status.build_id.as_deref().unwrap_or("none")
+            ),
+            None => "none (use load_symbols)".to_string(),
+        };
+
+        let watchpoints = session_arc.watchpoints(args.core_index).await;
+        let vector_catches = session_arc.vector_catches(args.core_index).await;
+
+        // Get target status
+        {
+            let mut session = session_arc.lock_session().await
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            let mut core = match get_core(&mut session, args.core_index) {
+                Ok(core) => core,
+                Err(e) => {
+                    error!("Failed to get core for session {}: {}", args.session_id, e);
+                    return Err(tool_error(format!("Failed to get core: {}", e), &e));
                 }
-                
-                let mut bytes = Vec::new();
-                for chunk in binary_str.chars().collect::<Vec<_>>().chunks(8) {
-                    let byte_str: String = chunk.iter().collect();
-                    match u8::from_str_radix(&byte_str, 2) {
-                        Ok(byte) => bytes.push(byte),
-                        Err(e) => {
-                            let error_msg = format!("❌ Invalid binary byte '{}': {}", byte_str, e);
-                            return Err(McpError::internal_error(error_msg, None));
-                        }
-                    }
-                }
-                bytes
-            }
-            _ => {
-                let error_msg = format!("❌ Unsupported encoding '{}'. Use 'utf8', 'hex', or 'binary'", args.encoding);
-                return Err(McpError::internal_error(error_msg, None));
-            }
-        };
-
-        // Write to RTT
-        {
-            let mut rtt_manager = session_arc.rtt_manager.lock().await;
-            if !rtt_manager.is_attached() {
-                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
-                return Err(McpError::internal_error(error_msg, None));
-            }
+            };
+            
+            match core.status() {
+                Ok(status) => {
+                    let pc = core.read_core_reg(core.program_counter()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
+                    let sp = core.read_core_reg(core.stack_pointer()).map(|v: RegisterValue| v.try_into().unwrap_or(0u32)).unwrap_or(0);
+                    
+                    let is_halted = matches!(status, CoreStatus::Halted(_));
+                    let halt_reason = match status {
+                        CoreStatus::Halted(HaltReason::Watchpoint) => match watchpoints::fired(&mut core, &watchpoints) {
+                            Some(watchpoint) => format!("Watchpoint hit: {}", describe_watchpoint(watchpoint)),
+                            None => "Watchpoint".to_string(),
+                        },
+                        CoreStatus::Halted(HaltReason::Exception) => vector_catch::describe_caught(&mut core)
+                            .unwrap_or_else(|| format!("{:?}", HaltReason::Exception)),
+                        CoreStatus::Halted(reason) => format!("{:?}", reason),
+                        CoreStatus::Running => "N/A".to_string(),
+                        _ => "Unknown".to_string(),
+                    };
+                    
+                    let function = symbols
+                        .as_ref()
+                        .and_then(|table| table.address_to_symbol(pc as u64))
+                        .map(|(symbol, offset)| format!(" <{}+0x{:X}>", symbol.display_name(), offset))
+                        .unwrap_or_default();
 
-            match rtt_manager.write_channel(args.channel, &data_bytes).await {
-                Ok(bytes_written) => {
                     let message = format!(
-                        "📤 RTT Write to Channel {}\n\n\
-                        Session ID: {}\n\
-                        Data: {}\n\
-                        Encoding: {}\n\
-                        Bytes Written: {}\n\n\
-                        Data sent successfully to target.",
-                        args.channel, args.session_id, args.data, args.encoding, bytes_written
+                        "📊 Debug Session Status\n\n\
+                        Core Information:\n\
+                        - Core: {}\n\
+                        - PC: 0x{:08X}{}\n\
+                        - SP: 0x{:08X}\n\
+                        - State: {}\n\
+                        - Halt reason: {}\n\
+                        - Vector catch: {}\n\n\
+                        Session Information:\n\
+                        - ID: {}\n\
+                        - Connected: true\n\
+                        - Target: {}\n\
+                        - Probe: {}\n\
+                        - Symbols: {}\n\
+                        - Duration: {:.1} minutes\n",
+                        args.core_index, pc, function, sp,
+                        if is_halted { "Halted" } else { "Running" },
+                        halt_reason,
+                        if vector_catches.is_empty() {
+                            "none".to_string()
+                        } else {
+                            vector_catches.iter().map(|catch| catch.to_string()).collect::<Vec<_>>().join(", ")
+                        },
+                        args.session_id,
+                        session_arc.target_chip,
+                        session_arc.probe_identifier,
+                        symbol_status,
+                        (chrono::Utc::now() - session_arc.created_at()).num_seconds() as f64 / 60.0
                     );
                     
-                    info!("Wrote {} bytes to RTT channel {} for session: {}", bytes_written, args.channel, args.session_id);
                     Ok(CallToolResult::success(vec![Content::text(message)]))
                 }
                 Err(e) => {
-                    error!("Failed to write to RTT channel {} for session {}: {}", args.channel, args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Failed to write to RTT channel {}\n\n\
-                        Session ID: {}\n\
-                        Error: {}",
-                        args.channel, args.session_id, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
+                    error!("Failed to get core status for session {}: {}", args.session_id, e);
+                    Err(tool_error(format!("Failed to get core status: {}", e), &DebugError::from(e)))
                 }
             }
         }
     }
 
-    #[tool(description = "List available RTT channels")]
-    async fn rtt_channels(&self, Parameters(args): Parameters<RttChannelsArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Listing RTT channels for session: {}", args.session_id);
-        
-        // Get session from storage
+    #[tool(description = "Report what the connected core and probe support: breakpoint and watchpoint comparators, FPU, SWO/ITM and nRST")]
+    async fn get_capabilities(&self, Parameters(args): Parameters<GetCapabilitiesArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Getting capabilities for session: {}", args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // List RTT channels
-        {
-            let rtt_manager = session_arc.rtt_manager.lock().await;
-            if !rtt_manager.is_attached() {
-                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
-                return Err(McpError::internal_error(error_msg, None));
-            }
-
-            let channels = rtt_manager.get_channels();
-            let channel_count = channels.len();
-            
-            if channels.is_empty() {
+        match session_arc.capabilities(args.core_index).await {
+            Ok(capabilities) => {
+                let yes_no = |supported: bool| if supported { "yes" } else { "no" };
                 let message = format!(
-                    "📋 RTT Channels\n\n\
-                    Session ID: {}\n\n\
-                    No RTT channels available.",
-                    args.session_id
+                    "🧰 Target Capabilities\n\n\
+                    Session ID: {}\n\
+                    Target: {}\n\
+                    Core: {} ({:?})\n\n\
+                    Hardware breakpoints: {}\n\
+                    Software breakpoints: {}\n\
+                    Watchpoints: {}\n\
+                    FPU: {}\n\
+                    ITM (SWO tracing): {}\n\
+                    Probe SWO capture: {}\n\
+                    nRST hardware reset: {}",
+                    args.session_id, session_arc.target_chip, args.core_index, capabilities.core_type,
+                    capabilities.hw_breakpoints,
+                    yes_no(capabilities.software_breakpoints),
+                    capabilities.watchpoints,
+                    if capabilities.fpu_registers > 0 {
+                        format!("yes ({} registers)", capabilities.fpu_registers)
+                    } else {
+                        "no".to_string()
+                    },
+                    yes_no(capabilities.itm),
+                    yes_no(capabilities.probe_swo),
+                    match capabilities.nrst {
+                        Some(works) => yes_no(works),
+                        None => "unknown (not tried yet)",
+                    }
                 );
-                return Ok(CallToolResult::success(vec![Content::text(message)]));
-            }
 
-            let mut message = format!("📋 RTT Channels\n\nSession ID: {}\n\n", args.session_id);
-            
-            // Group channels by direction
-            let mut up_channels = Vec::new();
-            let mut down_channels = Vec::new();
-            
-            for channel in &channels {
-                match channel.direction {
-                    crate::rtt::ChannelDirection::Up => up_channels.push(channel),
-                    crate::rtt::ChannelDirection::Down => down_channels.push(channel),
-                }
+                Ok(CallToolResult::success(vec![Content::text(message)]))
             }
-
-            if !up_channels.is_empty() {
-                message.push_str("📥 Up Channels (Target → Host):\n");
-                for channel in up_channels {
-                    message.push_str(&format!(
-                        "  {}. {} (Size: {} bytes, Mode: {})\n",
-                        channel.id, channel.name, channel.buffer_size, channel.mode
-                    ));
-                }
-                message.push('\n');
+            Err(e) => {
+                error!("Failed to read capabilities for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read capabilities: {}", e), &e))
             }
+        }
+    }
 
-            if !down_channels.is_empty() {
-                message.push_str("📤 Down Channels (Host → Target):\n");
-                for channel in down_channels {
-                    message.push_str(&format!(
-                        "  {}. {} (Size: {} bytes, Mode: {})\n",
-                        channel.id, channel.name, channel.buffer_size, channel.mode
-                    ));
+    #[tool(description = "List the cores of the target with their type and current state")]
+    async fn list_cores(&self, Parameters(args): Parameters<ListCoresArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing cores for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
+        };
+
+        let mut session = session_arc.lock_session().await
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+        let cores = session.list_cores();
+
+        let mut message = format!(
+            "🧩 Target Cores ({})\n\n\
+            Session ID: {}\n\
+            Target: {}\n\n",
+            cores.len(), args.session_id, session_arc.target_chip
+        );
 
-            info!("Listed {} RTT channels for session: {}", channel_count, args.session_id);
-            Ok(CallToolResult::success(vec![Content::text(message)]))
+        for (index, core_type) in cores {
+            let state = match get_core(&mut session, index).and_then(|mut core| {
+                core.status().map_err(|e| crate::error::DebugError::InternalError(e.to_string()))
+            }) {
+                Ok(CoreStatus::Halted(reason)) => format!("Halted ({:?})", reason),
+                Ok(CoreStatus::Running) => "Running".to_string(),
+                Ok(status) => format!("{:?}", status),
+                Err(e) => format!("Unavailable ({})", e),
+            };
+            message.push_str(&format!("Core {}: {:?} - {}\n", index, core_type, state));
         }
+
+        info!("Listed cores for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
     }
 
-    // =============================================================================
-    // Flash Programming Tools (4 tools)
-    // =============================================================================
+    #[tool(description = "Read the Cortex-M CPUID register and identify the exact core (M0, M0+, M3, M4, M7, M33, ...) and its revision")]
+    async fn read_cpuid(&self, Parameters(args): Parameters<ReadCpuidArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading CPUID for session: {}", args.session_id);
 
-    #[tool(description = "Erase flash memory sectors or entire chip")]
-    async fn flash_erase(&self, Parameters(args): Parameters<FlashEraseArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Flash erase for session: {}, type: {}", args.session_id, args.erase_type);
-        
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Parse erase type and parameters
-        let erase_type = match args.erase_type.as_str() {
-            "all" => crate::flash::EraseType::All,
-            "sectors" => {
-                let address = match args.address {
-                    Some(addr_str) => parse_address(&addr_str).map_err(|e| McpError::internal_error(e, None))?,
-                    None => return Err(McpError::internal_error("Address required for sector erase".to_string(), None)),
-                };
-                let size = match args.size {
-                    Some(sz) => sz as usize,
-                    None => return Err(McpError::internal_error("Size required for sector erase".to_string(), None)),
-                };
-                crate::flash::EraseType::Sectors { address, size }
+        match session_arc.read_cpuid(args.core_index).await {
+            Ok(cpuid) => {
+                let message = format!(
+                    "🆔 CPUID\n\n\
+                    Session ID: {}\n\
+                    Core: {} {}\n\
+                    Raw: 0x{:08X}\n\
+                    Implementer: 0x{:02X}{}\n\
+                    Part number: 0x{:03X}\n\
+                    Architecture: 0x{:X}",
+                    args.session_id,
+                    cpuid.core.unwrap_or("Unknown core"), cpuid.revision_name(),
+                    cpuid.raw,
+                    cpuid.implementer, if cpuid.implementer == 0x41 { " (ARM)" } else { "" },
+                    cpuid.part_number,
+                    cpuid.architecture
+                );
+                info!("Read CPUID for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
             }
-            _ => return Err(McpError::internal_error(format!("Invalid erase type: {}", args.erase_type), None)),
-        };
-
-        // Perform erase operation
-        {
-            let mut session = session_arc.session.lock().await;
-            match crate::flash::FlashManager::erase_flash(&mut session, erase_type).await {
-                Ok(result) => {
-                    let message = format!(
-                        "✅ Flash erase completed successfully!\n\n\
-                        Session ID: {}\n\
-                        Erase Type: {}\n\
-                        Duration: {}ms\n\
-                        {}\n\n\
-                        Flash memory has been erased and is ready for programming.",
-                        args.session_id,
-                        args.erase_type,
-                        result.erase_time_ms,
-                        match result.sectors_erased {
-                            Some(count) => format!("Sectors Erased: {}", count),
-                            None => "Full chip erased".to_string(),
-                        }
-                    );
-                    
-                    info!("Flash erase completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                }
-                Err(e) => {
-                    error!("Flash erase failed for session {}: {}", args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Flash erase failed\n\n\
-                        Session ID: {}\n\
-                        Error: {}\n\n\
-                        Suggestions:\n\
-                        - Check if flash is write-protected\n\
-                        - Ensure target is halted\n\
-                        - Verify flash address range",
-                        args.session_id, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
-                }
+            Err(e) => {
+                error!("CPUID read failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read CPUID: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Program file to flash memory (supports ELF, HEX, BIN)")]
-    async fn flash_program(&self, Parameters(args): Parameters<FlashProgramArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Flash program for session: {}, file: {}", args.session_id, args.file_path);
-        
+    #[tool(description = "Enable the Cortex-M DWT cycle counter (sets DEMCR.TRCENA and DWT_CTRL.CYCCNTENA) and return its current count")]
+    async fn cycle_counter_enable(&self, Parameters(args): Parameters<CycleCounterArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Enabling the cycle counter for session: {}", args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Parse file path and format
-        let file_path = std::path::Path::new(&args.file_path);
-        let format = match args.format.as_str() {
-            "auto" => crate::flash::FileFormat::Auto,
-            "elf" => crate::flash::FileFormat::Elf,
-            "hex" => crate::flash::FileFormat::Hex,
-            "bin" => crate::flash::FileFormat::Bin,
-            _ => return Err(McpError::internal_error(format!("Unsupported format: {}", args.format), None)),
-        };
+        match session_arc.enable_cycle_counter(args.core_index).await {
+            Ok(count) => {
+                let message = format!(
+                    "⏱️ Cycle counter enabled\n\n\
+                    Session ID: {}\n\
+                    CYCCNT: {}{}",
+                    args.session_id, count, describe_cycle_time(count as u64, args.cpu_hz)
+                );
+                info!("Enabled the cycle counter for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Enabling the cycle counter failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to enable the cycle counter: {}", e), &e))
+            }
+        }
+    }
 
-        // Parse base address if provided
-        let base_address = if let Some(addr_str) = args.base_address {
-            Some(parse_address(&addr_str).map_err(|e| McpError::internal_error(e, None))?)
-        } else {
-            None
-        };
+    #[tool(description = "Read the Cortex-M DWT cycle counter (CYCCNT), optionally converted to time with cpu_hz")]
+    async fn cycle_counter_read(&self, Parameters(args): Parameters<CycleCounterArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading the cycle counter for session: {}", args.session_id);
 
-        // Perform programming operation
-        {
-            let mut session = session_arc.session.lock().await;
-            match crate::flash::FlashManager::program_file(&mut session, file_path, format, base_address).await {
-                Ok(result) => {
-                    let message = format!(
-                        "✅ Flash programming completed successfully!\n\n\
-                        Session ID: {}\n\
-                        File: {}\n\
-                        Format: {}\n\
-                        Bytes Programmed: {}\n\
-                        Duration: {}ms\n\
-                        Verification: {}\n\n\
-                        Firmware has been programmed to flash memory.",
-                        args.session_id,
-                        args.file_path,
-                        args.format,
-                        result.bytes_programmed,
-                        result.programming_time_ms,
-                        match result.verification_result {
-                            Some(true) => "✅ Passed",
-                            Some(false) => "❌ Failed",
-                            None => "Not performed",
-                        }
-                    );
-                    
-                    info!("Flash programming completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
                 }
-                Err(e) => {
-                    error!("Flash programming failed for session {}: {}", args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Flash programming failed\n\n\
-                        Session ID: {}\n\
-                        File: {}\n\
-                        Error: {}\n\n\
-                        Suggestions:\n\
-                        - Check file exists and is readable\n\
-                        - Verify file format is correct\n\
-                        - Ensure flash is erased first\n\
-                        - Check target memory map",
-                        args.session_id, args.file_path, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.read_cycle_counter(args.core_index).await {
+            Ok((count, enabled)) => {
+                let mut message = format!(
+                    "⏱️ Cycle counter\n\n\
+                    Session ID: {}\n\
+                    CYCCNT: {}{}",
+                    args.session_id, count, describe_cycle_time(count as u64, args.cpu_hz)
+                );
+                if !enabled {
+                    message.push_str("\n\n⚠️ The counter is disabled; use 'cycle_counter_enable' to start it");
                 }
+                info!("Read the cycle counter for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Reading the cycle counter failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read the cycle counter: {}", e), &e))
             }
         }
     }
 
-    #[tool(description = "Verify flash memory contents")]
-    async fn flash_verify(&self, Parameters(args): Parameters<FlashVerifyArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Flash verify for session: {}", args.session_id);
-        
+    #[tool(description = "Reset the Cortex-M DWT cycle counter (CYCCNT) to zero")]
+    async fn cycle_counter_reset(&self, Parameters(args): Parameters<CycleCounterArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Resetting the cycle counter for session: {}", args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        // Parse address
-        let address = parse_address(&args.address).map_err(|e| McpError::internal_error(e, None))?;
+        match session_arc.reset_cycle_counter(args.core_index).await {
+            Ok(()) => {
+                info!("Reset the cycle counter for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "⏱️ Cycle counter reset to 0\n\nSession ID: {}", args.session_id
+                ))]))
+            }
+            Err(e) => {
+                error!("Resetting the cycle counter failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to reset the cycle counter: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Measure the cycles between two addresses: runs to start_address and then to end_address with temporary breakpoints, sampling the DWT cycle counter at each halt; reports counter wraparound and microseconds given cpu_hz")]
+    async fn cycle_counter_measure(&self, Parameters(args): Parameters<MeasureCyclesArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Measuring cycles from {} to {} for session: {}", args.start_address, args.end_address, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let start = resolve_code_address(&session_arc, &args.start_address, args.elf_path.as_deref()).await?;
+        let end = resolve_code_address(&session_arc, &args.end_address, args.elf_path.as_deref()).await?;
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.measure_cycles(args.core_index, start, end, timeout, args.cpu_hz).await {
+            Ok(measurement) => {
+                let mut message = format!(
+                    "⏱️ Cycle measurement\n\n\
+                    Session ID: {}\n\
+                    From: 0x{:08X} ({})\n\
+                    To: 0x{:08X} ({})\n\
+                    CYCCNT: {} -> {}\n\
+                    Cycles: {}",
+                    args.session_id,
+                    start, args.start_address, end, args.end_address,
+                    measurement.start_cycles, measurement.end_cycles,
+                    measurement.cycles
+                );
+                if let Some(microseconds) = measurement.microseconds {
+                    message.push_str(&format!("\nTime: {:.3} µs", microseconds));
+                }
+                if measurement.wrapped {
+                    message.push_str("\n\nℹ️ The 32-bit counter wrapped once during the measurement; the count accounts for it");
+                }
+                if measurement.ambiguous {
+                    message.push_str("\n\n⚠️ Enough time passed for the counter to wrap more than once; the count may be short by multiples of 2^32 cycles");
+                }
+                message.push_str("\n\nCore: halted at the end address");
+
+                info!("Measured {} cycles for session: {}", measurement.cycles, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Cycle measurement failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Cycle measurement failed\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    Suggestions:\n\
+                    - Make sure both addresses are executed in order after the current PC\n\
+                    - Increase timeout_ms for long-running code\n\
+                    - A free hardware breakpoint unit is needed for each address",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Analyze a Cortex-M fault: decode CFSR/HFSR/MMFAR/BFAR, locate the stacked exception frame through EXC_RETURN and symbolize the faulting PC and LR; returns JSON")]
+    async fn analyze_fault(&self, Parameters(args): Parameters<AnalyzeFaultArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Analyzing fault for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.analyze_fault(args.core_index).await {
+            Ok(report) => {
+                let json = serde_json::to_string_pretty(&report)
+                    .map_err(|e| tool_error(format!("Failed to serialize the fault report: {}", e), &DebugError::SerializationError(e)))?;
+                info!("Fault analysis for session {}: in_fault={}", args.session_id, report.in_fault);
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => {
+                error!("Fault analysis failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to analyze the fault: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "List the memory regions (flash, RAM, other) of the connected target with their address ranges and access")]
+    async fn get_memory_map(&self, Parameters(args): Parameters<GetMemoryMapArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Getting memory map for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let target_info = {
+            let session = session_arc.lock_session().await
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            let target = session.target();
+            TargetInfo {
+                chip_name: target.name.clone(),
+                architecture: format!("{:?}", target.architecture()),
+                core_type: session.list_cores().first()
+                    .map(|(_, core_type)| format!("{:?}", core_type))
+                    .unwrap_or_default(),
+                memory_map: target.memory_map.iter()
+                    .map(|region| {
+                        let range = region.address_range();
+                        MemoryRegion {
+                            name: memory::region_name(region),
+                            start: range.start,
+                            size: range.end - range.start,
+                            access: memory::region_access(region),
+                        }
+                    })
+                    .collect(),
+            }
+        };
+
+        let mut message = format!(
+            "🗺️ Memory Map ({} region(s))\n\n\
+            Session ID: {}\n\
+            Target: {}\n\
+            Architecture: {}\n\
+            Core type: {}\n\n",
+            target_info.memory_map.len(), args.session_id, target_info.chip_name,
+            target_info.architecture, target_info.core_type
+        );
+
+        if target_info.memory_map.is_empty() {
+            message.push_str("No memory regions are described for this target; addresses are not validated\n");
+        }
+        for region in &target_info.memory_map {
+            message.push_str(&format!(
+                "{:<16} 0x{:08X}..0x{:08X} ({} bytes) {}\n",
+                region.name, region.start, region.start + region.size, region.size, region.access
+            ));
+        }
+
+        info!("Listed {} memory region(s) for session: {}", target_info.memory_map.len(), args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Read core registers by name using the attached architecture's register file (ARM or RISC-V)")]
+    async fn read_registers(&self, Parameters(args): Parameters<ReadRegistersArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading registers {:?} for session: {}", args.registers, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.read_registers(args.core_index, &args.registers).await {
+            Ok(selection) => {
+                let mut message = format!(
+                    "🧮 Registers ({})\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\n",
+                    selection.readings.len(), args.session_id, args.core_index
+                );
+                for reading in &selection.readings {
+                    message.push_str(&format!("{:<12} {}\n", reading.name, reading.display_value()));
+                    if args.decode && reading.name.to_uppercase().starts_with("XPSR") {
+                        if let Some(value) = reading.value {
+                            message.push_str(&format!("{:<12} {}\n", "", decode_xpsr(value as u32)));
+                        }
+                    }
+                }
+                if !selection.unrecognized.is_empty() {
+                    message.push_str(&format!("\n⚠️ Unrecognized: {}\n", selection.unrecognized.join(", ")));
+                }
+
+                info!("Read {} registers for session: {}", selection.readings.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read registers for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read registers: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Write a core register by name, including Cortex-M special registers like CONTROL and PRIMASK")]
+    async fn write_register(&self, Parameters(args): Parameters<WriteRegisterArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing register {} for session: {}", args.register, args.session_id);
+
+        let value = parse_word(args.value.trim())
+            .map_err(|e| invalid_argument(format!("Invalid value {}", e)))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.write_register(args.core_index, &args.register, value, args.verify || args.strict_verify).await {
+            Ok(result) => {
+                if let (Some(false), Some(read_back)) = (result.verified(), result.read_back) {
+                    if args.strict_verify {
+                        return Err(invalid_argument(format!(
+                            "❌ Verification failed for {}: wrote 0x{:08X}, read back 0x{:08X}",
+                            result.name, result.written, read_back
+                        )));
+                    }
+                }
+
+                let mut message = format!(
+                    "✏️ Register write completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Register: {}\n\
+                    Value: 0x{:08X}\n",
+                    args.session_id, args.core_index, result.name, result.written
+                );
+                if let (Some(verified), Some(read_back)) = (result.verified(), result.read_back) {
+                    message.push_str(&format!(
+                        "Verified: {} (read back 0x{:08X})\n", verified, read_back
+                    ));
+                }
+                if let Some(warning) = &result.warning {
+                    message.push_str(&format!("\n⚠️ {}\n", warning));
+                }
+
+                info!("Wrote register {} for session: {}", result.name, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to write register {} for session {}: {}", args.register, args.session_id, e);
+                Err(tool_error(format!("Failed to write register: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read every core register (general purpose, stack pointers, xPSR, special and FPU registers) in one call")]
+    async fn read_all_registers(&self, Parameters(args): Parameters<ReadAllRegistersArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading all registers for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.read_all_registers(args.core_index).await {
+            Ok(readings) => {
+                let mut message = format!(
+                    "🧮 Core Registers ({})\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\n",
+                    readings.len(), args.session_id, args.core_index
+                );
+                for reading in &readings {
+                    message.push_str(&format!("{:<12} {}\n", reading.name, reading.display_value()));
+                }
+
+                info!("Read {} registers for session: {}", readings.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read registers for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read registers: {}", e), &e))
+            }
+        }
+    }
+
+    // =============================================================================
+    // Memory Operation Tools
+    // =============================================================================
+
+    #[tool(description = "Read memory from the target")]
+    async fn read_memory(&self, Parameters(args): Parameters<ReadMemoryArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading memory for session: {} at address {}", args.session_id, args.address);
+        
+        // Parse address
+        let address = match parse_address(&args.address) {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Invalid address '{}': {}", args.address, e);
+                return Err(invalid_argument(format!("Invalid address '{}': {}", args.address, e)));
+            }
+        };
+
+        // Word formats read whole words: either reject misaligned requests or widen them to word boundaries
+        let (address, read_size) = match word_width(&args.format) {
+            Some(width) => {
+                if args.strict {
+                    if address % width as u64 != 0 {
+                        return Err(invalid_argument(format!(
+                            "Address 0x{:08X} is not aligned to the {}-byte word width of format '{}'",
+                            address, width, args.format
+                        )));
+                    }
+                    if !args.size.is_multiple_of(width) {
+                        return Err(invalid_argument(format!(
+                            "Size {} is not a multiple of the {}-byte word width of format '{}'",
+                            args.size, width, args.format
+                        )));
+                    }
+                }
+                align_to_words(address, args.size, width).map_err(invalid_argument)?
+            }
+            None => (address, args.size),
+        };
+
+        if read_size > self.memory_config.max_read_size {
+            let error_msg = format!(
+                "❌ Read size {} exceeds the maximum of {} bytes\n\nRead a smaller region, use 'memory_dump' to save it to a file, or raise memory.max_read_size in the configuration",
+                read_size, self.memory_config.max_read_size
+            );
+            return Err(invalid_argument(error_msg));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let endianness = resolve_endianness(&session_arc, &args.endianness).await?;
+
+        // Read memory
+        match session_arc.read_memory(args.core_index, address, read_size, self.memory_config.read_chunk_size, args.allow_unmapped, args.allow_running).await {
+            Ok(result) if args.format == "json" => {
+                // Structured output carries only the data so clients can parse it directly
+                info!("Memory read completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(format_memory_data(&result.data, "json", address, endianness))]))
+            }
+            Ok(result) => {
+                let formatted_data = format_memory_data(&result.data, &args.format, address, endianness);
+                let core_state = match (result.was_running, args.allow_running, &result.note) {
+                    (false, _, _) => "halted".to_string(),
+                    (true, true, None) => "running (read without halting)".to_string(),
+                    (true, true, Some(note)) => format!("running (⚠️ {})", note),
+                    (true, false, _) => "running (halted for the read and resumed)".to_string(),
+                };
+                let message = format!(
+                    "📖 Memory read completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Size: {} bytes\n\
+                    Format: {}\n\
+                    Endianness: {}\n\
+                    Core state: {}\n\
+                    Duration: {}ms ({:.1} kB/s)\n\n\
+                    Data:\n{}",
+                    args.session_id, address, read_size, args.format, endianness, core_state,
+                    result.elapsed_ms, result.throughput_kbps(), formatted_data
+                );
+
+                info!("Memory read completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Sample a variable in the background while the firmware runs, keeping a rolling history; read it with read_variable_history")]
+    async fn poll_variable(&self, Parameters(args): Parameters<PollVariableArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Polling {} as {} for session: {}", args.variable, args.type_name, args.session_id);
+
+        let scalar_type = ScalarType::parse(&args.type_name)
+            .map_err(invalid_argument)?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // A variable that does not parse as an address is treated as a symbol name
+        let (address, label) = match parse_address(&args.variable) {
+            Ok(address) => (address, format!("0x{:08X}", address)),
+            Err(_) => {
+                let symbol = session_arc.resolve_symbol(&args.variable, args.elf_path.as_deref()).await
+                    .map_err(|e| tool_error(format!("Failed to resolve symbol '{}': {}", args.variable, e), &e))?;
+                (symbol.address, symbol.display_name().to_string())
+            }
+        };
+
+        let interval = std::time::Duration::from_millis(args.interval_ms);
+        match session_arc.start_variable_poll(args.core_index, label, address, scalar_type, interval, args.history).await {
+            Ok(poll) => {
+                let message = format!(
+                    "📈 Variable poll started\n\n\
+                    Session ID: {}\n\
+                    Poll ID: {}\n\
+                    Variable: {} (0x{:08X}, {})\n\
+                    Interval: {}ms\n\
+                    History: {} samples\n\n\
+                    Use 'read_variable_history' with this poll ID to see the samples, 'stop_variable_poll' to stop.",
+                    args.session_id, poll.id, poll.label, poll.address, poll.scalar_type,
+                    poll.interval.as_millis(), poll.capacity
+                );
+                info!("Started variable poll #{} for session: {}", poll.id, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Variable poll failed to start for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to start variable poll: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Return the most recent timestamped samples of variable polls started with poll_variable")]
+    async fn read_variable_history(&self, Parameters(args): Parameters<ReadVariableHistoryArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading variable history for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.variable_poll_history(args.poll_id, args.count) {
+            Ok(polls) => {
+                let mut message = format!("📈 Variable history\n\nSession ID: {}\n", args.session_id);
+                if polls.is_empty() {
+                    message.push_str("\nNo variable polls running; start one with 'poll_variable'\n");
+                }
+                for (poll, total, samples) in &polls {
+                    message.push_str(&format!(
+                        "\n#{} {} (0x{:08X}, {}) every {}ms: {} sample(s) taken, last {} shown\n",
+                        poll.id, poll.label, poll.address, poll.scalar_type, poll.interval.as_millis(), total, samples.len()
+                    ));
+                    for sample in samples {
+                        let value = match &sample.value {
+                            Ok(value) => value.to_string(),
+                            Err(e) => format!("<read failed: {}>", e),
+                        };
+                        message.push_str(&format!("  {}  {}\n", sample.timestamp.format("%H:%M:%S%.3f"), value));
+                    }
+                }
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => Err(tool_error(format!("Failed to read variable history: {}", e), &e)),
+        }
+    }
+
+    #[tool(description = "Stop a variable poll started with poll_variable and discard its history")]
+    async fn stop_variable_poll(&self, Parameters(args): Parameters<StopVariablePollArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Stopping variable poll #{} for session: {}", args.poll_id, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.stop_variable_poll(args.poll_id) {
+            Ok(poll) => {
+                info!("Stopped variable poll #{} for session: {}", poll.id, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "⏹️ Variable poll #{} of {} stopped\n\nSession ID: {}",
+                    poll.id, poll.label, args.session_id
+                ))]))
+            }
+            Err(e) => Err(tool_error(format!("Failed to stop variable poll: {}", e), &e)),
+        }
+    }
+
+    #[tool(description = "Start or stop event notifications for a session: the client is sent a logging notification when the core halts ({session_id, event: \"halted\", reason, pc}) and, optionally, when RTT text arrives ({session_id, event: \"rtt_data\", channel, text})")]
+    async fn subscribe_events(&self, Parameters(args): Parameters<SubscribeEventsArgs>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        debug!("Subscribing to events (enabled: {}) for session: {}", args.enabled, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        if !args.enabled {
+            let message = match session_arc.unsubscribe_events() {
+                Some(subscription) => format!(
+                    "🔕 Event notifications stopped\n\nSession ID: {}\nCore: {}",
+                    args.session_id, subscription.core_index
+                ),
+                None => format!("🔕 No event notifications were active\n\nSession ID: {}", args.session_id),
+            };
+            info!("Event notifications stopped for session: {}", args.session_id);
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<TargetEvent>();
+        let interval = std::time::Duration::from_millis(args.interval_ms);
+        match session_arc.subscribe_events(args.core_index, args.rtt, interval, sender).await {
+            Ok(subscription) => {
+                // Forward until the monitor stops or the client goes away
+                let peer = context.peer.clone();
+                let session_id = args.session_id.clone();
+                tokio::spawn(async move {
+                    while let Some(event) = receiver.recv().await {
+                        let level = match &event {
+                            TargetEvent::Halted { fault: true, .. } => LoggingLevel::Warning,
+                            _ => LoggingLevel::Info,
+                        };
+                        let notification = LoggingMessageNotificationParam {
+                            level,
+                            logger: Some("target-events".to_string()),
+                            data: event.to_json(&session_id),
+                        };
+                        if let Err(e) = peer.notify_logging_message(notification).await {
+                            debug!("Stopped forwarding events of session {}: {}", session_id, e);
+                            break;
+                        }
+                    }
+                });
+
+                let message = format!(
+                    "🔔 Event notifications started\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    RTT data: {}\n\
+                    Interval: {}ms\n\n\
+                    Halts arrive as logging notifications from 'target-events'. \
+                    Call 'subscribe_events' with enabled=false to stop them.",
+                    args.session_id, subscription.core_index,
+                    if subscription.rtt { "forwarded (consumed from the channels)" } else { "not forwarded" },
+                    subscription.interval.as_millis()
+                );
+
+                info!("Event notifications started for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to subscribe to events for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to subscribe to events: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read memory as an array of typed values (u8/u16/u32/u64/i8/i16/i32/i64/f32/f64)")]
+    async fn read_memory_typed(&self, Parameters(args): Parameters<ReadTypedArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading {} x {} for session: {} at address {}", args.count, args.type_name, args.session_id, args.address);
+
+        let address = match parse_address(&args.address) {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Invalid address '{}': {}", args.address, e);
+                return Err(invalid_argument(format!("Invalid address '{}': {}", args.address, e)));
+            }
+        };
+
+        let scalar_type = ScalarType::parse(&args.type_name)
+            .map_err(invalid_argument)?;
+        let endianness = args.endianness.as_deref()
+            .filter(|name| !name.eq_ignore_ascii_case("target"))
+            .map(Endianness::parse)
+            .transpose()
+            .map_err(invalid_argument)?;
+
+        let read_size = args.count.saturating_mul(scalar_type.size());
+        if read_size > self.memory_config.max_read_size {
+            let error_msg = format!(
+                "❌ Read size {} exceeds the maximum of {} bytes\n\nRead fewer values or raise memory.max_read_size in the configuration",
+                read_size, self.memory_config.max_read_size
+            );
+            return Err(invalid_argument(error_msg));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.read_memory_typed(args.core_index, address, scalar_type, args.count, endianness, args.allow_unmapped).await {
+            Ok(result) => {
+                let mut values = String::new();
+                for (index, value) in result.values.iter().enumerate() {
+                    let value_address = address + (index * scalar_type.size()) as u64;
+                    values.push_str(&format!("0x{:08X}: {}\n", value_address, value));
+                }
+                let array = result.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ");
+
+                let message = format!(
+                    "🔢 Typed memory read completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Type: {} x {}\n\
+                    Endianness: {}{}\n\n\
+                    {}\n\
+                    Values: [{}]",
+                    args.session_id, address, scalar_type, args.count, result.endianness,
+                    if endianness.is_none() { " (target default)" } else { "" },
+                    values, array
+                );
+
+                info!("Typed memory read completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read typed memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Sample a memory location repeatedly without halting the core and return the time series")]
+    async fn watch_memory(&self, Parameters(args): Parameters<WatchMemoryArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Watching {} bytes at {} for session: {}", args.size, args.address, args.session_id);
+
+        let address = match parse_address(&args.address) {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Invalid address '{}': {}", args.address, e);
+                return Err(invalid_argument(format!("Invalid address '{}': {}", args.address, e)));
+            }
+        };
+
+        if args.samples == 0 || args.samples > crate::debugger::memory::MAX_WATCH_SAMPLES {
+            return Err(invalid_argument(format!("Samples must be between 1 and {}", crate::debugger::memory::MAX_WATCH_SAMPLES)));
+        }
+        if args.size == 0 || args.size > self.memory_config.max_read_size {
+            return Err(invalid_argument(format!(
+                "Sample size must be between 1 and {} bytes", self.memory_config.max_read_size
+            )));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let endianness = resolve_endianness(&session_arc, &args.endianness).await?;
+
+        let interval = std::time::Duration::from_millis(args.interval_ms);
+        match session_arc.watch_memory(args.core_index, address, args.size, args.samples, interval).await {
+            Ok(series) => {
+                let as_word = matches!(args.size, 1 | 2 | 4 | 8);
+                let mut changes = 0;
+                let mut lines = String::new();
+                for (index, sample) in series.iter().enumerate() {
+                    let changed = index > 0 && sample.data != series[index - 1].data;
+                    if changed {
+                        changes += 1;
+                    }
+                    let value = if as_word {
+                        let word = endianness.word_from_bytes(&sample.data);
+                        format!("0x{:0digits$X} ({})", word, word, digits = args.size * 2)
+                    } else {
+                        hex::encode_upper(&sample.data)
+                    };
+                    lines.push_str(&format!(
+                        "+{:>6}ms: {}{}\n",
+                        sample.elapsed_ms, value, if changed { "  *" } else { "" }
+                    ));
+                }
+
+                let running = series.iter().any(|sample| sample.was_running);
+                let mut message = format!(
+                    "👀 Memory watch completed\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Size: {} bytes\n\
+                    Samples: {} every {}ms\n\
+                    Core state: {}\n\
+                    Changes: {}\n\n\
+                    {}",
+                    args.session_id, address, args.size, series.len(), args.interval_ms,
+                    if running { "running" } else { "halted" }, changes, lines
+                );
+                if let Some(note) = series.iter().find_map(|sample| sample.note.as_ref()) {
+                    message.push_str(&format!("\n⚠️ {}\n", note));
+                }
+
+                info!("Memory watch of {} samples completed for session: {}", series.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Memory watch failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to watch memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Write memory to the target")]
+    async fn write_memory(&self, Parameters(args): Parameters<WriteMemoryArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing memory for session: {} at address {}", args.session_id, args.address);
+        
+        // Parse address
+        let address = match parse_address(&args.address) {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Invalid address '{}': {}", args.address, e);
+                return Err(invalid_argument(format!("Invalid address '{}': {}", args.address, e)));
+            }
+        };
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse data based on format
+        let endianness = resolve_endianness(&session_arc, &args.endianness).await?;
+        let data = match parse_data(&args.data, &args.format, endianness) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Invalid data '{}': {}", args.data, e);
+                return Err(invalid_argument(format!("Invalid data '{}': {}", args.data, e)));
+            }
+        };
+
+        // Write memory
+        match session_arc.write_memory(args.core_index, address, &data, args.verify || args.strict_verify, args.allow_unmapped).await {
+            Ok(result) => {
+                if let Some(mismatches) = result.mismatches.as_ref().filter(|m| !m.is_empty()) {
+                    if args.strict_verify {
+                        return Err(invalid_argument(format!(
+                            "❌ Verification failed: {} byte(s) at 0x{:08X} differ after read-back (first at +0x{:X})",
+                            mismatches.len(), address, mismatches[0]
+                        )));
+                    }
+                }
+
+                let mut message = format!(
+                    "✏️ Memory write completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Data: {}\n\
+                    Format: {}\n\
+                    Bytes written: {}",
+                    args.session_id, address, args.data, args.format, result.bytes_written
+                );
+
+                if let Some(mismatches) = &result.mismatches {
+                    if mismatches.is_empty() {
+                        message.push_str("\nVerification: ✅ Read-back matches\nVerified: true");
+                    } else {
+                        message.push_str(&format!(
+                            "\nVerification: ❌ {} byte(s) differ after read-back\nMismatch offsets: {}",
+                            mismatches.len(),
+                            mismatches.iter().take(32).map(|o| format!("+0x{:X}", o)).collect::<Vec<_>>().join(", ")
+                        ));
+                        if mismatches.len() > 32 {
+                            message.push_str(&format!(" ... and {} more", mismatches.len() - 32));
+                        }
+                        message.push_str("\nVerified: false");
+                    }
+                }
+                if let Some(read_back) = &result.read_back {
+                    let shown = &read_back[..read_back.len().min(64)];
+                    message.push_str(&format!("\nRead back: {}", hex::encode_upper(shown)));
+                    if read_back.len() > shown.len() {
+                        message.push_str(&format!(" ... ({} bytes total)", read_back.len()));
+                    }
+                }
+                
+                info!("Memory write completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to write memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to write memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read several memory regions in one call; each entry succeeds or fails independently")]
+    async fn read_memory_batch(&self, Parameters(args): Parameters<ReadMemoryBatchArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Batch reading {} region(s) for session: {}", args.reads.len(), args.session_id);
+
+        let Some(total_size) = batch_total(args.reads.iter().map(|entry| entry.size), self.memory_config.max_batch_read_size) else {
+            let error_msg = format!(
+                "❌ Batch reads more than the maximum of {} bytes in total\n\nSplit the batch or raise memory.max_batch_read_size in the configuration",
+                self.memory_config.max_batch_read_size
+            );
+            return Err(invalid_argument(error_msg));
+        };
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let (addresses, requests) = batch_read_requests(&args.reads);
+        let endianness = session_arc.target_endianness().await
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let mut results = match session_arc.read_memory_batch(args.core_index, &requests, args.allow_unmapped).await {
+            Ok(results) => results.into_iter(),
+            Err(e) => {
+                error!("Batch read failed for session {}: {}", args.session_id, e);
+                return Err(tool_error(format!("Failed to read memory: {}", e), &e));
+            }
+        };
+
+        let mut succeeded = 0;
+        let mut message = String::new();
+        for (index, (entry, address)) in args.reads.iter().zip(addresses).enumerate() {
+            let outcome = address
+                .map_err(|e| format!("Invalid address '{}': {}", entry.address, e))
+                .and_then(|address| {
+                    let data = results.next().unwrap_or_else(|| Err(crate::error::DebugError::InternalError("Missing read result".to_string())));
+                    data.map(|data| (address, data)).map_err(|e| e.to_string())
+                });
+
+            match outcome {
+                Ok((address, data)) => {
+                    succeeded += 1;
+                    message.push_str(&format!(
+                        "\n[{}] ✅ 0x{:08X} ({} bytes, {})\n{}\n",
+                        index, address, data.len(), entry.format,
+                        format_memory_data(&data, &entry.format, address, endianness)
+                    ));
+                }
+                Err(e) => {
+                    message.push_str(&format!("\n[{}] ❌ {}: {}\n", index, entry.address, e));
+                }
+            }
+        }
+
+        let message = format!(
+            "📖 Batch memory read ({}/{} succeeded)\n\n\
+            Session ID: {}\n\
+            Total bytes requested: {}\n{}",
+            succeeded, args.reads.len(), args.session_id, total_size, message
+        );
+
+        info!("Batch read {}/{} region(s) for session: {}", succeeded, args.reads.len(), args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Apply several memory writes back-to-back in one call, optionally halting the core so they appear atomic")]
+    async fn write_memory_batch(&self, Parameters(args): Parameters<WriteMemoryBatchArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Batch writing {} region(s) for session: {}", args.writes.len(), args.session_id);
+
+        // Validate every entry before touching the target
+        let mut writes = Vec::with_capacity(args.writes.len());
+        for (index, entry) in args.writes.iter().enumerate() {
+            let address = parse_address(&entry.address)
+                .map_err(|e| invalid_argument(format!("Entry {}: invalid address '{}': {}", index, entry.address, e)))?;
+            let data = parse_data(&entry.data, &entry.format, Endianness::Little)
+                .map_err(|e| invalid_argument(format!("Entry {}: invalid data: {}", index, e)))?;
+            writes.push((address, data));
+        }
+
+        let total_size: usize = writes.iter().map(|(_, data)| data.len()).sum();
+        if total_size > self.memory_config.max_write_size {
+            let error_msg = format!(
+                "❌ Batch writes {} bytes in total, exceeding the maximum of {} bytes",
+                total_size, self.memory_config.max_write_size
+            );
+            return Err(invalid_argument(error_msg));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.write_memory_batch(args.core_index, &writes, args.halt_during_write, args.stop_on_error, args.allow_unmapped).await {
+            Ok(statuses) => {
+                use crate::debugger::memory::BatchWriteStatus;
+
+                let written = statuses.iter().filter(|status| matches!(status, BatchWriteStatus::Written)).count();
+                let mut message = format!(
+                    "{} Batch memory write ({}/{} written)\n\n\
+                    Session ID: {}\n\
+                    Halted during write: {}\n\n",
+                    if written == statuses.len() { "✅" } else { "⚠️" },
+                    written, statuses.len(), args.session_id, args.halt_during_write
+                );
+
+                for (index, ((address, data), status)) in writes.iter().zip(&statuses).enumerate() {
+                    let line = match status {
+                        BatchWriteStatus::Written => format!("[{}] ✅ 0x{:08X}: {} bytes written", index, address, data.len()),
+                        BatchWriteStatus::Failed(e) => format!("[{}] ❌ 0x{:08X}: {}", index, address, e),
+                        BatchWriteStatus::Skipped => format!("[{}] ⏭️ 0x{:08X}: skipped after earlier failure", index, address),
+                    };
+                    message.push_str(&line);
+                    message.push('\n');
+                }
+
+                info!("Batch wrote {}/{} region(s) for session: {}", written, statuses.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Batch write failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to write memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Run a sequence of halt/read/write/run operations atomically, without other requests interleaving")]
+    async fn batch(&self, Parameters(args): Parameters<BatchArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Running batch of {} operation(s) for session: {}", args.operations.len(), args.session_id);
+
+        // Validate every operation before touching the target
+        let mut ops = Vec::with_capacity(args.operations.len());
+        for (index, entry) in args.operations.iter().enumerate() {
+            let address = || -> Result<u64, McpError> {
+                let text = entry.address.as_deref()
+                    .ok_or_else(|| invalid_argument(format!("Entry {}: '{}' needs an address", index, entry.op)))?;
+                parse_address(text)
+                    .map_err(|e| invalid_argument(format!("Entry {}: invalid address '{}': {}", index, text, e)))
+            };
+            let op = match entry.op.to_lowercase().as_str() {
+                "halt" => BatchOp::Halt,
+                "run" | "resume" => BatchOp::Run,
+                "read" => {
+                    let size = entry.size
+                        .ok_or_else(|| invalid_argument(format!("Entry {}: 'read' needs a size", index)))?;
+                    BatchOp::Read { address: address()?, size }
+                }
+                "write" => {
+                    let data = entry.data.as_deref()
+                        .ok_or_else(|| invalid_argument(format!("Entry {}: 'write' needs data", index)))?;
+                    let data = parse_data(data, &entry.format, Endianness::Little)
+                        .map_err(|e| invalid_argument(format!("Entry {}: invalid data: {}", index, e)))?;
+                    BatchOp::Write { address: address()?, data }
+                }
+                other => {
+                    return Err(invalid_argument(format!("Entry {}: unknown operation '{}'. Use halt, run, read or write", index, other)));
+                }
+            };
+            ops.push(op);
+        }
+
+        if batch_op_sizes(&ops, self.memory_config.max_read_size, self.memory_config.max_write_size).is_none() {
+            let error_msg = format!(
+                "❌ Batch reads or writes more than the maximum of {} / {} bytes in total",
+                self.memory_config.max_read_size, self.memory_config.max_write_size
+            );
+            return Err(invalid_argument(error_msg));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.run_batch(args.core_index, &ops, args.stop_on_error, args.allow_unmapped).await {
+            Ok(results) => {
+                let failed = results.iter().filter(|result| matches!(result, BatchOpResult::Failed(_))).count();
+                let mut message = format!(
+                    "{} Batch completed ({} operation(s), {} failed)\n\n\
+                    Session ID: {}\n\n",
+                    if failed == 0 { "✅" } else { "⚠️" },
+                    results.len(), failed, args.session_id
+                );
+
+                for (index, (op, result)) in ops.iter().zip(&results).enumerate() {
+                    let line = match result {
+                        BatchOpResult::Halted { pc } => format!("[{}] ✅ halt: halted at PC 0x{:08X}", index, pc),
+                        BatchOpResult::Resumed => format!("[{}] ✅ run: core resumed", index),
+                        BatchOpResult::Read(data) => format!(
+                            "[{}] ✅ {}: {}",
+                            index, op, data.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+                        ),
+                        BatchOpResult::Written(len) => format!("[{}] ✅ {}: {} bytes written", index, op, len),
+                        BatchOpResult::Failed(e) => format!("[{}] ❌ {}: {}", index, op, e),
+                        BatchOpResult::Skipped => format!("[{}] ⏭️ {}: skipped after earlier failure", index, op),
+                    };
+                    message.push_str(&line);
+                    message.push('\n');
+                }
+
+                info!("Batch ran {} operation(s) for session: {}", results.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Batch failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to run batch: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read a peripheral register with a single aligned 32-bit or 16-bit bus access")]
+    async fn read_word(&self, Parameters(args): Parameters<ReadWordArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading {}-bit word for session: {} at address {}", args.width, args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let value = match args.width {
+            32 => session_arc.read_word32(args.core_index, address, args.allow_unmapped).await
+                .map(|value| format!("0x{:08X} ({})", value, value)),
+            16 => session_arc.read_word16(args.core_index, address, args.allow_unmapped).await
+                .map(|value| format!("0x{:04X} ({})", value, value)),
+            other => return Err(invalid_argument(format!("Invalid width {}: use 32 or 16", other))),
+        };
+
+        match value {
+            Ok(value) => {
+                let message = format!(
+                    "📖 {}-bit read\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Value: {}",
+                    args.width, args.session_id, address, value
+                );
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Word read failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read word: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Write a peripheral register with a single aligned 32-bit or 16-bit bus access")]
+    async fn write_word(&self, Parameters(args): Parameters<WriteWordArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing {}-bit word for session: {} at address {}", args.width, args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+        let value = parse_word(&args.value)
+            .map_err(|e| invalid_argument(format!("Invalid value {}", e)))?;
+        let max = match args.width {
+            32 => u32::MAX as u64,
+            16 => u16::MAX as u64,
+            other => return Err(invalid_argument(format!("Invalid width {}: use 32 or 16", other))),
+        };
+        if value > max {
+            return Err(invalid_argument(format!("Value 0x{:X} does not fit in {} bits", value, args.width)));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let result = if args.width == 32 {
+            session_arc.write_word32(args.core_index, address, value as u32, args.allow_unmapped).await
+        } else {
+            session_arc.write_word16(args.core_index, address, value as u16, args.allow_unmapped).await
+        };
+
+        match result {
+            Ok(()) => {
+                let message = format!(
+                    "✏️ {}-bit write\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Value: 0x{:0width$X}",
+                    args.width, args.session_id, address, value, width = (args.width / 4) as usize
+                );
+                info!("Wrote {}-bit word for session: {}", args.width, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Word write failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to write word: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Write a static variable by name, encoding the JSON value for its DWARF type; objects only change the struct fields they name")]
+    async fn write_variable(&self, Parameters(args): Parameters<WriteVariableArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing variable {} for session: {}", args.name, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.write_variable(args.core_index, &args.name, &args.value, args.elf_path.as_deref(), args.verify).await {
+            Ok(result) => {
+                let verification = match &result.mismatches {
+                    None => String::new(),
+                    Some(mismatches) if mismatches.is_empty() => "\nVerification: ✅ read-back matches".to_string(),
+                    Some(mismatches) => format!(
+                        "\nVerification: ⚠️ {} byte(s) differ at offsets {}",
+                        mismatches.len(),
+                        mismatches.iter().map(|offset| offset.to_string()).collect::<Vec<_>>().join(", ")
+                    ),
+                };
+                let message = format!(
+                    "✏️ Variable written\n\n\
+                    Session ID: {}\n\
+                    Variable: {}: {}\n\
+                    Address: 0x{:08X} ({} bytes)\n\
+                    Value: {}\n\
+                    Bytes written: {}{}",
+                    args.session_id, result.variable.name, result.variable.ty.name(),
+                    result.variable.address, result.variable.ty.size(),
+                    args.value, result.bytes_written, verification
+                );
+                info!("Wrote variable {} for session: {}", result.variable.name, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Variable write failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to write variable '{}': {}", args.name, e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Fill a memory region with a repeating 1, 2 or 4 byte pattern")]
+    async fn memory_fill(&self, Parameters(args): Parameters<MemoryFillArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Filling memory for session: {} at address {}", args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let pattern = crate::debugger::memory::parse_fill_pattern(&args.pattern)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.fill_memory(args.core_index, address, args.size, &pattern, args.allow_flash, args.allow_unmapped).await {
+            Ok(result) => {
+                let message = format!(
+                    "🧱 Memory fill completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Pattern: {} ({} byte(s))\n\
+                    Bytes written: {}\n\
+                    Duration: {}ms",
+                    args.session_id, address, args.pattern, result.pattern.len(),
+                    result.bytes_written, result.elapsed_ms
+                );
+
+                info!("Memory fill completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to fill memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to fill memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Search a memory region for a hex byte pattern or ASCII string")]
+    async fn memory_search(&self, Parameters(args): Parameters<MemorySearchArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Searching memory for session: {} at address {}", args.session_id, args.start_address);
+
+        let address = parse_address(&args.start_address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.start_address, e)))?;
+
+        let pattern = match args.pattern_format.as_str() {
+            "hex" | "ascii" => parse_data(&args.pattern, &args.pattern_format, Endianness::Little)
+                .map_err(|e| invalid_argument(format!("Invalid pattern: {}", e)))?,
+            other => {
+                return Err(invalid_argument(format!("Unsupported pattern format '{}'. Use 'hex' or 'ascii'", other)));
+            }
+        };
+
+        if args.size > self.memory_config.max_search_size {
+            let error_msg = format!(
+                "❌ Search size {} exceeds the maximum of {} bytes\n\nSearch a smaller region or raise memory.max_search_size in the configuration",
+                args.size, self.memory_config.max_search_size
+            );
+            return Err(invalid_argument(error_msg));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.search_memory(args.core_index, address, args.size, &pattern, args.max_results, args.allow_unmapped).await {
+            Ok(matches) => {
+                let mut message = format!(
+                    "🔍 Memory search completed\n\n\
+                    Session ID: {}\n\
+                    Range: 0x{:08X}..0x{:08X}\n\
+                    Pattern: {} ({} byte(s))\n\
+                    Matches: {}{}\n",
+                    args.session_id, address, address + args.size as u64,
+                    args.pattern, pattern.len(), matches.len(),
+                    if matches.len() >= args.max_results { " (limit reached)" } else { "" }
+                );
+
+                for hit in &matches {
+                    let context = hit.context.iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    message.push_str(&format!(
+                        "\n0x{:08X}  (context from 0x{:08X}: {})",
+                        hit.address, hit.context_address, context
+                    ));
+                }
+
+                info!("Memory search found {} match(es) for session: {}", matches.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to search memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to search memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Dump a memory region to a host file in raw binary, Intel HEX or S-record format, sending progress notifications when the request carries a progress token")]
+    async fn memory_dump(&self, Parameters(args): Parameters<MemoryDumpArgs>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        debug!("Dumping memory for session: {} at address {} to {}", args.session_id, args.address, args.file_path);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let format = crate::debugger::dump::DumpFormat::parse(&args.format)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let progress = ProgressForwarder::start(&context);
+        let outcome = session_arc.dump_memory_to_file(
+            args.core_index, address, args.size, &args.file_path, format, args.chunk_size, args.overwrite,
+            progress.as_ref().map(|progress| &progress.sink),
+        ).await;
+        finish_progress(progress).await;
+        match outcome {
+            Ok(result) => {
+                let rate_kbps = if result.elapsed_ms > 0 {
+                    result.bytes_read as f64 / 1024.0 / (result.elapsed_ms as f64 / 1000.0)
+                } else {
+                    0.0
+                };
+                let message = format!(
+                    "💾 Memory dump completed successfully!\n\n\
+                    Session ID: {}\n\
+                    Range: 0x{:08X}..0x{:08X}\n\
+                    File: {}\n\
+                    Format: {}\n\
+                    Bytes read: {}\n\
+                    File size: {} bytes\n\
+                    Duration: {}ms\n\
+                    Throughput: {:.1} kB/s",
+                    args.session_id, address, address + result.bytes_read as u64,
+                    args.file_path, format, result.bytes_read, result.file_size,
+                    result.elapsed_ms, rate_kbps
+                );
+
+                info!("Memory dump completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to dump memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to dump memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Halt the core and save its registers and RAM (or chosen regions) as an ELF core file for GDB, or in probe-rs's coredump format, resuming it afterwards")]
+    async fn core_dump(&self, Parameters(args): Parameters<CoreDumpArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing core dump for session: {} to {}", args.session_id, args.file_path);
+
+        let format = crate::debugger::coredump::CoreDumpFormat::parse(&args.format)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+        let regions = match &args.regions {
+            Some(regions) => Some(
+                regions.iter()
+                    .map(|region| parse_address(&region.address).map(|address| (address, region.size)).map_err(|e| {
+                        invalid_argument(format!("Invalid address '{}': {}", region.address, e))
+                    }))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.core_dump(
+            args.core_index, &args.file_path, regions, format, args.chunk_size, args.stay_halted, args.overwrite,
+        ).await {
+            Ok(result) => {
+                let regions: Vec<String> = result.regions.iter()
+                    .map(|(address, size)| format!("  0x{:08X}..0x{:08X} ({} bytes)", address, address + *size as u64, size))
+                    .collect();
+                let open_hint = match format {
+                    crate::debugger::coredump::CoreDumpFormat::Elf => "gdb <firmware.elf> <core file>",
+                    crate::debugger::coredump::CoreDumpFormat::ProbeRs => "probe-rs coredump tooling (CoreDump::load)",
+                };
+                let message = format!(
+                    "🧊 Core dump completed successfully!\n\n\
+                    Session ID: {}\n\
+                    File: {}\n\
+                    Format: {}\n\
+                    Registers: {}\n\
+                    Regions:\n{}\n\
+                    File size: {} bytes\n\
+                    Core state: {}\n\
+                    Duration: {}ms\n\n\
+                    Open with: {}",
+                    args.session_id, args.file_path, format, result.registers, regions.join("\n"),
+                    result.file_size,
+                    if result.resumed { "resumed" } else { "halted" },
+                    result.elapsed_ms, open_hint
+                );
+                info!("Core dump completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Core dump failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to write the core dump: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Paint the unused stack with a pattern so measure_stack_usage can report peak stack usage; the region comes from the ELF stack symbols unless given")]
+    async fn paint_stack(&self, Parameters(args): Parameters<PaintStackArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Painting stack for session: {}", args.session_id);
+
+        let region = stack_region_arg(&args.address, args.size)?;
+        let pattern = crate::debugger::memory::parse_fill_pattern(&args.pattern)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.paint_stack(args.core_index, region, &pattern, args.elf_path.as_deref()).await {
+            Ok(paint) => {
+                let message = format!(
+                    "🎨 Stack painted\n\n\
+                    Session ID: {}\n\
+                    Stack region: 0x{:08X}..0x{:08X} ({} bytes)\n\
+                    Painted: 0x{:08X}..0x{:08X} ({} bytes)\n\
+                    Stack pointer: 0x{:08X}{}\n\
+                    Pattern: {}\n\
+                    Duration: {}ms\n\n\
+                    Let the firmware run its workload, then use 'measure_stack_usage'",
+                    args.session_id,
+                    paint.region.start, paint.region.end, paint.region.end - paint.region.start,
+                    paint.painted.start, paint.painted.end, paint.painted.end - paint.painted.start,
+                    paint.stack_pointer,
+                    if paint.was_running { " (core running; a margin below the SP was left unpainted)" } else { "" },
+                    args.pattern, paint.elapsed_ms
+                );
+                info!("Painted stack for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Stack paint failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to paint the stack: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Report peak stack usage since paint_stack: scans the stack region from its low end for the first overwritten byte and compares with the current SP")]
+    async fn measure_stack_usage(&self, Parameters(args): Parameters<MeasureStackUsageArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Measuring stack usage for session: {}", args.session_id);
+
+        let region = stack_region_arg(&args.address, args.size)?;
+        let pattern = crate::debugger::memory::parse_fill_pattern(&args.pattern)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.measure_stack_usage(args.core_index, region, &pattern, args.elf_path.as_deref()).await {
+            Ok(usage) => {
+                let size = usage.region.end - usage.region.start;
+                let warning = if usage.peak_bytes == size {
+                    "\n\n⚠️ No paint is left: the stack was never painted, or it has used the whole region and likely overflowed"
+                } else if usage.peak_percent() >= 90.0 {
+                    "\n\n⚠️ Peak usage is close to the size of the stack"
+                } else {
+                    ""
+                };
+                let message = format!(
+                    "📏 Stack usage\n\n\
+                    Session ID: {}\n\
+                    Stack region: 0x{:08X}..0x{:08X} ({} bytes)\n\
+                    Peak usage: {} bytes ({:.1}%), deepest at 0x{:08X}\n\
+                    Headroom: {} bytes\n\
+                    Stack pointer: 0x{:08X} ({} bytes in use now){}",
+                    args.session_id,
+                    usage.region.start, usage.region.end, size,
+                    usage.peak_bytes, usage.peak_percent(), usage.region.end - usage.peak_bytes,
+                    size - usage.peak_bytes,
+                    usage.stack_pointer, usage.current_bytes, warning
+                );
+                info!("Measured stack usage for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Stack measurement failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to measure stack usage: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Compute a CRC32 or SHA-256 digest of a memory region without transferring its contents")]
+    async fn memory_checksum(&self, Parameters(args): Parameters<MemoryChecksumArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Checksumming memory for session: {} at address {}", args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let algorithm = crate::debugger::memory::ChecksumAlgorithm::parse(&args.algorithm)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.checksum_memory(args.core_index, address, args.size, algorithm).await {
+            Ok(result) => {
+                let rate_kbps = if result.elapsed_ms > 0 {
+                    result.bytes_read as f64 / 1024.0 / (result.elapsed_ms as f64 / 1000.0)
+                } else {
+                    0.0
+                };
+                let message = format!(
+                    "🔐 Memory checksum computed\n\n\
+                    Session ID: {}\n\
+                    Range: 0x{:08X}..0x{:08X}\n\
+                    Algorithm: {}\n\
+                    Digest: {}\n\
+                    Bytes read: {}\n\
+                    Duration: {}ms\n\
+                    Throughput: {:.1} kB/s",
+                    args.session_id, address, address + result.bytes_read as u64,
+                    algorithm, result.digest, result.bytes_read, result.elapsed_ms, rate_kbps
+                );
+
+                info!("Memory checksum completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to checksum memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to checksum memory: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Compare target memory against a binary/ELF file or hex data and list the differences")]
+    async fn memory_compare(&self, Parameters(args): Parameters<MemoryCompareArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Comparing memory for session: {}", args.session_id);
+
+        let address = args.address.as_deref()
+            .map(parse_address)
+            .transpose()
+            .map_err(|e| invalid_argument(format!("Invalid address: {}", e)))?;
+
+        // Build the reference segments
+        let (reference, segments) = if let Some(file_path) = &args.file_path {
+            let file_data = std::fs::read(file_path)
+                .map_err(|e| tool_error(format!("Failed to read file {}: {}", file_path, e), &DebugError::IoError(e)))?;
+            let elf_segments = crate::debugger::memory::elf_load_segments(&file_data)
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            match (elf_segments, address) {
+                (Some(segments), _) => (format!("{} (ELF, {} segment(s))", file_path, segments.len()), segments),
+                (None, Some(address)) => (file_path.clone(), vec![(address, file_data)]),
+                (None, None) => return Err(invalid_argument("address is required for non-ELF files".to_string())),
+            }
+        } else if let Some(hex_data) = &args.data {
+            let data = parse_data(hex_data, "hex", Endianness::Little)
+                .map_err(|e| invalid_argument(format!("Invalid hex data: {}", e)))?;
+            let address = address
+                .ok_or_else(|| invalid_argument("address is required when comparing against data".to_string()))?;
+            ("hex data".to_string(), vec![(address, data)])
+        } else {
+            return Err(invalid_argument("Either file_path or data must be provided".to_string()));
+        };
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.compare_memory(args.core_index, &segments, args.max_diffs).await {
+            Ok(result) => {
+                let mut message = format!(
+                    "{} Memory comparison {}\n\n\
+                    Session ID: {}\n\
+                    Reference: {}\n\
+                    Identical: {}\n\
+                    Bytes compared: {}\n\
+                    Mismatches: {}\n",
+                    if result.identical() { "✅" } else { "❌" },
+                    if result.identical() { "matched" } else { "found differences" },
+                    args.session_id, reference, result.identical(),
+                    result.bytes_compared, result.mismatch_count
+                );
+
+                if !result.diffs.is_empty() {
+                    message.push_str("\nDifferences:\n");
+                    for diff in &result.diffs {
+                        message.push_str(&format!(
+                            "0x{:08X} (offset {}): expected 0x{:02X}, actual 0x{:02X}\n",
+                            diff.address, diff.offset, diff.expected, diff.actual
+                        ));
+                    }
+                    if result.mismatch_count > result.diffs.len() {
+                        message.push_str(&format!("... and {} more\n", result.mismatch_count - result.diffs.len()));
+                    }
+                }
+
+                if let Some(e) = &result.error {
+                    message.push_str(&format!("\n⚠️ Comparison incomplete: {}\n", e));
+                }
+
+                info!("Memory comparison completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to compare memory for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to compare memory: {}", e), &e))
+            }
+        }
+    }
+
+    // =============================================================================
+    // Breakpoint Tools
+    // =============================================================================
+
+    #[tool(description = "Set a breakpoint at an address, a symbol or a source file:line")]
+    async fn set_breakpoint(&self, Parameters(args): Parameters<SetBreakpointArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Setting breakpoint for session: {} at {:?}", args.session_id, args.address.as_ref().or(args.symbol.as_ref()));
+        
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let software = parse_breakpoint_type(&args.breakpoint_type)
+            .map_err(invalid_argument)?;
+
+        let source_line = match (&args.file, args.line) {
+            (Some(file), Some(line)) => Some((file.clone(), line)),
+            (None, None) => None,
+            _ => {
+                return Err(invalid_argument("Provide file and line together".to_string()));
+            }
+        };
+        if source_line.is_some() && (args.address.is_some() || args.symbol.is_some()) {
+            return Err(invalid_argument("Provide either file and line, an address or a symbol".to_string()));
+        }
+
+        // An address that does not parse as a number is treated as a symbol name
+        let symbol_name = match (&args.address, &args.symbol) {
+            (None, None) if source_line.is_some() => None,
+            (Some(_), Some(_)) => {
+                return Err(invalid_argument("Provide either address or symbol, not both".to_string()));
+            }
+            (None, None) => {
+                return Err(invalid_argument("Provide an address or a symbol for the breakpoint".to_string()));
+            }
+            (None, Some(symbol)) => Some(symbol.clone()),
+            (Some(address), None) => parse_address(address).err().map(|_| address.clone()),
+        };
+
+        let mut location = None;
+        let (address, symbol) = match (&symbol_name, &source_line) {
+            (None, Some((file, line))) => {
+                match session_arc.resolve_source_line(file, *line, args.elf_path.as_deref()).await {
+                    Ok((address, resolved)) => {
+                        info!("Resolved {}:{} to 0x{:08X} at {}", file, line, address, resolved);
+                        let symbol = format!("{}:{}", file, resolved.line);
+                        location = Some(resolved);
+                        (address, Some(symbol))
+                    }
+                    Err(e) => {
+                        error!("Failed to resolve {}:{}: {}", file, line, e);
+                        return Err(tool_error(format!("Failed to resolve {}:{}: {}", file, line, e), &e));
+                    }
+                }
+            }
+            (Some(name), _) => match session_arc.resolve_symbol(name, args.elf_path.as_deref()).await {
+                Ok(symbol) => {
+                    info!("Resolved symbol '{}' to 0x{:08X}", name, symbol.address);
+                    (symbol.address, Some(symbol.display_name().to_string()))
+                }
+                Err(e) => {
+                    error!("Failed to resolve symbol '{}': {}", name, e);
+                    return Err(tool_error(format!(
+                        "Failed to resolve symbol '{}': {}\n\nLoad the firmware with 'load_symbols' or pass elf_path",
+                        name, e
+                    ), &e));
+                }
+            },
+            (None, None) => {
+                let address = parse_address(args.address.as_deref().unwrap_or_default())
+                    .map_err(invalid_argument)?;
+                (address, None)
+            }
+        };
+
+        match session_arc.set_breakpoint(args.core_index, address, software, symbol, args.temporary, args.skip_count).await {
+            Ok(breakpoint) => {
+                let mut message = format!(
+                    "🎯 Breakpoint set successfully!\n\n\
+                    Session ID: {}\n\
+                    Breakpoint: #{}\n\
+                    Address: 0x{:08X}\n\
+                    {}\
+                    Type: {} breakpoint\n\n\
+                    The target will halt when execution reaches this address.",
+                    args.session_id, breakpoint.id, address,
+                    breakpoint.symbol.as_ref().map(|symbol| format!("Symbol: {}\n", symbol)).unwrap_or_default(),
+                    if breakpoint.kind.is_software() { "Software" } else { "Hardware" }
+                );
+                if let (Some(location), Some((_, line))) = (&location, &source_line) {
+                    message.push_str(&format!("\nSource: {}", location));
+                    if location.line != *line {
+                        message.push_str(&format!("\nLine {} has no code; using line {} instead", line, location.line));
+                    }
+                }
+                if breakpoint.temporary {
+                    message.push_str("\nTemporary: removed by 'wait_for_halt' once the core halts on it.");
+                }
+                if breakpoint.skip_count > 0 {
+                    message.push_str(&format!(
+                        "\nSkip count: 'wait_for_halt' resumes the first {} hit(s) (hits so far: {}).",
+                        breakpoint.skip_count, breakpoint.hit_count
+                    ));
+                }
+                if breakpoint.kind.is_software() {
+                    message.push_str("\nClear the breakpoint before resuming from it, or the core halts again immediately.");
+                }
+
+                info!("Breakpoint set for session: {} at 0x{:08X}", args.session_id, address);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to set breakpoint for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to set breakpoint: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Load (or reload) the firmware ELF symbol and line tables for a session so tools accept function names")]
+    async fn load_symbols(&self, Parameters(args): Parameters<LoadSymbolsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Loading symbols from {} for session: {}", args.elf_path, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.load_symbols(&args.elf_path).await {
+            Ok((symbols, lines)) => {
+                let message = format!(
+                    "📚 Symbols loaded\n\n\
+                    Session ID: {}\n\
+                    ELF: {}\n\
+                    Build ID: {}\n\
+                    Symbols: {}\n\
+                    Line info: {}\n\n\
+                    Symbol names can now be used in place of addresses, e.g. set_breakpoint with symbol \"main\".",
+                    args.session_id, args.elf_path, symbols.build_id().unwrap_or("none"), symbols.len(),
+                    if lines.is_some() { "available" } else { "not available (no DWARF line table)" }
+                );
+
+                info!("Loaded {} symbols for session: {}", symbols.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to load symbols for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to load symbols: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Forget the firmware ELF and cached symbol/line tables of a session")]
+    async fn unload_symbols(&self, Parameters(args): Parameters<UnloadSymbolsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Unloading symbols for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let message = match session_arc.unload_symbols().await {
+            Some(elf_path) => format!(
+                "📚 Symbols unloaded\n\n\
+                Session ID: {}\n\
+                ELF: {}",
+                args.session_id, elf_path
+            ),
+            None => format!("📚 No symbols were loaded\n\nSession ID: {}", args.session_id),
+        };
+
+        info!("Unloaded symbols for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Map addresses to function, offset and source line using the loaded ELF, including inlined functions")]
+    async fn symbolize_address(&self, Parameters(args): Parameters<SymbolizeAddressArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Symbolizing {} address(es) for session: {}", args.addresses.len(), args.session_id);
+
+        let addresses = args.addresses.iter()
+            .map(|address| parse_address(address)
+                .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", address, e))))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.symbolize(&addresses, args.elf_path.as_deref()).await {
+            Ok(results) => {
+                let mut message = format!(
+                    "🔎 Symbolized {} address(es)\n\n\
+                    Session ID: {}\n\n",
+                    results.len(), args.session_id
+                );
+
+                for result in &results {
+                    let function = match (&result.function, result.offset) {
+                        (Some(function), Some(offset)) => format!("{}+0x{:X}", function, offset),
+                        (Some(function), None) => function.clone(),
+                        (None, _) => "<unknown>".to_string(),
+                    };
+                    message.push_str(&format!("0x{:08X} in {}", result.address, function));
+                    if let Some(location) = &result.location {
+                        message.push_str(&format!("\n    at {}", location));
+                    }
+                    for frame in &result.inlined {
+                        message.push_str(&format!(
+                            "\n    inlined: {}{}",
+                            frame.function.as_deref().unwrap_or("<unknown>"),
+                            frame.location.as_ref().map(|location| format!(" at {}", location)).unwrap_or_default()
+                        ));
+                    }
+                    message.push('\n');
+                }
+
+                info!("Symbolized {} address(es) for session: {}", results.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to symbolize addresses for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to symbolize addresses: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Look up symbols by name in the loaded ELF, returning address, size and section")]
+    async fn lookup_symbol(&self, Parameters(args): Parameters<LookupSymbolArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Looking up {} symbol(s) for session: {}", args.names.len(), args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Load the table up front so a missing ELF fails the call rather than every name
+        session_arc.symbols(args.elf_path.as_deref()).await
+            .map_err(|e| tool_error(format!("Failed to load symbols: {}", e), &e))?;
+
+        let mut found = 0;
+        let mut lines = Vec::with_capacity(args.names.len());
+        for name in &args.names {
+            match session_arc.resolve_symbol(name, args.elf_path.as_deref()).await {
+                Ok(symbol) => {
+                    found += 1;
+                    lines.push(format!(
+                        "✅ {}: 0x{:08X}, {} bytes, {:?} in {}",
+                        symbol.display_name(), symbol.address, symbol.size, symbol.kind,
+                        symbol.section.as_deref().unwrap_or("<no section>")
+                    ));
+                }
+                Err(e) => lines.push(format!("❌ {}", e)),
+            }
+        }
+
+        let message = format!(
+            "🔎 Symbol lookup ({}/{} found)\n\n\
+            Session ID: {}\n\n\
+            {}\n",
+            found, args.names.len(), args.session_id, lines.join("\n")
+        );
+
+        info!("Looked up {} symbol(s) for session: {}", args.names.len(), args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Clear a breakpoint at the specified address")]
+    async fn clear_breakpoint(&self, Parameters(args): Parameters<ClearBreakpointArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Clearing breakpoint for session: {} at address {}", args.session_id, args.address);
+        
+        // Parse address
+        let address = match parse_address(&args.address) {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Invalid address '{}': {}", args.address, e);
+                return Err(invalid_argument(format!("Invalid address '{}': {}", args.address, e)));
+            }
+        };
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.clear_breakpoint(args.core_index, address).await {
+            Ok(breakpoint) => {
+                let detail = match breakpoint.map(|breakpoint| breakpoint.kind) {
+                    Some(BreakpointKind::Software { .. }) => "The original instruction has been restored.",
+                    _ => "The breakpoint has been removed.",
+                };
+                let message = format!(
+                    "🎯 Breakpoint cleared successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\n\
+                    {}",
+                    args.session_id, address, detail
+                );
+
+                info!("Breakpoint cleared for session: {} at 0x{:08X}", args.session_id, address);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to clear breakpoint for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to clear breakpoint: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Re-enable a disabled breakpoint, installing it on the target again")]
+    async fn enable_breakpoint(&self, Parameters(args): Parameters<EnableBreakpointArgs>) -> Result<CallToolResult, McpError> {
+        self.toggle_breakpoint(&args.session_id, args.core_index, &args.breakpoint, true).await
+    }
+
+    #[tool(description = "Disable a breakpoint without forgetting it: it is removed from the target until re-enabled")]
+    async fn disable_breakpoint(&self, Parameters(args): Parameters<DisableBreakpointArgs>) -> Result<CallToolResult, McpError> {
+        self.toggle_breakpoint(&args.session_id, args.core_index, &args.breakpoint, false).await
+    }
+
+    #[tool(description = "Remove every breakpoint (and by default every watchpoint) from the target, including stale hardware comparators")]
+    async fn clear_all_breakpoints(&self, Parameters(args): Parameters<ClearAllBreakpointsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Clearing all breakpoints for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.clear_all_breakpoints(args.include_watchpoints).await {
+            Ok(result) => {
+                let join = |addresses: &[u64]| if addresses.is_empty() {
+                    "none".to_string()
+                } else {
+                    addresses.iter().map(|address| format!("0x{:08X}", address)).collect::<Vec<_>>().join(", ")
+                };
+                let mut message = format!(
+                    "🧹 Breakpoints cleared\n\n\
+                    Session ID: {}\n\
+                    Breakpoints removed: {}\n",
+                    args.session_id, join(&result.breakpoints)
+                );
+                if args.include_watchpoints {
+                    message.push_str(&format!("Watchpoints removed: {}\n", join(&result.watchpoints)));
+                }
+                for (address, e) in &result.failed {
+                    message.push_str(&format!("⚠️ 0x{:08X}: {}\n", address, e));
+                }
+                message.push_str("\nAll hardware breakpoint comparators are free.");
+
+                info!("Cleared {} breakpoint(s) for session: {}", result.breakpoints.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to clear breakpoints for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to clear breakpoints: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "List breakpoints installed in a debug session with their type")]
+    async fn list_breakpoints(&self, Parameters(args): Parameters<ListBreakpointsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing breakpoints for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let breakpoints = session_arc.breakpoints().await;
+        let capacity = match session_arc.breakpoint_capacity(args.core_index).await {
+            Ok(capacity) => format!("{}/{} used", capacity.used, capacity.total),
+            Err(e) => format!("unknown ({})", e),
+        };
+        let mut message = format!(
+            "🎯 Breakpoints\n\n\
+            Session ID: {}\n\
+            Count: {}\n\
+            Hardware units (core {}): {}\n",
+            args.session_id, breakpoints.len(), args.core_index, capacity
+        );
+        if !breakpoints.is_empty() {
+            message.push('\n');
+        }
+        for breakpoint in &breakpoints {
+            message.push_str(&format!(
+                "#{:<3} 0x{:08X}  core {}  {}  {}  hits {}{}{}{}\n",
+                breakpoint.id,
+                breakpoint.address,
+                breakpoint.core_index,
+                breakpoint.kind,
+                if breakpoint.enabled { "enabled" } else { "disabled" },
+                breakpoint.hit_count,
+                if breakpoint.skip_count > 0 { format!("/skip {}", breakpoint.skip_count) } else { String::new() },
+                if breakpoint.temporary { "  temporary" } else { "" },
+                breakpoint.symbol.as_ref().map(|symbol| format!("  ({})", symbol)).unwrap_or_default()
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Set a data watchpoint (DWT comparator, Cortex-M) that halts the core on read, write or any access to an address")]
+    async fn set_watchpoint(&self, Parameters(args): Parameters<SetWatchpointArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Setting watchpoint for session: {} at address {}", args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+        let access = WatchAccess::parse(&args.access)
+            .map_err(invalid_argument)?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.set_watchpoint(args.core_index, address, args.size, access).await {
+            Ok(watchpoint) => {
+                let message = format!(
+                    "👁️ Watchpoint set successfully!\n\n\
+                    Session ID: {}\n\
+                    Address: 0x{:08X}\n\
+                    Size: {} bytes\n\
+                    Access: {}\n\
+                    Comparator: DWT{}\n\n\
+                    The target will halt after a matching access; use 'wait_for_halt' to catch it.",
+                    args.session_id, watchpoint.address, watchpoint.size, watchpoint.access, watchpoint.comparator
+                );
+
+                info!("Watchpoint set for session: {} at 0x{:08X}", args.session_id, address);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to set watchpoint for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to set watchpoint: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Clear the data watchpoint at an address on a core")]
+    async fn clear_watchpoint(&self, Parameters(args): Parameters<ClearWatchpointArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Clearing watchpoint for session: {} at address {}", args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.clear_watchpoint(args.core_index, address).await {
+            Ok(watchpoint) => {
+                let message = format!(
+                    "👁️ Watchpoint cleared successfully!\n\n\
+                    Session ID: {}\n\
+                    Core: {}\n\
+                    Address: 0x{:08X}\n\
+                    Comparator DWT{} is free again.",
+                    args.session_id, args.core_index, address, watchpoint.comparator
+                );
+
+                info!("Watchpoint cleared for session: {} at 0x{:08X}", args.session_id, address);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to clear watchpoint for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to clear watchpoint: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "List data watchpoints installed on a core of a debug session")]
+    async fn list_watchpoints(&self, Parameters(args): Parameters<ListWatchpointsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing watchpoints for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let watchpoints = session_arc.watchpoints(args.core_index).await;
+        let mut message = format!(
+            "👁️ Watchpoints\n\n\
+            Session ID: {}\n\
+            Core: {}\n\
+            Count: {}\n",
+            args.session_id, args.core_index, watchpoints.len()
+        );
+        if !watchpoints.is_empty() {
+            message.push('\n');
+        }
+        for watchpoint in &watchpoints {
+            message.push_str(&format!("{}\n", describe_watchpoint(watchpoint)));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    // =============================================================================
+    // RTT Communication Tools
+    // =============================================================================
+
+    #[tool(description = "Attach to RTT (Real-Time Transfer) for communication with target")]
+    async fn rtt_attach(&self, Parameters(args): Parameters<RttAttachArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Attaching RTT for session: {}", args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse control block address if provided
+        let control_block_address = if let Some(addr_str) = args.control_block_address {
+            match parse_address(&addr_str) {
+                Ok(addr) => Some(addr),
+                Err(e) => {
+                    let error_msg = format!("❌ Invalid control block address '{}': {}", addr_str, e);
+                    return Err(invalid_argument(error_msg));
+                }
+            }
+        } else {
+            None
+        };
+
+        // Parse memory ranges if provided
+        let memory_ranges = match args.memory_ranges {
+            Some(ranges) => Some(parse_memory_ranges(&ranges).map_err(invalid_argument)?),
+            None => None,
+        };
+
+        // Scanning reserved or unmapped space can bus-fault the target, so ranges must lie in the memory map
+        if let (None, Some(ranges), false) = (control_block_address, &memory_ranges, args.allow_unmapped) {
+            for &(start, end) in ranges {
+                if let Err(e) = session_arc.validate_range(start, (end - start) as usize).await {
+                    let error_msg = format!("❌ RTT scan range 0x{:08X}..0x{:08X} is outside the memory map: {}", start, end, e);
+                    return Err(tool_error(error_msg, &e));
+                }
+            }
+        }
+        let scan_description = match (control_block_address, &memory_ranges) {
+            (Some(address), _) => format!("control block at 0x{:08X}", address),
+            (None, Some(ranges)) => ranges.iter()
+                .map(|(start, end)| format!("0x{:08X}..0x{:08X}", start, end))
+                .collect::<Vec<_>>()
+                .join(", "),
+            (None, None) => "all RAM".to_string(),
+        };
+
+        // Attach RTT
+        {
+            let mut rtt_manager = session_arc.rtt_manager.lock().await;
+            match rtt_manager.attach(session_arc.session.clone(), control_block_address, memory_ranges).await {
+                Ok(_) => {
+                    let up_channels = rtt_manager.up_channel_count();
+                    let down_channels = rtt_manager.down_channel_count();
+                    
+                    let message = format!(
+                        "✅ RTT attached successfully!\n\n\
+                        Session ID: {}\n\
+                        Scanned: {}\n\
+                        Up Channels (Target→Host): {}\n\
+                        Down Channels (Host→Target): {}\n\n\
+                        RTT is now ready for real-time communication with the target.\n\
+                        Use 'rtt_read' to read from target and 'rtt_write' to send data to target.",
+                        args.session_id, scan_description, up_channels, down_channels
+                    );
+                    
+                    info!("RTT attached successfully for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Failed to attach RTT for session {}: {}", args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Failed to attach RTT\n\n\
+                        Session ID: {}\n\
+                        Error: {}\n\n\
+                        Suggestions:\n\
+                        - Ensure the target firmware has RTT enabled and initialized\n\
+                        - Check that the target is halted\n\
+                        - Verify memory ranges if specified\n\
+                        - Try different control block address if known",
+                        args.session_id, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Detach from RTT communication")]
+    async fn rtt_detach(&self, Parameters(args): Parameters<RttDetachArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Detaching RTT for session: {}", args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Detach RTT
+        {
+            let mut rtt_manager = session_arc.rtt_manager.lock().await;
+            match rtt_manager.detach().await {
+                Ok(_) => {
+                    let message = format!(
+                        "✅ RTT detached successfully\n\n\
+                        Session ID: {}\n\n\
+                        RTT communication has been closed.",
+                        args.session_id
+                    );
+                    
+                    info!("RTT detached successfully for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Failed to detach RTT for session {}: {}", args.session_id, e);
+                    let error_msg = format!("❌ Failed to detach RTT: {}", e);
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Read data from RTT up channel (target to host)")]
+    async fn rtt_read(&self, Parameters(args): Parameters<RttReadArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading from RTT channel {} for session: {}", args.channel, args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Read from RTT
+        {
+            let mut rtt_manager = session_arc.rtt_manager.lock().await;
+            if !rtt_manager.is_attached() {
+                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+                return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+            }
+
+            let channel = match &args.channel_name {
+                Some(name) => rtt_manager.find_channel(name, ChannelDirection::Up)
+                    .map_err(|e| tool_error(e.to_string(), &e))?,
+                None => args.channel,
+            };
+
+            let timeout = std::time::Duration::from_millis(args.timeout_ms);
+            match rtt_manager.read_channel_timeout(channel, timeout).await {
+                Ok(data) => {
+                    let data_len = data.len();
+                    let data_str = if data.is_empty() {
+                        "No data available".to_string()
+                    } else {
+                        // Try to decode as UTF-8, fall back to hex if not valid
+                        match String::from_utf8(data.clone()) {
+                            Ok(text) => {
+                                if text.chars().all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace()) {
+                                    format!("Text: {}", text)
+                                } else {
+                                    format!("Mixed: {} (hex: {})", text, hex::encode(&data))
+                                }
+                            }
+                            Err(_) => format!("Binary data (hex): {}", hex::encode(&data))
+                        }
+                    };
+
+                    let message = format!(
+                        "📥 RTT Read from Channel {}\n\n\
+                        Session ID: {}\n\
+                        Bytes Read: {}\n\n\
+                        Data:\n{}",
+                        channel, args.session_id, data_len, data_str
+                    );
+                    
+                    debug!("Read {} bytes from RTT channel {} for session: {}", data_len, channel, args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Failed to read from RTT channel {} for session {}: {}", channel, args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Failed to read from RTT channel {}\n\n\
+                        Session ID: {}\n\
+                        Error: {}",
+                        channel, args.session_id, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Read complete UTF-8 text lines from an RTT up channel; an incomplete trailing line is kept for the next call")]
+    async fn rtt_read_lines(&self, Parameters(args): Parameters<RttReadLinesArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading lines from RTT channel {} for session: {}", args.channel, args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let mut rtt_manager = session_arc.rtt_manager.lock().await;
+        if !rtt_manager.is_attached() {
+            let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+            return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+        }
+
+        let channel = match &args.channel_name {
+            Some(name) => rtt_manager.find_channel(name, ChannelDirection::Up)
+                .map_err(|e| tool_error(e.to_string(), &e))?,
+            None => args.channel,
+        };
+
+        match rtt_manager.read_lines(channel, std::time::Duration::from_millis(args.timeout_ms)).await {
+            Ok((lines, fragment)) => {
+                let lines_str = if lines.is_empty() {
+                    "No complete lines available".to_string()
+                } else {
+                    lines.join("\n")
+                };
+                let fragment_str = if fragment.is_empty() {
+                    "None".to_string()
+                } else {
+                    format!("{:?} (kept for next read)", fragment)
+                };
+
+                let message = format!(
+                    "📥 RTT Lines from Channel {}\n\n\
+                    Session ID: {}\n\
+                    Lines Read: {}\n\
+                    Partial Line: {}\n\n\
+                    Lines:\n{}",
+                    channel, args.session_id, lines.len(), fragment_str, lines_str
+                );
+
+                debug!("Read {} lines from RTT channel {} for session: {}", lines.len(), channel, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read lines from RTT channel {} for session {}: {}", channel, args.session_id, e);
+                let error_msg = format!(
+                    "❌ Failed to read lines from RTT channel {}\n\n\
+                    Session ID: {}\n\
+                    Error: {}",
+                    channel, args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Switch an RTT up channel to length-prefixed binary frames (u16/u32, little or big endian) so rtt_read_frames returns only complete frames")]
+    async fn rtt_attach_framed(&self, Parameters(args): Parameters<RttAttachFramedArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Framing RTT channel {} as {} for session: {}", args.channel, args.frame_header, args.session_id);
+
+        let header = FrameHeader::parse(&args.frame_header)
+            .map_err(invalid_argument)?;
+        if args.max_frame_size == 0 {
+            return Err(invalid_argument("max_frame_size must be greater than 0".to_string()));
+        }
+
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let mut rtt_manager = session_arc.rtt_manager.lock().await;
+        if !rtt_manager.is_attached() {
+            let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+            return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+        }
+
+        let channel = match &args.channel_name {
+            Some(name) => rtt_manager.find_channel(name, ChannelDirection::Up)
+                .map_err(|e| tool_error(e.to_string(), &e))?,
+            None => args.channel,
+        };
+
+        match rtt_manager.attach_framed(channel, header, args.max_frame_size) {
+            Ok(()) => {
+                let message = format!(
+                    "🧱 RTT channel {} framed\n\n\
+                    Session ID: {}\n\
+                    Frame header: {} ({} byte {}-endian length prefix)\n\
+                    Max frame size: {} bytes\n\n\
+                    Read it with 'rtt_read_frames'; raw reads of this channel would split frames.",
+                    channel, args.session_id, header, header.size, header.endianness, args.max_frame_size
+                );
+                info!("Framed RTT channel {} as {} for session: {}", channel, header, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to frame RTT channel {} for session {}: {}", channel, args.session_id, e);
+                Err(tool_error(format!("Failed to frame RTT channel {}: {}", channel, e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read complete length-prefixed frames from an RTT up channel set up with rtt_attach_framed; returns JSON with the payloads as hex, and keeps a partial frame for the next call")]
+    async fn rtt_read_frames(&self, Parameters(args): Parameters<RttReadFramesArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading frames from RTT channel {} for session: {}", args.channel, args.session_id);
+
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let mut rtt_manager = session_arc.rtt_manager.lock().await;
+        if !rtt_manager.is_attached() {
+            let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+            return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+        }
+
+        let channel = match &args.channel_name {
+            Some(name) => rtt_manager.find_channel(name, ChannelDirection::Up)
+                .map_err(|e| tool_error(e.to_string(), &e))?,
+            None => args.channel,
+        };
+
+        match rtt_manager.read_frames(channel, std::time::Duration::from_millis(args.timeout_ms)).await {
+            Ok(read) => {
+                let result = serde_json::json!({
+                    "channel": channel,
+                    "frames": read.frames.iter().map(hex::encode_upper).collect::<Vec<_>>(),
+                    "pending_bytes": read.pending,
+                    "desync": read.desync,
+                });
+                debug!("Read {} frames from RTT channel {} for session: {}", read.frames.len(), channel, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string()),
+                )]))
+            }
+            Err(e) => {
+                error!("Failed to read frames from RTT channel {} for session {}: {}", channel, args.session_id, e);
+                let error_msg = format!(
+                    "❌ Failed to read frames from RTT channel {}\n\n\
+                    Session ID: {}\n\
+                    Error: {}",
+                    channel, args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Write data to RTT down channel (host to target)")]
+    async fn rtt_write(&self, Parameters(args): Parameters<RttWriteArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Writing to RTT channel {} for session: {}", args.channel, args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse data based on encoding
+        let data_bytes = match args.encoding.as_str() {
+            "utf8" => args.data.as_bytes().to_vec(),
+            "hex" => {
+                match hex::decode(&args.data) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        let error_msg = format!("❌ Invalid hex data '{}': {}", args.data, e);
+                        return Err(invalid_argument(error_msg));
+                    }
+                }
+            }
+            "binary" => {
+                // Parse binary string like "10110011 11001100"
+                let binary_str = args.data.replace(' ', "");
+                if binary_str.len() % 8 != 0 {
+                    let error_msg = format!("❌ Binary data must be multiple of 8 bits: '{}'", args.data);
+                    return Err(invalid_argument(error_msg));
+                }
+                
+                let mut bytes = Vec::new();
+                for chunk in binary_str.chars().collect::<Vec<_>>().chunks(8) {
+                    let byte_str: String = chunk.iter().collect();
+                    match u8::from_str_radix(&byte_str, 2) {
+                        Ok(byte) => bytes.push(byte),
+                        Err(e) => {
+                            let error_msg = format!("❌ Invalid binary byte '{}': {}", byte_str, e);
+                            return Err(invalid_argument(error_msg));
+                        }
+                    }
+                }
+                bytes
+            }
+            _ => {
+                let error_msg = format!("❌ Unsupported encoding '{}'. Use 'utf8', 'hex', or 'binary'", args.encoding);
+                return Err(invalid_argument(error_msg));
+            }
+        };
+
+        // Write to RTT
+        {
+            let mut rtt_manager = session_arc.rtt_manager.lock().await;
+            if !rtt_manager.is_attached() {
+                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+                return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+            }
+
+            let channel = match &args.channel_name {
+                Some(name) => rtt_manager.find_channel(name, ChannelDirection::Down)
+                    .map_err(|e| tool_error(e.to_string(), &e))?,
+                None => args.channel,
+            };
+
+            let result = if args.blocking {
+                let timeout = std::time::Duration::from_millis(args.timeout_ms);
+                rtt_manager.write_channel_blocking(channel, &data_bytes, timeout).await
+            } else {
+                rtt_manager.write_channel(channel, &data_bytes).await
+            };
+
+            match result {
+                Ok(bytes_written) => {
+                    let outcome = if bytes_written == data_bytes.len() {
+                        "Data sent successfully to target.".to_string()
+                    } else if args.blocking {
+                        format!(
+                            "⚠️ Timed out after {}ms: the target did not drain the buffer, {} byte(s) were not sent.",
+                            args.timeout_ms, data_bytes.len() - bytes_written
+                        )
+                    } else {
+                        format!(
+                            "⚠️ Down buffer full: {} byte(s) were not sent. Retry with blocking=true to wait for the target.",
+                            data_bytes.len() - bytes_written
+                        )
+                    };
+                    let message = format!(
+                        "📤 RTT Write to Channel {}\n\n\
+                        Session ID: {}\n\
+                        Data: {}\n\
+                        Encoding: {}\n\
+                        Bytes Written: {}/{}\n\n\
+                        {}",
+                        channel, args.session_id, args.data, args.encoding,
+                        bytes_written, data_bytes.len(), outcome
+                    );
+                    
+                    info!("Wrote {} bytes to RTT channel {} for session: {}", bytes_written, channel, args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Failed to write to RTT channel {} for session {}: {}", channel, args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Failed to write to RTT channel {}\n\n\
+                        Session ID: {}\n\
+                        Error: {}",
+                        channel, args.session_id, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "List available RTT channels with their up/down counts, buffer sizes and the mode flags of up channels (blocking, non-blocking skip or trim)")]
+    async fn rtt_channels(&self, Parameters(args): Parameters<RttChannelsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing RTT channels for session: {}", args.session_id);
+        
+        // Get session from storage
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let channels = match session_arc.list_rtt_channels().await {
+            Ok(channels) => channels,
+            Err(_) => {
+                let error_msg = format!("❌ RTT not attached for session '{}'\n\nUse 'rtt_attach' first", args.session_id);
+                return Err(tool_error(error_msg, &DebugError::RttNotAvailable));
+            }
+        };
+
+        if channels.is_empty() {
+            let message = format!(
+                "📋 RTT Channels\n\n\
+                Session ID: {}\n\n\
+                No RTT channels available.",
+                args.session_id
+            );
+            return Ok(CallToolResult::success(vec![Content::text(message)]));
+        }
+
+        let (up_channels, down_channels): (Vec<_>, Vec<_>) = channels.iter()
+            .partition(|channel| matches!(channel.direction, ChannelDirection::Up));
+        let mut message = format!(
+            "📋 RTT Channels\n\nSession ID: {}\nUp channels: {}\nDown channels: {}\n\n",
+            args.session_id, up_channels.len(), down_channels.len()
+        );
+
+        if !up_channels.is_empty() {
+            message.push_str("📥 Up Channels (Target → Host):\n");
+            for channel in up_channels {
+                message.push_str(&format!(
+                    "  {}. {} (Size: {} bytes, Mode: {}, Flags: {})\n",
+                    channel.id, channel.name, channel.buffer_size, channel.mode_name(),
+                    channel.flags.map_or("unknown".to_string(), |flags| format!("0x{:X}", flags))
+                ));
+            }
+            message.push('\n');
+        }
+
+        if !down_channels.is_empty() {
+            message.push_str("📤 Down Channels (Host → Target):\n");
+            for channel in down_channels {
+                message.push_str(&format!(
+                    "  {}. {} (Size: {} bytes)\n",
+                    channel.id, channel.name, channel.buffer_size
+                ));
+            }
+        }
+
+        info!("Listed {} RTT channels for session: {}", channels.len(), args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    // =============================================================================
+    // Flash Programming Tools
+    // =============================================================================
+
+    #[tool(description = "Erase flash memory sectors or entire chip")]
+    async fn flash_erase(&self, Parameters(args): Parameters<FlashEraseArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Flash erase for session: {}, type: {}", args.session_id, args.erase_type);
+        
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse erase type and parameters
+        let erase_type = match args.erase_type.as_str() {
+            "all" => crate::flash::EraseType::All,
+            "sectors" => {
+                let address = match args.address {
+                    Some(addr_str) => parse_address(&addr_str).map_err(invalid_argument)?,
+                    None => return Err(invalid_argument("Address required for sector erase".to_string())),
+                };
+                let size = match args.size {
+                    Some(sz) => sz as usize,
+                    None => return Err(invalid_argument("Size required for sector erase".to_string())),
+                };
+                crate::flash::EraseType::Sectors { address, size }
+            }
+            _ => return Err(invalid_argument(format!("Invalid erase type: {}", args.erase_type))),
+        };
+
+        // Perform erase operation
+        {
+            let mut session = session_arc.lock_session().await
+                .map_err(|e| tool_error(e.to_string(), &e))?;
+            match crate::flash::FlashManager::erase_flash(&mut session, erase_type).await {
+                Ok(result) => {
+                    let message = format!(
+                        "✅ Flash erase completed successfully!\n\n\
+                        Session ID: {}\n\
+                        Erase Type: {}\n\
+                        Duration: {}ms\n\
+                        {}\n\n\
+                        Flash memory has been erased and is ready for programming.",
+                        args.session_id,
+                        args.erase_type,
+                        result.erase_time_ms,
+                        match result.sectors_erased {
+                            Some(count) => format!("Sectors Erased: {}", count),
+                            None => "Full chip erased".to_string(),
+                        }
+                    );
+                    
+                    info!("Flash erase completed for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Flash erase failed for session {}: {}", args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Flash erase failed\n\n\
+                        Session ID: {}\n\
+                        Error: {}\n\n\
+                        Suggestions:\n\
+                        - Check if flash is write-protected\n\
+                        - Ensure target is halted\n\
+                        - Verify flash address range",
+                        args.session_id, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Program file to flash memory (supports ELF, HEX, BIN), sending erase/program/verify progress notifications when the request carries a progress token")]
+    async fn flash_program(&self, Parameters(args): Parameters<FlashProgramArgs>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        debug!("Flash program for session: {}, file: {}", args.session_id, args.file_path);
+        
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse file path and format
+        let file_path = std::path::Path::new(&args.file_path);
+        let format = crate::flash::FileFormat::parse(&args.format)
+            .map_err(invalid_argument)?;
+
+        // Parse base address if provided
+        let base_address = if let Some(addr_str) = args.base_address {
+            Some(parse_address(&addr_str).map_err(invalid_argument)?)
+        } else {
+            None
+        };
+
+        // Perform programming operation
+        {
+            let progress = ProgressForwarder::start(&context);
+            let outcome = {
+                let mut session = session_arc.lock_session().await
+                    .map_err(|e| tool_error(e.to_string(), &e))?;
+                let sink = progress.as_ref().map(|progress| progress.sink.clone());
+                crate::flash::FlashManager::program_file(&mut session, file_path, format, base_address, sink).await
+            };
+            finish_progress(progress).await;
+            match outcome {
+                Ok(result) => {
+                    let message = format!(
+                        "✅ Flash programming completed successfully!\n\n\
+                        Session ID: {}\n\
+                        File: {}\n\
+                        Format: {}\n\
+                        Bytes Programmed: {}\n\
+                        Duration: {}ms\n\
+                        Verification: {}\n\n\
+                        Firmware has been programmed to flash memory.",
+                        args.session_id,
+                        args.file_path,
+                        args.format,
+                        result.bytes_programmed,
+                        result.programming_time_ms,
+                        match result.verification_result {
+                            Some(true) => "✅ Passed",
+                            Some(false) => "❌ Failed",
+                            None => "Not performed",
+                        }
+                    );
+                    
+                    info!("Flash programming completed for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Flash programming failed for session {}: {}", args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Flash programming failed\n\n\
+                        Session ID: {}\n\
+                        File: {}\n\
+                        Error: {}\n\n\
+                        Suggestions:\n\
+                        - Check file exists and is readable\n\
+                        - Verify file format is correct\n\
+                        - Ensure flash is erased first\n\
+                        - Check target memory map",
+                        args.session_id, args.file_path, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Verify flash memory contents, sending progress notifications when the request carries a progress token")]
+    async fn flash_verify(&self, Parameters(args): Parameters<FlashVerifyArgs>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        debug!("Flash verify for session: {}", args.session_id);
+        
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        // Parse address
+        let address = parse_address(&args.address).map_err(invalid_argument)?;
+
+        // Get expected data
+        let expected_data = if let Some(file_path) = &args.file_path {
+            // Read from file
+            std::fs::read(file_path)
+                .map_err(|e| tool_error(format!("Failed to read file {}: {}", file_path, e), &DebugError::IoError(e)))?
+        } else if let Some(hex_data) = &args.data {
+            // Parse hex data
+            match parse_data(hex_data, "hex", Endianness::Little) {
+                Ok(data) => data,
+                Err(e) => return Err(invalid_argument(format!("Invalid hex data: {}", e))),
+            }
+        } else {
+            return Err(invalid_argument("Either file_path or data must be provided".to_string()));
+        };
+
+        // Limit to specified size
+        let expected_data = if expected_data.len() > args.size as usize {
+            &expected_data[..args.size as usize]
+        } else {
+            &expected_data
+        };
+
+        if args.quick_verify {
+            let algorithm = crate::debugger::memory::ChecksumAlgorithm::Crc32;
+            let expected_digest = algorithm.digest(expected_data);
+            return match session_arc.checksum_memory(0, address, expected_data.len(), algorithm).await {
+                Ok(result) => {
+                    let matched = result.digest == expected_digest;
+                    let message = format!(
+                        "{} Flash quick verification {}\n\n\
+                        Session ID: {}\n\
+                        Address: 0x{:08X}\n\
+                        Bytes Verified: {}\n\
+                        Expected CRC32: {}\n\
+                        Actual CRC32: {}\n\
+                        Duration: {}ms{}",
+                        if matched { "✅" } else { "❌" },
+                        if matched { "successful!" } else { "failed!" },
+                        args.session_id, address, result.bytes_read,
+                        expected_digest, result.digest, result.elapsed_ms,
+                        if matched { "" } else { "\n\nRun without quick_verify to list the mismatching bytes." }
+                    );
+
+                    info!("Flash quick verification completed for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Flash quick verification failed for session {}: {}", args.session_id, e);
+                    Err(tool_error(format!("❌ Flash verification error\n\nSession ID: {}\nError: {}", args.session_id, e), &e))
+                }
+            };
+        }
+
+        // Perform verification
+        {
+            let progress = ProgressForwarder::start(&context);
+            let outcome = {
+                let mut session = session_arc.lock_session().await
+                    .map_err(|e| tool_error(e.to_string(), &e))?;
+                let sink = progress.as_ref().map(|progress| &progress.sink);
+                crate::flash::FlashManager::verify_flash(&mut session, expected_data, address, sink).await
+            };
+            finish_progress(progress).await;
+            match outcome {
+                Ok(result) => {
+                    let message = if result.success {
+                        format!(
+                            "✅ Flash verification successful!\n\n\
+                            Session ID: {}\n\
+                            Address: 0x{:08X}\n\
+                            Bytes Verified: {}\n\n\
+                            All flash contents match expected data.",
+                            args.session_id, address, result.bytes_verified
+                        )
+                    } else {
+                        let mut message = format!(
+                            "❌ Flash verification failed!\n\n\
+                            Session ID: {}\n\
+                            Address: 0x{:08X}\n\
+                            Bytes Verified: {}\n\
+                            Mismatches: {}\n\n\
+                            First {} mismatches:\n",
+                            args.session_id, address, result.bytes_verified, result.mismatches.len(),
+                            std::cmp::min(10, result.mismatches.len())
+                        );
+                        
+                        for (i, mismatch) in result.mismatches.iter().take(10).enumerate() {
+                            message.push_str(&format!(
+                                "  {}. 0x{:08X}: expected 0x{:02X}, got 0x{:02X}\n",
+                                i + 1, mismatch.address, mismatch.expected, mismatch.actual
+                            ));
+                        }
+                        
+                        if result.mismatches.len() > 10 {
+                            message.push_str(&format!("  ... and {} more mismatches\n", result.mismatches.len() - 10));
+                        }
+                        
+                        message
+                    };
+                    
+                    info!("Flash verification completed for session: {}", args.session_id);
+                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                }
+                Err(e) => {
+                    error!("Flash verification failed for session {}: {}", args.session_id, e);
+                    let error_msg = format!(
+                        "❌ Flash verification error\n\n\
+                        Session ID: {}\n\
+                        Error: {}",
+                        args.session_id, e
+                    );
+                    Err(tool_error(error_msg, &e))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Check whether a flash region is blank (all bytes equal the erased value, usually 0xFF) to skip a redundant erase")]
+    async fn flash_blank_check(&self, Parameters(args): Parameters<BlankCheckArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Blank checking flash for session: {} at address {}", args.session_id, args.address);
+
+        let address = parse_address(&args.address)
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", args.address, e)))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.is_blank(args.core_index, address, args.size).await {
+            Ok(result) => {
+                let message = match result.first_non_blank {
+                    None => format!(
+                        "✅ Flash region is blank\n\n\
+                        Session ID: {}\n\
+                        Range: 0x{:08X}..0x{:08X}\n\
+                        Blank: true\n\
+                        Erased value: 0x{:02X}\n\
+                        Duration: {}ms",
+                        args.session_id, address, address + args.size as u64, result.erased_value, result.elapsed_ms
+                    ),
+                    Some(first) => format!(
+                        "📝 Flash region is not blank\n\n\
+                        Session ID: {}\n\
+                        Range: 0x{:08X}..0x{:08X}\n\
+                        Blank: false\n\
+                        First non-blank address: 0x{:08X}\n\
+                        Erased value: 0x{:02X}\n\
+                        Bytes checked: {}\n\
+                        Duration: {}ms",
+                        args.session_id, address, address + args.size as u64, first, result.erased_value,
+                        result.bytes_checked, result.elapsed_ms
+                    ),
+                };
+
+                info!("Blank check completed for session: {} (blank: {})", args.session_id, result.is_blank());
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Blank check failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to blank check flash: {}", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Complete firmware deployment: program and verify, reset and run, then attach RTT")]
+    async fn run_firmware(&self, Parameters(args): Parameters<RunFirmwareArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Run firmware for session: {}, file: {}", args.session_id, args.file_path);
+        
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let format = crate::flash::FileFormat::parse(&args.format)
+            .map_err(invalid_argument)?;
+        let rtt_timeout = args.attach_rtt.then(|| std::time::Duration::from_millis(args.rtt_timeout_ms as u64));
+        let start_time = std::time::Instant::now();
+
+        match session_arc.run_firmware(std::path::Path::new(&args.file_path), format, args.reset_after_flash, rtt_timeout).await {
+            Ok(result) => {
+                let core_status = match result.core_status {
+                    Some(status) => format!("{:?}", status),
+                    None => "Not reset".to_string(),
+                };
+                let rtt_status = match (&result.rtt, &result.rtt_error) {
+                    (Some(rtt), _) => format!("✅ Attached ({} up, {} down channels)", rtt.up_channels, rtt.down_channels),
+                    (None, Some(e)) => format!("⚠️ Not found within {}ms: {}", args.rtt_timeout_ms, e),
+                    (None, None) => "Not requested".to_string(),
+                };
+
+                let message = format!(
+                    "🚀 Firmware deployment completed!\n\n\
+                    Session ID: {}\n\
+                    File: {}\n\
+                    Format: {}\n\
+                    Bytes Programmed: {}\n\
+                    Verification: {}\n\
+                    Core Status: {}\n\
+                    RTT: {}\n\
+                    Total Time: {:.1}s\n\n\
+                    {}",
+                    args.session_id,
+                    args.file_path,
+                    args.format,
+                    result.flash.bytes_programmed,
+                    match result.flash.verification_result {
+                        Some(true) => "✅ Passed",
+                        Some(false) => "❌ Failed",
+                        None => "Not performed",
+                    },
+                    core_status,
+                    rtt_status,
+                    start_time.elapsed().as_secs_f64(),
+                    if result.rtt.is_some() { "Use 'rtt_read' to monitor target output." } else { "Use 'rtt_attach' to enable real-time communication." }
+                );
+
+                info!("Firmware deployment completed for session: {} in {:.1}s", args.session_id, start_time.elapsed().as_secs_f64());
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Firmware deployment failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Firmware deployment failed\n\n\
+                    Session ID: {}\n\
+                    File: {}\n\
+                    Error: {}",
+                    args.session_id, args.file_path, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Run an on-target test binary: flash the ELF, reset, collect its RTT or semihosting log until it exits, halts or times out, and report per-test pass/fail. A timed out core is halted with RTT kept attached for debugging")]
+    async fn run_tests(&self, Parameters(args): Parameters<RunTestsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Running tests for session: {}, elf: {}", args.session_id, args.elf_path);
+
+        let output = crate::debugger::testrun::TestOutput::parse(&args.output)
+            .map_err(|e| tool_error(e.to_string(), &e))?;
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.run_tests(std::path::Path::new(&args.elf_path), output, timeout).await {
+            Ok(result) => {
+                use crate::debugger::testrun::{TestCaseStatus, TestRunStatus};
+
+                let exit = result.halt.as_ref().and_then(|event| event.semihosting_exit);
+                let status = result.log.status(exit.as_ref(), result.halt.is_some());
+                let (icon, status_line) = match status {
+                    TestRunStatus::Passed => ("✅", "PASSED"),
+                    TestRunStatus::Failed => ("❌", "FAILED"),
+                    TestRunStatus::TimedOut => ("⏳", "TIMED OUT (core halted)"),
+                };
+                let stop = match (&result.halt, result.timed_out) {
+                    (_, true) => format!("Timeout after {}ms", args.timeout_ms),
+                    (Some(event), _) => match &event.semihosting_exit {
+                        Some(exit) => describe_semihosting_exit(exit),
+                        None => format!(
+                            "Halted ({:?}) at PC {}",
+                            event.status,
+                            event.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "unavailable".to_string())
+                        ),
+                    },
+                    (None, false) => "End of run reported in the log".to_string(),
+                };
+
+                let mut message = format!(
+                    "{} Test run {}\n\n\
+                    Session ID: {}\n\
+                    ELF: {}\n\
+                    Output: {}\n\
+                    Stopped by: {}\n\
+                    Exit code: {}\n\
+                    Tests: {} passed, {} failed, {} ignored, {} unfinished\n\
+                    Duration: {:.1}s\n",
+                    icon, status_line, args.session_id, args.elf_path, args.output, stop,
+                    exit.map(|exit| exit.exit_code().to_string()).unwrap_or_else(|| "none".to_string()),
+                    result.log.count(TestCaseStatus::Passed), result.log.count(TestCaseStatus::Failed),
+                    result.log.count(TestCaseStatus::Ignored), result.log.count(TestCaseStatus::Running),
+                    result.elapsed_ms as f64 / 1000.0
+                );
+                if !result.log.tests.is_empty() {
+                    message.push('\n');
+                    for test in &result.log.tests {
+                        let mark = match test.status {
+                            TestCaseStatus::Passed => "✅",
+                            TestCaseStatus::Failed => "❌",
+                            TestCaseStatus::Ignored => "⏭️",
+                            TestCaseStatus::Running => "⏳",
+                        };
+                        message.push_str(&format!("{} {} ({})\n", mark, test.name, test.status));
+                    }
+                }
+                message.push_str(&format!("\nLog ({} lines):\n", result.log.lines.len()));
+                if result.log.lines.is_empty() {
+                    message.push_str("(no output)\n");
+                }
+                for line in &result.log.lines {
+                    message.push_str(line);
+                    message.push('\n');
+                }
+                if status != TestRunStatus::Passed {
+                    message.push_str("\nThe core is halted where the run stopped; use 'backtrace' or 'read_locals' to inspect it.");
+                }
+
+                info!("Test run for session {} finished: {:?}", args.session_id, status);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Test run failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Test run failed\n\n\
+                    Session ID: {}\n\
+                    ELF: {}\n\
+                    Error: {}",
+                    args.session_id, args.elf_path, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    // =============================================================================
+    // Debug Information Tools
+    // =============================================================================
+
+    #[tool(description = "Unwind the call stack of the target using the firmware ELF debug info, including the interrupted context below exception handlers")]
+    async fn backtrace(&self, Parameters(args): Parameters<BacktraceArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Backtrace for session: {}, elf: {:?}", args.session_id, args.elf_path);
 
-        // Get expected data
-        let expected_data = if let Some(file_path) = &args.file_path {
-            // Read from file
-            std::fs::read(file_path)
-                .map_err(|e| McpError::internal_error(format!("Failed to read file {}: {}", file_path, e), None))?
-        } else if let Some(hex_data) = &args.data {
-            // Parse hex data
-            match parse_data(hex_data, "hex") {
-                Ok(data) => data,
-                Err(e) => return Err(McpError::internal_error(format!("Invalid hex data: {}", e), None)),
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
             }
-        } else {
-            return Err(McpError::internal_error("Either file_path or data must be provided".to_string(), None));
         };
 
-        // Limit to specified size
-        let expected_data = if expected_data.len() > args.size as usize {
-            &expected_data[..args.size as usize]
-        } else {
-            &expected_data
+        match session_arc.backtrace(args.core_index, args.elf_path.as_deref(), args.max_frames, args.halt).await {
+            Ok((trace, halted_now)) => {
+                let mut message = format!(
+                    "🧵 Backtrace ({} frame(s))\n\n\
+                    Session ID: {}\n\
+                    ELF: {}\n",
+                    trace.frames.len(), args.session_id, args.elf_path.as_deref().unwrap_or("(loaded symbols)")
+                );
+                if halted_now {
+                    message.push_str("Core: halted for the backtrace\n");
+                }
+                message.push('\n');
+
+                for frame in &trace.frames {
+                    if frame.is_exception_frame {
+                        message.push_str("--- exception entry: interrupted context ---\n");
+                    }
+                    message.push_str(&format!(
+                        "#{} 0x{:08X} in {}{}",
+                        frame.index, frame.address, frame.function_name,
+                        if frame.is_inlined { " [inlined]" } else { "" }
+                    ));
+                    if let Some(sp) = frame.sp {
+                        message.push_str(&format!(" (SP=0x{:08X})", sp));
+                    }
+                    if let Some(file) = &frame.file {
+                        message.push_str(&format!("\n    at {}", file));
+                        if let Some(line) = frame.line {
+                            message.push_str(&format!(":{}", line));
+                            if let Some(column) = frame.column {
+                                message.push_str(&format!(":{}", column));
+                            }
+                        }
+                    }
+                    message.push('\n');
+                }
+
+                if trace.lr_fallback {
+                    message.push_str("\n⚠️ No unwind info for this PC: frames are only the PC and the link register, callers further up are unknown\n");
+                } else if trace.truncated {
+                    message.push_str("\n⚠️ Backtrace truncated (frame limit reached or unwind info exhausted)\n");
+                }
+
+                info!("Backtrace completed for session: {}", args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Backtrace failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Backtrace failed\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    Suggestions:\n\
+                    - Halt the target before requesting a backtrace, or pass halt=true\n\
+                    - Make sure the ELF matches the running firmware and contains debug info",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Read the parameters and local variables in scope at a backtrace frame of the halted target, decoded through the firmware DWARF info")]
+    async fn read_locals(&self, Parameters(args): Parameters<ReadLocalsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading locals of frame {} for session: {}", args.frame, args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
         };
 
-        // Perform verification
-        {
-            let mut session = session_arc.session.lock().await;
-            match crate::flash::FlashManager::verify_flash(&mut session, expected_data, address).await {
-                Ok(result) => {
-                    let message = if result.success {
-                        format!(
-                            "✅ Flash verification successful!\n\n\
-                            Session ID: {}\n\
-                            Address: 0x{:08X}\n\
-                            Bytes Verified: {}\n\n\
-                            All flash contents match expected data.",
-                            args.session_id, address, result.bytes_verified
-                        )
-                    } else {
-                        let mut message = format!(
-                            "❌ Flash verification failed!\n\n\
-                            Session ID: {}\n\
-                            Address: 0x{:08X}\n\
-                            Bytes Verified: {}\n\
-                            Mismatches: {}\n\n\
-                            First {} mismatches:\n",
-                            args.session_id, address, result.bytes_verified, result.mismatches.len(),
-                            std::cmp::min(10, result.mismatches.len())
-                        );
-                        
-                        for (i, mismatch) in result.mismatches.iter().take(10).enumerate() {
+        match session_arc.read_locals(args.core_index, args.frame, args.elf_path.as_deref()).await {
+            Ok(locals) => {
+                let mut message = format!(
+                    "🔎 Locals of frame #{}\n\n\
+                    Session ID: {}\n\
+                    Function: {}{}\n\
+                    PC: 0x{:08X}\n\n",
+                    locals.index, args.session_id, locals.function_name,
+                    if locals.is_inlined { " [inlined]" } else { "" },
+                    locals.address
+                );
+                if locals.variables.is_empty() {
+                    message.push_str("No parameters or local variables in scope\n");
+                }
+                for variable in &locals.variables {
+                    message.push_str(&format!(
+                        "{}: {} = {}\n    ({})\n",
+                        variable.name, variable.type_name, variable.value, variable.location
+                    ));
+                }
+
+                info!("Read {} locals for session: {}", locals.variables.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Reading locals failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Failed to read local variables\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    Suggestions:\n\
+                    - Halt the target first, e.g. at a breakpoint\n\
+                    - Use 'backtrace' to list the valid frame numbers\n\
+                    - Make sure the ELF matches the running firmware and contains debug info",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "List the FreeRTOS tasks with state, priority and stack high-water mark, and a short backtrace per task unwound from its saved context; kernel structure offsets come from the firmware DWARF info")]
+    async fn rtos_threads(&self, Parameters(args): Parameters<RtosThreadsArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Listing RTOS threads for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.rtos_threads(args.core_index, args.elf_path.as_deref(), args.backtraces, args.max_frames, args.halt).await {
+            Ok((tasks, halted_now)) => {
+                let mut message = format!(
+                    "🧶 FreeRTOS tasks ({})\n\n\
+                    Session ID: {}\n",
+                    tasks.len(), args.session_id
+                );
+                if halted_now {
+                    message.push_str("Core: halted to walk the task lists\n");
+                }
+
+                for task in &tasks {
+                    message.push_str(&format!(
+                        "\n{} [{}] priority {} (TCB 0x{:08X})\n    Stack: base 0x{:08X}, SP 0x{:08X}",
+                        if task.name.is_empty() { "<unnamed>" } else { &task.name },
+                        task.state, task.priority, task.tcb, task.stack_base, task.stack_pointer
+                    ));
+                    if let Some(size) = task.stack_size {
+                        message.push_str(&format!(", size {} bytes", size));
+                    }
+                    match task.high_water_bytes {
+                        Some(0) => message.push_str("\n    ⚠️ High-water mark: 0 bytes free, the stack has overflowed or was never filled"),
+                        Some(free) => message.push_str(&format!("\n    High-water mark: {} bytes never used", free)),
+                        None => message.push_str("\n    High-water mark: stack unreadable"),
+                    }
+                    message.push('\n');
+
+                    if let Some(trace) = &task.backtrace {
+                        for frame in &trace.frames {
                             message.push_str(&format!(
-                                "  {}. 0x{:08X}: expected 0x{:02X}, got 0x{:02X}\n",
-                                i + 1, mismatch.address, mismatch.expected, mismatch.actual
+                                "    #{} 0x{:08X} in {}{}",
+                                frame.index, frame.address, frame.function_name,
+                                if frame.is_inlined { " [inlined]" } else { "" }
                             ));
+                            if let (Some(file), Some(line)) = (&frame.file, frame.line) {
+                                message.push_str(&format!(" at {}:{}", file, line));
+                            }
+                            message.push('\n');
                         }
-                        
-                        if result.mismatches.len() > 10 {
-                            message.push_str(&format!("  ... and {} more mismatches\n", result.mismatches.len() - 10));
-                        }
-                        
-                        message
-                    };
-                    
-                    info!("Flash verification completed for session: {}", args.session_id);
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
+                    }
+                    if let Some(note) = &task.note {
+                        message.push_str(&format!("    {}\n", note));
+                    }
                 }
-                Err(e) => {
-                    error!("Flash verification failed for session {}: {}", args.session_id, e);
-                    let error_msg = format!(
-                        "❌ Flash verification error\n\n\
-                        Session ID: {}\n\
-                        Error: {}",
-                        args.session_id, e
-                    );
-                    Err(McpError::internal_error(error_msg, None))
+
+                info!("Listed {} RTOS threads for session: {}", tasks.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Listing RTOS threads failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Failed to list RTOS threads\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    Suggestions:\n\
+                    - Load the FreeRTOS firmware ELF with debug info using 'load_symbols'\n\
+                    - Halt the target first or set halt\n\
+                    - The scheduler must have started for the task lists to be valid",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    #[tool(description = "Disassemble target code at an address or around the PC, marking the PC and naming symbols and branch targets when symbols are loaded")]
+    async fn disassemble(&self, Parameters(args): Parameters<DisassembleArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Disassembling for session: {} at {:?}", args.session_id, args.address);
+
+        let around = match args.context.as_deref() {
+            None => false,
+            Some("around_pc") => true,
+            Some(other) => return Err(invalid_argument(format!("Invalid context '{}': use \"around_pc\"", other))),
+        };
+        let max_count = self.memory_config.max_read_size as u64 / crate::debugger::disasm::MAX_INSTRUCTION_SIZE;
+        if args.count == 0 || args.count as u64 > max_count {
+            return Err(invalid_argument(format!("Invalid count {}: disassemble between 1 and {} instructions", args.count, max_count)));
+        }
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
+        };
+
+        // An address that does not parse as a number is treated as a symbol name
+        let address = match args.address.as_deref() {
+            None => None,
+            Some(text) => match parse_address(text) {
+                Ok(address) => Some(address),
+                Err(_) => {
+                    let symbol = session_arc.resolve_symbol(text, args.elf_path.as_deref()).await
+                        .map_err(|e| tool_error(format!("Failed to resolve symbol '{}': {}", text, e), &e))?;
+                    Some(symbol.address)
+                }
+            },
+        };
+        let before = if around { args.count / 2 } else { 0 };
+
+        match session_arc.disassemble(args.core_index, address, args.count, before, args.elf_path.as_deref()).await {
+            Ok(listing) => {
+                let mut message = format!(
+                    "🧩 Disassembly ({} instruction(s))\n\n\
+                    Session ID: {}\n\
+                    Instruction set: {}\n\
+                    PC: 0x{:08X}\n\n",
+                    listing.instructions.len(), args.session_id, listing.mode, listing.pc
+                );
+                for insn in &listing.instructions {
+                    let marker = if insn.address == listing.pc { "=>" } else { "  " };
+                    let symbol = insn.symbol.as_ref()
+                        .map(|(name, offset)| format!(" <{}+{}>", name, offset))
+                        .unwrap_or_default();
+                    let bytes = insn.bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
+                    message.push_str(&format!(
+                        "{} 0x{:08X}{}: {:<11} {} {}",
+                        marker, insn.address, symbol, bytes, insn.mnemonic, insn.operands
+                    ));
+                    if let Some((_, Some(name))) = &insn.branch_target {
+                        message.push_str(&format!(" <{}>", name));
+                    }
+                    message.push('\n');
+                }
+                if listing.unaligned_scan {
+                    message.push_str("\n⚠️ No symbol precedes this code: instructions before the anchor may be misdecoded\n");
+                }
+
+                info!("Disassembled {} instructions for session: {}", listing.instructions.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Disassembly failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to disassemble: {}", e), &e))
+            }
         }
     }
 
-    #[tool(description = "Complete firmware deployment: erase, program, verify, run and attach RTT")]
-    async fn run_firmware(&self, Parameters(args): Parameters<RunFirmwareArgs>) -> Result<CallToolResult, McpError> {
-        debug!("Run firmware for session: {}, file: {}", args.session_id, args.file_path);
-        
+    #[tool(description = "Map the current program counter to a source file and line using the firmware ELF line info")]
+    async fn pc_to_source(&self, Parameters(args): Parameters<PcToSourceArgs>) -> Result<CallToolResult, McpError> {
+        debug!("PC to source for session: {}, elf: {}", args.session_id, args.elf_path);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        match session_arc.pc_to_source(args.core_index, &args.elf_path).await {
+            Ok((pc, Some(location))) => {
+                let mut message = format!(
+                    "📍 Source Location\n\n\
+                    Session ID: {}\n\
+                    PC: 0x{:08X}\n\
+                    Location: {}\n",
+                    args.session_id, pc, location
+                );
+
+                if args.context_lines > 0 {
+                    if let Some(lines) = crate::symbols::lines::read_source_context(&location.file, location.line, args.context_lines) {
+                        message.push('\n');
+                        for (number, text) in lines {
+                            let marker = if number == location.line { "▶" } else { " " };
+                            message.push_str(&format!("{} {:>5} | {}\n", marker, number, text));
+                        }
+                    }
+                }
+
+                info!("PC mapped to {} for session: {}", location, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Ok((pc, None)) => {
+                let message = format!(
+                    "📍 Source Location\n\n\
+                    Session ID: {}\n\
+                    PC: 0x{:08X}\n\
+                    Location: unknown (no line info for this address)",
+                    args.session_id, pc
+                );
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("PC to source failed for session {}: {}", args.session_id, e);
+                let error_msg = format!(
+                    "❌ Failed to map PC to source\n\n\
+                    Session ID: {}\n\
+                    Error: {}\n\n\
+                    Suggestions:\n\
+                    - Make sure the ELF matches the running firmware and was built with debug info",
+                    args.session_id, e
+                );
+                Err(tool_error(error_msg, &e))
+            }
+        }
+    }
+
+    // =============================================================================
+    // Semihosting Tools
+    // =============================================================================
+
+    #[tool(description = "Enable ARM semihosting capture (console output and exit requests) for a session")]
+    async fn enable_semihosting(&self, Parameters(args): Parameters<EnableSemihostingArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Enabling semihosting for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        session_arc.enable_semihosting().await;
+
+        let message = format!(
+            "✅ Semihosting enabled\n\n\
+            Session ID: {}\n\n\
+            Use 'read_semihosting_output' or 'wait_for_halt' while the target runs to service requests and collect output.",
+            args.session_id
+        );
+
+        info!("Semihosting enabled for session: {}", args.session_id);
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Service pending semihosting requests and return captured console output and exit status")]
+    async fn read_semihosting_output(&self, Parameters(args): Parameters<ReadSemihostingOutputArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Reading semihosting output for session: {}", args.session_id);
+
+        let session_arc = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(&args.session_id) {
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
+                None => {
+                    let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
+                }
+            }
+        };
+
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.read_semihosting_output(args.core_index, timeout).await {
+            Ok((output, exit)) => {
+                let exit_line = exit.as_ref().map(describe_semihosting_exit).unwrap_or_else(|| "Still running".to_string());
+
+                let message = format!(
+                    "🖨️ Semihosting Output\n\n\
+                    Session ID: {}\n\
+                    Status: {}\n\
+                    Bytes: {}\n\n\
+                    {}",
+                    args.session_id, exit_line, output.len(),
+                    if output.is_empty() { "(no new output)" } else { &output }
+                );
+
+                info!("Read {} bytes of semihosting output for session: {}", output.len(), args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Failed to read semihosting output for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Failed to read semihosting output: {}\n\nUse 'enable_semihosting' first", e), &e))
+            }
+        }
+    }
+
+    #[tool(description = "Enable semihosting, resume the target and service its console and file requests until it exits (SYS_EXIT), halts or times out. Returns the console output and exit code")]
+    async fn run_with_semihosting(&self, Parameters(args): Parameters<RunWithSemihostingArgs>) -> Result<CallToolResult, McpError> {
+        debug!("Running with semihosting for session: {}", args.session_id);
+
         let session_arc = {
             let sessions = self.sessions.read().await;
             match sessions.get(&args.session_id) {
-                Some(session) => session.clone(),
+                Some(session) => {
+                    session.touch();
+                    session.clone()
+                }
                 None => {
                     let error_msg = format!("❌ Session '{}' not found\n\nUse 'connect' to establish a debug session first", args.session_id);
-                    return Err(McpError::internal_error(error_msg, None));
+                    return Err(tool_error(error_msg, &DebugError::InvalidSession(args.session_id.clone())));
                 }
             }
         };
 
-        let mut status_messages = Vec::new();
-        let start_time = std::time::Instant::now();
+        let timeout = std::time::Duration::from_millis(args.timeout_ms);
+        match session_arc.run_with_semihosting(args.core_index, timeout).await {
+            Ok(run) => {
+                let (status, exit_code) = match &run.halt {
+                    Some(event) => match &event.semihosting_exit {
+                        Some(exit) => (describe_semihosting_exit(exit), Some(exit.exit_code())),
+                        None => (format!(
+                            "Halted without exiting ({:?}) at PC {}",
+                            event.status,
+                            event.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "unavailable".to_string())
+                        ), None),
+                    },
+                    None => (format!("Still running after {}ms; use 'read_semihosting_output' to keep servicing it", args.timeout_ms), None),
+                };
 
-        // Step 1: Erase flash
-        status_messages.push("🔄 Step 1/5: Erasing flash memory...".to_string());
-        {
-            let mut session = session_arc.session.lock().await;
-            match crate::flash::FlashManager::erase_flash(&mut session, crate::flash::EraseType::All).await {
-                Ok(_) => status_messages.push("✅ Flash erased successfully".to_string()),
-                Err(e) => {
-                    let error_msg = format!("❌ Flash erase failed: {}", e);
-                    status_messages.push(error_msg.clone());
-                    return Err(McpError::internal_error(format!("{}\n\n{}", status_messages.join("\n"), error_msg), None));
-                }
+                let message = format!(
+                    "{} Semihosting Run\n\n\
+                    Session ID: {}\n\
+                    Status: {}\n\
+                    Exit code: {}\n\
+                    Bytes: {}\n\n\
+                    {}",
+                    match exit_code { Some(0) => "✅", Some(_) => "❌", None => "⏳" },
+                    args.session_id, status,
+                    exit_code.map(|code| code.to_string()).unwrap_or_else(|| "none".to_string()),
+                    run.output.len(),
+                    if run.output.is_empty() { "(no output)" } else { &run.output }
+                );
+
+                info!("Semihosting run ended with exit code {:?} for session: {}", exit_code, args.session_id);
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => {
+                error!("Semihosting run failed for session {}: {}", args.session_id, e);
+                Err(tool_error(format!("Semihosting run failed: {}", e), &e))
             }
         }
+    }
+}
 
-        // Step 2: Program firmware
-        status_messages.push("🔄 Step 2/5: Programming firmware...".to_string());
-        let format = match args.format.as_str() {
-            "auto" => crate::flash::FileFormat::Auto,
-            "elf" => crate::flash::FileFormat::Elf,
-            "hex" => crate::flash::FileFormat::Hex,
-            "bin" => crate::flash::FileFormat::Bin,
-            _ => return Err(McpError::internal_error(format!("Unsupported format: {}", args.format), None)),
-        };
+// =============================================================================
+// Utility Functions
+// =============================================================================
 
-        {
-            let mut session = session_arc.session.lock().await;
-            match crate::flash::FlashManager::program_file(&mut session, std::path::Path::new(&args.file_path), format, None).await {
-                Ok(result) => status_messages.push(format!("✅ Programmed {} bytes", result.bytes_programmed)),
-                Err(e) => {
-                    let error_msg = format!("❌ Programming failed: {}", e);
-                    status_messages.push(error_msg.clone());
-                    return Err(McpError::internal_error(format!("{}\n\n{}", status_messages.join("\n"), error_msg), None));
-                }
-            }
+/// Describe how a target ended its run through semihosting
+fn describe_semihosting_exit(exit: &crate::debugger::semihosting::SemihostingExit) -> String {
+    match exit {
+        crate::debugger::semihosting::SemihostingExit::Success => "Exited successfully (code 0)".to_string(),
+        crate::debugger::semihosting::SemihostingExit::Error { reason, .. } => {
+            format!("Exited with error (code {}, reason 0x{:X})", exit.exit_code(), reason)
         }
+    }
+}
 
-        // Step 3: Reset and run
-        if args.reset_after_flash {
-            status_messages.push("🔄 Step 3/5: Resetting target...".to_string());
-            {
-                let mut session = session_arc.session.lock().await;
-                let mut core = match session.core(0) {
-                    Ok(core) => core,
-                    Err(e) => return Err(McpError::internal_error(format!("Failed to get core: {}", e), None)),
-                };
-                
-                match core.reset() {
-                    Ok(_) => {
-                        status_messages.push("✅ Target reset successfully".to_string());
-                        // Run the target
-                        match core.run() {
-                            Ok(_) => status_messages.push("✅ Target running".to_string()),
-                            Err(e) => warn!("Failed to run after reset: {}", e),
-                        }
-                    }
-                    Err(e) => {
-                        let error_msg = format!("❌ Reset failed: {}", e);
-                        status_messages.push(error_msg.clone());
-                        return Err(McpError::internal_error(format!("{}\n\n{}", status_messages.join("\n"), error_msg), None));
-                    }
-                }
-            }
-        }
+/// Address of one batch entry, or why it did not parse
+type EntryAddress = Result<u64, String>;
 
-        // Step 4: Attach RTT (if requested) - Mimic probe-rs run behavior
-        if args.attach_rtt {
-            status_messages.push("🔄 Step 4/5: Attaching RTT (probe-rs style)...".to_string());
-            
-            // Key improvement: Give target more time to boot, mimic probe-rs run timing
-            info!("Allowing target firmware to fully initialize RTT control block...");
-            tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await; // Initial 2s delay
-            
-            // Give target additional time to fully initialize RTT (key improvement)
-            info!("Giving target additional time to initialize RTT control block...");
-            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-            
-            // Enhanced RTT retry mechanism with probe-rs style timing
-            let mut rtt_attached = false;
-            let max_attempts = 8; // Increase retry attempts
-            let mut attempt = 1;
-            
-            while attempt <= max_attempts && !rtt_attached {
-                // probe-rs style delay strategy: 1s, 1.5s, 2s, 2.5s, 3s, 3.5s, 4s, 4.5s
-                let delay_ms = 1000 + (attempt - 1) * 500;
-                info!("RTT attach attempt {}/{}, waiting {}ms for RTT control block...", attempt, max_attempts, delay_ms);
-                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms as u64)).await;
-                
-                // Small delay between RTT attempts (let target stabilize)
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                
-                // Try RTT attachment with different strategies (probe-rs style optimization)
-                let mut rtt_manager = session_arc.rtt_manager.lock().await;
-                let rtt_result = match attempt {
-                    1..=2 => {
-                        // First 2 attempts: ELF symbol detection (probe-rs priority method)
-                        debug!("RTT attempt {}: Using ELF symbol detection (probe-rs style)", attempt);
-                        rtt_manager.attach_with_elf(session_arc.session.clone(), std::path::Path::new(&args.file_path)).await
-                    }
-                    3..=5 => {
-                        // Attempts 3-5: standard attach, let probe-rs auto-scan memory
-                        debug!("RTT attempt {}: Using standard memory map scan", attempt);
-                        rtt_manager.attach(session_arc.session.clone(), None, None).await
-                    }
-                    6..=7 => {
-                        // Attempts 6-7: try STM32G4 specific memory ranges
-                        debug!("RTT attempt {}: Using STM32G4 specific memory ranges", attempt);
-                        let stm32g4_ranges = vec![
-                            (0x20000000, 0x20004000), // SRAM1 first half: 16KB - most likely RTT location
-                            (0x20004000, 0x20008000), // SRAM1 second half: 16KB
-                            (0x20008000, 0x2000A000), // SRAM2: 8KB
-                        ];
-                        rtt_manager.attach(session_arc.session.clone(), None, Some(stm32g4_ranges)).await
-                    }
-                    _ => {
-                        // Last attempt: try common RTT control block addresses
-                        let cb_addr = 0x20000000;
-                        debug!("RTT attempt {}: Using specific control block address 0x{:08X}", attempt, cb_addr);
-                        rtt_manager.attach(session_arc.session.clone(), Some(cb_addr), None).await
-                    }
-                };
-                
-                match rtt_result {
-                    Ok(_) => {
-                        let up_channels = rtt_manager.up_channel_count();
-                        let down_channels = rtt_manager.down_channel_count();
-                        status_messages.push(format!("✅ RTT attached on attempt {} ({} up, {} down channels)", attempt, up_channels, down_channels));
-                        info!("RTT successfully attached after {} attempts!", attempt);
-                        rtt_attached = true;
-                    }
-                    Err(e) => {
-                        if attempt == max_attempts {
-                            // Final attempt failed
-                            status_messages.push(format!("⚠️ RTT attach failed after {} attempts: {}", max_attempts, e));
-                            warn!("RTT attachment failed completely after {} attempts", max_attempts);
-                        } else {
-                            debug!("RTT attach attempt {}/{} failed: {}, retrying with different strategy...", attempt, max_attempts, e);
-                        }
-                    }
-                }
-                attempt += 1;
-            }
-            
-            // If RTT successfully connected, give extra initialization time
-            if rtt_attached {
-                info!("RTT connected successfully, allowing channel stabilization...");
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            }
+/// Parse the addresses of a batch read and the `(address, size)` reads of the valid ones.
+///
+/// A bad address fails only its own entry, so the reads skip it and keep request order.
+fn batch_read_requests(reads: &[BatchReadEntry]) -> (Vec<EntryAddress>, Vec<(u64, usize)>) {
+    let addresses: Vec<EntryAddress> = reads.iter().map(|entry| parse_address(&entry.address)).collect();
+    let requests = reads.iter()
+        .zip(&addresses)
+        .filter_map(|(entry, address)| address.as_ref().ok().map(|&address| (address, entry.size)))
+        .collect();
+    (addresses, requests)
+}
+
+/// Total of the sizes in a batch, `None` when one entry or the running total exceeds `limit`
+fn batch_total(sizes: impl IntoIterator<Item = usize>, limit: usize) -> Option<usize> {
+    sizes.into_iter().try_fold(0usize, |total, size| {
+        if size > limit {
+            return None;
         }
+        total.checked_add(size).filter(|&total| total <= limit)
+    })
+}
 
-        status_messages.push("🔄 Step 5/5: Finalizing...".to_string());
-        let elapsed = start_time.elapsed();
+/// Bytes a `batch` reads and writes in total, `None` when either exceeds its limit
+fn batch_op_sizes(ops: &[BatchOp], max_read_size: usize, max_write_size: usize) -> Option<(usize, usize)> {
+    let reads = ops.iter().filter_map(|op| match op { BatchOp::Read { size, .. } => Some(*size), _ => None });
+    let writes = ops.iter().filter_map(|op| match op { BatchOp::Write { data, .. } => Some(data.len()), _ => None });
+    Some((batch_total(reads, max_read_size)?, batch_total(writes, max_write_size)?))
+}
 
-        let message = format!(
-            "🚀 Firmware deployment completed!\n\n\
-            Session ID: {}\n\
-            File: {}\n\
-            Format: {}\n\
-            Total Time: {:.1}s\n\n\
-            Status:\n{}\n\n\
-            ✅ Firmware is now running on target.\n\
-            {}",
-            args.session_id,
-            args.file_path,
-            args.format,
-            elapsed.as_secs_f64(),
-            status_messages.join("\n"),
-            if args.attach_rtt { "Use 'rtt_read' to monitor target output." } else { "Use 'rtt_attach' to enable real-time communication." }
-        );
+/// Probe selector and target chip of `connect`, falling back to the [debugger] defaults from the configuration
+fn connect_target(args: &ConnectArgs, config: &DebuggerConfig) -> Result<(String, String), McpError> {
+    let Some(probe_selector) = args.probe_selector.clone().or_else(|| config.default_probe_selector.clone()) else {
+        let error_msg = "❌ No probe_selector given and no debugger.default_probe_selector configured\n\nPass probe_selector (e.g. \"auto\") or set a default in the configuration file".to_string();
+        return Err(tool_error(error_msg, &DebugError::InvalidConfig("missing probe_selector".to_string())));
+    };
+    let Some(target_chip) = args.target_chip.clone().or_else(|| config.default_target_chip.clone()) else {
+        let error_msg = "❌ No target_chip given and no debugger.default_target_chip configured\n\nPass target_chip (e.g. \"STM32F407VGTx\" or \"auto\") or set a default in the configuration file".to_string();
+        return Err(tool_error(error_msg, &DebugError::InvalidConfig("missing target_chip".to_string())));
+    };
+    Ok((probe_selector, target_chip))
+}
 
-        info!("Firmware deployment completed for session: {} in {:.1}s", args.session_id, elapsed.as_secs_f64());
-        Ok(CallToolResult::success(vec![Content::text(message)]))
+/// Whether `reset` re-arms breakpoints; `skip_rearm` is the older spelling of `persist_breakpoints: false`
+fn rearm_after_reset(args: &ResetArgs) -> bool {
+    args.persist_breakpoints && !args.skip_rearm
+}
+
+/// Count a session under the state of its core 0; `None` when the state could not be read
+fn count_core_state(statistics: &mut SessionStatistics, status: Option<CoreStatus>) {
+    match status {
+        Some(CoreStatus::Halted(_)) => statistics.halted_sessions += 1,
+        Some(CoreStatus::Running) | Some(CoreStatus::Sleeping) => statistics.running_sessions += 1,
+        _ => statistics.disconnected_sessions += 1,
     }
 }
 
-// =============================================================================
-// Utility Functions
-// =============================================================================
+/// How often the session reaper looks for sessions idle longer than `timeout`
+fn reaper_scan_interval(timeout: std::time::Duration) -> std::time::Duration {
+    (timeout / 4).clamp(std::time::Duration::from_secs(1), std::time::Duration::from_secs(60))
+}
+
+/// Build a tool error carrying the stable code of the underlying [`DebugError`]
+fn tool_error(message: String, error: &DebugError) -> McpError {
+    McpError::internal_error(message, Some(error.to_json()))
+}
+
+/// Tool error for an argument that does not parse or is out of range
+fn invalid_argument(message: String) -> McpError {
+    tool_error(message.clone(), &DebugError::InvalidConfig(message))
+}
+
+/// Address and core of a breakpoint named by "#id" or by address.
+///
+/// An id names the breakpoint on its own core; an address is looked up on `core_index`.
+fn resolve_breakpoint(breakpoint: &str, core_index: usize, known: &[BreakpointInfo]) -> Result<(u64, usize), String> {
+    match breakpoint.trim().strip_prefix('#') {
+        Some(id) => {
+            let id: u32 = id.parse().map_err(|_| format!("Invalid breakpoint id '{}'", breakpoint))?;
+            known.iter()
+                .find(|known| known.id == id)
+                .map(|known| (known.address, known.core_index))
+                .ok_or_else(|| format!("No breakpoint with id #{}", id))
+        }
+        None => parse_address(breakpoint)
+            .map(|address| (address, core_index))
+            .map_err(|e| format!("Invalid address '{}': {}", breakpoint, e)),
+    }
+}
+
+/// Parse RTT scan ranges into `(start, end)` pairs, rejecting empty or reversed ones
+fn parse_memory_ranges(ranges: &[MemoryRange]) -> Result<Vec<(u64, u64)>, String> {
+    ranges.iter()
+        .map(|range| {
+            let start = parse_address(&range.start)
+                .map_err(|e| format!("Invalid start address '{}': {}", range.start, e))?;
+            let end = parse_address(&range.end)
+                .map_err(|e| format!("Invalid end address '{}': {}", range.end, e))?;
+            if end <= start {
+                return Err(format!("Invalid memory range {}..{}: the end must be past the start", range.start, range.end));
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
 
 /// Parse address string (hex or decimal) to u64
 fn parse_address(addr_str: &str) -> Result<u64, String> {
@@ -1675,13 +6092,264 @@ fn parse_address(addr_str: &str) -> Result<u64, String> {
     }
 }
 
+/// Progress notifications of one tool call, sent while the operation runs
+struct ProgressForwarder {
+    sink: ProgressSink,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl ProgressForwarder {
+    /// Forward progress to the client when it asked for it with a progress token
+    fn start(context: &RequestContext<RoleServer>) -> Option<Self> {
+        let progress_token = context.meta.get_progress_token()?;
+        let peer = context.peer.clone();
+        let (sink, mut receiver) = tokio::sync::mpsc::unbounded_channel::<ProgressUpdate>();
+        let task = tokio::spawn(async move {
+            // Bytes of the earlier phases, so the progress value only grows
+            let mut completed = 0u64;
+            let mut current: Option<(ProgressPhase, u64)> = None;
+            let mut last_sent: Option<std::time::Instant> = None;
+            while let Some(update) = receiver.recv().await {
+                let new_phase = current.is_none_or(|(phase, _)| phase != update.phase);
+                if new_phase {
+                    completed += current.map_or(0, |(_, total)| total);
+                    current = Some((update.phase, update.total));
+                }
+                let due = last_sent.is_none_or(|sent| sent.elapsed() >= PROGRESS_INTERVAL);
+                if !(new_phase || due || update.finished()) {
+                    continue;
+                }
+                last_sent = Some(std::time::Instant::now());
+                let notification = ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: u32::try_from(completed + update.done).unwrap_or(u32::MAX),
+                    total: Some(u32::try_from(completed + update.total).unwrap_or(u32::MAX)),
+                    message: Some(update.describe()),
+                };
+                if let Err(e) = peer.notify_progress(notification).await {
+                    debug!("Stopped sending progress: {}", e);
+                    break;
+                }
+            }
+        });
+        Some(ProgressForwarder { sink, task })
+    }
+
+    /// Wait until the last update went out, so it arrives before the tool result
+    async fn finish(self) {
+        drop(self.sink);
+        let _ = self.task.await;
+    }
+}
+
+/// Wait for the progress of a finished operation, when it was forwarded
+async fn finish_progress(forwarder: Option<ProgressForwarder>) {
+    if let Some(forwarder) = forwarder {
+        forwarder.finish().await;
+    }
+}
+
+/// Why opening a probe and attaching to the target failed
+enum ConnectFailure {
+    Open(probe_rs::probe::DebugProbeError),
+    /// The probe was in use and the USB reset to reclaim it failed
+    Reclaim { open: probe_rs::probe::DebugProbeError, reset: DebugError },
+    Attach(probe_rs::Error),
+}
+
+impl ConnectFailure {
+    /// Whether another attempt may succeed; a missing chip or a failed reclaim will not
+    fn is_transient(&self) -> bool {
+        !matches!(self, ConnectFailure::Reclaim { .. } | ConnectFailure::Attach(probe_rs::Error::ChipNotFound(_)))
+    }
+}
+
+/// Error kind of a failed attach: an unknown chip is unsupported, anything else a connection failure
+fn attach_error(error: &probe_rs::Error) -> DebugError {
+    match error {
+        probe_rs::Error::ChipNotFound(_) => DebugError::TargetNotSupported(error.to_string()),
+        _ => DebugError::ConnectionFailed(error.to_string()),
+    }
+}
+
+impl std::fmt::Display for ConnectFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectFailure::Open(e) => write!(f, "open failed: {}", e),
+            ConnectFailure::Reclaim { open, reset } => write!(f, "open failed: {}; reclaim failed: {}", open, reset),
+            ConnectFailure::Attach(e) => write!(f, "attach failed: {}", e),
+        }
+    }
+}
+
+/// Open a probe and attach to the target, returning the session, the
+/// negotiated speed and whether the probe has SWO.
+///
+/// `attach_mode` picks a plain attach or one under reset. With `force` a
+/// probe reported in use is reset over USB and opened once more.
+async fn open_and_attach(
+    probe_info: &probe_rs::probe::DebugProbeInfo,
+    target_chip: &str,
+    speed_khz: u32,
+    attach_mode: AttachMode,
+    force: bool,
+) -> Result<(probe_rs::Session, u32, bool), ConnectFailure> {
+    info!("Opening probe: {}", probe_info.identifier);
+    let mut probe = match probe_info.open() {
+        Ok(probe) => probe,
+        // A probe left claimed by a crashed process is reset over USB and opened once more
+        Err(e) if force && ProbeDiscovery::is_probe_busy(&e) => {
+            warn!("Probe '{}' is in use ({}); resetting it to reclaim", probe_info.identifier, e);
+            if let Err(reset) = ProbeDiscovery::reset_probe_usb(probe_info) {
+                return Err(ConnectFailure::Reclaim { open: e, reset });
+            }
+            tokio::time::sleep(PROBE_RESET_SETTLE).await;
+            probe_info.open().map_err(ConnectFailure::Open)?
+        }
+        Err(e) => return Err(ConnectFailure::Open(e)),
+    };
+
+    let actual_speed_khz = match probe.set_speed(speed_khz) {
+        Ok(actual_khz) => actual_khz,
+        Err(e) => {
+            warn!("Failed to set probe speed to {} kHz: {}", speed_khz, e);
+            probe.speed_khz()
+        }
+    };
+
+    let probe_swo = probe.get_swo_interface().is_some();
+    info!("Attaching to target: {}", target_chip);
+    // A plain probe-rs attach neither halts nor resets the core; halting is left to the caller
+    let session = match attach_mode {
+        AttachMode::UnderReset => probe.attach_under_reset(target_selector(target_chip), Permissions::default()),
+        AttachMode::Normal | AttachMode::AttachRunning => probe.attach(target_selector(target_chip), Permissions::default()),
+    }
+    .map_err(ConnectFailure::Attach)?;
+    Ok((session, actual_speed_khz, probe_swo))
+}
+
+/// Explicit stack region from an address/size argument pair; both or neither must be given
+fn stack_region_arg(address: &Option<String>, size: Option<usize>) -> Result<Option<(u64, usize)>, McpError> {
+    match (address, size) {
+        (Some(address), Some(size)) => parse_address(address)
+            .map(|parsed| Some((parsed, size)))
+            .map_err(|e| invalid_argument(format!("Invalid address '{}': {}", address, e))),
+        (None, None) => Ok(None),
+        _ => Err(invalid_argument("Give both address and size, or neither to use the ELF stack symbols".to_string())),
+    }
+}
+
+/// Resolve a code address argument, treating text that does not parse as a number as a symbol name
+async fn resolve_code_address(session: &DebugSession, text: &str, elf_path: Option<&str>) -> Result<u64, McpError> {
+    match parse_address(text) {
+        Ok(address) => Ok(address),
+        Err(_) => {
+            let symbol = session.resolve_symbol(text, elf_path).await
+                .map_err(|e| tool_error(format!("Failed to resolve symbol '{}': {}", text, e), &e))?;
+            Ok(symbol.address)
+        }
+    }
+}
+
+/// " (x µs at y Hz)" for a cycle count, or nothing without a clock
+fn describe_cycle_time(cycles: u64, cpu_hz: Option<u64>) -> String {
+    match cpu_hz {
+        Some(hz) => format!(" ({:.3} µs at {} Hz)", cycles::cycles_to_microseconds(cycles, hz), hz),
+        None => String::new(),
+    }
+}
+
+/// Resolve an endianness argument, where "target" means the target's native byte order
+async fn resolve_endianness(session: &DebugSession, name: &str) -> Result<Endianness, McpError> {
+    if name.eq_ignore_ascii_case("target") {
+        session.target_endianness().await
+            .map_err(|e| tool_error(e.to_string(), &e))
+    } else {
+        Endianness::parse(name).map_err(invalid_argument)
+    }
+}
+
+/// Warning lines for breakpoints and watchpoints that could not be restored
+fn describe_rearm_failures(result: &ReconnectResult) -> String {
+    let mut lines = String::new();
+    for (address, e) in &result.breakpoints_failed {
+        lines.push_str(&format!("⚠️ Failed to restore breakpoint at 0x{:08X}: {}\n", address, e));
+    }
+    for (address, e) in &result.watchpoints_failed {
+        lines.push_str(&format!("⚠️ Failed to restore watchpoint at 0x{:08X}: {}\n", address, e));
+    }
+    lines
+}
+
+/// One-line summary of a watchpoint, e.g. "0x20000010 (4 bytes, write) on DWT0"
+fn describe_watchpoint(watchpoint: &WatchpointInfo) -> String {
+    format!(
+        "0x{:08X} ({} bytes, {}) on DWT{}",
+        watchpoint.address, watchpoint.size, watchpoint.access, watchpoint.comparator
+    )
+}
+
+/// Tool output for `step_over` and `step_out`
+fn describe_step(operation: &str, session_id: &str, result: &StepResult) -> String {
+    let headline = if result.timed_out {
+        format!("⏱️ {} timed out; target halted", operation)
+    } else {
+        format!("✅ {} completed", operation)
+    };
+    let method = match (result.instructions, result.ran_to) {
+        (Some(count), _) => format!("Single-stepped {} instruction(s)", count),
+        (None, Some(address)) => format!("Ran to return address 0x{:08X}", address),
+        (None, None) => "Ran".to_string(),
+    };
+    let mut message = format!(
+        "{}\n\n\
+        Session ID: {}\n\
+        Method: {}\n\
+        PC: {}\n\
+        Status: {:?}\n\
+        Duration: {}ms",
+        headline,
+        session_id,
+        method,
+        result.pc.map(|pc| format!("0x{:08X}", pc)).unwrap_or_else(|| "unavailable".to_string()),
+        result.status,
+        result.elapsed_ms
+    );
+    if let (Some(address), Some(pc)) = (result.ran_to, result.pc) {
+        if address != pc && !result.timed_out {
+            message.push_str("\n\nThe core halted before returning (fault, breakpoint or watchpoint); the temporary breakpoint was removed.");
+        }
+    }
+    message
+}
+
+/// What stopped a multi-instruction step early: a breakpoint, a lockup or a fault
+fn step_stop_reason(result: &StepResult) -> Option<String> {
+    match (&result.breakpoint, &result.status) {
+        (Some(breakpoint), _) => Some(format!(
+            "Breakpoint at 0x{:08X}{}",
+            breakpoint.address,
+            breakpoint.symbol.as_ref().map(|symbol| format!(" <{}>", symbol)).unwrap_or_default()
+        )),
+        (None, CoreStatus::LockedUp) => Some("Core locked up".to_string()),
+        (None, CoreStatus::Halted(HaltReason::Exception)) => Some("Exception (fault)".to_string()),
+        (None, _) => None,
+    }
+}
+
 /// Parse data string based on format
-fn parse_data(data_str: &str, format: &str) -> Result<Vec<u8>, String> {
+///
+/// - "hex": byte string like "DEADBEEF" or "0xDE 0xAD"
+/// - "ascii": the literal string bytes
+/// - "binary": base64-encoded raw bytes
+/// - "words16"/"words32": comma or whitespace separated values like "0xDEADBEEF,0x00000001",
+///   serialized in `endianness` byte order
+fn parse_data(data_str: &str, format: &str, endianness: Endianness) -> Result<Vec<u8>, String> {
     match format {
         "hex" => {
             // Remove spaces and 0x prefixes
             let clean_str = data_str.replace(" ", "").replace("0x", "").replace("0X", "");
-            if clean_str.len() % 2 != 0 {
+            if !clean_str.len().is_multiple_of(2) {
                 return Err("Hex data must have even number of characters".to_string());
             }
             
@@ -1692,125 +6360,46 @@ fn parse_data(data_str: &str, format: &str) -> Result<Vec<u8>, String> {
                 .map_err(|e| format!("Invalid hex data: {}", e))
         }
         "ascii" => Ok(data_str.as_bytes().to_vec()),
-        "words32" => {
-            let words: Result<Vec<u32>, _> = data_str
-                .split_whitespace()
-                .map(|s| {
-                    if s.starts_with("0x") || s.starts_with("0X") {
-                        u32::from_str_radix(&s[2..], 16)
-                    } else {
-                        s.parse::<u32>()
-                    }
-                })
-                .collect();
-            
-            match words {
-                Ok(words) => {
-                    let mut data = Vec::new();
-                    for word in words {
-                        data.extend_from_slice(&word.to_le_bytes());
-                    }
-                    Ok(data)
-                }
-                Err(e) => Err(format!("Invalid word32 data: {}", e)),
-            }
+        "binary" => {
+            use base64::Engine;
+            base64::engine::general_purpose::STANDARD
+                .decode(data_str.trim())
+                .map_err(|e| format!("Invalid base64 data: {}", e))
         }
-        "words16" => {
-            let words: Result<Vec<u16>, _> = data_str
-                .split_whitespace()
-                .map(|s| {
-                    if s.starts_with("0x") || s.starts_with("0X") {
-                        u16::from_str_radix(&s[2..], 16)
-                    } else {
-                        s.parse::<u16>()
-                    }
-                })
-                .collect();
-            
-            match words {
-                Ok(words) => {
-                    let mut data = Vec::new();
-                    for word in words {
-                        data.extend_from_slice(&word.to_le_bytes());
-                    }
-                    Ok(data)
-                }
-                Err(e) => Err(format!("Invalid word16 data: {}", e)),
+        "words32" | "words16" => {
+            let width = word_width(format).unwrap_or(4);
+            let mut data = Vec::new();
+            for word in split_words(data_str) {
+                let value = parse_word(word)
+                    .and_then(|v| {
+                        if v >> (width * 8) != 0 {
+                            Err(format!("Value {} does not fit in {} bits", word, width * 8))
+                        } else {
+                            Ok(v)
+                        }
+                    })
+                    .map_err(|e| format!("Invalid {} data: {}", format, e))?;
+                data.extend_from_slice(&endianness.word_to_bytes(value, width));
             }
+            Ok(data)
         }
         _ => Err(format!("Unsupported data format: {}", format)),
     }
 }
 
-/// Format memory data for display
-fn format_memory_data(data: &[u8], format: &str, base_address: u64) -> String {
-    match format {
-        "hex" => {
-            let mut result = String::new();
-            for (i, chunk) in data.chunks(16).enumerate() {
-                let addr = base_address + (i * 16) as u64;
-                result.push_str(&format!("0x{:08X}: ", addr));
-                
-                // Hex bytes
-                for (j, byte) in chunk.iter().enumerate() {
-                    if j == 8 { result.push(' '); }
-                    result.push_str(&format!("{:02X} ", byte));
-                }
-                
-                // Pad if needed
-                if chunk.len() < 16 {
-                    let padding = (16 - chunk.len()) * 3 + (if chunk.len() <= 8 { 1 } else { 0 });
-                    result.push_str(&" ".repeat(padding));
-                }
-                
-                // ASCII representation
-                result.push_str("| ");
-                for byte in chunk {
-                    if byte.is_ascii_graphic() || *byte == b' ' {
-                        result.push(*byte as char);
-                    } else {
-                        result.push('.');
-                    }
-                }
-                result.push('\n');
-            }
-            result
-        }
-        "binary" => {
-            data.iter()
-                .map(|b| format!("{:08b}", b))
-                .collect::<Vec<_>>()
-                .join(" ")
-        }
-        "words32" => {
-            let mut result = String::new();
-            for (i, chunk) in data.chunks(4).enumerate() {
-                if chunk.len() == 4 {
-                    let addr = base_address + (i * 4) as u64;
-                    let word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                    result.push_str(&format!("0x{:08X}: 0x{:08X}\n", addr, word));
-                }
-            }
-            result
-        }
-        "words16" => {
-            let mut result = String::new();
-            for (i, chunk) in data.chunks(2).enumerate() {
-                if chunk.len() == 2 {
-                    let addr = base_address + (i * 2) as u64;
-                    let word = u16::from_le_bytes([chunk[0], chunk[1]]);
-                    result.push_str(&format!("0x{:08X}: 0x{:04X}\n", addr, word));
-                }
-            }
-            result
-        }
-        "ascii" => {
-            String::from_utf8_lossy(data).to_string()
-        }
-        _ => {
-            // Default to hex if unknown format
-            format_memory_data(data, "hex", base_address)
-        }
+/// Split a word list on commas and whitespace
+fn split_words(data_str: &str) -> impl Iterator<Item = &str> {
+    data_str
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+}
+
+/// Parse a single hex ("0x...") or decimal word value
+fn parse_word(word: &str) -> Result<u64, String> {
+    if let Some(hex) = word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).map_err(|e| format!("'{}': {}", word, e))
+    } else {
+        word.parse::<u64>().map_err(|e| format!("'{}': {}", word, e))
     }
 }
 
@@ -1819,9 +6408,9 @@ impl ServerHandler for EmbeddedDebuggerToolHandler {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder().enable_tools().enable_logging().build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some("Complete embedded debugging and flash programming MCP server supporting ARM Cortex-M, RISC-V, and other architectures via probe-rs. Provides comprehensive debugging and flash programming capabilities including probe detection, target connection, memory operations, breakpoints, RTT communication, and flash programming with real hardware integration. All 22 tools available: list_probes, connect, disconnect, probe_info, halt, run, reset, step, get_status, read_memory, write_memory, set_breakpoint, clear_breakpoint, rtt_attach, rtt_detach, rtt_read, rtt_write, rtt_channels, flash_erase, flash_program, flash_verify, run_firmware.".to_string()),
+            instructions: Some("Complete embedded debugging and flash programming MCP server supporting ARM Cortex-M, RISC-V, and other architectures via probe-rs. Provides comprehensive debugging and flash programming capabilities including probe detection, target connection, memory operations, breakpoints, RTT communication, and flash programming with real hardware integration. All 94 tools available: list_probes, connect, disconnect, probe_info, halt, run, reset, step, get_status, read_memory, write_memory, set_breakpoint, clear_breakpoint, rtt_attach, rtt_detach, rtt_read, rtt_write, rtt_channels, flash_erase, flash_program, flash_verify, run_firmware, backtrace, memory_fill, pc_to_source, memory_search, list_cores, memory_dump, memory_compare, get_session_status, read_memory_batch, reconnect, write_memory_batch, enable_semihosting, read_semihosting_output, memory_checksum, read_all_registers, read_memory_typed, watch_memory, read_registers, set_speed, write_register, wait_for_halt, vector_catch, register_snapshot, register_diff, delete_register_snapshot, list_breakpoints, set_watchpoint, clear_watchpoint, list_watchpoints, load_symbols, rtt_read_lines, run_to_address, list_supported_chips, search_chips, step_over, step_out, load_target, enable_breakpoint, disable_breakpoint, get_capabilities, clear_all_breakpoints, batch, get_memory_map, unload_symbols, symbolize_address, lookup_symbol, read_word, write_word, write_variable, read_locals, disassemble, read_cpuid, poll_variable, read_variable_history, stop_variable_poll, analyze_fault, core_dump, paint_stack, measure_stack_usage, rtos_threads, get_statistics, cycle_counter_enable, cycle_counter_read, cycle_counter_reset, cycle_counter_measure, rtt_attach_framed, rtt_read_frames, run_instructions, run_with_semihosting, flash_blank_check, run_tests, subscribe_events.".to_string()),
         }
     }
 
@@ -1830,7 +6419,270 @@ impl ServerHandler for EmbeddedDebuggerToolHandler {
         _request: InitializeRequestParam,
         _context: RequestContext<RoleServer>,
     ) -> Result<InitializeResult, McpError> {
-        info!("Complete Embedded Debugger MCP server initialized with all 22 tools (18 debug + 4 flash)");
+        info!("Complete Embedded Debugger MCP server initialized with all 94 tools");
         Ok(self.get_info())
     }
-}
\ No newline at end of file
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        // Target events are only sent to sessions that subscribed, so there is nothing to filter
+        debug!("Client requested log level {:?}", request.level);
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argument_and_attach_errors_carry_codes() {
+        let error = invalid_argument("Invalid hex address: 0xZZ".to_string());
+        let data = error.data.unwrap();
+        assert_eq!(data["code"], "invalid-config");
+        assert_eq!(data["message"], "Invalid configuration: Invalid hex address: 0xZZ");
+        assert_eq!(error.message, "Invalid hex address: 0xZZ");
+
+        assert_eq!(attach_error(&probe_rs::Error::ChipNotFound(probe_rs::config::RegistryError::ChipNotFound("nrf99".to_string()))).code(), "target-not-supported");
+        assert_eq!(attach_error(&probe_rs::Error::Timeout).code(), "connection-failed");
+    }
+
+    #[test]
+    fn test_batch_read_requests_skip_bad_addresses() {
+        let entry = |address: &str, size| BatchReadEntry { address: address.to_string(), size, format: "hex".to_string() };
+        let reads = [entry("0x20000000", 4), entry("nowhere", 8), entry("536870928", 2)];
+
+        let (addresses, requests) = batch_read_requests(&reads);
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses[0], Ok(0x2000_0000));
+        assert!(addresses[1].is_err());
+        assert_eq!(requests, vec![(0x2000_0000, 4), (0x2000_0010, 2)]);
+    }
+
+    #[test]
+    fn test_batch_total() {
+        assert_eq!(batch_total([512, 512], 1024), Some(1024));
+        assert_eq!(batch_total([], 1024), Some(0));
+        assert_eq!(batch_total([512, 513], 1024), None);
+        // A single huge entry must not wrap the total back under the limit
+        assert_eq!(batch_total([usize::MAX], 1024), None);
+        assert_eq!(batch_total([1, usize::MAX], 1024), None);
+        assert_eq!(batch_total([usize::MAX, usize::MAX], usize::MAX), None);
+    }
+
+    #[test]
+    fn test_batch_op_sizes() {
+        let read = |size| BatchOp::Read { address: 0x2000_0000, size };
+        let write = BatchOp::Write { address: 0x2000_0000, data: vec![0; 8] };
+        assert_eq!(batch_op_sizes(&[BatchOp::Halt, read(16), write.clone(), read(16)], 64, 8), Some((32, 8)));
+        assert_eq!(batch_op_sizes(&[write.clone(), write], 64, 8), None);
+        // Sizes that wrap around usize must not slip under the limit
+        assert_eq!(batch_op_sizes(&[read(usize::MAX), read(2)], 64, 8), None);
+        assert_eq!(batch_op_sizes(&[read(usize::MAX / 2 + 1), read(usize::MAX / 2 + 1)], usize::MAX, 8), None);
+    }
+
+    #[test]
+    fn test_probe_speed_display() {
+        let speed = ProbeSpeed { requested_khz: 8000, actual_khz: 4000 };
+        assert_eq!(speed.to_string(), "4000 kHz (requested 8000 kHz)");
+    }
+
+    #[test]
+    fn test_rtt_write_is_non_blocking_by_default() {
+        let args: RttWriteArgs = serde_json::from_value(serde_json::json!({ "session_id": "s", "data": "hi" })).unwrap();
+        assert!(!args.blocking);
+        assert_eq!(args.timeout_ms, 1000);
+    }
+
+    #[test]
+    fn test_rearm_after_reset() {
+        let reset_args = |extra: serde_json::Value| {
+            let mut args = serde_json::json!({ "session_id": "s" });
+            args.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            serde_json::from_value::<ResetArgs>(args).unwrap()
+        };
+        assert!(rearm_after_reset(&reset_args(serde_json::json!({}))));
+        assert!(!rearm_after_reset(&reset_args(serde_json::json!({ "persist_breakpoints": false }))));
+        assert!(!rearm_after_reset(&reset_args(serde_json::json!({ "skip_rearm": true }))));
+    }
+
+    #[test]
+    fn test_session_statistics() {
+        let mut statistics = SessionStatistics { total_sessions: 4, max_sessions: 5, total_breakpoints: 3, total_uptime_seconds: 90, ..Default::default() };
+        count_core_state(&mut statistics, Some(CoreStatus::Halted(probe_rs::HaltReason::Breakpoint(probe_rs::BreakpointCause::Hardware))));
+        count_core_state(&mut statistics, Some(CoreStatus::Sleeping));
+        count_core_state(&mut statistics, Some(CoreStatus::LockedUp));
+        count_core_state(&mut statistics, None);
+        assert_eq!((statistics.halted_sessions, statistics.running_sessions, statistics.disconnected_sessions), (1, 1, 2));
+    }
+
+    #[test]
+    fn test_disconnect_leave_running() {
+        let args: DisconnectArgs = serde_json::from_value(serde_json::json!({ "session_id": "s" })).unwrap();
+        assert!(!args.leave_running);
+
+        let args: DisconnectArgs = serde_json::from_value(serde_json::json!({ "session_id": "s", "leave_running": true })).unwrap();
+        assert!(args.leave_running);
+    }
+
+    #[test]
+    fn test_connect_failure_is_transient() {
+        use probe_rs::probe::DebugProbeError;
+
+        let open = ConnectFailure::Open(DebugProbeError::Timeout);
+        assert!(open.is_transient());
+        assert!(open.to_string().starts_with("open failed: "));
+        assert!(ConnectFailure::Attach(probe_rs::Error::Timeout).is_transient());
+
+        let missing_chip = ConnectFailure::Attach(probe_rs::Error::ChipNotFound(
+            probe_rs::config::RegistryError::ChipNotFound("nrf99".to_string()),
+        ));
+        assert!(!missing_chip.is_transient());
+        assert!(missing_chip.to_string().starts_with("attach failed: "));
+
+        let reclaim = ConnectFailure::Reclaim {
+            open: DebugProbeError::Attached,
+            reset: DebugError::ProbeError("USB reset not supported".to_string()),
+        };
+        assert!(!reclaim.is_transient());
+        assert!(reclaim.to_string().contains("; reclaim failed: "));
+    }
+
+    #[test]
+    fn test_connect_target_falls_back_to_config() {
+        let mut config = DebuggerConfig::default();
+        let args: ConnectArgs = serde_json::from_value(serde_json::json!({})).unwrap();
+        let error = connect_target(&args, &config).unwrap_err();
+        assert!(error.message.contains("No probe_selector given"));
+        assert_eq!(error.data.unwrap()["code"], "invalid-config");
+
+        config.default_probe_selector = Some("auto".to_string());
+        assert!(connect_target(&args, &config).unwrap_err().message.contains("No target_chip given"));
+
+        config.default_target_chip = Some("STM32F407VGTx".to_string());
+        assert_eq!(connect_target(&args, &config).unwrap(), ("auto".to_string(), "STM32F407VGTx".to_string()));
+
+        let args: ConnectArgs = serde_json::from_value(serde_json::json!({ "probe_selector": "0483:374b", "target_chip": "nRF52840_xxAA" })).unwrap();
+        assert_eq!(connect_target(&args, &config).unwrap(), ("0483:374b".to_string(), "nRF52840_xxAA".to_string()));
+    }
+
+    #[test]
+    fn test_connect_halts_by_default() {
+        let args: ConnectArgs = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(args.halt_after_connect);
+        let args: ConnectArgs = serde_json::from_value(serde_json::json!({ "halt_after_connect": false })).unwrap();
+        assert!(!args.halt_after_connect);
+    }
+
+    #[test]
+    fn test_reaper_scan_interval() {
+        use std::time::Duration;
+        assert_eq!(reaper_scan_interval(Duration::from_secs(3600)), Duration::from_secs(60));
+        assert_eq!(reaper_scan_interval(Duration::from_secs(120)), Duration::from_secs(30));
+        assert_eq!(reaper_scan_interval(Duration::from_secs(2)), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_parse_data_formats() {
+        assert_eq!(parse_data("0xDE AD be ef", "hex", Endianness::Little).unwrap(), vec![0xDE, 0xAD, 0xBE, 0xEF]);
+        assert!(parse_data("ABC", "hex", Endianness::Little).is_err());
+        assert_eq!(parse_data("Hi!", "ascii", Endianness::Little).unwrap(), b"Hi!".to_vec());
+        assert_eq!(parse_data(" AQID\n", "binary", Endianness::Little).unwrap(), vec![1, 2, 3]);
+        assert!(parse_data("not base64", "binary", Endianness::Little).is_err());
+        assert!(parse_data("00", "octal", Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn test_parse_data_words() {
+        assert_eq!(parse_data("0x12345678, 1", "words32", Endianness::Little).unwrap(), vec![0x78, 0x56, 0x34, 0x12, 1, 0, 0, 0]);
+        assert_eq!(parse_data("0x1234 0X5678", "words16", Endianness::Big).unwrap(), vec![0x12, 0x34, 0x56, 0x78]);
+        assert!(parse_data("0x10000", "words16", Endianness::Little).unwrap_err().contains("does not fit in 16 bits"));
+        assert!(parse_data("0xZZ", "words32", Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn test_describe_rearm_failures() {
+        let mut result = ReconnectResult {
+            breakpoints_restored: vec![0x0800_0100],
+            breakpoints_failed: Vec::new(),
+            watchpoints_failed: Vec::new(),
+        };
+        assert_eq!(describe_rearm_failures(&result), "");
+
+        result.breakpoints_failed.push((0x0800_0200, "no free comparator".to_string()));
+        result.watchpoints_failed.push((0x2000_0010, "DWT unavailable".to_string()));
+        let report = describe_rearm_failures(&result);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("breakpoint at 0x08000200") && lines[0].contains("no free comparator"));
+        assert!(lines[1].contains("watchpoint at 0x20000010") && lines[1].contains("DWT unavailable"));
+    }
+
+    #[test]
+    fn test_step_stop_reason() {
+        let mut result = StepResult {
+            status: CoreStatus::Halted(HaltReason::Step),
+            pc: Some(0x0800_0104),
+            instructions: Some(3),
+            ran_to: None,
+            timed_out: false,
+            elapsed_ms: 1,
+            trace: Some(vec![0x0800_0100, 0x0800_0102, 0x0800_0104]),
+            breakpoint: None,
+        };
+        assert_eq!(step_stop_reason(&result), None);
+
+        result.status = CoreStatus::LockedUp;
+        assert_eq!(step_stop_reason(&result).as_deref(), Some("Core locked up"));
+        result.status = CoreStatus::Halted(HaltReason::Exception);
+        assert_eq!(step_stop_reason(&result).as_deref(), Some("Exception (fault)"));
+
+        result.breakpoint = Some(BreakpointInfo {
+            id: 2,
+            address: 0x0800_0104,
+            core_index: 0,
+            symbol: Some("main".to_string()),
+            kind: BreakpointKind::Hardware,
+            temporary: false,
+            enabled: true,
+            hit_count: 0,
+            skip_count: 0,
+        });
+        assert_eq!(step_stop_reason(&result).as_deref(), Some("Breakpoint at 0x08000104 <main>"));
+    }
+
+    #[test]
+    fn test_parse_memory_ranges() {
+        let range = |start: &str, end: &str| MemoryRange { start: start.to_string(), end: end.to_string() };
+        let ranges = parse_memory_ranges(&[range("0x20000000", "0x20000400"), range("0x20010000", "0x20010100")]).unwrap();
+        assert_eq!(ranges, vec![(0x2000_0000, 0x2000_0400), (0x2001_0000, 0x2001_0100)]);
+
+        assert_eq!(
+            parse_memory_ranges(&[range("0x20000400", "0x20000400")]),
+            Err("Invalid memory range 0x20000400..0x20000400: the end must be past the start".to_string())
+        );
+        assert!(parse_memory_ranges(&[range("ram", "0x20000400")]).unwrap_err().starts_with("Invalid start address 'ram'"));
+    }
+
+    #[test]
+    fn test_resolve_breakpoint() {
+        let known = vec![BreakpointInfo {
+            id: 7,
+            address: 0x0800_0200,
+            core_index: 1,
+            symbol: None,
+            kind: BreakpointKind::Hardware,
+            temporary: false,
+            enabled: false,
+            hit_count: 3,
+            skip_count: 0,
+        }];
+        assert_eq!(resolve_breakpoint("#7", 0, &known), Ok((0x0800_0200, 1)));
+        assert_eq!(resolve_breakpoint(" 0x08000300 ", 0, &known), Ok((0x0800_0300, 0)));
+        assert_eq!(resolve_breakpoint("#8", 0, &known), Err("No breakpoint with id #8".to_string()));
+        assert_eq!(resolve_breakpoint("#x", 0, &known), Err("Invalid breakpoint id '#x'".to_string()));
+        assert!(resolve_breakpoint("main", 0, &known).unwrap_err().starts_with("Invalid address 'main'"));
+    }
+}
@@ -7,6 +7,6 @@
 pub mod debugger_tools;
 pub mod types;
 
-// Export all 18 tools (13 base debugging + 5 RTT communication)
+// Export the tool handler and its argument types
 pub use debugger_tools::*;
 pub use types::*;
\ No newline at end of file
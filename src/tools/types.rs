@@ -12,138 +12,1094 @@ pub struct ListProbesArgs {
     // No parameters needed
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListSupportedChipsArgs {
+    /// Only list chip families whose name contains this text (case-insensitive)
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchChipsArgs {
+    /// Part of a chip name like "stm32f407" or "nrf52840" (case-insensitive)
+    pub query: String,
+    /// Maximum number of chips to return
+    #[serde(default = "default_max_chips")]
+    pub max_results: usize,
+}
+
+fn default_max_chips() -> usize { 50 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LoadTargetArgs {
+    /// Path to a probe-rs target description YAML (as produced by `target-gen`)
+    pub file_path: String,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ConnectArgs {
-    /// Probe selector (serial number, identifier, or "auto" for first available)
-    pub probe_selector: String,
-    /// Target chip name (e.g., "STM32F407VGTx", "nRF52840_xxAA")
-    pub target_chip: String,
+    /// Probe selector (serial number, identifier, or "auto" for first available);
+    /// defaults to debugger.default_probe_selector from the configuration
+    pub probe_selector: Option<String>,
+    /// Target chip name (e.g., "STM32F407VGTx", "nRF52840_xxAA"), or "auto" to let probe-rs identify the chip;
+    /// defaults to debugger.default_target_chip from the configuration
+    pub target_chip: Option<String>,
     /// Connection speed in kHz (default: 4000)
     #[serde(default = "default_speed_khz")]
     pub speed_khz: u32,
-    /// Whether to connect under reset
+    /// Whether to connect under reset; same as attach_mode "under_reset"
+    #[serde(default)]
+    pub connect_under_reset: bool,
+    /// How to attach: "normal", "under_reset" (hold nRST during the attach) or "attach_running"
+    /// (no halt and no reset, for live systems; halt_after_connect is ignored).
+    /// Defaults to "under_reset" when connect_under_reset is set, else "normal"
+    pub attach_mode: Option<String>,
+    /// Halt the core after connecting (default: true); when false the core is left running
+    #[serde(default = "default_true")]
+    pub halt_after_connect: bool,
+    /// When the probe is reported in use, e.g. after a crashed debugger, reset its USB
+    /// interface and retry the open once
+    #[serde(default)]
+    pub force: bool,
+    /// Extra open+attach attempts after a failure, e.g. while the probe is still
+    /// enumerating (default: debugger.retry_count from the configuration)
+    pub connect_retries: Option<u32>,
+    /// Delay between connect attempts in milliseconds (default: debugger.connect_retry_delay_ms)
+    pub connect_retry_delay_ms: Option<u64>,
+}
+
+fn default_speed_khz() -> u32 { 4000 }
+fn default_true() -> bool { true }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DisconnectArgs {
+    /// Session ID to disconnect
+    pub session_id: String,
+    /// Remove breakpoints, watchpoints and vector catches and resume the target before
+    /// releasing the probe. A plain disconnect may leave the target halted
+    #[serde(default)]
+    pub leave_running: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ProbeInfoArgs {
+    /// Session ID to get info for
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReconnectArgs {
+    /// Session ID to reconnect
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetSpeedArgs {
+    /// Session ID
+    pub session_id: String,
+    /// New probe clock in kHz
+    pub speed_khz: u32,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SessionStatusArgs {
+    /// Session ID
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetStatisticsArgs {
+    // No parameters needed
+}
+
+// =============================================================================
+// Target Control Types
+// =============================================================================
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct HaltArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Show the source lines around the halt location, when symbols are loaded
+    #[serde(default)]
+    pub include_source: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WaitForHaltArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Maximum time to wait in milliseconds (default: 5000)
+    #[serde(default = "default_halt_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Show the source lines around the halt location, when symbols are loaded
+    #[serde(default)]
+    pub include_source: bool,
+}
+
+fn default_halt_timeout_ms() -> u64 { 5000 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct VectorCatchArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Conditions: "reset", "hard_fault", "secure_fault", "bus_err", "mm_err", "usage_err", "int_err" or "all".
+    /// When disabling, an empty list clears every catch enabled on the core
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    /// Enable (default) or disable the conditions
+    #[serde(default = "default_true")]
+    pub enable: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RegisterSnapshotArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Snapshot name; an existing snapshot with this name is replaced
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RegisterDiffArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Snapshot to compare from
+    pub from: String,
+    /// Snapshot to compare to (default: the current registers)
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeleteRegisterSnapshotArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Snapshot to delete (default: all snapshots)
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResetArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Reset type: "software" (default, the target's reset sequence), "system" (AIRCR.SYSRESETREQ,
+    /// Cortex-M only) or "hardware" (pulses nRST and re-attaches the session)
+    #[serde(default = "default_reset_type")]
+    pub reset_type: String,
+    /// Whether to halt after reset
+    #[serde(default = "default_true")]
+    pub halt_after_reset: bool,
+    /// Re-apply known breakpoints and watchpoints once the reset completes (default: true).
+    /// Best-effort: breakpoints that no longer fit, e.g. when the reset changes the available
+    /// comparator count, are reported and left disabled
+    #[serde(default = "default_true")]
+    pub persist_breakpoints: bool,
+    /// Same as `persist_breakpoints: false`
+    #[serde(default)]
+    pub skip_rearm: bool,
+}
+
+fn default_reset_type() -> String { "software".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetCapabilitiesArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListCoresArgs {
+    /// Session ID
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadCpuidArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyzeFaultArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CycleCounterArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Core clock in Hz, to also report the count as time (optional)
+    pub cpu_hz: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MeasureCyclesArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Address where the measurement starts (hex string like "0x8000400" or decimal) or a function name
+    pub start_address: String,
+    /// Address where the measurement ends (hex string or decimal) or a function name
+    pub end_address: String,
+    /// Firmware ELF used to resolve function names (optional once loaded for the session)
+    pub elf_path: Option<String>,
+    /// Maximum time for both runs together in milliseconds (default: 5000)
+    #[serde(default = "default_halt_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Core clock in Hz, to also report the duration in microseconds (optional)
+    pub cpu_hz: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetMemoryMapArgs {
+    /// Session ID
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StepArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Number of instructions to step (default: 1); stops early at a breakpoint or fault
+    #[serde(default = "default_step_count")]
+    pub count: u32,
+    /// Return the PC after every step as an instruction trace
+    #[serde(default)]
+    pub trace: bool,
+    /// Show the source lines around the new PC, when symbols are loaded
+    #[serde(default)]
+    pub include_source: bool,
+}
+
+fn default_step_count() -> u32 { 1 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunInstructionsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Number of instructions to run; stops early at a breakpoint or fault
+    pub count: u64,
+    /// Maximum time to spend stepping in milliseconds (default: 60000)
+    #[serde(default = "default_run_instructions_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_run_instructions_timeout_ms() -> u64 { 60_000 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StepOverArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Maximum time to let a called function run, in milliseconds (default: 5000)
+    #[serde(default = "default_halt_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StepOutArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Maximum time to wait for the function to return, in milliseconds (default: 5000)
+    #[serde(default = "default_halt_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetStatusArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Firmware ELF used to annotate the PC with its function name (optional)
+    pub elf_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadRegistersArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Register names, case-insensitive ("r0", "sp", "pc", "xpsr", "msp", "psp", "primask",
+    /// "basepri", "faultmask", "control", FPU "s0"-"s31", "d0"-"d15", "fpscr"; "ra" or "x1" on RISC-V).
+    /// Empty reads the general purpose registers plus PC, SP and status register
+    #[serde(default)]
+    pub registers: Vec<String>,
+    /// Decode xPSR into its N/Z/C/V/Q flags, Thumb bit and exception number
+    #[serde(default)]
+    pub decode: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteRegisterArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Register name, case-insensitive (e.g. "r0", "pc", "control", "primask", "s0", "d1", "fpscr")
+    pub register: String,
+    /// Value to write (hex string like "0x20001000" or decimal); raw IEEE 754 bits for FPU registers
+    pub value: String,
+    /// Read the register back after writing and report whether it matches
+    #[serde(default)]
+    pub verify: bool,
+    /// With verify, fail the call when the read-back differs. Leave off for registers whose
+    /// read-back legitimately differs, such as PC alignment or write-1-to-clear bits
+    #[serde(default)]
+    pub strict_verify: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadAllRegistersArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
+
+// =============================================================================
+// Memory Operation Types
+// =============================================================================
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadMemoryArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Memory address (hex string like "0x8000000" or decimal)
+    pub address: String,
+    /// Number of bytes to read
+    pub size: usize,
+    /// Output format: "hex", "binary", "ascii", "words16", "words32", "words64", or "json" for
+    /// a structured `{ address, bytes, words32 }` object instead of display text
+    #[serde(default = "default_format")]
+    pub format: String,
+    /// Byte order used to group bytes into words: "little", "big" or "target" (default, the target's native order)
+    #[serde(default = "default_endianness")]
+    pub endianness: String,
+    /// For word formats, reject sizes/addresses that are not word aligned instead of rounding the
+    /// address down and the size up to whole words
+    #[serde(default)]
+    pub strict: bool,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+    /// Read a running core without halting it (default: true); when false the core is halted for the read
+    #[serde(default = "default_true")]
+    pub allow_running: bool,
+}
+
+fn default_format() -> String { "hex".to_string() }
+fn default_endianness() -> String { "target".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadTypedArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Memory address (hex string like "0x20000000" or decimal)
+    pub address: String,
+    /// Value type: "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32" or "f64"
+    pub type_name: String,
+    /// Number of consecutive values to read (default: 1)
+    #[serde(default = "default_typed_count")]
+    pub count: usize,
+    /// Byte order: "little", "big" or "target" (default: the target architecture's native order)
+    #[serde(default)]
+    pub endianness: Option<String>,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+fn default_typed_count() -> usize { 1 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PollVariableArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Variable to sample: symbol name (e.g. "ADC_VALUE") or address (hex string like "0x20000000" or decimal)
+    pub variable: String,
+    /// Value type: "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32" or "f64"
+    pub type_name: String,
+    /// Time between samples in milliseconds (default: 100, minimum: 10)
+    #[serde(default = "default_poll_interval_ms")]
+    pub interval_ms: u64,
+    /// Samples kept before the oldest are dropped (default: 1000)
+    #[serde(default = "default_poll_history")]
+    pub history: usize,
+    /// Firmware ELF file for symbol names (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+}
+
+fn default_poll_interval_ms() -> u64 { 100 }
+fn default_poll_history() -> usize { 1000 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadVariableHistoryArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Poll to read, as returned by poll_variable (default: every poll of the session)
+    pub poll_id: Option<u32>,
+    /// Most recent samples to return per poll (default: 20)
+    #[serde(default = "default_history_count")]
+    pub count: usize,
+}
+
+fn default_history_count() -> usize { 20 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StopVariablePollArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Poll to stop, as returned by poll_variable
+    pub poll_id: u32,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SubscribeEventsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Start (true) or stop (false) the event notifications of this session
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Also forward text arriving on the attached RTT up channels. The data is consumed, so rtt_read no longer returns it
+    #[serde(default)]
+    pub rtt: bool,
+    /// How often to check the target, in milliseconds (default: 100)
+    #[serde(default = "default_event_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_event_interval_ms() -> u64 { 100 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WatchMemoryArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Memory address (hex string like "0x20000000" or decimal)
+    pub address: String,
+    /// Number of bytes per sample (default: 4)
+    #[serde(default = "default_watch_size")]
+    pub size: usize,
+    /// Number of samples to take (default: 10)
+    #[serde(default = "default_watch_samples")]
+    pub samples: usize,
+    /// Delay between samples in milliseconds (default: 100)
+    #[serde(default = "default_watch_interval_ms")]
+    pub interval_ms: u64,
+    /// Byte order for 1/2/4/8-byte samples: "little", "big" or "target" (default)
+    #[serde(default = "default_endianness")]
+    pub endianness: String,
+}
+
+fn default_watch_size() -> usize { 4 }
+fn default_watch_samples() -> usize { 10 }
+fn default_watch_interval_ms() -> u64 { 100 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadMemoryBatchArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Regions to read, returned in the same order
+    pub reads: Vec<BatchReadEntry>,
+    /// Skip the memory map check for every entry
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchReadEntry {
+    /// Memory address (hex string like "0x20000000" or decimal)
+    pub address: String,
+    /// Number of bytes to read
+    pub size: usize,
+    /// Output format: "hex", "binary", "ascii", "words16", "words32", "words64", "json"
+    #[serde(default = "default_format")]
+    pub format: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteMemoryArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Memory address (hex string like "0x8000000" or decimal)
+    pub address: String,
+    /// Data to write
+    pub data: String,
+    /// Input format: "hex", "ascii" (literal string bytes), "binary" (base64-encoded bytes),
+    /// "words32" or "words16" (list like "0xDEADBEEF,0x00000001")
+    #[serde(default = "default_format")]
+    pub format: String,
+    /// Byte order for the word formats: "little", "big" or "target" (default, the target's native order)
+    #[serde(default = "default_endianness")]
+    pub endianness: String,
+    /// Read the region back after writing and report mismatching offsets
+    #[serde(default)]
+    pub verify: bool,
+    /// With verify, fail the call when the read-back differs instead of reporting the mismatch
+    #[serde(default)]
+    pub strict_verify: bool,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteVariableArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Static variable name, plain or qualified (e.g. "COUNTER", "app::CONFIG")
+    pub name: String,
+    /// New value: a number, bool, enum variant name, array, or an object naming only the struct fields to change
+    pub value: serde_json::Value,
+    /// Firmware ELF file (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+    /// Read the written bytes back and report mismatching offsets
+    #[serde(default)]
+    pub verify: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadWordArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Register address, aligned to the access width (hex string like "0x40021018" or decimal)
+    pub address: String,
+    /// Access width in bits: 32 or 16
+    #[serde(default = "default_word_width")]
+    pub width: u32,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteWordArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Register address, aligned to the access width (hex string like "0x40021018" or decimal)
+    pub address: String,
+    /// Value to write (hex string like "0x00000001" or decimal)
+    pub value: String,
+    /// Access width in bits: 32 or 16
+    #[serde(default = "default_word_width")]
+    pub width: u32,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+fn default_word_width() -> u32 { 32 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteMemoryBatchArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Writes to apply, in order
+    pub writes: Vec<BatchWriteEntry>,
+    /// Halt a running core for the duration of the batch and resume it afterwards
+    #[serde(default)]
+    pub halt_during_write: bool,
+    /// Skip the remaining writes after the first failure
+    #[serde(default = "default_true")]
+    pub stop_on_error: bool,
+    /// Skip the memory map check for every entry
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchWriteEntry {
+    /// Memory address (hex string like "0x40021000" or decimal)
+    pub address: String,
+    /// Data to write
+    pub data: String,
+    /// Input format: "hex", "ascii", "binary", "words32" or "words16"
+    #[serde(default = "default_format")]
+    pub format: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Operations to run in order while the core is locked
+    pub operations: Vec<BatchOpEntry>,
+    /// Skip the remaining operations after the first failure
+    #[serde(default = "default_true")]
+    pub stop_on_error: bool,
+    /// Skip the memory map check for every operation
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchOpEntry {
+    /// Operation: "halt", "run", "read" or "write"
+    pub op: String,
+    /// Memory address for "read" and "write" (hex string like "0x20000000" or decimal)
+    pub address: Option<String>,
+    /// Number of bytes to read for "read"
+    pub size: Option<usize>,
+    /// Data to write for "write"
+    pub data: Option<String>,
+    /// Input format of `data`: "hex", "ascii", "binary", "words32" or "words16"
+    #[serde(default = "default_format")]
+    pub format: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MemoryFillArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string like "0x20000000" or decimal)
+    pub address: String,
+    /// Number of bytes to fill
+    pub size: usize,
+    /// 1, 2 or 4 byte hex pattern like "0xAA" or "0xDEADBEEF" (stored little-endian)
+    pub pattern: String,
+    /// Allow filling regions that overlap flash
+    #[serde(default)]
+    pub allow_flash: bool,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PaintStackArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Lowest address of the stack region; with size, overrides the region found from
+    /// the ELF stack symbols (_stack_start, _stack_end, __StackTop, ...)
+    pub address: Option<String>,
+    /// Size of the stack region in bytes
+    pub size: Option<usize>,
+    /// 1, 2 or 4 byte hex paint pattern (stored little-endian)
+    #[serde(default = "default_stack_pattern")]
+    pub pattern: String,
+    /// ELF file for the stack symbols (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MeasureStackUsageArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Lowest address of the stack region; must match the region given to paint_stack
+    pub address: Option<String>,
+    /// Size of the stack region in bytes
+    pub size: Option<usize>,
+    /// Paint pattern used by paint_stack
+    #[serde(default = "default_stack_pattern")]
+    pub pattern: String,
+    /// ELF file for the stack symbols (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+}
+
+fn default_stack_pattern() -> String { "0xA5A5A5A5".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MemorySearchArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
     #[serde(default)]
-    pub connect_under_reset: bool,
-    /// Whether to halt after connecting
-    #[serde(default = "default_true")]
-    pub halt_after_connect: bool,
+    pub core_index: usize,
+    /// Start address (hex string like "0x20000000" or decimal)
+    pub start_address: String,
+    /// Number of bytes to scan
+    pub size: usize,
+    /// Pattern to search for
+    pub pattern: String,
+    /// Pattern format: "hex" (byte string like "DEADBEEF") or "ascii"
+    #[serde(default = "default_format")]
+    pub pattern_format: String,
+    /// Maximum number of matches to return
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// Skip the memory map check, e.g. for vendor peripheral space the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
 }
 
-fn default_speed_khz() -> u32 { 4000 }
-fn default_true() -> bool { true }
+fn default_max_results() -> usize { 16 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct DisconnectArgs {
-    /// Session ID to disconnect
+pub struct MemoryDumpArgs {
+    /// Session ID
     pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string like "0x8000000" or decimal)
+    pub address: String,
+    /// Number of bytes to dump
+    pub size: usize,
+    /// Host file to write
+    pub file_path: String,
+    /// File format: "bin", "ihex" or "srec"
+    #[serde(default = "default_dump_format")]
+    pub format: String,
+    /// Bytes read from the target per transfer
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Replace the file if it already exists
+    #[serde(default)]
+    pub overwrite: bool,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct ProbeInfoArgs {
-    /// Session ID to get info for
+pub struct CoreDumpArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Host file to write
+    pub file_path: String,
+    /// Memory regions to include (default: all RAM regions of the memory map)
+    pub regions: Option<Vec<CoreDumpRegion>>,
+    /// File format: "elf" (ELF core file for `gdb firmware.elf core`) or "probe-rs"
+    /// (probe-rs's own coredump format)
+    #[serde(default = "default_core_dump_format")]
+    pub format: String,
+    /// Bytes read from the target per transfer
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Leave the core halted after the dump even if it was running
+    #[serde(default)]
+    pub stay_halted: bool,
+    /// Replace the file if it already exists
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CoreDumpRegion {
+    /// Start address (hex string like "0x20000000" or decimal)
+    pub address: String,
+    /// Number of bytes to include
+    pub size: usize,
+}
+
+fn default_core_dump_format() -> String { "elf".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MemoryChecksumArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string like "0x8000000" or decimal)
+    pub address: String,
+    /// Number of bytes to checksum
+    pub size: usize,
+    /// Digest algorithm: "crc32" or "sha256"
+    #[serde(default = "default_checksum_algorithm")]
+    pub algorithm: String,
+}
+
+fn default_checksum_algorithm() -> String { "crc32".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MemoryCompareArgs {
+    /// Session ID
     pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string or decimal); not needed for ELF files, which use their load addresses
+    pub address: Option<String>,
+    /// Reference file: raw binary, or ELF whose loadable segments are compared
+    pub file_path: Option<String>,
+    /// Reference hex data (alternative to file_path)
+    pub data: Option<String>,
+    /// Maximum number of mismatches to list
+    #[serde(default = "default_max_diffs")]
+    pub max_diffs: usize,
 }
 
+fn default_max_diffs() -> usize { 16 }
+
+fn default_dump_format() -> String { "bin".to_string() }
+fn default_chunk_size() -> usize { 4096 }
+
+
 // =============================================================================
-// Target Control Types
+// Breakpoint Management Types
 // =============================================================================
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct HaltArgs {
+pub struct LoadSymbolsArgs {
     /// Session ID
     pub session_id: String,
+    /// Firmware ELF file, used by later symbol lookups of this session
+    pub elf_path: String,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct RunArgs {
+pub struct UnloadSymbolsArgs {
     /// Session ID
     pub session_id: String,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct ResetArgs {
+pub struct SymbolizeAddressArgs {
     /// Session ID
     pub session_id: String,
-    /// Reset type: "hardware" or "software"
-    #[serde(default = "default_reset_type")]
-    pub reset_type: String,
-    /// Whether to halt after reset
-    #[serde(default = "default_true")]
-    pub halt_after_reset: bool,
+    /// Addresses to resolve (hex strings like "0x08000420" or decimal)
+    pub addresses: Vec<String>,
+    /// Firmware ELF file (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
 }
 
-fn default_reset_type() -> String { "hardware".to_string() }
-
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct StepArgs {
+pub struct LookupSymbolArgs {
     /// Session ID
     pub session_id: String,
+    /// Symbol names to look up, raw or demangled (e.g. "main", "app::init")
+    pub names: Vec<String>,
+    /// Firmware ELF file (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct GetStatusArgs {
+pub struct SetBreakpointArgs {
     /// Session ID
     pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Breakpoint address (hex string like "0x8000000" or decimal); mutually exclusive with `symbol`
+    pub address: Option<String>,
+    /// Function name like "main" or "HardFault_Handler", resolved through the ELF symbol table
+    pub symbol: Option<String>,
+    /// Firmware ELF used to resolve symbols and source lines (optional once loaded for the session)
+    pub elf_path: Option<String>,
+    /// Source file like "src/main.rs", used with `line`; needs symbols loaded with `load_symbols`
+    pub file: Option<String>,
+    /// 1-based source line in `file`; the first line at or after it that has code is used
+    pub line: Option<u64>,
+    /// Breakpoint type: "hardware" or "software" (software patches a BKPT into code in RAM)
+    #[serde(default = "default_breakpoint_type")]
+    pub breakpoint_type: String,
+    /// Remove the breakpoint automatically the first time the core halts on it
+    #[serde(default)]
+    pub temporary: bool,
+    /// Hits to skip before halting: 'wait_for_halt' resumes the core on the first
+    /// `skip_count` hits, so 99 stops on the 100th
+    #[serde(default)]
+    pub skip_count: u32,
 }
 
-// =============================================================================
-// Memory Operation Types
-// =============================================================================
+fn default_breakpoint_type() -> String { "hardware".to_string() }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct ReadMemoryArgs {
+pub struct RunToAddressArgs {
     /// Session ID
     pub session_id: String,
-    /// Memory address (hex string like "0x8000000" or decimal)
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Address to run to (hex string like "0x8000400" or decimal) or a function name
     pub address: String,
-    /// Number of bytes to read
-    pub size: usize,
-    /// Output format: "hex", "binary", "ascii", "words32", "words16"
-    #[serde(default = "default_format")]
-    pub format: String,
+    /// Firmware ELF used to resolve a function name (optional once loaded for the session)
+    pub elf_path: Option<String>,
+    /// Maximum time to wait in milliseconds (default: 5000)
+    #[serde(default = "default_halt_timeout_ms")]
+    pub timeout_ms: u64,
 }
 
-fn default_format() -> String { "hex".to_string() }
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListBreakpointsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core whose hardware breakpoint usage is reported (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct WriteMemoryArgs {
+pub struct SetWatchpointArgs {
     /// Session ID
     pub session_id: String,
-    /// Memory address (hex string like "0x8000000" or decimal)
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Address to watch (hex string like "0x20000000" or decimal)
     pub address: String,
-    /// Data to write
-    pub data: String,
-    /// Input format: "hex", "binary", "ascii", "words32", "words16"
-    #[serde(default = "default_format")]
-    pub format: String,
+    /// Watched size in bytes: 1, 2 or 4 (default: 4), the address must be aligned to it
+    #[serde(default = "default_watchpoint_size")]
+    pub size: u32,
+    /// Access that halts the core: "read", "write" (default) or "readwrite"
+    #[serde(default = "default_watch_access")]
+    pub access: String,
 }
 
-
-// =============================================================================
-// Breakpoint Management Types
-// =============================================================================
+fn default_watchpoint_size() -> u32 { 4 }
+fn default_watch_access() -> String { "write".to_string() }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct SetBreakpointArgs {
+pub struct ClearWatchpointArgs {
     /// Session ID
     pub session_id: String,
-    /// Breakpoint address (hex string like "0x8000000" or decimal)
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Watched address (hex string like "0x20000000" or decimal)
     pub address: String,
-    /// Breakpoint type: "hardware" or "software"
-    #[serde(default = "default_breakpoint_type")]
-    pub breakpoint_type: String,
 }
 
-fn default_breakpoint_type() -> String { "hardware".to_string() }
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListWatchpointsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core whose watchpoints are listed (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+}
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ClearBreakpointArgs {
     /// Session ID
     pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
     /// Breakpoint address (hex string like "0x8000000" or decimal)
     pub address: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ClearAllBreakpointsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Also remove all watchpoints (default: true)
+    #[serde(default = "default_true")]
+    pub include_watchpoints: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EnableBreakpointArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core the breakpoint address is set on; ignored for ids (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Breakpoint address (hex string like "0x8000000" or decimal) or id like "#2"
+    pub breakpoint: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DisableBreakpointArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core the breakpoint address is set on; ignored for ids (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Breakpoint address (hex string like "0x8000000" or decimal) or id like "#2"
+    pub breakpoint: String,
+}
 
 // =============================================================================
 // Flash Programming Types
@@ -200,6 +1156,39 @@ pub struct FlashVerifyArgs {
     pub address: String,
     /// Number of bytes to verify
     pub size: u32,
+    /// Compare a CRC32 of the region instead of reading it back byte by byte (reports no mismatch details)
+    #[serde(default)]
+    pub quick_verify: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunTestsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Path to the test binary ELF (defmt-test, embedded-test or similar)
+    pub elf_path: String,
+    /// How long to let the tests run before halting the core, in milliseconds (default: 60000)
+    #[serde(default = "default_test_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Where the tests write their log: "rtt" (up channel 0, text) or "semihosting"
+    #[serde(default = "default_test_output")]
+    pub output: String,
+}
+
+fn default_test_timeout_ms() -> u64 { 60_000 }
+fn default_test_output() -> String { "rtt".to_string() }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlankCheckArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string like "0x8000000" or decimal)
+    pub address: String,
+    /// Number of bytes to check
+    pub size: usize,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -234,14 +1223,19 @@ pub struct RttAttachArgs {
     pub session_id: String,
     /// RTT control block address (optional, auto-detected if not provided)
     pub control_block_address: Option<String>,
-    /// Memory ranges to search for RTT control block
-    /// Each range is a tuple of (start_address, end_address)
+    /// Memory ranges to search for the RTT control block instead of all of RAM, e.g. the
+    /// linker-defined `.rtt` section. Ignored when control_block_address is given
     pub memory_ranges: Option<Vec<MemoryRange>>,
+    /// Skip the memory map check of memory_ranges, e.g. for RAM the target description does not list
+    #[serde(default)]
+    pub allow_unmapped: bool,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct MemoryRange {
+    /// First address of the range (hex string like "0x20000000" or decimal)
     pub start: String,
+    /// Address just past the end of the range
     pub end: String,
 }
 
@@ -258,17 +1252,65 @@ pub struct RttReadArgs {
     /// RTT channel number (usually 0 for default output)
     #[serde(default)]
     pub channel: u32,
+    /// Up channel name such as "Terminal" or "defmt", used instead of `channel`
+    pub channel_name: Option<String>,
     /// Maximum bytes to read
     #[serde(default = "default_max_bytes")]
     pub max_bytes: usize,
-    /// Timeout in milliseconds
+    /// How long to wait for data in milliseconds; 0 returns immediately, even with nothing read
     #[serde(default = "default_timeout_ms")]
     pub timeout_ms: u64,
 }
 
 fn default_max_bytes() -> usize { 1024 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RttReadLinesArgs {
+    /// Session ID
+    pub session_id: String,
+    /// RTT up channel number (usually 0 for default output)
+    #[serde(default)]
+    pub channel: u32,
+    /// Up channel name such as "Terminal" or "defmt", used instead of `channel`
+    pub channel_name: Option<String>,
+    /// How long to wait for a complete line, in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
 fn default_timeout_ms() -> u64 { 1000 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RttAttachFramedArgs {
+    /// Session ID
+    pub session_id: String,
+    /// RTT up channel number
+    #[serde(default)]
+    pub channel: u32,
+    /// Up channel name, used instead of `channel`
+    pub channel_name: Option<String>,
+    /// Length prefix of each frame: "u16le", "u16be", "u32le" or "u32be"
+    pub frame_header: String,
+    /// Largest payload accepted before the stream is considered out of sync (default: 65536)
+    #[serde(default = "default_max_frame_size")]
+    pub max_frame_size: usize,
+}
+
+fn default_max_frame_size() -> usize { crate::rtt::frames::DEFAULT_MAX_FRAME_SIZE }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RttReadFramesArgs {
+    /// Session ID
+    pub session_id: String,
+    /// RTT up channel number, switched to framed reads with rtt_attach_framed
+    #[serde(default)]
+    pub channel: u32,
+    /// Up channel name, used instead of `channel`
+    pub channel_name: Option<String>,
+    /// How long to wait for a complete frame, in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RttWriteArgs {
     /// Session ID
@@ -276,14 +1318,23 @@ pub struct RttWriteArgs {
     /// RTT channel number (usually 0 for default input)
     #[serde(default)]
     pub channel: u32,
+    /// Down channel name such as "Terminal" or "defmt", used instead of `channel`
+    pub channel_name: Option<String>,
     /// Data to write
     pub data: String,
     /// Data encoding: "utf8", "hex", "binary"
     #[serde(default = "default_encoding")]
     pub encoding: String,
+    /// Keep retrying until all bytes are accepted by the target instead of dropping what does not fit
+    #[serde(default)]
+    pub blocking: bool,
+    /// Maximum time to wait for a blocking write in milliseconds (default: 1000)
+    #[serde(default = "default_rtt_write_timeout_ms")]
+    pub timeout_ms: u64,
 }
 
 fn default_encoding() -> String { "utf8".to_string() }
+fn default_rtt_write_timeout_ms() -> u64 { 1000 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RttChannelsArgs {
@@ -291,6 +1342,139 @@ pub struct RttChannelsArgs {
     pub session_id: String,
 }
 
+// =============================================================================
+// Debug Information Types
+// =============================================================================
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BacktraceArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Path to the firmware ELF file with debug info (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+    /// Maximum number of frames to return
+    #[serde(default = "default_max_frames")]
+    pub max_frames: usize,
+    /// Halt a running core before unwinding; it is left halted
+    #[serde(default)]
+    pub halt: bool,
+}
+
+fn default_max_frames() -> usize { 32 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadLocalsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Backtrace frame to read, 0 being the innermost (default: 0)
+    #[serde(default)]
+    pub frame: usize,
+    /// Path to the firmware ELF file with debug info (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RtosThreadsArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Path to the FreeRTOS firmware ELF file with debug info (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+    /// Unwind a short backtrace for every task (default: true)
+    #[serde(default = "default_true")]
+    pub backtraces: bool,
+    /// Maximum number of frames per task backtrace (default: 8)
+    #[serde(default = "default_task_frames")]
+    pub max_frames: usize,
+    /// Halt a running core before walking the task lists; it is left halted
+    #[serde(default)]
+    pub halt: bool,
+}
+
+fn default_task_frames() -> usize { 8 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DisassembleArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Start address (hex string like "0x8000000" or decimal) or symbol name (default: the PC)
+    pub address: Option<String>,
+    /// Number of instructions to disassemble (default: 16)
+    #[serde(default = "default_disassemble_count")]
+    pub count: usize,
+    /// "around_pc" to center the listing on the address or PC instead of starting at it
+    pub context: Option<String>,
+    /// Firmware ELF file for symbol names (default: the ELF loaded with load_symbols)
+    pub elf_path: Option<String>,
+}
+
+fn default_disassemble_count() -> usize { 16 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PcToSourceArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// Path to the firmware ELF file with debug info
+    pub elf_path: String,
+    /// Number of source lines to show before and after the current line (0 to disable)
+    #[serde(default = "default_context_lines")]
+    pub context_lines: usize,
+}
+
+fn default_context_lines() -> usize { 3 }
+
+// =============================================================================
+// Semihosting Types
+// =============================================================================
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EnableSemihostingArgs {
+    /// Session ID
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadSemihostingOutputArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// How long to keep servicing semihosting requests before returning, in milliseconds
+    #[serde(default = "default_semihosting_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_semihosting_timeout_ms() -> u64 { 500 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunWithSemihostingArgs {
+    /// Session ID
+    pub session_id: String,
+    /// Core index for multi-core targets (default: 0)
+    #[serde(default)]
+    pub core_index: usize,
+    /// How long to let the target run before returning, in milliseconds (default: 10000)
+    #[serde(default = "default_semihosting_run_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_semihosting_run_timeout_ms() -> u64 { 10_000 }
+
 // =============================================================================
 // Response Types (for internal use)
 // =============================================================================
@@ -333,12 +1517,30 @@ pub struct CoreInfo {
 #[derive(Debug, Serialize)]
 pub struct SessionStatus {
     pub session_id: String,
+    pub target_chip: String,
     pub connected: bool,
     pub target_state: String,
     pub created_at: String,
     pub last_activity: String,
 }
 
+/// Counts over all sessions of the server, from `get_statistics`
+#[derive(Debug, Default, Serialize)]
+pub struct SessionStatistics {
+    pub total_sessions: usize,
+    pub max_sessions: usize,
+    /// Sessions whose core 0 is halted
+    pub halted_sessions: usize,
+    pub running_sessions: usize,
+    /// Sessions held by a long operation such as flashing, whose state was not queried
+    pub busy_sessions: usize,
+    /// Sessions waiting for `reconnect`, or whose core state could not be read
+    pub disconnected_sessions: usize,
+    pub total_breakpoints: usize,
+    /// Sum of the ages of all sessions
+    pub total_uptime_seconds: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct RegisterValue {
     pub name: String,
@@ -360,12 +1562,3 @@ pub struct FlashResult {
     pub programming_time_ms: u64,
     pub verification_result: bool,
 }
-
-#[derive(Debug, Serialize)]
-pub struct RttChannelInfo {
-    pub channel: u32,
-    pub name: String,
-    pub direction: String, // "up", "down"
-    pub buffer_size: usize,
-    pub flags: u32,
-}
\ No newline at end of file
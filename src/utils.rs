@@ -36,4 +36,400 @@ impl std::fmt::Display for ProbeType {
             ProbeType::Unknown => write!(f, "Unknown"),
         }
     }
-}
\ No newline at end of file
+}
+
+/// How the target is reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetType {
+    /// Pulse the nRST line from the probe
+    Hardware,
+    /// The target's reset sequence from probe-rs (SYSRESETREQ on Cortex-M)
+    Software,
+    /// Request a system reset by writing AIRCR.SYSRESETREQ directly (Cortex-M only)
+    System,
+}
+
+impl ResetType {
+    /// Parse a reset type name ("hardware", "software" or "system")
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "hardware" | "hw" => Ok(ResetType::Hardware),
+            "software" | "sw" => Ok(ResetType::Software),
+            "system" | "sysresetreq" => Ok(ResetType::System),
+            _ => Err(format!("Unsupported reset type '{}'. Use 'hardware', 'software' or 'system'", name)),
+        }
+    }
+}
+
+/// How `connect` attaches to the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachMode {
+    /// Plain attach; the core is halted or resumed afterwards as requested
+    Normal,
+    /// Hold nRST asserted while attaching, so the core stops at the reset vector
+    UnderReset,
+    /// Attach without halting or resetting, leaving the running core undisturbed
+    AttachRunning,
+}
+
+impl AttachMode {
+    /// Parse an attach mode name ("normal", "under_reset" or "attach_running")
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "normal" => Ok(AttachMode::Normal),
+            "under_reset" => Ok(AttachMode::UnderReset),
+            "attach_running" => Ok(AttachMode::AttachRunning),
+            _ => Err(format!("Unsupported attach mode '{}'. Use 'normal', 'under_reset' or 'attach_running'", name)),
+        }
+    }
+}
+
+impl std::fmt::Display for AttachMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachMode::Normal => write!(f, "normal"),
+            AttachMode::UnderReset => write!(f, "under_reset"),
+            AttachMode::AttachRunning => write!(f, "attach_running"),
+        }
+    }
+}
+
+impl std::fmt::Display for ResetType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResetType::Hardware => write!(f, "hardware"),
+            ResetType::Software => write!(f, "software"),
+            ResetType::System => write!(f, "system"),
+        }
+    }
+}
+
+/// Byte order used when grouping raw memory bytes into words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Parse an endianness name ("little"/"le" or "big"/"be")
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "little" | "le" => Ok(Endianness::Little),
+            "big" | "be" => Ok(Endianness::Big),
+            _ => Err(format!("Unsupported endianness '{}'. Use 'little' or 'big'", name)),
+        }
+    }
+
+    /// Native byte order of a target architecture
+    pub fn for_architecture(architecture: probe_rs::Architecture) -> Self {
+        match architecture {
+            probe_rs::Architecture::Arm
+            | probe_rs::Architecture::Riscv
+            | probe_rs::Architecture::Xtensa => Endianness::Little,
+        }
+    }
+
+    /// Serialize the low `width` bytes of `value` in this byte order
+    pub fn word_to_bytes(&self, value: u64, width: usize) -> Vec<u8> {
+        let bytes = value.to_le_bytes()[..width].to_vec();
+        match self {
+            Endianness::Little => bytes,
+            Endianness::Big => bytes.into_iter().rev().collect(),
+        }
+    }
+
+    /// Assemble a word from up to 8 bytes in this byte order
+    pub fn word_from_bytes(&self, bytes: &[u8]) -> u64 {
+        match self {
+            Endianness::Little => bytes.iter().rev().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+            Endianness::Big => bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+        }
+    }
+}
+
+impl std::fmt::Display for Endianness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endianness::Little => write!(f, "little"),
+            Endianness::Big => write!(f, "big"),
+        }
+    }
+}
+
+/// Word width in bytes for the word-oriented memory formats
+pub fn word_width(format: &str) -> Option<usize> {
+    match format {
+        "words16" => Some(2),
+        "words32" => Some(4),
+        "words64" => Some(8),
+        _ => None,
+    }
+}
+
+/// Widen `[address, address + size)` to whole words: the address is rounded
+/// down and the end up to a multiple of `width`. Fails when the widened
+/// range runs past the end of the address space.
+pub fn align_to_words(address: u64, size: usize, width: usize) -> Result<(u64, usize), String> {
+    let width = width as u64;
+    let start = address - address % width;
+    let end = address
+        .checked_add(size as u64)
+        .and_then(|end| end.div_ceil(width).checked_mul(width))
+        .ok_or_else(|| format!("Range of {} bytes at 0x{:08X} runs past the end of the address space", size, address))?;
+    let size = usize::try_from(end - start).map_err(|_| format!("Range of {} bytes at 0x{:08X} is too large", size, address))?;
+    Ok((start, size))
+}
+
+/// Format memory data for display
+pub fn format_memory_data(data: &[u8], format: &str, base_address: u64, endianness: Endianness) -> String {
+    match format {
+        "hex" => {
+            let mut result = String::new();
+            for (i, chunk) in data.chunks(16).enumerate() {
+                let addr = base_address + (i * 16) as u64;
+                result.push_str(&format!("0x{:08X}: ", addr));
+                
+                // Hex bytes
+                for (j, byte) in chunk.iter().enumerate() {
+                    if j == 8 { result.push(' '); }
+                    result.push_str(&format!("{:02X} ", byte));
+                }
+                
+                // Pad if needed
+                if chunk.len() < 16 {
+                    let padding = (16 - chunk.len()) * 3 + (if chunk.len() <= 8 { 1 } else { 0 });
+                    result.push_str(&" ".repeat(padding));
+                }
+                
+                // ASCII representation
+                result.push_str("| ");
+                for byte in chunk {
+                    if byte.is_ascii_graphic() || *byte == b' ' {
+                        result.push(*byte as char);
+                    } else {
+                        result.push('.');
+                    }
+                }
+                result.push('\n');
+            }
+            result
+        }
+        "binary" => {
+            data.iter()
+                .map(|b| format!("{:08b}", b))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        "words16" | "words32" | "words64" => {
+            let width = word_width(format).unwrap_or(4);
+            let mut result = String::new();
+            let mut values = Vec::new();
+            for (i, chunk) in data.chunks_exact(width).enumerate() {
+                let addr = base_address + (i * width) as u64;
+                let value = format!("0x{:0digits$X}", endianness.word_from_bytes(chunk), digits = width * 2);
+                result.push_str(&format!("0x{:08X}: {}\n", addr, value));
+                values.push(format!("\"{}\"", value));
+            }
+            result.push_str(&format!("\nValues: [{}]\n", values.join(", ")));
+            result
+        }
+        "ascii" => {
+            String::from_utf8_lossy(data).to_string()
+        }
+        "json" => memory_data_json(data, base_address, endianness).to_string(),
+        _ => {
+            // Default to hex if unknown format
+            format_memory_data(data, "hex", base_address, endianness)
+        }
+    }
+}
+
+/// Memory data as `{ address, bytes, words32 }` for programmatic consumers.
+///
+/// `words32` groups whole 4-byte words in the given byte order; trailing bytes
+/// that do not fill a word only appear in `bytes`.
+pub fn memory_data_json(data: &[u8], base_address: u64, endianness: Endianness) -> serde_json::Value {
+    let words32: Vec<u64> = data.chunks_exact(4).map(|chunk| endianness.word_from_bytes(chunk)).collect();
+    serde_json::json!({
+        "address": base_address,
+        "bytes": data,
+        "words32": words32,
+    })
+}
+
+/// Scalar type decoded by typed memory reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    /// Parse a Rust-style type name like "u32" or "f32"
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "u8" => Ok(ScalarType::U8),
+            "u16" => Ok(ScalarType::U16),
+            "u32" => Ok(ScalarType::U32),
+            "u64" => Ok(ScalarType::U64),
+            "i8" => Ok(ScalarType::I8),
+            "i16" => Ok(ScalarType::I16),
+            "i32" => Ok(ScalarType::I32),
+            "i64" => Ok(ScalarType::I64),
+            "f32" => Ok(ScalarType::F32),
+            "f64" => Ok(ScalarType::F64),
+            _ => Err(format!(
+                "Unsupported type '{}'. Use u8, u16, u32, u64, i8, i16, i32, i64, f32 or f64",
+                name
+            )),
+        }
+    }
+
+    /// Size of one value in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            ScalarType::U8 | ScalarType::I8 => 1,
+            ScalarType::U16 | ScalarType::I16 => 2,
+            ScalarType::U32 | ScalarType::I32 | ScalarType::F32 => 4,
+            ScalarType::U64 | ScalarType::I64 | ScalarType::F64 => 8,
+        }
+    }
+
+    /// Decode one value from exactly `size()` bytes
+    pub fn decode(&self, bytes: &[u8], endianness: Endianness) -> TypedValue {
+        let raw = endianness.word_from_bytes(bytes);
+        let shift = 64 - self.size() as u32 * 8;
+        match self {
+            ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => TypedValue::Unsigned(raw),
+            ScalarType::I8 | ScalarType::I16 | ScalarType::I32 | ScalarType::I64 => {
+                TypedValue::Signed(((raw << shift) as i64) >> shift)
+            }
+            ScalarType::F32 => TypedValue::F32(f32::from_bits(raw as u32)),
+            ScalarType::F64 => TypedValue::F64(f64::from_bits(raw)),
+        }
+    }
+}
+
+impl std::fmt::Display for ScalarType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScalarType::U8 => "u8",
+            ScalarType::U16 => "u16",
+            ScalarType::U32 => "u32",
+            ScalarType::U64 => "u64",
+            ScalarType::I8 => "i8",
+            ScalarType::I16 => "i16",
+            ScalarType::I32 => "i32",
+            ScalarType::I64 => "i64",
+            ScalarType::F32 => "f32",
+            ScalarType::F64 => "f64",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A decoded scalar value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypedValue {
+    Unsigned(u64),
+    Signed(i64),
+    F32(f32),
+    F64(f64),
+}
+
+impl std::fmt::Display for TypedValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypedValue::Unsigned(value) => write!(f, "{}", value),
+            TypedValue::Signed(value) => write!(f, "{}", value),
+            TypedValue::F32(value) => write!(f, "{:?}", value),
+            TypedValue::F64(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_attach_mode() {
+        assert_eq!(AttachMode::parse("normal").unwrap(), AttachMode::Normal);
+        assert_eq!(AttachMode::parse("Under_Reset").unwrap(), AttachMode::UnderReset);
+        assert_eq!(AttachMode::parse("attach_running").unwrap(), AttachMode::AttachRunning);
+        assert!(AttachMode::parse("hot").is_err());
+        assert_eq!(AttachMode::AttachRunning.to_string(), "attach_running");
+    }
+
+    #[test]
+    fn test_parse_reset_type() {
+        assert_eq!(ResetType::parse("Hardware").unwrap(), ResetType::Hardware);
+        assert_eq!(ResetType::parse("sw").unwrap(), ResetType::Software);
+        assert_eq!(ResetType::parse("system").unwrap(), ResetType::System);
+        assert!(ResetType::parse("warm").is_err());
+    }
+
+    #[test]
+    fn test_word_formats_honor_endianness() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+
+        let little = format_memory_data(&data, "words32", 0x2000_0000, Endianness::Little);
+        assert!(little.contains("0x20000000: 0x04030201"));
+        let big = format_memory_data(&data, "words32", 0x2000_0000, Endianness::Big);
+        assert!(big.contains("0x20000000: 0x01020304"));
+
+        let little = format_memory_data(&data, "words16", 0x2000_0000, Endianness::Little);
+        assert!(little.contains("0x20000000: 0x0201") && little.contains("0x20000002: 0x0403"));
+        let big = format_memory_data(&data, "words16", 0x2000_0000, Endianness::Big);
+        assert!(big.contains("0x20000000: 0x0102") && big.contains("0x20000002: 0x0304"));
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let bytes = endianness.word_to_bytes(0xDEADBEEF, 4);
+            assert_eq!(endianness.word_from_bytes(&bytes), 0xDEADBEEF);
+        }
+        assert_eq!(Endianness::Big.word_to_bytes(0x1234, 2), vec![0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_align_to_words() {
+        assert_eq!(align_to_words(0x2000_0000, 8, 4), Ok((0x2000_0000, 8)));
+        assert_eq!(align_to_words(0x2000_0000, 6, 4), Ok((0x2000_0000, 8)));
+        // A misaligned start moves down to the enclosing word and keeps the requested end covered
+        assert_eq!(align_to_words(0x2000_0002, 4, 4), Ok((0x2000_0000, 8)));
+        assert_eq!(align_to_words(0x2000_0003, 1, 2), Ok((0x2000_0002, 2)));
+        assert_eq!(align_to_words(0x2000_0004, 4, 8), Ok((0x2000_0000, 8)));
+        // Near the top of the address space the end or its round-up overflows
+        assert!(align_to_words(u64::MAX - 1, 4, 4).is_err());
+        assert!(align_to_words(u64::MAX - 2, 1, 4).is_err());
+        assert!(align_to_words(0, usize::MAX, 4).is_err());
+    }
+
+    #[test]
+    fn test_json_format_round_trip() {
+        let data = [0xEF, 0xBE, 0xAD, 0xDE, 0x01, 0x02, 0x03, 0x04, 0xAA];
+        let text = format_memory_data(&data, "json", 0x2000_0000, Endianness::Little);
+        let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(parsed["address"], 0x2000_0000u64);
+        let bytes: Vec<u8> = serde_json::from_value(parsed["bytes"].clone()).unwrap();
+        assert_eq!(bytes, data);
+
+        // The trailing byte does not fill a word and is left out of words32
+        let words: Vec<u64> = serde_json::from_value(parsed["words32"].clone()).unwrap();
+        assert_eq!(words, vec![0xDEADBEEF, 0x04030201]);
+        let rebuilt: Vec<u8> = words.iter().flat_map(|&word| Endianness::Little.word_to_bytes(word, 4)).collect();
+        assert_eq!(rebuilt, data[..8]);
+
+        let big: serde_json::Value = serde_json::from_str(&format_memory_data(&data, "json", 0, Endianness::Big)).unwrap();
+        assert_eq!(big["words32"][0], 0xEFBEADDEu64);
+    }
+}
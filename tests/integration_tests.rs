@@ -14,6 +14,87 @@ async fn test_config_validation() {
     assert!(toml_str.contains("[debugger]"));
 }
 
+#[test]
+fn test_connect_defaults_validation() {
+    // Connect defaults are optional, but must not be blank when set
+    let mut config = Config::default();
+    config.debugger.default_target_chip = Some("STM32F407VGTx".to_string());
+    assert!(config.validate().is_ok());
+    config.debugger.default_probe_selector = Some(" ".to_string());
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_config_load_without_newer_keys() {
+    use std::io::Write;
+
+    // A config file written before the newer [debugger] and [memory] keys existed
+    let legacy = r#"
+[server]
+max_sessions = 5
+session_timeout_seconds = 3600
+
+[debugger]
+default_speed_khz = 4000
+connection_timeout_ms = 5000
+retry_count = 3
+probe_discovery_timeout_ms = 2000
+halt_on_connect = true
+reset_on_connect = false
+connect_under_reset = false
+default_reset_type = "hardware"
+
+[rtt]
+buffer_size = 1024
+poll_interval_ms = 10
+max_channels = 16
+scan_timeout_ms = 1000
+scan_memory = true
+scan_ram_only = true
+
+[memory]
+max_read_size = 65536
+max_write_size = 4096
+cache_enable = true
+cache_size = 1048576
+
+[flash]
+default_erase_timeout_ms = 30000
+default_program_timeout_ms = 60000
+verify_after_program = true
+allow_erase = false
+max_binary_size = 10485760
+
+[security]
+allow_flash_erase = false
+allow_memory_write = true
+restrict_memory_access = false
+allowed_file_paths = []
+max_file_size = 10485760
+
+[targets]
+
+[logging]
+level = "info"
+format = "text"
+timestamp_format = "rfc3339"
+include_location = false
+include_thread_names = false
+"#;
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(legacy.as_bytes()).unwrap();
+
+    let config = Config::load(Some(&file.path().to_path_buf())).unwrap();
+    let defaults = Config::default();
+    assert_eq!(config.memory.read_chunk_size, defaults.memory.read_chunk_size);
+    assert_eq!(config.memory.max_search_size, defaults.memory.max_search_size);
+    assert_eq!(config.memory.max_batch_read_size, defaults.memory.max_batch_read_size);
+    assert_eq!(config.debugger.connect_retry_delay_ms, defaults.debugger.connect_retry_delay_ms);
+    assert_eq!(config.debugger.max_step_count, defaults.debugger.max_step_count);
+    assert!(config.debugger.source_search_paths.is_empty());
+    assert_eq!(config.debugger.source_context_lines, defaults.debugger.source_context_lines);
+}
+
 #[tokio::test]
 async fn test_probe_discovery() {
     // Test probe discovery (this will work even without hardware)
@@ -55,6 +136,16 @@ fn test_probe_type_detection() {
     assert_eq!(ProbeType::from_vid_pid(0xFFFF, 0xFFFF), ProbeType::Unknown);
 }
 
+#[test]
+fn test_target_selector() {
+    use embedded_debugger_mcp::debugger::target_selector;
+    use probe_rs::config::TargetSelector;
+
+    assert!(matches!(target_selector("auto"), TargetSelector::Auto));
+    assert!(matches!(target_selector("AUTO"), TargetSelector::Auto));
+    assert!(matches!(target_selector("STM32F407VGTx"), TargetSelector::Unspecified(name) if name == "STM32F407VGTx"));
+}
+
 #[tokio::test]
 async fn test_mcp_tool_handler() {
     // Test the main MCP tool handler
@@ -67,4 +158,15 @@ async fn test_mcp_tool_handler() {
     
     // Verify the handler was created - this is more meaningful than just instantiation
     println!("MCP tool handler created and ready for use");
+}
+
+#[tokio::test]
+async fn test_statistics_without_sessions() {
+    use embedded_debugger_mcp::EmbeddedDebuggerToolHandler;
+
+    let handler = EmbeddedDebuggerToolHandler::new(5);
+    let statistics = handler.statistics().await;
+    assert_eq!(statistics.total_sessions, 0);
+    assert_eq!(statistics.max_sessions, 5);
+    assert_eq!(statistics.total_breakpoints, 0);
 }
\ No newline at end of file